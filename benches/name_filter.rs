@@ -0,0 +1,107 @@
+//! 忽略大小写的文件名匹配基准
+//!
+//! 在内存中的大目录树上比较 `NameFilter::new_ignore_case`（模式在创建时转为小写并编译一次）
+//! 与为每个条目重新编译小写模式的朴素实现。
+//! 条目先遍历一次收集起来，计时只包括名称匹配本身。
+//!
+//! 运行：`cargo bench --bench name_filter`
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use glob::Pattern;
+use rust_find::finder::filter::{FilterCost, NameFilter};
+use rust_find::finder::{FileFilter, FindEntry, FindOptions, Finder, MemoryFileSystem};
+
+const DIRS: usize = 200;
+const FILES_PER_DIR: usize = 500;
+const ITERATIONS: usize = 5;
+const PATTERN: &str = "*.TXT";
+
+/// 每个条目都重新编译小写的模式
+struct RecompileFilter(String);
+
+impl FileFilter for RecompileFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        let pattern = Pattern::new(&self.0.to_lowercase()).expect("有效的模式");
+        entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| pattern.matches(&name.to_lowercase()))
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
+    fn description(&self) -> String {
+        format!("recompile '{}' per entry", self.0)
+    }
+}
+
+/// 收集遍历到的所有条目
+struct Collect(Arc<Mutex<Vec<FindEntry>>>);
+
+impl FileFilter for Collect {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        self.0.lock().expect("条目列表").push(entry.clone());
+        false
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
+    fn description(&self) -> String {
+        "collect entries".to_string()
+    }
+}
+
+/// 构造大目录树并收集其中的条目，约十分之一的文件名包含非 ASCII 字符
+fn build_tree() -> Vec<FindEntry> {
+    let mut fs = MemoryFileSystem::new();
+    for dir in 0..DIRS {
+        for file in 0..FILES_PER_DIR {
+            let name = match file % 10 {
+                0 => format!("Überblick_{}.Txt", file),
+                1..=4 => format!("Report_{}.TXT", file),
+                _ => format!("data_{}.json", file),
+            };
+            fs = fs.with_file(PathBuf::from(format!("/tree/dir{}/{}", dir, name)), "");
+        }
+    }
+    let entries = Arc::new(Mutex::new(Vec::new()));
+    Finder::new(FindOptions::new()).find_in(Arc::new(fs), "/tree", Collect(entries.clone()));
+    let entries = std::mem::take(&mut *entries.lock().expect("条目列表"));
+    entries
+}
+
+/// 多次匹配所有条目，返回匹配数和耗时的中位数
+fn measure(entries: &[FindEntry], filter: &dyn FileFilter) -> (usize, Duration) {
+    let mut matches = 0;
+    let mut times: Vec<Duration> = (0..ITERATIONS)
+        .map(|_| {
+            let start = Instant::now();
+            matches = entries.iter().filter(|entry| filter.matches(entry)).count();
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+    (matches, times[times.len() / 2])
+}
+
+fn main() {
+    let entries = build_tree();
+    println!("{} 个条目，模式 '{}'，每项运行 {} 次取中位数", entries.len(), PATTERN, ITERATIONS);
+    println!("{:<24} {:>10} {:>12} {:>10}", "filter", "matches", "wall time", "speedup");
+
+    let filter = NameFilter::new_ignore_case(PATTERN).expect("有效的模式");
+    let (expected, fastest) = measure(&entries, &filter);
+    let (matches, elapsed) = measure(&entries, &RecompileFilter(PATTERN.to_string()));
+    assert_eq!(matches, expected, "两种实现的匹配数不同");
+
+    let speedup = elapsed.as_secs_f64() / fastest.as_secs_f64().max(f64::EPSILON);
+    println!("{:<24} {:>10} {:>12.2?} {:>9.1}x", "recompile per entry", matches, elapsed, speedup);
+    println!("{:<24} {:>10} {:>12.2?} {:>10}", "NameFilter (ignore case)", expected, fastest, "1.0x");
+}
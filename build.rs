@@ -0,0 +1,38 @@
+//! 构建脚本
+//!
+//! 启用 grpc 特性时生成搜索服务的 gRPC 代码。消息类型手写在 src/grpc.rs 中，
+//! 与 proto/search.proto 保持一致，因此构建时不需要 protoc。
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "grpc")]
+    {
+        use tonic_build::manual::{Builder, Method, Service};
+
+        let service = Service::builder()
+            .name("Search")
+            .package("rust_find")
+            .method(
+                Method::builder()
+                    .name("search")
+                    .route_name("Search")
+                    .input_type("crate::grpc::SearchRequest")
+                    .output_type("crate::grpc::SearchResult")
+                    .codec_path("tonic_prost::ProstCodec")
+                    .server_streaming()
+                    .build(),
+            )
+            .method(
+                Method::builder()
+                    .name("cancel")
+                    .route_name("Cancel")
+                    .input_type("crate::grpc::CancelRequest")
+                    .output_type("crate::grpc::CancelReply")
+                    .codec_path("tonic_prost::ProstCodec")
+                    .build(),
+            )
+            .build();
+        Builder::new().compile(&[service]);
+    }
+}
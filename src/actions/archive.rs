@@ -0,0 +1,382 @@
+//! 将匹配项写入 tar 归档的操作
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread::{self, JoinHandle};
+
+use flate2::write::GzEncoder;
+use log::{debug, info};
+use ruzstd::encoding::{CompressionLevel, FrameCompressor};
+
+use crate::errors::{FindError, FindResult};
+use crate::finder::archive::ArchiveFormat;
+use super::{relative_to_root, Action};
+
+/// 归档输出，压缩格式在写入时压缩
+enum ArchiveWriter {
+    Plain(BufWriter<File>),
+    Gz(GzEncoder<BufWriter<File>>),
+    Zst(ZstdWriter),
+}
+
+impl ArchiveWriter {
+    /// 写出压缩流的结尾并刷新文件
+    fn finish(self) -> io::Result<()> {
+        let mut file = match self {
+            ArchiveWriter::Plain(file) => file,
+            ArchiveWriter::Gz(encoder) => encoder.finish()?,
+            ArchiveWriter::Zst(encoder) => encoder.finish()?,
+        };
+        file.flush()
+    }
+}
+
+impl Write for ArchiveWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            ArchiveWriter::Plain(file) => file.write(buf),
+            ArchiveWriter::Gz(encoder) => encoder.write(buf),
+            ArchiveWriter::Zst(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            ArchiveWriter::Plain(file) => file.flush(),
+            ArchiveWriter::Gz(encoder) => encoder.flush(),
+            ArchiveWriter::Zst(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// 交给压缩线程的数据块大小
+const ZSTD_CHUNK_SIZE: usize = 128 * 1024;
+
+/// 以 zstd 压缩写入的数据
+///
+/// ruzstd 的压缩器从 `Read` 中拉取数据，因此在后台线程中压缩，
+/// 写入的数据按块通过有界通道交给压缩线程，内存占用与归档大小无关。
+struct ZstdWriter {
+    buffer: Vec<u8>,
+    sender: Option<SyncSender<Vec<u8>>>,
+    worker: Option<JoinHandle<io::Result<BufWriter<File>>>>,
+}
+
+impl ZstdWriter {
+    fn new(file: BufWriter<File>) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(4);
+        let worker = thread::spawn(move || {
+            let mut compressor = FrameCompressor::new(CompressionLevel::Fastest);
+            compressor.set_source(ChunkReader { receiver, chunk: Vec::new(), offset: 0 });
+            compressor.set_drain(CheckedWriter { inner: file, error: None });
+            compressor.compress();
+            let drain = compressor.take_drain().expect("压缩输出已设置");
+            match drain.error {
+                Some(e) => Err(e),
+                None => Ok(drain.inner),
+            }
+        });
+        Self {
+            buffer: Vec::with_capacity(ZSTD_CHUNK_SIZE),
+            sender: Some(sender),
+            worker: Some(worker),
+        }
+    }
+
+    /// 把缓冲的数据交给压缩线程
+    fn send(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(ZSTD_CHUNK_SIZE));
+        let sent = self.sender.as_ref().is_some_and(|sender| sender.send(chunk).is_ok());
+        if sent {
+            return Ok(());
+        }
+        // 压缩线程已经退出，返回它遇到的错误
+        Err(self.join().err().unwrap_or_else(|| io::Error::other("zstd 压缩已结束")))
+    }
+
+    /// 结束输入并等待压缩线程写完
+    fn join(&mut self) -> io::Result<BufWriter<File>> {
+        self.sender = None;
+        match self.worker.take() {
+            Some(worker) => worker
+                .join()
+                .unwrap_or_else(|_| Err(io::Error::other("zstd 压缩线程异常退出"))),
+            None => Err(io::Error::other("zstd 压缩已结束")),
+        }
+    }
+
+    /// 写出剩余的数据和压缩流的结尾
+    fn finish(mut self) -> io::Result<BufWriter<File>> {
+        self.send()?;
+        self.join()
+    }
+}
+
+impl Write for ZstdWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= ZSTD_CHUNK_SIZE {
+            self.send()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send()
+    }
+}
+
+/// 从通道中读取数据块，发送端关闭时视为输入结束
+struct ChunkReader {
+    receiver: Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    offset: usize,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.offset == self.chunk.len() {
+            match self.receiver.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.offset = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+        let len = buf.len().min(self.chunk.len() - self.offset);
+        buf[..len].copy_from_slice(&self.chunk[self.offset..self.offset + len]);
+        self.offset += len;
+        Ok(len)
+    }
+}
+
+/// 记录第一个写入错误的输出
+///
+/// 压缩器遇到写入错误时会 panic，这里保存错误并丢弃之后的数据，由压缩线程在结束时返回。
+struct CheckedWriter {
+    inner: BufWriter<File>,
+    error: Option<io::Error>,
+}
+
+impl Write for CheckedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.error.is_none() {
+            if let Err(e) = self.inner.write_all(buf) {
+                self.error = Some(e);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 将匹配项逐个写入 tar 归档（`--archive`）
+///
+/// 压缩格式由输出文件的扩展名决定：`.tar`、`.tar.gz`/`.tgz` 或 `.tar.zst`/`.tzst`。
+/// 归档中的名称是条目相对于搜索根目录的路径；搜索根目录是文件时使用文件名。
+/// 匹配的目录只加入目录本身，不加入其中的内容；符号链接作为链接加入。
+/// 归档在收到第一个匹配项时创建，没有匹配项时在结束时创建空归档。
+/// 演练模式下只输出将要加入的条目。
+pub struct ArchiveAction {
+    output: PathBuf,
+    format: ArchiveFormat,
+    roots: Vec<PathBuf>,
+    dry_run: bool,
+    builder: Option<tar::Builder<ArchiveWriter>>,
+    archived: usize,
+}
+
+impl ArchiveAction {
+    /// 创建归档操作
+    ///
+    /// # 参数
+    /// - `output`: 归档文件路径，已存在时被覆盖
+    /// - `roots`: 搜索根目录，用于计算归档中的名称
+    ///
+    /// # 错误
+    /// 如果无法从扩展名判断 tar 归档格式，返回 Other 错误
+    pub fn new(output: PathBuf, roots: Vec<PathBuf>) -> FindResult<Self> {
+        let format = match ArchiveFormat::detect(&output) {
+            Some(ArchiveFormat::Zip) | None => {
+                return Err(FindError::Other {
+                    message: format!(
+                        "无法识别的归档格式: {}（支持 .tar、.tar.gz、.tgz、.tar.zst 和 .tzst）",
+                        output.display()
+                    ),
+                    context: None,
+                    timestamp: std::time::SystemTime::now(),
+                })
+            }
+            Some(format) => format,
+        };
+        Ok(Self {
+            output,
+            format,
+            roots,
+            dry_run: false,
+            builder: None,
+            archived: 0,
+        })
+    }
+
+    /// 设置是否为演练模式
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// 获取已加入（或演练模式下将加入）归档的条目数
+    pub fn archived_count(&self) -> usize {
+        self.archived
+    }
+
+    /// 获取归档，第一次调用时创建归档文件
+    fn builder(&mut self) -> FindResult<&mut tar::Builder<ArchiveWriter>> {
+        if self.builder.is_none() {
+            let output_error = |source| FindError::FilesystemError { source, path: self.output.clone() };
+            let file = BufWriter::new(File::create(&self.output).map_err(output_error)?);
+            let writer = match self.format {
+                ArchiveFormat::TarGz => ArchiveWriter::Gz(GzEncoder::new(file, flate2::Compression::default())),
+                ArchiveFormat::TarZst => ArchiveWriter::Zst(ZstdWriter::new(file)),
+                _ => ArchiveWriter::Plain(file),
+            };
+            let mut builder = tar::Builder::new(writer);
+            builder.follow_symlinks(false);
+            self.builder = Some(builder);
+        }
+        Ok(self.builder.as_mut().expect("归档已创建"))
+    }
+
+    /// 是否为正在写入的归档文件本身
+    fn is_output(&self, path: &Path) -> bool {
+        match (path.canonicalize(), self.output.canonicalize()) {
+            (Ok(path), Ok(output)) => path == output,
+            _ => false,
+        }
+    }
+}
+
+impl Action for ArchiveAction {
+    fn run(&mut self, path: &Path) -> FindResult<()> {
+        let name = match relative_to_root(path, &self.roots) {
+            Some(relative) => relative.to_path_buf(),
+            // 搜索根目录本身是目录时跳过，是文件时使用文件名
+            None if path.is_dir() => {
+                debug!("跳过搜索根目录: {}", path.display());
+                return Ok(());
+            }
+            None => path.file_name().map(PathBuf::from).unwrap_or_else(|| path.to_path_buf()),
+        };
+        if self.is_output(path) {
+            return Ok(());
+        }
+
+        if self.dry_run {
+            println!("将归档: {} -> {}", path.display(), name.display());
+        } else {
+            let failed = |source| FindError::FilesystemError { source, path: path.to_path_buf() };
+            let metadata = fs::symlink_metadata(path).map_err(failed)?;
+            let builder = self.builder()?;
+            if metadata.is_dir() {
+                builder.append_dir(&name, path).map_err(failed)?;
+            } else {
+                builder.append_path_with_name(path, &name).map_err(failed)?;
+            }
+        }
+        self.archived += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> FindResult<()> {
+        if self.dry_run {
+            info!("将归档 {} 个条目", self.archived);
+            return Ok(());
+        }
+
+        // 没有匹配项时也创建归档
+        self.builder()?;
+        if let Some(builder) = self.builder.take() {
+            builder
+                .into_inner()
+                .and_then(ArchiveWriter::finish)
+                .map_err(|source| FindError::FilesystemError { source, path: self.output.clone() })?;
+        }
+        info!("已将 {} 个条目写入 {}", self.archived, self.output.display());
+        Ok(())
+    }
+
+    fn description(&self) -> String {
+        let mode = if self.dry_run { "（演练）" } else { "" };
+        format!("归档到 {}{}", self.output.display(), mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finder::archive::ArchiveFileSystem;
+    use crate::finder::FileSystem;
+    use std::io::Read;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_archive_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let src = dir.path().join("logs");
+        fs::create_dir_all(src.join("old"))?;
+        fs::write(src.join("old/a.log"), "a")?;
+        fs::write(src.join("b.log"), "b")?;
+        // 超过一个压缩数据块的文件
+        let big: Vec<u8> = (0..400_000u32).map(|i| (i * 7 % 251) as u8).collect();
+        fs::write(src.join("big.bin"), &big)?;
+
+        for name in ["out.tar", "out.tar.gz", "out.tar.zst"] {
+            let output = dir.path().join(name);
+            let mut action = ArchiveAction::new(output.clone(), vec![src.clone()])?;
+            action.run(&src)?;
+            action.run(&src.join("old/a.log"))?;
+            action.run(&src.join("b.log"))?;
+            action.run(&src.join("big.bin"))?;
+            action.finish()?;
+            assert_eq!(action.archived_count(), 3);
+
+            let archive = ArchiveFileSystem::open(&output)?;
+            let mut contents = String::new();
+            archive.open(Path::new("/old/a.log"))?.read_to_string(&mut contents)?;
+            assert_eq!(contents, "a");
+            assert_eq!(archive.symlink_metadata(Path::new("/b.log"))?.len, 1);
+            let mut contents = Vec::new();
+            archive.open(Path::new("/big.bin"))?.read_to_end(&mut contents)?;
+            assert!(contents == big);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_empty_and_dry_run() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let output = dir.path().join("empty.tgz");
+        ArchiveAction::new(output.clone(), Vec::new())?.finish()?;
+        assert!(ArchiveFileSystem::open(&output)?.read_dir(Path::new("/"))?.is_empty());
+
+        let output = dir.path().join("dry.tar");
+        let mut action = ArchiveAction::new(output.clone(), Vec::new())?.with_dry_run(true);
+        action.run(&dir.path().join("empty.tgz"))?;
+        action.finish()?;
+        assert!(!output.exists());
+        assert_eq!(action.description(), format!("归档到 {}（演练）", output.display()));
+
+        assert!(ArchiveAction::new(PathBuf::from("out.zip"), Vec::new()).is_err());
+        assert!(ArchiveAction::new(PathBuf::from("out.rar"), Vec::new()).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+//! 执行前逐项确认的操作
+
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use crate::errors::{FindError, FindResult};
+use super::Action;
+
+/// 对每个匹配项执行操作前在标准错误上询问，回答 y 时才执行
+///
+/// 与 GNU find 的 `-ok` 相同，提示形式为 `< 操作 路径 > ? `，
+/// 回答从标准输入读取；以 y、yes 或“是”开头的回答表示确认，其他回答和输入结束都表示跳过。
+///
+/// # 示例
+/// ```
+/// use std::path::Path;
+/// use rust_find::actions::{Action, ConfirmAction, ExecAction};
+///
+/// let exec = ExecAction::new(vec!["true".into()]).unwrap().with_dry_run(true);
+/// let mut action = ConfirmAction::new(Box::new(exec), "true").with_input(&b"n\n"[..]);
+/// action.run(Path::new("file.txt")).unwrap();
+/// assert_eq!(action.confirmed_count(), 0);
+/// ```
+pub struct ConfirmAction {
+    inner: Box<dyn Action>,
+    label: String,
+    input: Box<dyn BufRead>,
+    confirmed: usize,
+}
+
+impl ConfirmAction {
+    /// 创建确认操作
+    ///
+    /// # 参数
+    /// - `inner`: 确认后执行的操作
+    /// - `label`: 提示中显示的操作名称
+    pub fn new(inner: Box<dyn Action>, label: impl Into<String>) -> Self {
+        Self {
+            inner,
+            label: label.into(),
+            input: Box::new(io::BufReader::new(io::stdin())),
+            confirmed: 0,
+        }
+    }
+
+    /// 设置读取回答的输入（默认为标准输入）
+    pub fn with_input(mut self, input: impl BufRead + 'static) -> Self {
+        self.input = Box::new(input);
+        self
+    }
+
+    /// 获取已确认执行的条目数
+    pub fn confirmed_count(&self) -> usize {
+        self.confirmed
+    }
+
+    /// 输出提示并读取回答
+    fn ask(&mut self, path: &Path) -> FindResult<bool> {
+        let io_error = |source| FindError::ActionFailed {
+            path: path.to_path_buf(),
+            message: format!("无法读取确认: {}", source),
+        };
+
+        let mut stderr = io::stderr().lock();
+        write!(stderr, "< {} {} > ? ", self.label, path.display())
+            .and_then(|_| stderr.flush())
+            .map_err(io_error)?;
+
+        let mut answer = String::new();
+        if self.input.read_line(&mut answer).map_err(io_error)? == 0 {
+            // 输入已结束，后续提示都无法得到回答
+            writeln!(stderr).map_err(io_error)?;
+            return Ok(false);
+        }
+        Ok(is_yes(&answer))
+    }
+}
+
+impl Action for ConfirmAction {
+    fn run(&mut self, path: &Path) -> FindResult<()> {
+        if !self.ask(path)? {
+            return Ok(());
+        }
+        self.confirmed += 1;
+        self.inner.run(path)
+    }
+
+    fn finish(&mut self) -> FindResult<()> {
+        self.inner.finish()
+    }
+
+    fn description(&self) -> String {
+        format!("{}（逐项确认）", self.inner.description())
+    }
+}
+
+/// 检查回答是否表示确认
+fn is_yes(answer: &str) -> bool {
+    let answer = answer.trim_start();
+    answer.starts_with(['y', 'Y']) || answer.starts_with('是')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    /// 记录收到的路径的操作
+    struct Recorder(Rc<RefCell<Vec<PathBuf>>>);
+
+    impl Action for Recorder {
+        fn run(&mut self, path: &Path) -> FindResult<()> {
+            self.0.borrow_mut().push(path.to_path_buf());
+            Ok(())
+        }
+
+        fn description(&self) -> String {
+            "record".to_string()
+        }
+    }
+
+    #[test]
+    fn test_confirm_runs_only_confirmed_paths() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut action = ConfirmAction::new(Box::new(Recorder(seen.clone())), "record")
+            .with_input(&b"y\nno\n  Yes\n\xe6\x98\xaf\n"[..]);
+        for name in ["a", "b", "c", "d", "e"] {
+            action.run(Path::new(name)).unwrap();
+        }
+
+        // 输入结束后的条目都被跳过
+        assert_eq!(*seen.borrow(), [PathBuf::from("a"), PathBuf::from("c"), PathBuf::from("d")]);
+        assert_eq!(action.confirmed_count(), 3);
+        assert_eq!(action.description(), "record（逐项确认）");
+    }
+
+    #[test]
+    fn test_is_yes() {
+        assert!(is_yes("y\n"));
+        assert!(is_yes("YES"));
+        assert!(!is_yes("\n"));
+        assert!(!is_yes("n"));
+        assert!(!is_yes("ok"));
+    }
+}
@@ -0,0 +1,201 @@
+//! 删除匹配项的操作
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+
+use crate::errors::{FindError, FindResult};
+use super::{Action, ProtectedPaths};
+
+/// 删除匹配的文件（以及可选的空目录）
+///
+/// 为了保证安全：
+/// - 搜索根目录本身永远不会被删除
+/// - 目录只有在启用后才会删除，并且只删除空目录
+/// - 目录在所有文件处理完后按从深到浅的顺序删除
+/// - 演练模式下只输出将要删除的路径
+pub struct DeleteAction {
+    include_dirs: bool,
+    dry_run: bool,
+    protected: ProtectedPaths,
+    pending_dirs: Vec<PathBuf>,
+    deleted: usize,
+}
+
+impl DeleteAction {
+    /// 创建删除操作
+    ///
+    /// # 参数
+    /// - `protected`: 不允许删除的路径（通常是搜索根目录）
+    pub fn new(protected: Vec<PathBuf>) -> Self {
+        Self {
+            include_dirs: false,
+            dry_run: false,
+            protected: ProtectedPaths::new(protected),
+            pending_dirs: Vec::new(),
+            deleted: 0,
+        }
+    }
+
+    /// 设置是否删除匹配的空目录
+    pub fn with_include_dirs(mut self, include_dirs: bool) -> Self {
+        self.include_dirs = include_dirs;
+        self
+    }
+
+    /// 设置是否为演练模式
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// 获取已删除（或演练模式下将删除）的条目数
+    pub fn deleted_count(&self) -> usize {
+        self.deleted
+    }
+
+    /// 删除单个条目
+    fn remove(&mut self, path: &Path, is_dir: bool) -> FindResult<()> {
+        if self.dry_run {
+            println!("将删除: {}", path.display());
+        } else {
+            let result = if is_dir {
+                fs::remove_dir(path)
+            } else {
+                fs::remove_file(path)
+            };
+            result.map_err(|e| FindError::FilesystemError {
+                source: e,
+                path: path.to_path_buf(),
+            })?;
+        }
+        self.deleted += 1;
+        Ok(())
+    }
+}
+
+impl Action for DeleteAction {
+    fn run(&mut self, path: &Path) -> FindResult<()> {
+        if self.protected.contains(path) {
+            warn!("跳过搜索根目录: {}", path.display());
+            return Ok(());
+        }
+
+        let metadata = fs::symlink_metadata(path).map_err(|e| FindError::FilesystemError {
+            source: e,
+            path: path.to_path_buf(),
+        })?;
+
+        if metadata.is_dir() {
+            if self.include_dirs {
+                self.pending_dirs.push(path.to_path_buf());
+            }
+            return Ok(());
+        }
+
+        self.remove(path, false)
+    }
+
+    fn finish(&mut self) -> FindResult<()> {
+        // 深层目录先删除，使父目录有机会变为空目录
+        let mut dirs = std::mem::take(&mut self.pending_dirs);
+        dirs.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+
+        let mut first_error = None;
+        for dir in dirs {
+            if let Err(e) = self.remove(&dir, true) {
+                warn!("{}", e);
+                first_error.get_or_insert(e);
+            }
+        }
+
+        info!("已删除 {} 个条目", self.deleted);
+        first_error.map_or(Ok(()), Err)
+    }
+
+    fn description(&self) -> String {
+        match (self.include_dirs, self.dry_run) {
+            (true, true) => "删除文件和空目录（演练）".to_string(),
+            (true, false) => "删除文件和空目录".to_string(),
+            (false, true) => "删除文件（演练）".to_string(),
+            (false, false) => "删除文件".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_delete_files_only() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let sub = dir.path().join("sub");
+        fs::create_dir(&sub)?;
+        let file = sub.join("a.tmp");
+        fs::write(&file, "x")?;
+
+        let mut action = DeleteAction::new(vec![dir.path().to_path_buf()]);
+        action.run(&file)?;
+        action.run(&sub)?;
+        action.finish()?;
+
+        assert!(!file.exists());
+        assert!(sub.exists());
+        assert_eq!(action.deleted_count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_empty_dirs_deepest_first() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let outer = dir.path().join("outer");
+        let inner = outer.join("inner");
+        fs::create_dir_all(&inner)?;
+
+        let mut action = DeleteAction::new(vec![dir.path().to_path_buf()]).with_include_dirs(true);
+        action.run(&outer)?;
+        action.run(&inner)?;
+        action.finish()?;
+
+        assert!(!outer.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_never_removes_root() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let mut action = DeleteAction::new(vec![dir.path().to_path_buf()]).with_include_dirs(true);
+        action.run(dir.path())?;
+        action.finish()?;
+        assert!(dir.path().exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_dry_run_keeps_files() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let file = dir.path().join("a.tmp");
+        fs::write(&file, "x")?;
+
+        let mut action = DeleteAction::new(Vec::new()).with_dry_run(true);
+        action.run(&file)?;
+        action.finish()?;
+
+        assert!(file.exists());
+        assert_eq!(action.deleted_count(), 1);
+        assert_eq!(action.description(), "删除文件（演练）");
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_reports_failures() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let missing = dir.path().join("missing.tmp");
+        let mut action = DeleteAction::new(Vec::new());
+        assert!(matches!(action.run(&missing), Err(FindError::FilesystemError { .. })));
+        Ok(())
+    }
+}
@@ -0,0 +1,448 @@
+//! 执行外部命令的操作
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::errors::{FindError, FindResult};
+use super::Action;
+
+/// 命令中的路径占位符
+const PLACEHOLDER: &str = "{}";
+
+/// 批量执行的结束符（`{} +`）
+const BATCH_TERMINATOR: &str = "+";
+
+/// 为环境变量变化等预留的参数空间（与 xargs 相同）
+const ARG_HEADROOM: usize = 2048;
+
+/// 对每个匹配项执行外部命令
+///
+/// 命令参数中的 `{}` 会被替换为匹配的路径；与 GNU find 相同，没有占位符时按原样执行命令，不追加路径。
+/// 如果命令以 `{} +` 结尾，则进入批量模式：多个匹配项会追加到同一次命令调用中，
+/// 每批的大小保证不超过操作系统的参数长度限制。
+///
+/// # 示例
+/// ```
+/// use rust_find::actions::ExecAction;
+///
+/// // 等价于 `--exec wc -l {} ;`
+/// let action = ExecAction::new(vec!["wc".to_string(), "-l".to_string(), "{}".to_string()]).unwrap();
+///
+/// // 等价于 `--exec wc -l {} +`，批量执行
+/// let batched = ExecAction::new(vec!["wc".into(), "-l".into(), "{}".into(), "+".into()]).unwrap();
+/// assert!(batched.is_batched());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExecAction {
+    command: Vec<String>,
+    in_parent_dir: bool,
+    dry_run: bool,
+    batch: Option<Batch>,
+}
+
+/// 批量模式下等待执行的匹配项
+#[derive(Debug, Clone)]
+struct Batch {
+    /// 单次调用允许的参数总大小
+    limit: usize,
+    /// 命令本身占用的参数大小
+    base_size: usize,
+    /// 等待执行的参数及其对应的路径
+    pending: Vec<(PathBuf, PathBuf)>,
+    /// 等待执行的参数占用的大小
+    pending_size: usize,
+    /// 等待执行的匹配项所在目录（仅 `--execdir`）
+    work_dir: Option<PathBuf>,
+}
+
+impl ExecAction {
+    /// 创建在当前目录中执行命令的操作（`--exec`）
+    ///
+    /// # 错误
+    /// 如果命令为空，或批量模式中 `{}` 没有紧接在 `+` 之前，返回Other错误
+    pub fn new(mut command: Vec<String>) -> FindResult<Self> {
+        if command.is_empty() {
+            return Err(invalid_command("命令不能为空"));
+        }
+
+        let batch = if command.last().map(String::as_str) == Some(BATCH_TERMINATOR)
+            && command.len() >= 3
+            && command[command.len() - 2] == PLACEHOLDER
+        {
+            command.truncate(command.len() - 2);
+            if command.iter().any(|arg| arg.contains(PLACEHOLDER)) {
+                return Err(invalid_command("批量模式下 {} 只能出现在 + 之前"));
+            }
+            let base_size = command.iter().map(|arg| arg_size(arg.len())).sum();
+            Some(Batch {
+                limit: max_args_size(),
+                base_size,
+                pending: Vec::new(),
+                pending_size: 0,
+                work_dir: None,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            command,
+            in_parent_dir: false,
+            dry_run: false,
+            batch,
+        })
+    }
+
+    /// 创建在匹配项所在目录中执行命令的操作（`--execdir`）
+    ///
+    /// 与 GNU find 的 `-execdir` 相同，命令的工作目录为匹配项的父目录，
+    /// 占位符被替换为 `./文件名`，避免相对命令和路径注入问题。
+    /// 批量模式下，同一目录中的匹配项合并到一次调用中。
+    pub fn new_in_parent_dir(command: Vec<String>) -> FindResult<Self> {
+        let mut action = Self::new(command)?;
+        action.in_parent_dir = true;
+        Ok(action)
+    }
+
+    /// 设置是否为演练模式：只输出将要执行的命令，不实际执行
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// 是否为批量模式（`{} +`）
+    pub fn is_batched(&self) -> bool {
+        self.batch.is_some()
+    }
+
+    /// 计算命令的工作目录和用于替换占位符的路径
+    fn resolve(&self, path: &Path) -> (Option<PathBuf>, PathBuf) {
+        if self.in_parent_dir {
+            let parent = match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                _ => PathBuf::from("."),
+            };
+            let name = path.file_name().map(PathBuf::from).unwrap_or_else(|| path.to_path_buf());
+            (Some(parent), Path::new(".").join(name))
+        } else {
+            (None, path.to_path_buf())
+        }
+    }
+
+    /// 构建针对指定路径的命令
+    fn build_command(&self, path: &Path) -> Command {
+        let (work_dir, arg_path) = self.resolve(path);
+
+        let mut command = Command::new(&self.command[0]);
+        for arg in &self.command[1..] {
+            if arg.contains(PLACEHOLDER) {
+                command.arg(substitute(arg, &arg_path));
+            } else {
+                command.arg(arg);
+            }
+        }
+
+        if let Some(dir) = work_dir {
+            command.current_dir(dir);
+        }
+
+        command
+    }
+
+    /// 执行命令并检查退出状态
+    fn execute(&self, mut command: Command, path: &Path) -> FindResult<()> {
+        if self.dry_run {
+            println!("将执行: {}", command_line(&command));
+            return Ok(());
+        }
+
+        let status = command.status().map_err(|e| FindError::ActionFailed {
+            path: path.to_path_buf(),
+            message: format!("无法执行命令 '{}': {}", self.command[0], e),
+        })?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(FindError::ActionFailed {
+                path: path.to_path_buf(),
+                message: format!("命令 '{}' 返回 {}", self.command[0], status),
+            })
+        }
+    }
+
+    /// 执行等待中的批量命令
+    fn flush_batch(&mut self) -> FindResult<()> {
+        let Some(batch) = self.batch.as_mut() else {
+            return Ok(());
+        };
+        if batch.pending.is_empty() {
+            return Ok(());
+        }
+
+        let pending = std::mem::take(&mut batch.pending);
+        batch.pending_size = 0;
+        let work_dir = batch.work_dir.take();
+
+        let mut command = Command::new(&self.command[0]);
+        command.args(&self.command[1..]);
+        command.args(pending.iter().map(|(arg, _)| arg));
+        if let Some(dir) = work_dir {
+            command.current_dir(dir);
+        }
+
+        // 报告批次中的第一个路径作为错误位置
+        self.execute(command, &pending[0].1)
+    }
+}
+
+/// 将参数中的每个 `{}` 替换为路径，不是 UTF-8 的路径保持原样
+fn substitute(arg: &str, path: &Path) -> OsString {
+    let mut result = OsString::with_capacity(arg.len() + path.as_os_str().len());
+    let mut parts = arg.split(PLACEHOLDER);
+    if let Some(first) = parts.next() {
+        result.push(first);
+    }
+    for part in parts {
+        result.push(path.as_os_str());
+        result.push(part);
+    }
+    result
+}
+
+impl Action for ExecAction {
+    fn run(&mut self, path: &Path) -> FindResult<()> {
+        if self.batch.is_none() {
+            let command = self.build_command(path);
+            return self.execute(command, path);
+        }
+
+        let (work_dir, arg_path) = self.resolve(path);
+        let size = arg_size(arg_path.as_os_str().len());
+
+        let mut result = Ok(());
+        if let Some(batch) = &self.batch {
+            let dir_changed = !batch.pending.is_empty() && batch.work_dir != work_dir;
+            let too_large = !batch.pending.is_empty()
+                && batch.base_size + batch.pending_size + size > batch.limit;
+            if dir_changed || too_large {
+                result = self.flush_batch();
+            }
+        }
+
+        if let Some(batch) = self.batch.as_mut() {
+            batch.work_dir = work_dir;
+            batch.pending_size += size;
+            batch.pending.push((arg_path, path.to_path_buf()));
+        }
+
+        result
+    }
+
+    fn finish(&mut self) -> FindResult<()> {
+        self.flush_batch()
+    }
+
+    fn description(&self) -> String {
+        let name = if self.in_parent_dir { "在所在目录中执行" } else { "执行" };
+        let terminator = if self.is_batched() { " {} +" } else { "" };
+        let mode = if self.dry_run { "（演练）" } else { "" };
+        format!("{} {}{}{}", name, self.command.join(" "), terminator, mode)
+    }
+}
+
+/// 构造无效命令错误
+fn invalid_command(message: &str) -> FindError {
+    FindError::Other {
+        message: message.to_string(),
+        context: None,
+        timestamp: std::time::SystemTime::now(),
+    }
+}
+
+/// 将命令格式化为便于阅读的命令行，用于演练模式输出
+fn command_line(command: &Command) -> String {
+    let mut line = command.get_program().to_string_lossy().into_owned();
+    for arg in command.get_args() {
+        let arg = arg.to_string_lossy();
+        line.push(' ');
+        if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == '\'' || c == '"') {
+            line.push_str(&format!("'{}'", arg.replace('\'', "'\\''")));
+        } else {
+            line.push_str(&arg);
+        }
+    }
+    if let Some(dir) = command.get_current_dir() {
+        line.push_str(&format!(" (在 {} 中)", dir.display()));
+    }
+    line
+}
+
+/// 单个参数在参数区中占用的空间：字符串本身、结束符和指针
+fn arg_size(len: usize) -> usize {
+    len + 1 + std::mem::size_of::<usize>()
+}
+
+/// 单次命令调用允许的参数总大小
+///
+/// 基于系统的 ARG_MAX，扣除当前环境变量占用的空间和预留空间。
+fn max_args_size() -> usize {
+    let env_size: usize = std::env::vars_os()
+        .map(|(key, value)| arg_size(key.len() + value.len() + 1))
+        .sum();
+    system_arg_max()
+        .saturating_sub(env_size)
+        .saturating_sub(ARG_HEADROOM)
+        .max(ARG_HEADROOM)
+}
+
+/// 系统允许的参数和环境变量总大小
+#[cfg(unix)]
+fn system_arg_max() -> usize {
+    // SAFETY: sysconf 只读取系统配置，没有内存安全方面的前置条件
+    let arg_max = unsafe { libc::sysconf(libc::_SC_ARG_MAX) };
+    if arg_max > 0 {
+        arg_max as usize
+    } else {
+        // POSIX 规定的最小值
+        4096
+    }
+}
+
+/// 系统允许的参数和环境变量总大小
+#[cfg(not(unix))]
+fn system_arg_max() -> usize {
+    // Windows 命令行长度限制为 32767 个字符
+    32767
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command: &Command) -> Vec<String> {
+        command.get_args().map(|a| a.to_string_lossy().into_owned()).collect()
+    }
+
+    #[test]
+    fn test_exec_replaces_placeholder() {
+        let action = ExecAction::new(vec!["echo".into(), "file={}".into()]).unwrap();
+        let command = action.build_command(Path::new("dir/a.txt"));
+        assert_eq!(args(&command), vec!["file=dir/a.txt"]);
+        assert_eq!(command.get_current_dir(), None);
+    }
+
+    #[test]
+    fn test_exec_without_placeholder_runs_as_written() {
+        let action = ExecAction::new(vec!["echo".into(), "ARGS".into()]).unwrap();
+        let command = action.build_command(Path::new("a/x.txt"));
+        assert_eq!(args(&command), vec!["ARGS"]);
+
+        let action = ExecAction::new_in_parent_dir(vec!["pwd".into()]).unwrap();
+        let command = action.build_command(Path::new("a/x.txt"));
+        assert!(args(&command).is_empty());
+        assert_eq!(command.get_current_dir(), Some(Path::new("a")));
+    }
+
+    #[test]
+    fn test_execdir_runs_in_parent() {
+        let action = ExecAction::new_in_parent_dir(vec!["echo".into(), "{}".into()]).unwrap();
+        let command = action.build_command(Path::new("dir/sub/a.txt"));
+        assert_eq!(args(&command), vec!["./a.txt"]);
+        assert_eq!(command.get_current_dir(), Some(Path::new("dir/sub")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exec_keeps_non_utf8_path() -> Result<(), Box<dyn std::error::Error>> {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let action = ExecAction::new(vec!["cp".into(), "{}".into(), "{}.bak".into()])?;
+        let path = Path::new(OsStr::from_bytes(b"dir/bad\xff.txt"));
+        let command = action.build_command(path);
+        let args: Vec<&OsStr> = command.get_args().collect();
+        assert_eq!(args, [OsStr::from_bytes(b"dir/bad\xff.txt"), OsStr::from_bytes(b"dir/bad\xff.txt.bak")]);
+
+        // 部分文件系统（如 APFS）不接受非 UTF-8 的文件名
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join(OsStr::from_bytes(b"bad\xff.txt"));
+        if std::fs::write(&file, "x").is_ok() {
+            let mut action = action;
+            action.run(&file)?;
+            assert!(dir.path().join(OsStr::from_bytes(b"bad\xff.txt.bak")).exists());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_exec_empty_command() {
+        assert!(ExecAction::new(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_exec_batch_detection() {
+        let action = ExecAction::new(vec!["rm".into(), "{}".into(), "+".into()]).unwrap();
+        assert!(action.is_batched());
+        assert_eq!(action.description(), "执行 rm {} +");
+
+        // `+` 不紧跟在 `{}` 之后时按普通参数处理
+        let action = ExecAction::new(vec!["echo".into(), "+".into()]).unwrap();
+        assert!(!action.is_batched());
+
+        assert!(ExecAction::new(vec!["cp".into(), "{}".into(), "{}".into(), "+".into()]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exec_batch_respects_limit() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let log = dir.path().join("calls.log");
+        let script = format!("echo $# >> {}", log.display());
+        let mut action = ExecAction::new(vec![
+            "sh".into(), "-c".into(), script, "sh".into(), "{}".into(), "+".into(),
+        ])?;
+        // 将限制设置为大约能容纳三个参数
+        if let Some(batch) = action.batch.as_mut() {
+            batch.limit = batch.base_size + 3 * arg_size("file00".len());
+        }
+
+        for i in 0..10 {
+            action.run(Path::new(&format!("file{:02}", i)))?;
+        }
+        action.finish()?;
+
+        let calls = std::fs::read_to_string(&log)?;
+        let counts: Vec<usize> = calls.lines().map(|l| l.trim().parse().unwrap()).collect();
+        assert_eq!(counts, vec![3, 3, 3, 1]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_exec_dry_run_formats_command() -> Result<(), Box<dyn std::error::Error>> {
+        let mut action = ExecAction::new_in_parent_dir(vec!["rm".into(), "{}".into()])?
+            .with_dry_run(true);
+        let command = action.build_command(Path::new("dir/a b.txt"));
+        assert_eq!(command_line(&command), "rm './a b.txt' (在 dir 中)");
+
+        // 演练模式不执行命令，因此不存在的命令也不会失败
+        let mut missing = ExecAction::new(vec!["no-such-command-xyz".into()])?.with_dry_run(true);
+        missing.run(Path::new("a.txt"))?;
+        action.run(Path::new("dir/a b.txt"))?;
+        assert_eq!(missing.description(), "执行 no-such-command-xyz（演练）");
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exec_reports_failure() {
+        let mut action = ExecAction::new(vec!["false".into()]).unwrap();
+        assert!(matches!(
+            action.run(Path::new("x")),
+            Err(FindError::ActionFailed { .. })
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+//! 对匹配结果执行的操作
+//!
+//! 本模块定义了操作接口以及具体的操作实现，包括：
+//! - 执行外部命令（`--exec` / `--execdir`，支持 `{} +` 批量执行）
+//! - 逐项确认后执行（`--ok`，以及 `--delete --interactive`）
+//! - 删除匹配项（`--delete`）
+//! - 移至回收站（`--trash`）
+//! - 按模板重命名（`--rename`）
+//! - 复制或移动到目标目录（`--copy-to` / `--move-to`）
+//! - 写入 tar 归档（`--archive`）
+//! - 修改权限和属主（`--chmod` / `--chown`）
+//!
+//! 所有操作都支持演练模式（`--dry-run`）：只输出将要执行的操作，不修改文件系统。
+
+mod archive;
+mod confirm;
+mod delete;
+mod exec;
+mod permissions;
+mod rename;
+mod transfer;
+mod trash;
+
+use std::path::{Path, PathBuf};
+
+use crate::errors::FindResult;
+
+pub use archive::ArchiveAction;
+pub use confirm::ConfirmAction;
+pub use delete::DeleteAction;
+pub use exec::ExecAction;
+pub use permissions::{ChangeSummary, ChmodAction, ChownAction, ModeChange, OwnerSpec};
+pub use rename::{RenameAction, RenameTemplate};
+pub use transfer::{TransferAction, TransferMode};
+pub use trash::TrashAction;
+
+/// 操作trait
+///
+/// 定义对每个匹配结果执行的操作
+pub trait Action {
+    /// 对单个匹配结果执行操作
+    ///
+    /// # 参数
+    /// - `path`: 匹配的路径
+    ///
+    /// # 错误
+    /// 操作失败时返回错误，调用方可以记录错误并继续处理其他结果
+    fn run(&mut self, path: &Path) -> FindResult<()>;
+
+    /// 在所有结果处理完后调用，用于完成延迟的工作
+    fn finish(&mut self) -> FindResult<()> {
+        Ok(())
+    }
+
+    /// 获取操作描述
+    fn description(&self) -> String;
+}
+
+/// 路径相对于所在搜索根目录的部分
+///
+/// 路径可能位于多个嵌套的搜索根目录下，此时取最深的一个。
+/// 路径不在任何搜索根目录下，或者就是搜索根目录本身时返回 None。
+pub(crate) fn relative_to_root<'a>(path: &'a Path, roots: &[PathBuf]) -> Option<&'a Path> {
+    roots
+        .iter()
+        .filter_map(|root| path.strip_prefix(root).ok())
+        .min_by_key(|relative| relative.components().count())
+        .filter(|relative| !relative.as_os_str().is_empty())
+}
+
+/// 不允许被破坏性操作修改的路径集合（通常是搜索根目录）
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProtectedPaths {
+    paths: Vec<PathBuf>,
+}
+
+impl ProtectedPaths {
+    /// 创建路径集合，同时记录路径的规范形式
+    pub(crate) fn new(paths: Vec<PathBuf>) -> Self {
+        let paths = paths
+            .into_iter()
+            .flat_map(|path| {
+                let canonical = path.canonicalize().ok();
+                std::iter::once(path).chain(canonical)
+            })
+            .collect();
+        Self { paths }
+    }
+
+    /// 检查路径是否受保护
+    pub(crate) fn contains(&self, path: &Path) -> bool {
+        self.paths.iter().any(|p| p == path)
+            || path
+                .canonicalize()
+                .map(|canonical| self.paths.contains(&canonical))
+                .unwrap_or(false)
+    }
+}
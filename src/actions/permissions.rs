@@ -0,0 +1,469 @@
+//! 修改匹配项权限和属主的操作
+
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use log::debug;
+
+use crate::errors::{FindError, FindResult};
+use crate::output::meta;
+use super::Action;
+
+/// 属主和属组的权限位（包括 setuid/setgid）以及其他用户的权限位（包括粘滞位）
+const USER_BITS: u32 = 0o4700;
+const GROUP_BITS: u32 = 0o2070;
+const OTHER_BITS: u32 = 0o1007;
+
+/// 与 chmod 相同的权限修改，可以是八进制数字或符号形式
+///
+/// 符号形式由逗号分隔的子句组成，每个子句为 `[ugoa]*[+-=][rwxXst]*`，
+/// 例如 `u+x`、`go-w` 或 `a=r,u+w`。未指定 `ugoa` 时等同于 `a`（不考虑 umask）。
+/// `X` 只对目录和已有执行权限的文件添加执行权限。
+///
+/// # 示例
+/// ```
+/// use rust_find::actions::ModeChange;
+///
+/// let change: ModeChange = "go-w,u+x".parse().unwrap();
+/// assert_eq!(change.apply(0o666, false), 0o744);
+/// assert_eq!("750".parse::<ModeChange>().unwrap().apply(0o644, false), 0o750);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModeChange {
+    spec: String,
+    kind: ModeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ModeKind {
+    /// 八进制形式，直接替换权限位
+    Absolute(u32),
+    /// 符号形式的子句
+    Symbolic(Vec<Clause>),
+}
+
+/// 符号形式中的单个子句
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Clause {
+    /// 作用的位（由 ugoa 决定）
+    who: u32,
+    op: char,
+    /// 读写执行和特殊位，`X` 单独记录
+    perms: u32,
+    conditional_exec: bool,
+}
+
+impl ModeChange {
+    /// 计算修改后的权限位
+    ///
+    /// # 参数
+    /// - `mode`: 当前的权限位
+    /// - `is_dir`: 条目是否为目录（影响 `X`）
+    pub fn apply(&self, mode: u32, is_dir: bool) -> u32 {
+        let mut mode = mode & 0o7777;
+        let clauses = match &self.kind {
+            ModeKind::Absolute(bits) => return *bits,
+            ModeKind::Symbolic(clauses) => clauses,
+        };
+        for clause in clauses {
+            let mut perms = clause.perms;
+            if clause.conditional_exec && (is_dir || mode & 0o111 != 0) {
+                perms |= 0o111;
+            }
+            let bits = perms & clause.who;
+            mode = match clause.op {
+                '+' => mode | bits,
+                '-' => mode & !bits,
+                _ => (mode & !clause.who) | bits,
+            };
+        }
+        mode
+    }
+}
+
+impl FromStr for ModeChange {
+    type Err = FindError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let invalid = || FindError::PatternError {
+            message: format!("Invalid mode '{}', expected octal MODE or [ugoa]*[+-=][rwxXst]*", spec),
+        };
+
+        if !spec.is_empty() && spec.bytes().all(|b| b.is_ascii_digit()) {
+            let bits = u32::from_str_radix(spec, 8).ok().filter(|bits| *bits <= 0o7777).ok_or_else(invalid)?;
+            return Ok(Self { spec: spec.to_string(), kind: ModeKind::Absolute(bits) });
+        }
+
+        let mut clauses = Vec::new();
+        for part in spec.split(',') {
+            let op_index = part.find(['+', '-', '=']).ok_or_else(invalid)?;
+            let mut who = 0;
+            for c in part[..op_index].chars() {
+                who |= match c {
+                    'u' => USER_BITS,
+                    'g' => GROUP_BITS,
+                    'o' => OTHER_BITS,
+                    'a' => USER_BITS | GROUP_BITS | OTHER_BITS,
+                    _ => return Err(invalid()),
+                };
+            }
+            if who == 0 {
+                who = USER_BITS | GROUP_BITS | OTHER_BITS;
+            }
+
+            // 同一子句中可以有多个操作，例如 u+r-w
+            let mut rest = &part[op_index..];
+            while let Some(op) = rest.chars().next() {
+                let end = rest[1..].find(['+', '-', '=']).map_or(rest.len(), |i| i + 1);
+                let mut clause = Clause { who, op, perms: 0, conditional_exec: false };
+                for c in rest[1..end].chars() {
+                    match c {
+                        'r' => clause.perms |= 0o444,
+                        'w' => clause.perms |= 0o222,
+                        'x' => clause.perms |= 0o111,
+                        'X' => clause.conditional_exec = true,
+                        's' => clause.perms |= 0o6000,
+                        't' => clause.perms |= 0o1000,
+                        _ => return Err(invalid()),
+                    }
+                }
+                clauses.push(clause);
+                rest = &rest[end..];
+            }
+        }
+        Ok(Self { spec: spec.to_string(), kind: ModeKind::Symbolic(clauses) })
+    }
+}
+
+impl std::fmt::Display for ModeChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.spec)
+    }
+}
+
+/// 操作结果的计数，用于结束时的汇总
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChangeSummary {
+    /// 已修改（演练模式下将修改）的条目数
+    pub changed: usize,
+    /// 已经符合要求、无需修改的条目数
+    pub unchanged: usize,
+    /// 修改失败的条目数
+    pub failed: usize,
+}
+
+impl ChangeSummary {
+    /// 记录单个条目的结果
+    fn record(&mut self, result: FindResult<bool>) -> FindResult<()> {
+        match result {
+            Ok(true) => self.changed += 1,
+            Ok(false) => self.unchanged += 1,
+            Err(e) => {
+                self.failed += 1;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// 在标准错误上输出汇总，有条目修改失败时返回错误
+    fn report(&self, name: &str, dry_run: bool) -> FindResult<()> {
+        let verb = if dry_run { "将修改" } else { "已修改" };
+        eprintln!(
+            "{}: {} {} 个条目，{} 个无需修改，{} 个失败",
+            name, verb, self.changed, self.unchanged, self.failed
+        );
+        if self.failed > 0 {
+            return Err(FindError::Other {
+                message: format!("{}: {} 个条目修改失败", name, self.failed),
+                context: None,
+                timestamp: std::time::SystemTime::now(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// 修改匹配项的权限位（`--chmod`）
+///
+/// 符号链接本身没有独立的权限位，会被跳过而不是修改链接指向的文件。
+/// 每个失败的条目单独报告，结束时在标准错误上输出修改、无需修改和失败的数量，有失败的条目时返回错误。
+pub struct ChmodAction {
+    mode: ModeChange,
+    dry_run: bool,
+    summary: ChangeSummary,
+}
+
+impl ChmodAction {
+    /// 创建修改权限的操作
+    pub fn new(mode: ModeChange) -> Self {
+        Self { mode, dry_run: false, summary: ChangeSummary::default() }
+    }
+
+    /// 设置是否为演练模式
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// 获取目前为止的结果计数
+    pub fn summary(&self) -> ChangeSummary {
+        self.summary
+    }
+
+    /// 修改单个条目，返回是否需要修改
+    fn change(&self, path: &Path) -> FindResult<bool> {
+        let failed = |source| FindError::FilesystemError { source, path: path.to_path_buf() };
+        let metadata = fs::symlink_metadata(path).map_err(failed)?;
+        if metadata.file_type().is_symlink() {
+            debug!("跳过符号链接: {}", path.display());
+            return Ok(false);
+        }
+
+        let current = meta::mode_bits(&metadata) & 0o7777;
+        let mode = self.mode.apply(current, metadata.is_dir());
+        if mode == current {
+            return Ok(false);
+        }
+        if self.dry_run {
+            println!("将修改权限: {} {:04o} -> {:04o}", path.display(), current, mode);
+            return Ok(true);
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(mode)).map_err(failed)?;
+        }
+        #[cfg(not(unix))]
+        {
+            // 只能修改只读属性
+            let mut permissions = metadata.permissions();
+            permissions.set_readonly(mode & 0o222 == 0);
+            fs::set_permissions(path, permissions).map_err(failed)?;
+        }
+        Ok(true)
+    }
+}
+
+impl Action for ChmodAction {
+    fn run(&mut self, path: &Path) -> FindResult<()> {
+        let result = self.change(path);
+        self.summary.record(result)
+    }
+
+    fn finish(&mut self) -> FindResult<()> {
+        self.summary.report("chmod", self.dry_run)
+    }
+
+    fn description(&self) -> String {
+        let mode = if self.dry_run { "（演练）" } else { "" };
+        format!("修改权限为 {}{}", self.mode, mode)
+    }
+}
+
+/// `USER[:GROUP]` 形式的属主设置，用户或组可以是名称或数字 ID
+///
+/// # 示例
+/// ```
+/// use rust_find::actions::OwnerSpec;
+///
+/// let owner: OwnerSpec = "0:0".parse().unwrap();
+/// assert_eq!((owner.uid(), owner.gid()), (Some(0), Some(0)));
+/// assert_eq!(":100".parse::<OwnerSpec>().unwrap().uid(), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnerSpec {
+    spec: String,
+    uid: Option<u32>,
+    gid: Option<u32>,
+}
+
+impl OwnerSpec {
+    /// 要设置的用户 ID，None 表示不修改
+    pub fn uid(&self) -> Option<u32> {
+        self.uid
+    }
+
+    /// 要设置的组 ID，None 表示不修改
+    pub fn gid(&self) -> Option<u32> {
+        self.gid
+    }
+}
+
+impl FromStr for OwnerSpec {
+    type Err = FindError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| FindError::PatternError {
+            message: format!("Invalid owner '{}': {}", spec, reason),
+        };
+        let (user, group) = match spec.split_once(':') {
+            Some((user, group)) => (user, Some(group)),
+            None => (spec, None),
+        };
+        let uid = match user {
+            "" => None,
+            name => Some(meta::user_id(name).ok_or_else(|| invalid("unknown user"))?),
+        };
+        let gid = match group {
+            None | Some("") => None,
+            Some(name) => Some(meta::group_id(name).ok_or_else(|| invalid("unknown group"))?),
+        };
+        if uid.is_none() && gid.is_none() {
+            return Err(invalid("expected USER, USER:GROUP or :GROUP"));
+        }
+        Ok(Self { spec: spec.to_string(), uid, gid })
+    }
+}
+
+/// 修改匹配项的属主和属组（`--chown`）
+///
+/// 符号链接修改的是链接本身（与 `chown -h` 相同），不会影响链接指向的文件。
+/// 每个失败的条目单独报告，结束时在标准错误上输出修改、无需修改和失败的数量，有失败的条目时返回错误。
+pub struct ChownAction {
+    owner: OwnerSpec,
+    dry_run: bool,
+    summary: ChangeSummary,
+}
+
+impl ChownAction {
+    /// 创建修改属主的操作
+    pub fn new(owner: OwnerSpec) -> Self {
+        Self { owner, dry_run: false, summary: ChangeSummary::default() }
+    }
+
+    /// 设置是否为演练模式
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// 获取目前为止的结果计数
+    pub fn summary(&self) -> ChangeSummary {
+        self.summary
+    }
+
+    /// 修改单个条目，返回是否需要修改
+    fn change(&self, path: &Path) -> FindResult<bool> {
+        let failed = |source| FindError::FilesystemError { source, path: path.to_path_buf() };
+        let metadata = fs::symlink_metadata(path).map_err(failed)?;
+        let Some((uid, gid)) = meta::owner_ids(&metadata) else {
+            return Err(FindError::ActionFailed {
+                path: path.to_path_buf(),
+                message: "当前平台不支持修改属主".to_string(),
+            });
+        };
+
+        let target = (self.owner.uid.unwrap_or(uid), self.owner.gid.unwrap_or(gid));
+        if target == (uid, gid) {
+            return Ok(false);
+        }
+        if self.dry_run {
+            println!("将修改属主: {} {}:{} -> {}:{}", path.display(), uid, gid, target.0, target.1);
+            return Ok(true);
+        }
+
+        #[cfg(unix)]
+        std::os::unix::fs::lchown(path, self.owner.uid, self.owner.gid).map_err(failed)?;
+        Ok(true)
+    }
+}
+
+impl Action for ChownAction {
+    fn run(&mut self, path: &Path) -> FindResult<()> {
+        let result = self.change(path);
+        self.summary.record(result)
+    }
+
+    fn finish(&mut self) -> FindResult<()> {
+        self.summary.report("chown", self.dry_run)
+    }
+
+    fn description(&self) -> String {
+        let mode = if self.dry_run { "（演练）" } else { "" };
+        format!("修改属主为 {}{}", self.owner.spec, mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn apply(spec: &str, mode: u32, is_dir: bool) -> u32 {
+        spec.parse::<ModeChange>().unwrap().apply(mode, is_dir)
+    }
+
+    #[test]
+    fn test_mode_change() {
+        assert_eq!(apply("644", 0o777, false), 0o644);
+        assert_eq!(apply("u+x", 0o644, false), 0o744);
+        assert_eq!(apply("+x", 0o644, false), 0o755);
+        assert_eq!(apply("go-rwx", 0o755, false), 0o700);
+        assert_eq!(apply("a=r,u+w", 0o777, false), 0o644);
+        assert_eq!(apply("u+r-w", 0o200, false), 0o400);
+        assert_eq!(apply("a+X", 0o644, false), 0o644);
+        assert_eq!(apply("a+X", 0o644, true), 0o755);
+        assert_eq!(apply("a+X", 0o744, false), 0o755);
+        assert_eq!(apply("u+s,o+t", 0o755, false), 0o5755);
+        assert_eq!(apply("g=", 0o2775, false), 0o705);
+
+        for spec in ["", "9", "17777", "u", "z+x", "u+q", "u+x,"] {
+            assert!(spec.parse::<ModeChange>().is_err(), "{}", spec);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_chmod_action() -> Result<(), Box<dyn std::error::Error>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir()?;
+        let script = dir.path().join("run.sh");
+        let done = dir.path().join("done.sh");
+        fs::write(&script, "")?;
+        fs::write(&done, "")?;
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o644))?;
+        fs::set_permissions(&done, fs::Permissions::from_mode(0o755))?;
+        std::os::unix::fs::symlink(&script, dir.path().join("link"))?;
+
+        let mut action = ChmodAction::new("a+x".parse()?);
+        action.run(&script)?;
+        action.run(&done)?;
+        action.run(&dir.path().join("link"))?;
+        assert!(action.run(&dir.path().join("missing")).is_err());
+        // 有失败的条目时结束也返回错误
+        assert!(action.finish().is_err());
+
+        assert_eq!(fs::metadata(&script)?.permissions().mode() & 0o7777, 0o755);
+        assert_eq!(action.summary(), ChangeSummary { changed: 1, unchanged: 2, failed: 1 });
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_chown_action() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "")?;
+        let (uid, gid) = meta::owner_ids(&fs::metadata(&file)?).unwrap();
+
+        // 改为当前的属主不需要权限，只计为无需修改
+        let mut action = ChownAction::new(format!("{}:{}", uid, gid).parse()?);
+        action.run(&file)?;
+        action.finish()?;
+        assert_eq!(action.summary(), ChangeSummary { changed: 0, unchanged: 1, failed: 0 });
+
+        let mut action = ChownAction::new(format!(":{}", gid + 1).parse()?).with_dry_run(true);
+        action.run(&file)?;
+        assert_eq!(action.summary().changed, 1);
+        assert_eq!(meta::owner_ids(&fs::metadata(&file)?), Some((uid, gid)));
+
+        assert!("".parse::<OwnerSpec>().is_err());
+        assert!(":".parse::<OwnerSpec>().is_err());
+        assert!("no-such-user-rust-find".parse::<OwnerSpec>().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,411 @@
+//! 按模板重命名匹配项的操作
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use log::{debug, info, warn};
+
+use crate::errors::{FindError, FindResult};
+use super::{Action, ProtectedPaths};
+
+/// 日期占位符未指定格式时使用的格式
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 解析后的重命名模板
+///
+/// 模板描述新的文件名（不含目录），支持的占位符：
+/// - `{name}` 完整文件名、`{stem}` 去掉最后一个扩展名的文件名、`{ext}` 扩展名（不含 `.`）
+/// - `{parent}` 所在目录的名称
+/// - `{n}` 从 1 开始的序号，`{n:3}` 表示补零到 3 位
+/// - `{mtime}` 修改日期、`{now}` 当前日期，可以带 strftime 格式，如 `{mtime:%Y%m%d}`
+/// - `{{` 和 `}}` 表示花括号本身
+///
+/// 没有扩展名的文件中，`{ext}` 前紧邻的 `.` 也会被省略，因此 `{stem}.{ext}` 不会产生以 `.` 结尾的名称。
+///
+/// # 示例
+/// ```
+/// use rust_find::actions::RenameTemplate;
+///
+/// assert!(RenameTemplate::parse("{stem}_{mtime:%Y%m%d}.{ext}").is_ok());
+/// assert!(RenameTemplate::parse("{size}").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenameTemplate {
+    segments: Vec<Segment>,
+}
+
+/// 模板的组成部分
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Name,
+    Stem,
+    Ext,
+    Parent,
+    Counter(usize),
+    Modified(String),
+    Now(String),
+}
+
+/// 渲染单个文件名所需的信息
+struct Context<'a> {
+    name: &'a str,
+    parent: &'a str,
+    counter: usize,
+    modified: Option<SystemTime>,
+    now: SystemTime,
+}
+
+impl RenameTemplate {
+    /// 解析模板
+    ///
+    /// # 错误
+    /// 遇到未知占位符、不完整的花括号或无效的日期格式时返回 Other 错误
+    pub fn parse(template: &str) -> FindResult<Self> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| invalid_template(template, "缺少 '}'"))?;
+                    let (key, arg) = match rest[..end].split_once(':') {
+                        Some((key, arg)) => (key, Some(arg)),
+                        None => (&rest[..end], None),
+                    };
+                    let segment = parse_placeholder(key, arg).ok_or_else(|| {
+                        invalid_template(template, &format!("无效的占位符 '{{{}}}'", &rest[..end]))
+                    })?;
+                    chars = rest[end + 1..].chars();
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(segment);
+                }
+                '}' => return Err(invalid_template(template, "多余的 '}'")),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        if segments.is_empty() {
+            return Err(invalid_template(template, "模板不能为空"));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// 生成新的文件名
+    fn render(&self, context: &Context) -> String {
+        let (stem, ext) = split_extension(context.name);
+        let format_time =
+            |time: SystemTime, format: &str| DateTime::<Local>::from(time).format(format).to_string();
+
+        let mut output = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => output.push_str(text),
+                Segment::Name => output.push_str(context.name),
+                Segment::Stem => output.push_str(stem),
+                Segment::Ext => match ext {
+                    Some(ext) => output.push_str(ext),
+                    None => {
+                        if output.ends_with('.') {
+                            output.pop();
+                        }
+                    }
+                },
+                Segment::Parent => output.push_str(context.parent),
+                Segment::Counter(width) => {
+                    output.push_str(&format!("{:0width$}", context.counter, width = *width))
+                }
+                Segment::Modified(format) => {
+                    if let Some(modified) = context.modified {
+                        output.push_str(&format_time(modified, format));
+                    }
+                }
+                Segment::Now(format) => output.push_str(&format_time(context.now, format)),
+            }
+        }
+        output
+    }
+}
+
+/// 解析单个占位符，无效时返回 None
+fn parse_placeholder(key: &str, arg: Option<&str>) -> Option<Segment> {
+    let date_format = |arg: Option<&str>| {
+        let format = arg.unwrap_or(DEFAULT_DATE_FORMAT);
+        let valid = !format.is_empty() && StrftimeItems::new(format).all(|item| !matches!(item, Item::Error));
+        valid.then(|| format.to_string())
+    };
+    match (key, arg) {
+        ("name", None) => Some(Segment::Name),
+        ("stem", None) => Some(Segment::Stem),
+        ("ext", None) => Some(Segment::Ext),
+        ("parent", None) => Some(Segment::Parent),
+        ("n", None) => Some(Segment::Counter(0)),
+        ("n", Some(width)) => width.parse().ok().map(Segment::Counter),
+        ("mtime", arg) => date_format(arg).map(Segment::Modified),
+        ("now", arg) => date_format(arg).map(Segment::Now),
+        _ => None,
+    }
+}
+
+/// 拆分文件名为主干和扩展名，以 `.` 开头的隐藏文件名整体作为主干
+fn split_extension(name: &str) -> (&str, Option<&str>) {
+    match name.rfind('.') {
+        Some(index) if index > 0 => (&name[..index], Some(&name[index + 1..])),
+        _ => (name, None),
+    }
+}
+
+/// 构造无效模板错误
+fn invalid_template(template: &str, message: &str) -> FindError {
+    FindError::Other {
+        message: format!("无效的重命名模板 '{}': {}", template, message),
+        context: None,
+        timestamp: SystemTime::now(),
+    }
+}
+
+/// 按模板重命名匹配的文件
+///
+/// 新名称位于原文件所在的目录中。为了保证安全：
+/// - 只重命名文件，目录和搜索根目录本身会被跳过
+/// - 目标已存在，或本次运行中已有其他文件使用该名称时，报告冲突并保留原文件
+/// - 演练模式下只输出将要进行的重命名
+pub struct RenameAction {
+    template: RenameTemplate,
+    dry_run: bool,
+    protected: ProtectedPaths,
+    now: SystemTime,
+    /// 本次运行中已使用的目标路径
+    claimed: HashSet<PathBuf>,
+    /// 本次运行中已被重命名（演练模式下将被重命名）的原路径
+    vacated: HashSet<PathBuf>,
+    /// 已处理的文件数，用于 `{n}`
+    counter: usize,
+    renamed: usize,
+}
+
+impl RenameAction {
+    /// 创建重命名操作
+    ///
+    /// # 参数
+    /// - `template`: 新文件名的模板
+    /// - `protected`: 不允许重命名的路径（通常是搜索根目录）
+    pub fn new(template: RenameTemplate, protected: Vec<PathBuf>) -> Self {
+        Self {
+            template,
+            dry_run: false,
+            protected: ProtectedPaths::new(protected),
+            now: SystemTime::now(),
+            claimed: HashSet::new(),
+            vacated: HashSet::new(),
+            counter: 0,
+            renamed: 0,
+        }
+    }
+
+    /// 设置是否为演练模式
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// 获取已重命名（或演练模式下将重命名）的文件数
+    pub fn renamed_count(&self) -> usize {
+        self.renamed
+    }
+
+    /// 计算文件的新路径
+    fn target(&self, path: &Path, metadata: &fs::Metadata) -> FindResult<PathBuf> {
+        let failed = |message: String| FindError::ActionFailed {
+            path: path.to_path_buf(),
+            message,
+        };
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| failed("文件名不是有效的 UTF-8".to_string()))?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let parent = dir
+            .canonicalize()
+            .ok()
+            .and_then(|dir| dir.file_name().map(|name| name.to_string_lossy().into_owned()))
+            .unwrap_or_default();
+
+        let new_name = self.template.render(&Context {
+            name,
+            parent: &parent,
+            counter: self.counter,
+            modified: metadata.modified().ok(),
+            now: self.now,
+        });
+        if new_name.is_empty() || new_name == "." || new_name == ".." || new_name.contains(['/', '\0'])
+            || (cfg!(windows) && new_name.contains('\\'))
+        {
+            return Err(failed(format!("模板生成了无效的文件名 '{}'", new_name)));
+        }
+        Ok(dir.join(new_name))
+    }
+}
+
+impl Action for RenameAction {
+    fn run(&mut self, path: &Path) -> FindResult<()> {
+        if self.protected.contains(path) {
+            warn!("跳过搜索根目录: {}", path.display());
+            return Ok(());
+        }
+
+        let metadata = fs::symlink_metadata(path).map_err(|e| FindError::FilesystemError {
+            source: e,
+            path: path.to_path_buf(),
+        })?;
+        if metadata.is_dir() {
+            debug!("跳过目录: {}", path.display());
+            return Ok(());
+        }
+
+        self.counter += 1;
+        let target = self.target(path, &metadata)?;
+        if target == path {
+            return Ok(());
+        }
+        let exists = target.symlink_metadata().is_ok() && !self.vacated.contains(&target);
+        if exists || self.claimed.contains(&target) {
+            return Err(FindError::ActionFailed {
+                path: path.to_path_buf(),
+                message: format!("目标已存在: {}", target.display()),
+            });
+        }
+
+        if self.dry_run {
+            println!("将重命名: {} -> {}", path.display(), target.display());
+        } else {
+            fs::rename(path, &target).map_err(|e| FindError::FilesystemError {
+                source: e,
+                path: path.to_path_buf(),
+            })?;
+        }
+        self.vacated.insert(path.to_path_buf());
+        self.vacated.remove(&target);
+        self.claimed.insert(target);
+        self.renamed += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> FindResult<()> {
+        if self.dry_run {
+            info!("将重命名 {} 个文件", self.renamed);
+        } else {
+            info!("已重命名 {} 个文件", self.renamed);
+        }
+        Ok(())
+    }
+
+    fn description(&self) -> String {
+        if self.dry_run {
+            "重命名文件（演练）".to_string()
+        } else {
+            "重命名文件".to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn render(template: &str, name: &str) -> String {
+        RenameTemplate::parse(template).unwrap().render(&Context {
+            name,
+            parent: "photos",
+            counter: 7,
+            modified: Some(SystemTime::UNIX_EPOCH),
+            now: SystemTime::now(),
+        })
+    }
+
+    #[test]
+    fn test_render_template() {
+        assert_eq!(render("{stem}_{n:3}.{ext}", "a.tar.gz"), "a.tar_007.gz");
+        assert_eq!(render("{parent}-{name}", "a.txt"), "photos-a.txt");
+        assert_eq!(render("{stem}.{ext}", "Makefile"), "Makefile");
+        assert_eq!(render("{stem}.{ext}", ".bashrc"), ".bashrc");
+        assert_eq!(render("{{{n}}}", "a"), "{7}");
+
+        let date = DateTime::<Local>::from(SystemTime::UNIX_EPOCH).format("%Y").to_string();
+        assert_eq!(render("{mtime:%Y}_{name}", "a"), format!("{}_a", date));
+    }
+
+    #[test]
+    fn test_parse_invalid_template() {
+        for template in ["", "{size}", "{stem", "stem}", "{n:x}", "{mtime:%Q}", "{ext:x}"] {
+            assert!(RenameTemplate::parse(template).is_err(), "{}", template);
+        }
+    }
+
+    #[test]
+    fn test_rename_detects_collisions() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        for name in ["a.txt", "b.txt", "taken.txt"] {
+            fs::write(dir.path().join(name), name)?;
+        }
+
+        // 两个文件生成同一名称时只重命名第一个
+        let template = RenameTemplate::parse("same.{ext}")?;
+        let mut action = RenameAction::new(template, vec![dir.path().to_path_buf()]);
+        action.run(&dir.path().join("a.txt"))?;
+        assert!(action.run(&dir.path().join("b.txt")).is_err());
+        assert_eq!(fs::read_to_string(dir.path().join("same.txt"))?, "a.txt");
+        assert!(dir.path().join("b.txt").exists());
+
+        // 已存在的文件不会被覆盖
+        let template = RenameTemplate::parse("taken.txt")?;
+        let mut action = RenameAction::new(template, Vec::new());
+        assert!(action.run(&dir.path().join("b.txt")).is_err());
+        assert_eq!(fs::read_to_string(dir.path().join("taken.txt"))?, "taken.txt");
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_dry_run_and_chains() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let sub = dir.path().join("sub");
+        fs::create_dir(&sub)?;
+        fs::write(dir.path().join("2"), "")?;
+        fs::write(dir.path().join("x"), "")?;
+
+        // 2 -> 1 之后 2 已空出，即使演练模式下文件仍在，x -> 2 也不是冲突
+        let template = RenameTemplate::parse("{n}")?;
+        let mut action = RenameAction::new(template, Vec::new()).with_dry_run(true);
+        action.run(&sub)?;
+        action.run(&dir.path().join("2"))?;
+        action.run(&dir.path().join("x"))?;
+        assert_eq!(action.renamed_count(), 2);
+        assert!(dir.path().join("2").exists());
+        assert!(!dir.path().join("1").exists());
+        assert!(sub.exists());
+        Ok(())
+    }
+}
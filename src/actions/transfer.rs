@@ -0,0 +1,285 @@
+//! 将匹配项复制或移动到目标目录的操作
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use log::{debug, info, warn};
+use rayon::prelude::*;
+
+use crate::errors::{FindError, FindResult};
+use super::{relative_to_root, Action};
+
+/// 复制还是移动
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferMode {
+    Copy,
+    Move,
+}
+
+impl TransferMode {
+    /// 用于输出的动词
+    fn verb(self) -> &'static str {
+        match self {
+            TransferMode::Copy => "复制",
+            TransferMode::Move => "移动",
+        }
+    }
+}
+
+/// 将匹配的文件复制或移动到目标目录（`--copy-to` / `--move-to`）
+///
+/// 默认所有文件直接放在目标目录中；保留路径时，文件在目标目录中的位置与它相对于搜索根目录的位置相同。
+/// 为了保证安全：
+/// - 只处理文件（包括符号链接本身），目录会被跳过，需要的目录在目标中自动创建
+/// - 目标已存在，或本次运行中已有其他文件使用该位置时，报告冲突并保留原文件
+/// - 演练模式下只输出将要进行的操作
+///
+/// 冲突检查在收到每个匹配项时进行，实际的复制或移动在所有结果处理完后并行执行，
+/// 标准错误是终端时显示进度。
+pub struct TransferAction {
+    mode: TransferMode,
+    target: PathBuf,
+    roots: Vec<PathBuf>,
+    preserve_paths: bool,
+    dry_run: bool,
+    /// 等待执行的原路径和目标路径
+    pending: Vec<(PathBuf, PathBuf)>,
+    claimed: HashSet<PathBuf>,
+    transferred: usize,
+}
+
+impl TransferAction {
+    /// 创建复制或移动操作
+    ///
+    /// # 参数
+    /// - `mode`: 复制还是移动
+    /// - `target`: 目标目录，不存在时自动创建
+    /// - `roots`: 搜索根目录，用于保留相对路径
+    pub fn new(mode: TransferMode, target: PathBuf, roots: Vec<PathBuf>) -> Self {
+        Self {
+            mode,
+            target,
+            roots,
+            preserve_paths: false,
+            dry_run: false,
+            pending: Vec::new(),
+            claimed: HashSet::new(),
+            transferred: 0,
+        }
+    }
+
+    /// 设置是否在目标目录中保留相对于搜索根目录的路径
+    pub fn with_preserve_paths(mut self, preserve_paths: bool) -> Self {
+        self.preserve_paths = preserve_paths;
+        self
+    }
+
+    /// 设置是否为演练模式
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// 获取已复制或移动（演练模式下将复制或移动）的文件数
+    pub fn transferred_count(&self) -> usize {
+        self.transferred
+    }
+
+    /// 计算文件在目标目录中的位置
+    fn destination(&self, path: &Path) -> PathBuf {
+        let name = || path.file_name().map(PathBuf::from).unwrap_or_else(|| path.to_path_buf());
+        if !self.preserve_paths {
+            return self.target.join(name());
+        }
+
+        match relative_to_root(path, &self.roots) {
+            Some(relative) => self.target.join(relative),
+            None => self.target.join(name()),
+        }
+    }
+}
+
+impl Action for TransferAction {
+    fn run(&mut self, path: &Path) -> FindResult<()> {
+        let metadata = fs::symlink_metadata(path).map_err(|e| FindError::FilesystemError {
+            source: e,
+            path: path.to_path_buf(),
+        })?;
+        if metadata.is_dir() {
+            debug!("跳过目录: {}", path.display());
+            return Ok(());
+        }
+
+        let destination = self.destination(path);
+        if destination == path {
+            return Ok(());
+        }
+        if destination.symlink_metadata().is_ok() || self.claimed.contains(&destination) {
+            return Err(FindError::ActionFailed {
+                path: path.to_path_buf(),
+                message: format!("目标已存在: {}", destination.display()),
+            });
+        }
+
+        if self.dry_run {
+            println!("将{}: {} -> {}", self.mode.verb(), path.display(), destination.display());
+        } else {
+            self.pending.push((path.to_path_buf(), destination.clone()));
+        }
+        self.claimed.insert(destination);
+        self.transferred += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> FindResult<()> {
+        let verb = self.mode.verb();
+        if self.dry_run {
+            info!("将{} {} 个文件", verb, self.transferred);
+            return Ok(());
+        }
+
+        let pending = std::mem::take(&mut self.pending);
+        let total = pending.len();
+        let done = AtomicUsize::new(0);
+        let show_progress = total > 0 && io::stderr().is_terminal();
+
+        let errors: Vec<FindError> = pending
+            .par_iter()
+            .filter_map(|(source, destination)| {
+                let result = transfer(self.mode, source, destination);
+                let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                if show_progress {
+                    eprint!("\r{}: {}/{}", verb, done, total);
+                    let _ = io::stderr().flush();
+                }
+                result.err()
+            })
+            .collect();
+        if show_progress {
+            eprintln!();
+        }
+
+        for e in &errors {
+            warn!("{}", e);
+        }
+        self.transferred -= errors.len();
+        info!("已{} {} 个文件到 {}", verb, self.transferred, self.target.display());
+        errors.into_iter().next().map_or(Ok(()), Err)
+    }
+
+    fn description(&self) -> String {
+        let structure = if self.preserve_paths { "，保留目录结构" } else { "" };
+        let mode = if self.dry_run { "（演练）" } else { "" };
+        format!("{}文件到 {}{}{}", self.mode.verb(), self.target.display(), structure, mode)
+    }
+}
+
+/// 复制或移动单个文件，需要时创建目标所在的目录
+fn transfer(mode: TransferMode, source: &Path, destination: &Path) -> FindResult<()> {
+    let failed = |e: io::Error| FindError::FilesystemError {
+        source: e,
+        path: source.to_path_buf(),
+    };
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent).map_err(failed)?;
+    }
+
+    match mode {
+        TransferMode::Copy => copy_file(source, destination).map_err(failed),
+        TransferMode::Move => match fs::rename(source, destination) {
+            // 跨文件系统时改为复制后删除
+            Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+                copy_file(source, destination)
+                    .and_then(|_| fs::remove_file(source))
+                    .map_err(failed)
+            }
+            result => result.map_err(failed),
+        },
+    }
+}
+
+/// 复制文件内容、权限和修改时间，符号链接复制为指向相同目标的链接
+fn copy_file(source: &Path, destination: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(source)?;
+    if metadata.file_type().is_symlink() {
+        #[cfg(unix)]
+        return std::os::unix::fs::symlink(fs::read_link(source)?, destination);
+    }
+
+    fs::copy(source, destination)?;
+    if let Ok(modified) = metadata.modified() {
+        fs::File::options().write(true).open(destination)?.set_modified(modified)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_copy_flat_detects_collisions() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("a"))?;
+        fs::create_dir_all(src.join("b"))?;
+        fs::write(src.join("a/file.txt"), "a")?;
+        fs::write(src.join("b/file.txt"), "b")?;
+        let target = dir.path().join("out");
+
+        let mut action = TransferAction::new(TransferMode::Copy, target.clone(), vec![src.clone()]);
+        action.run(&src.join("a"))?;
+        action.run(&src.join("a/file.txt"))?;
+        assert!(action.run(&src.join("b/file.txt")).is_err());
+        action.finish()?;
+
+        assert_eq!(fs::read_to_string(target.join("file.txt"))?, "a");
+        assert!(src.join("a/file.txt").exists());
+        assert_eq!(action.transferred_count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_preserving_paths() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("a/deep"))?;
+        fs::write(src.join("a/deep/file.txt"), "a")?;
+        fs::write(src.join("top.txt"), "top")?;
+        let target = dir.path().join("out");
+
+        let mut action = TransferAction::new(TransferMode::Move, target.clone(), vec![src.clone()])
+            .with_preserve_paths(true);
+        action.run(&src.join("a/deep/file.txt"))?;
+        action.run(&src.join("top.txt"))?;
+        action.finish()?;
+
+        assert_eq!(fs::read_to_string(target.join("a/deep/file.txt"))?, "a");
+        assert!(target.join("top.txt").exists());
+        assert!(!src.join("top.txt").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_transfer_dry_run() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "x")?;
+        let target = dir.path().join("out");
+
+        let mut action = TransferAction::new(TransferMode::Move, target.clone(), Vec::new())
+            .with_dry_run(true);
+        action.run(&file)?;
+        action.finish()?;
+
+        assert!(file.exists());
+        assert!(!target.exists());
+        assert_eq!(action.transferred_count(), 1);
+        assert_eq!(action.description(), format!("移动文件到 {}（演练）", target.display()));
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+//! 将匹配项移至回收站的操作
+
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+
+use crate::errors::{FindError, FindResult};
+use super::{Action, ProtectedPaths};
+
+/// 将匹配的文件和目录移至系统回收站
+///
+/// 作为 `--delete` 的安全替代，被移走的条目可以从回收站恢复。
+/// 搜索根目录本身永远不会被移走；已移走目录中的条目会被跳过。
+pub struct TrashAction {
+    dry_run: bool,
+    protected: ProtectedPaths,
+    trashed_dirs: Vec<PathBuf>,
+    trashed: usize,
+}
+
+impl TrashAction {
+    /// 创建移至回收站的操作
+    ///
+    /// # 参数
+    /// - `protected`: 不允许移走的路径（通常是搜索根目录）
+    pub fn new(protected: Vec<PathBuf>) -> Self {
+        Self {
+            dry_run: false,
+            protected: ProtectedPaths::new(protected),
+            trashed_dirs: Vec::new(),
+            trashed: 0,
+        }
+    }
+
+    /// 设置是否为演练模式
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// 获取已移至回收站（或演练模式下将移走）的条目数
+    pub fn trashed_count(&self) -> usize {
+        self.trashed
+    }
+}
+
+impl Action for TrashAction {
+    fn run(&mut self, path: &Path) -> FindResult<()> {
+        if self.protected.contains(path) {
+            warn!("跳过搜索根目录: {}", path.display());
+            return Ok(());
+        }
+        if self.trashed_dirs.iter().any(|dir| path.starts_with(dir)) {
+            return Ok(());
+        }
+
+        let is_dir = path.symlink_metadata().map(|m| m.is_dir()).unwrap_or(false);
+        if self.dry_run {
+            println!("将移至回收站: {}", path.display());
+        } else {
+            trash::delete(path).map_err(|e| FindError::ActionFailed {
+                path: path.to_path_buf(),
+                message: format!("无法移至回收站: {}", e),
+            })?;
+        }
+
+        if is_dir {
+            self.trashed_dirs.push(path.to_path_buf());
+        }
+        self.trashed += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> FindResult<()> {
+        if self.dry_run {
+            info!("将有 {} 个条目移至回收站", self.trashed);
+        } else {
+            info!("已将 {} 个条目移至回收站", self.trashed);
+        }
+        Ok(())
+    }
+
+    fn description(&self) -> String {
+        if self.dry_run {
+            "移至回收站（演练）".to_string()
+        } else {
+            "移至回收站".to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_trash_dry_run_keeps_files() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let file = dir.path().join("a.tmp");
+        std::fs::write(&file, "x")?;
+
+        let mut action = TrashAction::new(vec![dir.path().to_path_buf()]).with_dry_run(true);
+        action.run(&file)?;
+        action.run(dir.path())?;
+        action.finish()?;
+
+        assert!(file.exists());
+        assert_eq!(action.trashed_count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_trash_skips_entries_inside_trashed_dir() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let sub = dir.path().join("sub");
+        std::fs::create_dir(&sub)?;
+
+        let mut action = TrashAction::new(Vec::new()).with_dry_run(true);
+        action.run(&sub)?;
+        action.run(&sub.join("inner.txt"))?;
+
+        assert_eq!(action.trashed_count(), 1);
+        Ok(())
+    }
+}
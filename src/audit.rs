@@ -0,0 +1,203 @@
+//! 权限风险审计
+//!
+//! 在目录树中查找常见的权限风险，并按类别汇总：
+//! - 全局可写的文件
+//! - 没有粘滞位的全局可写目录（任何用户都可以删除或替换其中其他用户的文件）
+//! - 属主不存在的条目（用户已被删除，其 UID 可能被重新分配给新用户）
+//!
+//! 使用与普通搜索相同的并行遍历和过滤器，一次遍历完成所有检查。
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+
+use crate::errors::{FindError, FindResult};
+use crate::finder::{FileFilter, FindEntry, FindOptions, Finder};
+use crate::output::meta;
+
+/// 审计发现的风险类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Finding {
+    /// 全局可写的文件（不含目录和符号链接）
+    WorldWritableFile,
+    /// 没有粘滞位的全局可写目录
+    WorldWritableDir,
+    /// 属主不存在的条目
+    UnknownOwner,
+}
+
+impl Finding {
+    /// 所有类别，按报告中的顺序排列
+    pub const ALL: [Finding; 3] = [
+        Finding::WorldWritableFile,
+        Finding::WorldWritableDir,
+        Finding::UnknownOwner,
+    ];
+
+    /// 报告中的类别标题
+    pub fn title(self) -> &'static str {
+        match self {
+            Finding::WorldWritableFile => "全局可写的文件",
+            Finding::WorldWritableDir => "没有粘滞位的全局可写目录",
+            Finding::UnknownOwner => "属主不存在的条目",
+        }
+    }
+}
+
+/// 审计报告，每个类别中的路径按名称排序
+#[derive(Debug, Clone, Default)]
+pub struct AuditReport {
+    /// 各类别及其中的路径，按 [`Finding::ALL`] 的顺序排列
+    pub groups: Vec<(Finding, Vec<PathBuf>)>,
+}
+
+impl AuditReport {
+    /// 指定类别中的路径
+    pub fn paths(&self, finding: Finding) -> &[PathBuf] {
+        self.groups
+            .iter()
+            .find(|(group, _)| *group == finding)
+            .map(|(_, paths)| paths.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// 是否没有发现任何风险
+    pub fn is_clean(&self) -> bool {
+        self.groups.iter().all(|(_, paths)| paths.is_empty())
+    }
+}
+
+impl fmt::Display for AuditReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (finding, paths)) in self.groups.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "== {} ({}) ==", finding.title(), paths.len())?;
+            for path in paths {
+                writeln!(f, "{}", path.display())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 检查条目属于哪些风险类别
+///
+/// 用户数据库查询可能较慢（例如经过 NSS/LDAP），每个 UID 只查询一次。
+#[derive(Debug, Default)]
+struct Auditor {
+    known_users: Mutex<HashMap<u32, bool>>,
+}
+
+impl Auditor {
+    /// 条目是否属于指定类别
+    fn applies(&self, finding: Finding, metadata: &Metadata) -> bool {
+        let mode = meta::mode_bits(metadata);
+        let file_type = metadata.file_type();
+        let world_writable = mode & 0o002 != 0;
+        match finding {
+            Finding::WorldWritableFile => {
+                world_writable && !file_type.is_dir() && !file_type.is_symlink()
+            }
+            Finding::WorldWritableDir => world_writable && file_type.is_dir() && mode & 0o1000 == 0,
+            Finding::UnknownOwner => meta::owner_ids(metadata).is_some_and(|(uid, _)| !self.user_exists(uid)),
+        }
+    }
+
+    /// UID 是否对应存在的用户
+    fn user_exists(&self, uid: u32) -> bool {
+        let mut known = self.known_users.lock().unwrap_or_else(|e| e.into_inner());
+        *known.entry(uid).or_insert_with(|| meta::user_exists(uid))
+    }
+}
+
+impl FileFilter for &Auditor {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        entry
+            .metadata()
+            .is_ok_and(|metadata| Finding::ALL.iter().any(|&finding| self.applies(finding, metadata)))
+    }
+
+    fn description(&self) -> String {
+        "has risky permissions or an unknown owner".to_string()
+    }
+}
+
+/// 审计目录树
+///
+/// 隐藏文件和被忽略文件规则排除的条目同样会被检查，不跟随符号链接。
+/// `options` 中的深度和文件系统边界等设置仍然生效。
+///
+/// # 错误
+/// 如果目录不存在，返回InvalidPath错误
+pub fn run(root: &Path, options: FindOptions) -> FindResult<AuditReport> {
+    if !root.is_dir() {
+        return Err(FindError::InvalidPath(root.to_path_buf()));
+    }
+
+    let options = options
+        .with_ignore_hidden(false)
+        .with_respect_gitignore(false)
+        .with_respect_findignore(false);
+    let auditor = Auditor::default();
+    let mut matches = Finder::new(options).find_parallel(root.to_path_buf(), &auditor);
+    matches.sort();
+
+    // 只对匹配的少量条目再次读取元数据并归类
+    let mut groups: Vec<(Finding, Vec<PathBuf>)> =
+        Finding::ALL.iter().map(|&finding| (finding, Vec::new())).collect();
+    for path in matches {
+        let Ok(metadata) = path.symlink_metadata() else {
+            continue;
+        };
+        for (finding, paths) in &mut groups {
+            if auditor.applies(*finding, &metadata) {
+                paths.push(path.clone());
+            }
+        }
+    }
+    Ok(AuditReport { groups })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_audit_groups_findings() -> Result<(), Box<dyn std::error::Error>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        fs::create_dir_all(root.join("open"))?;
+        fs::create_dir_all(root.join("tmp"))?;
+        fs::write(root.join("open/.shared"), "x")?;
+        fs::write(root.join("private"), "x")?;
+        fs::set_permissions(root.join("open"), fs::Permissions::from_mode(0o777))?;
+        fs::set_permissions(root.join("tmp"), fs::Permissions::from_mode(0o1777))?;
+        fs::set_permissions(root.join("open/.shared"), fs::Permissions::from_mode(0o666))?;
+        fs::set_permissions(root.join("private"), fs::Permissions::from_mode(0o600))?;
+
+        let report = run(&root, FindOptions::new())?;
+        assert_eq!(report.paths(Finding::WorldWritableFile), [root.join("open/.shared")]);
+        assert_eq!(report.paths(Finding::WorldWritableDir), [root.join("open")]);
+        assert!(!report.is_clean());
+
+        let text = report.to_string();
+        assert!(text.contains("== 全局可写的文件 (1) =="));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_audit_rejects_missing_dir() {
+        assert!(run(Path::new("/nonexistent/rust-find-audit"), FindOptions::new()).is_err());
+    }
+}
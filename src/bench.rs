@@ -0,0 +1,254 @@
+//! 遍历性能基准
+//!
+//! 在同一目录上分别运行串行遍历和不同线程数的并行遍历，
+//! 报告每种组合的耗时和吞吐量，用于为所在的存储调整
+//! `--max-threads` 和 `--dirs-per-thread`。
+
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use walkdir::WalkDir;
+
+use crate::errors::{FindError, FindResult};
+use crate::finder::filter::{FilterCost, NameFilter, TypeFilter};
+use crate::finder::{FileFilter, FindEntry, FindOptions, Finder};
+
+/// 基准测试使用的过滤器组合
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BenchFilter {
+    /// 不过滤，匹配所有条目
+    All,
+    /// 按文件名模式过滤（模式由 `--pattern` 指定）
+    Name,
+    /// 按文件名模式过滤，忽略大小写
+    Iname,
+    /// 只匹配普通文件（需要读取文件类型）
+    Type,
+}
+
+impl fmt::Display for BenchFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BenchFilter::All => "all",
+            BenchFilter::Name => "name",
+            BenchFilter::Iname => "iname",
+            BenchFilter::Type => "type",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 基准测试配置
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    /// 并行遍历使用的线程数
+    pub threads: Vec<usize>,
+    /// 测试的过滤器组合
+    pub filters: Vec<BenchFilter>,
+    /// `name` 过滤器使用的文件名模式
+    pub pattern: String,
+    /// 每种组合重复运行的次数，报告耗时的中位数
+    pub iterations: usize,
+    /// 并行遍历时每个线程处理的目录数
+    pub dirs_per_thread: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        let cpus = num_cpus::get();
+        let mut threads: Vec<usize> = [1, 2, 4, 8].into_iter().filter(|&n| n < cpus).collect();
+        threads.push(cpus);
+        Self {
+            threads,
+            filters: vec![BenchFilter::All, BenchFilter::Name],
+            pattern: "*.txt".to_string(),
+            iterations: 3,
+            dirs_per_thread: 10,
+        }
+    }
+}
+
+/// 单个组合的测试结果
+#[derive(Debug, Clone)]
+pub struct BenchRun {
+    /// 线程数，None 表示串行遍历
+    pub threads: Option<usize>,
+    /// 使用的过滤器组合
+    pub filter: BenchFilter,
+    /// 遍历的条目数
+    pub entries: usize,
+    /// 匹配的结果数
+    pub matches: usize,
+    /// 耗时（多次运行的中位数）
+    pub elapsed: Duration,
+}
+
+impl BenchRun {
+    /// 每秒遍历的条目数
+    pub fn entries_per_sec(&self) -> f64 {
+        self.entries as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl fmt::Display for BenchRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match self.threads {
+            Some(threads) => format!("parallel x{}", threads),
+            None => "serial".to_string(),
+        };
+        write!(
+            f,
+            "{:<12} {:<6} {:>10} {:>10} {:>12.2?} {:>14.0}",
+            mode,
+            self.filter,
+            self.entries,
+            self.matches,
+            self.elapsed,
+            self.entries_per_sec()
+        )
+    }
+}
+
+/// 结果表头，列与 [`BenchRun`] 的显示格式对齐
+pub const HEADER: &str = "mode         filter    entries    matches    wall time    entries/sec";
+
+/// 不做额外限制的过滤器
+struct MatchAll;
+
+impl FileFilter for MatchAll {
+    fn matches(&self, _: &FindEntry) -> bool {
+        true
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
+    fn description(&self) -> String {
+        "始终匹配所有文件".to_string()
+    }
+}
+
+/// 在指定目录上运行基准测试
+///
+/// 对每个过滤器组合先运行一次串行遍历，再依次以各个线程数运行并行遍历。
+///
+/// # 错误
+/// 如果目录不存在或模式无效，返回相应的错误
+pub fn run(root: &Path, config: &BenchConfig) -> FindResult<Vec<BenchRun>> {
+    if !root.is_dir() {
+        return Err(FindError::InvalidPath(root.to_path_buf()));
+    }
+    let iterations = config.iterations.max(1);
+
+    let mut runs = Vec::new();
+    for &kind in &config.filters {
+        let mut entries = 0;
+        let mut matches = 0;
+        let elapsed = median(iterations, || {
+            let filter = build_filter(kind, &config.pattern)?;
+            let start = Instant::now();
+            (entries, matches) = serial_walk(root, filter.as_ref());
+            Ok(start.elapsed())
+        })?;
+        runs.push(BenchRun { threads: None, filter: kind, entries, matches, elapsed });
+
+        for &threads in &config.threads {
+            let threads = threads.max(1);
+            let mut matches = 0;
+            let elapsed = median(iterations, || {
+                // 查找器在自己的线程池中以固定的线程数运行
+                let options = FindOptions::new()
+                    .with_max_threads(threads)
+                    .with_min_threads(threads)
+                    .with_auto_adjust(false)
+                    .with_dirs_per_thread(config.dirs_per_thread);
+                let finder = Finder::new(options).with_filters(vec![build_filter(kind, &config.pattern)?]);
+                let start = Instant::now();
+                matches = finder.find_parallel(root.to_path_buf(), MatchAll).len();
+                Ok(start.elapsed())
+            })?;
+            // 并行遍历访问的条目与串行遍历相同
+            runs.push(BenchRun { threads: Some(threads), filter: kind, entries, matches, elapsed });
+        }
+    }
+    Ok(runs)
+}
+
+/// 在当前线程中遍历目录，返回遍历的条目数和匹配数
+fn serial_walk(root: &Path, filter: &(dyn FileFilter + Send + Sync)) -> (usize, usize) {
+    let mut entries = 0;
+    let mut matches = 0;
+    for entry in WalkDir::new(root).into_iter().filter_map(Result::ok) {
+        entries += 1;
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if !hidden && filter.matches(&FindEntry::from(entry)) {
+            matches += 1;
+        }
+    }
+    (entries, matches)
+}
+
+/// 创建过滤器组合对应的过滤器
+fn build_filter(kind: BenchFilter, pattern: &str) -> FindResult<Box<dyn FileFilter + Send + Sync>> {
+    Ok(match kind {
+        BenchFilter::All => Box::new(MatchAll),
+        BenchFilter::Name => Box::new(NameFilter::new(pattern)?),
+        BenchFilter::Iname => Box::new(NameFilter::new_ignore_case(pattern)?),
+        BenchFilter::Type => Box::new(TypeFilter::new("f")?),
+    })
+}
+
+/// 重复运行并返回耗时的中位数
+fn median<R>(iterations: usize, mut run: R) -> FindResult<Duration>
+where
+    R: FnMut() -> FindResult<Duration>,
+{
+    let mut times = (0..iterations).map(|_| run()).collect::<FindResult<Vec<_>>>()?;
+    times.sort();
+    Ok(times[times.len() / 2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_bench_runs_each_combination() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root: PathBuf = dir.path().join("tree");
+        fs::create_dir_all(root.join("sub"))?;
+        fs::write(root.join("a.txt"), "x")?;
+        fs::write(root.join("sub/b.txt"), "x")?;
+        fs::write(root.join("sub/c.log"), "x")?;
+
+        let config = BenchConfig {
+            threads: vec![1, 2],
+            filters: vec![BenchFilter::All, BenchFilter::Name, BenchFilter::Iname],
+            iterations: 1,
+            ..BenchConfig::default()
+        };
+        let runs = run(&root, &config)?;
+        assert_eq!(runs.len(), 9);
+        for run in &runs {
+            assert_eq!(run.entries, 5);
+            let expected = if run.filter == BenchFilter::All { 5 } else { 2 };
+            assert_eq!(run.matches, expected, "{}", run);
+        }
+        assert_eq!(runs[1].threads, Some(1));
+        assert!(runs[0].threads.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bench_rejects_missing_dir() {
+        let config = BenchConfig::default();
+        assert!(run(Path::new("/nonexistent/rust-find-bench"), &config).is_err());
+    }
+}
@@ -3,26 +3,145 @@
 //! 本模块提供了find工具的命令行接口，
 //! 包括参数解析和验证功能。
 
-use clap::Parser;
-use crate::errors::FindError;
-use crate::finder::FindOptions;
+use std::ffi::OsString;
+use std::io::Read;
+
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueHint};
+use clap_complete::Shell;
+use crate::actions::{
+    Action, ArchiveAction, ChmodAction, ChownAction, ConfirmAction, DeleteAction, ExecAction, ModeChange,
+    OwnerSpec, RenameAction, RenameTemplate, TransferAction, TransferMode, TrashAction,
+};
+use crate::bench::{BenchConfig, BenchFilter};
+use crate::errors::{FindError, FindResult};
+use crate::finder::{
+    default_global_ignore_file, default_stats_file, ErrorFormat, ErrorPolicies, ErrorPolicy, FileFilter, FindOptions,
+    HiddenAttributes, SymlinkPolicy,
+};
+use crate::finder::size::ByteSize;
+use crate::finder::filter::{
+    AccessFilter, AccessMode, AttrFilter, BrokenSymlinkFilter, InodeFilter, LengthFilter, LinksFilter,
+    MultiNameFilter, NotFilter, NumericComparison, PathFilter, PermFilter, PermMatch, SameFileFilter,
+    SizeFilter, SizeMeasure, TypeFilter, XattrFilter,
+};
+use crate::finder::content::{ContentFilter, MimeFilter, RelevanceScorer};
+use crate::finder::metadata::SidecarProvider;
+use crate::finder::s3::S3Url;
+use crate::finder::git::{GitChangedFilter, GitStatus, GitStatusFilter, GitTrackedFilter};
+use crate::finder::sftp::SftpUrl;
+use crate::finder::query::QueryFilter;
+use crate::serve::FilterSpec;
+use crate::finder::security::{
+    AclEntryFilter, CapFilter, HasAclFilter, HasCapsFilter, InodeFlag, InodeFlagFilter,
+};
+use crate::output::{
+    ColorChoice, Field, LsColors, OutputConfig, OutputFormat, PathStyle, PrintfFormat, SortKey,
+};
+use crate::snapshot::DiffField;
 
 /// Linux find 命令的 Rust 实现
-#[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about = None, args_override_self = true, args_conflicts_with_subcommands = true,
+          after_help = "环境变量 RUST_FIND_OPTS 中的参数会作为默认值插入到命令行参数之前，\n命令行中再次给出的同一选项以命令行为准（使用子命令时不插入）。\n\n\
+第一个参数与子命令同名（如 bench、audit）时按子命令解析，\n搜索同名目录时写作 ./bench 或 -- bench。搜索选项不能与子命令一起使用。")]
 pub struct Cli {
+    /// 子命令
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// 搜索路径（默认：当前目录）
-    #[arg(default_value = ".")]
+    ///
+    /// 与子命令同名的目录需要写作 ./bench 或放在 -- 之后；
+    /// sftp://user@host/path 通过 SFTP 搜索远程目录（需要 sftp 特性），
+    /// s3://bucket/prefix 搜索对象存储中的对象（需要 s3 特性）
+    #[arg(default_value = ".", value_hint = ValueHint::AnyPath)]
     pub paths: Vec<String>,
 
+    /// 从文件读取起始路径，每行一个，- 表示标准输入（不能同时在命令行指定路径）
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath,
+          conflicts_with_all = ["paths", "files0_from"])]
+    pub files_from: Option<std::path::PathBuf>,
+
+    /// 从文件读取以 NUL 分隔的起始路径，- 表示标准输入（可与 find -print0 等配合）
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, conflicts_with = "paths")]
+    pub files0_from: Option<std::path::PathBuf>,
+
     /// 最大搜索深度
     #[arg(long, value_name = "NUM")]
     pub max_depth: Option<usize>,
 
+    /// 找到指定数量的结果后停止搜索
+    #[arg(long, value_name = "NUM")]
+    pub max_results: Option<usize>,
+
+    /// 只搜索指定目录本身的内容，不进入子目录（等价于 --max-depth 1）
+    #[arg(long, conflicts_with = "max_depth")]
+    pub no_recurse: bool,
+
     /// 跟随符号链接
-    #[arg(short = 'L', long)]
+    #[arg(short = 'L', long, overrides_with_all = ["follow_root_links", "no_follow_links"])]
     pub follow_links: bool,
 
+    /// 仅跟随作为搜索路径给出的符号链接
+    #[arg(short = 'H', long, overrides_with_all = ["follow_links", "no_follow_links"])]
+    pub follow_root_links: bool,
+
+    /// 从不跟随符号链接（默认）
+    #[arg(short = 'P', long, overrides_with_all = ["follow_links", "follow_root_links"])]
+    pub no_follow_links: bool,
+
+    /// 同一条目只输出一次：去除重叠的搜索路径、跟随符号链接或硬链接产生的重复结果
+    #[arg(long)]
+    pub dedup: bool,
+
+    /// 在找到的 zip、tar、tar.gz 和 tar.zst 归档中查找，归档中的匹配项显示为 `归档路径!/内部路径`
+    ///
+    /// 名称、类型、大小、时间和内容等条件作用于归档中的条目；操作、汇总和快照只处理归档外的结果。
+    #[arg(long)]
+    pub search_archives: bool,
+
+    /// 跟随符号链接时，一条路径上最多跟随的符号链接数
+    #[arg(long, value_name = "NUM")]
+    pub max_symlink_depth: Option<usize>,
+
+    /// 跳过重解析点：Windows 上的联接（junction）、挂载点和符号链接，其他平台上的符号链接
+    #[arg(long)]
+    pub skip_reparse_points: bool,
+
+    /// 遵循 .gitignore、.git/info/exclude 和全局 gitignore，跳过被忽略的条目
+    #[arg(long)]
+    pub respect_gitignore: bool,
+
+    /// 不读取 .findignore 和全局忽略文件（~/.config/rust-find/ignore）
+    #[arg(long)]
+    pub no_findignore: bool,
+
+    /// 跳过名称（或含 / 时相对路径）匹配的条目，可多次指定
+    #[arg(long, value_name = "GLOB", value_parser = parse_glob)]
+    pub exclude: Vec<glob::Pattern>,
+
+    /// 不进入名称（或含 / 时相对路径）匹配的目录，可多次指定
+    #[arg(long, value_name = "GLOB", value_parser = parse_glob)]
+    pub exclude_dir: Vec<glob::Pattern>,
+
+    /// 后序输出：目录中的内容在目录本身之前输出（同 find -depth）
+    #[arg(long, visible_alias = "depth", conflicts_with = "sort")]
+    pub depth_first_post: bool,
+
+    /// 按稳定的顺序（按名称排序的遍历顺序）输出结果，并行搜索时也保证多次运行的输出相同
+    #[arg(long)]
+    pub deterministic: bool,
+
+    /// 不进入其他文件系统上的目录（不跨越挂载点，同 find -xdev）
+    #[arg(short = 'x', long, visible_alias = "xdev")]
+    pub one_file_system: bool,
+
+    /// 使用平台原生接口读取目录（Windows 上使用 FindFirstFileExW，Linux 上需要以 getdents 特性编译，否则与普通读取相同）；
+    /// 与普通搜索一样在线程池中并行遍历，但不跟随符号链接
+    #[arg(long, conflicts_with_all = ["follow_links", "follow_root_links", "cache"])]
+    pub native_readdir: bool,
+
     /// 启用调试日志
     #[arg(short, long)]
     pub debug: bool,
@@ -35,6 +154,69 @@ pub struct Cli {
     #[arg(long, conflicts_with = "absolute")]
     pub relative: bool,
 
+    /// 输出相对于所属搜索路径的路径
+    #[arg(long, conflicts_with_all = ["absolute", "relative"])]
+    pub relative_to_root: bool,
+
+    /// 以 NUL 字符而不是换行结束每个输出结果（配合 `xargs -0` 使用）
+    #[arg(short = '0', long)]
+    pub print0: bool,
+
+    /// 输出格式
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t = OutputFormat::Plain)]
+    pub format: OutputFormat,
+
+    /// 按 GNU find -printf 格式输出（如 '%p %s %TY-%Tm-%Td\n'），不自动追加换行
+    #[arg(long, value_name = "FORMAT", conflicts_with_all = ["format", "print0"])]
+    pub printf: Option<String>,
+
+    /// 以 find -ls 风格输出：inode、块数、权限、链接数、属主、属组、大小、修改时间和路径
+    #[arg(long, conflicts_with_all = ["format", "printf"])]
+    pub ls: bool,
+
+    /// 不输出路径，只输出匹配条目的数量和总大小
+    #[arg(long, conflicts_with_all = ["format", "printf", "ls", "security_audit", "print0"])]
+    pub summarize: bool,
+
+    /// 不输出路径，只输出匹配条目的数量
+    #[arg(short = 'c', long, conflicts_with_all = ["format", "printf", "ls", "security_audit", "print0",
+                                                  "summarize", "exec", "execdir", "ok", "delete", "trash",
+                                                  "rename", "copy_to", "move_to", "archive", "chmod", "chown",
+                                                  "diff_snapshot", "sort", "watch"])]
+    pub count: bool,
+
+    /// 与 --count 一起使用：分别输出每个搜索路径的匹配数量（每行为数量和路径，以制表符分隔）
+    #[arg(long, requires = "count")]
+    pub per_root: bool,
+
+    /// 不输出任何内容，找到匹配的条目时以 0 退出，否则以 1 退出（找到第一个匹配后即停止）
+    #[arg(short = 'q', long, conflicts_with_all = ["format", "printf", "ls", "security_audit", "print0",
+                                                  "summarize", "count", "exec", "execdir", "ok", "delete", "trash",
+                                                  "rename", "copy_to", "move_to", "archive", "chmod", "chown",
+                                                  "save_snapshot", "diff_snapshot", "sort", "watch"])]
+    pub quiet: bool,
+
+    /// 运行结束后向标准错误输出搜索统计：遍历的目录和条目、匹配数、跳过的条目、各类错误、匹配文件的大小和耗时
+    #[arg(long, conflicts_with_all = ["quiet", "cache", "watch"])]
+    pub stats: bool,
+
+    /// 向标准错误输出每个候选条目经过的过滤器及其判断（通过、拒绝或出错），用于调试过滤条件
+    #[arg(long, conflicts_with_all = ["quiet", "cache", "watch"])]
+    pub explain: bool,
+
+    /// 只检查指定的路径，依次列出每个过滤器的判断后退出；匹配时以 0 退出，否则以 1 退出
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::AnyPath,
+          conflicts_with_all = ["explain", "quiet", "watch"])]
+    pub explain_path: Option<std::path::PathBuf>,
+
+    /// 何时按条目类型着色（遵循 LS_COLORS 和 NO_COLOR）
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// CSV 输出的列，逗号分隔（默认 path,size,mtime,type）
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "FIELDS")]
+    pub fields: Vec<Field>,
+
     /// 按文件名模式匹配 (支持通配符，可多次指定)
     #[arg(short = 'n', long, conflicts_with = "iname")]
     pub name: Vec<String>,
@@ -43,22 +225,229 @@ pub struct Cli {
     #[arg(short = 'i', long = "iname", conflicts_with = "name")]
     pub iname: Vec<String>,
 
+    /// 排除文件名匹配模式的条目（可多次指定，匹配任一即排除）
+    ///
+    /// 也可以在 --name/--iname 的模式前加 ! 表示排除，以 \! 开头表示字面的 !
+    #[arg(long, value_name = "PATTERN")]
+    pub not_name: Vec<String>,
+
+    /// 将 --name/--iname 的模式作为字面文本，文件名包含该文本即匹配（不解释通配符）
+    #[arg(short = 'F', long)]
+    pub fixed_strings: bool,
+
+    /// 按文件类型匹配：f 普通文件、d 目录、l 符号链接、b 块设备、c 字符设备、
+    /// p 命名管道、s 套接字，多个类型以逗号分隔（如 f,l）
+    #[arg(short = 't', long = "type", value_name = "TYPE", value_parser = TypeFilter::new)]
+    pub file_type: Option<TypeFilter>,
+
+    /// 按路径匹配 glob 模式，可多次指定，须全部满足
+    ///
+    /// 模式与完整路径或相对于搜索根目录的路径比较；默认 * 和 ? 不匹配 /，
+    /// ** 可以跨越任意层目录（如 'src/**/tests/*.rs'）
+    #[arg(long, value_name = "GLOB")]
+    pub path: Vec<String>,
+
+    /// --path 中的 * 和 ? 也匹配路径分隔符（同 GNU find -path）
+    #[arg(long)]
+    pub path_wildcard_slash: bool,
+
+    /// 按类 SQL 的查询表达式匹配，与其他过滤条件同时生效
+    ///
+    /// 例如 "type = file AND size > 10M AND (name LIKE '%.log' OR mtime < 30d)"，
+    /// 字段有 name、path、type、size、links、depth、mtime、atime
+    #[arg(long, value_name = "QUERY")]
+    pub query: Option<String>,
+
+    /// 按旁路文件中的属性匹配（KEY 或 KEY=VALUE，可多次指定，须全部满足）
+    #[arg(long, value_name = "KEY[=VALUE]")]
+    pub attr: Vec<String>,
+
+    /// 属性旁路文件的扩展名（文件 a.txt 的属性读取自 a.txt.<EXT>）
+    #[arg(long, value_name = "EXT", default_value = "meta")]
+    pub attr_sidecar: String,
+
+    /// 只匹配带有指定扩展属性的条目（可多次指定，须全部满足），NAME=VALUE 同时要求属性值相等
+    #[arg(long, value_name = "NAME[=VALUE]")]
+    pub xattr: Vec<String>,
+
+    /// 只匹配带有扩展 POSIX ACL 的条目（实际权限可能与权限位不同）
+    #[arg(long)]
+    pub has_acl: bool,
+
+    /// 只匹配访问 ACL 中带有指定条目的文件，格式同 setfacl，如 user:bob:rwx、group::r-x（可多次指定，须全部满足）
+    #[arg(long, value_name = "TYPE:QUALIFIER[:PERMS]")]
+    pub acl_entry: Vec<String>,
+
+    /// 只匹配设置了文件能力（setcap）的文件
+    #[arg(long)]
+    pub has_caps: bool,
+
+    /// 只匹配带有指定能力的文件，如 cap_net_raw（可多次指定，须全部满足）
+    #[arg(long, value_name = "CAPABILITY")]
+    pub cap: Vec<String>,
+
+    /// 只匹配设置了指定 inode 标志的文件（chattr，如 immutable、append-only；可多次指定，须全部满足）
+    #[arg(long, value_enum, value_name = "FLAG")]
+    pub flag: Vec<InodeFlag>,
+
+    /// 只匹配目标不存在的符号链接（同 find -xtype l）
+    #[arg(long)]
+    pub broken_symlinks: bool,
+
+    /// 按权限位匹配（同 find -perm）：MODE 完全相同，-MODE 包含所有位，/MODE 包含任一位（八进制）
+    #[arg(long, value_name = "MODE", allow_hyphen_values = true)]
+    pub perm: Option<PermMatch>,
+
+    /// 只匹配设置了 setuid 位的条目（同 --perm -4000）
+    #[arg(long)]
+    pub suid: bool,
+
+    /// 只匹配设置了 setgid 位的条目（同 --perm -2000）
+    #[arg(long)]
+    pub sgid: bool,
+
+    /// 只匹配当前用户可执行的文件和可进入的目录（检查实际访问权限）
+    #[arg(long)]
+    pub executable: bool,
+
+    /// 只匹配当前用户可读的条目（检查实际访问权限）
+    #[arg(long)]
+    pub readable: bool,
+
+    /// 只匹配当前用户可写的条目（检查实际访问权限）
+    #[arg(long)]
+    pub writable: bool,
+
+    /// 安全审计：查找设置了 setuid 或 setgid 位的普通文件，并以 -ls 风格输出
+    #[arg(long, conflicts_with_all = ["format", "printf"])]
+    pub security_audit: bool,
+
+    /// 按硬链接数匹配：+N 多于 N 个，-N 少于 N 个，N 恰好 N 个
+    #[arg(long, value_name = "[+-]N", allow_hyphen_values = true)]
+    pub links: Option<NumericComparison>,
+
+    /// 按文件名长度（字符数）匹配：+N 多于 N，-N 少于 N，N 恰好 N
+    #[arg(long, value_name = "[+-]N", allow_hyphen_values = true)]
+    pub name_length: Option<NumericComparison>,
+
+    /// 按路径长度（字符数）匹配，例如 --path-length +259 查找超过 Windows 路径限制的条目
+    #[arg(long, value_name = "[+-]N", allow_hyphen_values = true)]
+    pub path_length: Option<NumericComparison>,
+
+    /// 按大小匹配：+N 大于 N，-N 小于 N，N 恰好 N
+    ///
+    /// N 可以带单位：k、M、G、T、P 或 KiB、MiB 等为 1024 的幂，kB、MB 等为 1000 的幂，
+    /// 例如 +1.5G、-500k、10MiB。
+    #[arg(long, value_name = "[+-]SIZE", allow_hyphen_values = true,
+          value_parser = NumericComparison::parse_size)]
+    pub size: Option<NumericComparison>,
+
+    /// 只匹配不小于指定大小的条目（单位同 --size）
+    #[arg(long, value_name = "SIZE")]
+    pub min_size: Option<ByteSize>,
+
+    /// 只匹配不大于指定大小的条目（单位同 --size）
+    #[arg(long, value_name = "SIZE")]
+    pub max_size: Option<ByteSize>,
+
+    /// --size、--min-size、--max-size 和 --summarize 使用实际占用的磁盘空间而不是文件大小
+    ///
+    /// 压缩、稀疏或去重的文件系统上两者可能相差很大。
+    #[arg(long)]
+    pub size_on_disk: bool,
+
+    /// 只匹配指定 inode 编号的条目
+    #[arg(long, value_name = "NUM")]
+    pub inum: Option<u64>,
+
+    /// 只匹配与指定文件是同一文件的条目（该文件本身及其硬链接）
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::AnyPath)]
+    pub samefile: Option<std::path::PathBuf>,
+
+    /// 只匹配所在 git 仓库中被跟踪的文件（git ls-files），排除构建产物和未加入索引的文件
+    #[arg(long)]
+    pub git_tracked: bool,
+
+    /// 只匹配 git 仓库中有未提交修改的文件（包括已暂存和新加入索引的文件）
+    #[arg(long)]
+    pub git_modified: bool,
+
+    /// 只匹配 git 仓库中未被跟踪且未被忽略的文件
+    #[arg(long)]
+    pub git_untracked: bool,
+
+    /// 只匹配自指定 git 引用以来改动过的文件（与工作区比较，包括未提交的修改）
+    #[arg(long, value_name = "REF")]
+    pub changed_in_git: Option<String>,
+
+    /// 只匹配内容中包含指定文本的文件（可多次指定，须全部满足）
+    #[arg(long, value_name = "TEXT")]
+    pub contains: Vec<String>,
+
+    /// 只匹配内容中有行符合正则表达式的文件（可多次指定，须全部满足）
+    #[arg(long, value_name = "REGEX")]
+    pub contains_regex: Vec<String>,
+
+    /// 只匹配文本文件，内容搜索时跳过二进制文件（开头 8 KiB 中含有 NUL 字节）
+    #[arg(long, visible_alias = "skip-binary", conflicts_with = "binary_only")]
+    pub text_only: bool,
+
+    /// 只匹配二进制文件
+    #[arg(long)]
+    pub binary_only: bool,
+
+    /// 按文件内容识别的 MIME 类型匹配，如 image/* 或 application/pdf（可多次指定，匹配任一即可）
+    #[arg(long, value_name = "TYPE/SUBTYPE")]
+    pub mime: Vec<String>,
+
     /// 并行搜索（实验性功能）
     #[arg(short = 'p', long)]
     pub parallel: bool,
 
-    /// 忽略IO错误（如权限不足、符号链接循环等）
+    /// 忽略权限错误以外的遍历错误（路径不存在、符号链接循环等），等价于对这些类别使用 silent
     #[arg(long)]
     pub ignore_io_errors: bool,
 
-    /// 忽略权限错误
+    /// 忽略权限错误，等价于 --on-permission-error silent
     #[arg(long)]
     pub ignore_permission_errors: bool,
 
-    /// 不忽略隐藏文件（以点开头的文件）
+    /// 遇到权限错误时的处理方式：fail（中止搜索）、warn（报告后继续，默认）或 silent（不报告）
+    #[arg(long, value_enum, value_name = "POLICY")]
+    pub on_permission_error: Option<ErrorPolicy>,
+
+    /// 路径不存在时的处理方式（取值同 --on-permission-error）
+    #[arg(long, value_enum, value_name = "POLICY")]
+    pub on_not_found: Option<ErrorPolicy>,
+
+    /// 遇到符号链接循环时的处理方式（取值同 --on-permission-error）
+    #[arg(long, value_enum, value_name = "POLICY")]
+    pub on_symlink_loop: Option<ErrorPolicy>,
+
+    /// 遇到其他 I/O 错误时的处理方式（取值同 --on-permission-error）
+    #[arg(long, value_enum, value_name = "POLICY")]
+    pub on_io_error: Option<ErrorPolicy>,
+
+    /// 遇到任何遍历错误时立即中止搜索（按类别指定的 --on-* 选项仍然优先）
+    #[arg(long, conflicts_with_all = ["ignore_io_errors", "ignore_permission_errors", "no_fail_on_error"])]
+    pub strict: bool,
+
+    /// 遍历中出现错误时仍以 0 退出（默认与 GNU find 相同，出现任何错误时以 1 退出，包括被忽略的错误）
+    #[arg(long)]
+    pub no_fail_on_error: bool,
+
+    /// 遍历错误的报告格式：text（日志警告）或 json（每行一个 JSON 对象，输出到标准错误）
+    #[arg(long, value_enum, value_name = "FORMAT", default_value = "text")]
+    pub error_format: ErrorFormat,
+
+    /// 不忽略隐藏文件（以点开头的文件，Windows 上还包括带有隐藏属性的文件）
     #[arg(long)]
     pub no_ignore_hidden: bool,
 
+    /// Windows 上视为隐藏的文件属性：none（只按名称）、hidden 或 system（隐藏或系统文件）
+    #[arg(long, value_enum, value_name = "ATTRS", default_value = "hidden")]
+    pub hidden_attributes: HiddenAttributes,
+
     /// 最大线程数（并行搜索时）
     #[arg(long, value_name = "NUM")]
     pub max_threads: Option<usize>,
@@ -74,22 +463,795 @@ pub struct Cli {
     /// 禁用自动调整线程数（并行搜索时）
     #[arg(long)]
     pub no_auto_adjust: bool,
+
+    /// 不使用上次运行的统计预热线程池
+    #[arg(long)]
+    pub no_warm_start: bool,
+
+    /// 按命令行上的顺序检查过滤器（默认先检查名称、类型等代价低的过滤器）
+    #[arg(long)]
+    pub no_reorder: bool,
+
+    /// 对每个匹配项执行命令（`{}` 替换为路径，没有 `{}` 时按原样执行，以 `;` 结束；以 `{} +` 结尾时批量执行）
+    #[arg(long, num_args = 1.., value_terminator = ";", allow_hyphen_values = true,
+          value_name = "CMD", conflicts_with = "execdir")]
+    pub exec: Vec<String>,
+
+    /// 在匹配项所在目录中执行命令（`{}` 替换为 `./文件名`，没有 `{}` 时按原样执行，以 `;` 结束；以 `{} +` 结尾时批量执行）
+    #[arg(long, num_args = 1.., value_terminator = ";", allow_hyphen_values = true,
+          value_name = "CMD")]
+    pub execdir: Vec<String>,
+
+    /// 与 --exec 相同，但执行前在标准错误上询问，从标准输入读取回答（y 表示执行；不支持 `{} +`）
+    #[arg(long, num_args = 1.., value_terminator = ";", allow_hyphen_values = true,
+          value_name = "CMD")]
+    pub ok: Vec<String>,
+
+    /// 删除匹配的文件（需要至少一个过滤条件，不会删除搜索路径本身）
+    #[arg(long)]
+    pub delete: bool,
+
+    /// 与 --delete 一起使用时，同时删除匹配的空目录
+    #[arg(long, requires = "delete")]
+    pub delete_dirs: bool,
+
+    /// 与 --delete 一起使用时，删除每个匹配项前询问确认
+    #[arg(long, requires = "delete")]
+    pub interactive: bool,
+
+    /// 将匹配项移至系统回收站（需要至少一个过滤条件，不会移走搜索路径本身）
+    #[arg(long, conflicts_with = "delete")]
+    pub trash: bool,
+
+    /// 按模板重命名匹配的文件，新名称位于原目录中，例如 `{stem}_{mtime:%Y%m%d}.{ext}`
+    /// （占位符：name、stem、ext、parent、n[:宽度]、mtime[:格式]、now[:格式]；目标已存在时跳过）
+    #[arg(long, value_name = "TEMPLATE", conflicts_with_all = ["delete", "trash"])]
+    pub rename: Option<String>,
+
+    /// 将匹配的文件复制到指定目录（目录不存在时创建；目标已存在的文件会被跳过）
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath, group = "transfer")]
+    pub copy_to: Option<std::path::PathBuf>,
+
+    /// 将匹配的文件移动到指定目录（目录不存在时创建；目标已存在的文件会被跳过）
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath, group = "transfer",
+          conflicts_with_all = ["delete", "trash", "rename"])]
+    pub move_to: Option<std::path::PathBuf>,
+
+    /// 与 --copy-to/--move-to 一起使用时，在目标目录中保留相对于搜索路径的目录结构
+    #[arg(long, requires = "transfer")]
+    pub preserve_paths: bool,
+
+    /// 将匹配项写入 tar 归档，按扩展名压缩（.tar、.tar.gz/.tgz、.tar.zst/.tzst），名称相对于搜索路径；
+    /// 匹配项在遍历过程中逐个写入，不在内存中收集（与 --sort score、--summarize、--deterministic、
+    /// --search-archives、快照、--no-recurse 或 --cache 一起使用时在搜索结束后写入）。
+    /// 与其他操作一起使用时先写入归档。归档在运行结束时才写完，
+    /// 因此不能与 --delete、--trash 或 --move-to 一起使用，以免写入失败时丢失文件
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath,
+          conflicts_with_all = ["delete", "trash", "move_to"])]
+    pub archive: Option<std::path::PathBuf>,
+
+    /// 修改匹配项的权限，八进制（如 644）或符号形式（如 u+x、go-w、a=r,u+w）；跳过符号链接，结束时输出汇总
+    #[arg(long, value_name = "MODE", conflicts_with_all = ["delete", "trash"])]
+    pub chmod: Option<ModeChange>,
+
+    /// 修改匹配项的属主和属组（USER、USER:GROUP 或 :GROUP，可以是名称或数字 ID）；结束时输出汇总
+    #[arg(long, value_name = "USER[:GROUP]", conflicts_with_all = ["delete", "trash"])]
+    pub chown: Option<OwnerSpec>,
+
+    /// 演练模式：所有操作只输出将要执行的内容，不修改文件系统
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// 将搜索结果及其属性保存为快照文件
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub save_snapshot: Option<String>,
+
+    /// 与快照文件比较，输出新增(+)、删除(-)和变化(~)的条目
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub diff_snapshot: Option<String>,
+
+    /// 差异比较时检查的属性字段（逗号分隔）
+    #[arg(long, value_enum, value_delimiter = ',', requires = "diff_snapshot")]
+    pub diff_fields: Vec<DiffField>,
+
+    /// 使用增量扫描缓存文件：修改时间未变化的目录直接使用上次扫描的结果
+    /// （原地修改的文件不会使缓存失效）；在 macOS 上按 FSEvents 的历史事件只重新读取发生变化的目录
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub cache: Option<String>,
+
+    /// 结果的排序方式（score：按内容匹配次数从高到低，需要 --contains 或 --contains-regex）
+    #[arg(long, value_enum, value_name = "KEY")]
+    pub sort: Option<SortKey>,
+
+    /// 输出指定 shell 的补全脚本后退出
+    #[arg(long, value_enum, value_name = "SHELL")]
+    pub completions: Option<Shell>,
+
+    /// 初始搜索后持续监视文件系统变化，输出新出现或被修改的匹配条目
+    #[arg(long, conflicts_with_all = ["no_recurse", "exec", "execdir", "ok", "delete", "trash", "rename",
+                                      "copy_to", "move_to", "archive", "chmod", "chown", "save_snapshot", "diff_snapshot", "cache", "sort"])]
+    pub watch: bool,
+
+    /// 不遍历目录树，从 NTFS 的 USN 变更日志中读取指定 USN 之后发生变化的匹配条目（仅 Windows），
+    /// 结束后向标准错误输出下一次查询使用的 USN
+    #[arg(long, value_name = "USN", value_parser = clap::value_parser!(i64).range(0..),
+          conflicts_with_all = ["watch", "no_recurse", "cache", "save_snapshot", "diff_snapshot", "explain_path"])]
+    pub changed_since_usn: Option<i64>,
+}
+
+/// 子命令
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// 比较串行遍历和不同线程数的并行遍历，报告耗时和吞吐量
+    Bench(BenchArgs),
+    /// 审计目录树中的权限风险，按类别汇总全局可写的文件和目录以及属主不存在的条目
+    Audit(AuditArgs),
+    /// 作为常驻服务运行，通过 HTTP 接口接受搜索请求（POST /search，结果为 NDJSON）
+    Serve(ServeArgs),
+    /// 作为常驻进程运行，在内存中缓存目录列表，通过 Unix 套接字接受 query 子命令的搜索请求（仅 Unix）
+    Daemon(DaemonArgs),
+    /// 通过守护进程搜索，目录列表已缓存时无需重新读取（仅 Unix）
+    Query(QueryArgs),
+    /// 通过 gRPC 提供搜索接口（需要以 grpc 特性编译，接口定义见 proto/search.proto）
+    Grpc(GrpcArgs),
+    /// 管理守护进程缓存的目录列表（仅 Unix）
+    Index(IndexArgs),
+}
+
+/// `bench` 子命令的参数
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// 测试的目录
+    #[arg(value_hint = ValueHint::DirPath)]
+    pub path: std::path::PathBuf,
+
+    /// 并行遍历使用的线程数（逗号分隔，默认：1,2,4,8 中小于 CPU 核心数的值以及 CPU 核心数）
+    #[arg(long, value_name = "NUM", value_delimiter = ',')]
+    pub threads: Vec<usize>,
+
+    /// 测试的过滤器组合（逗号分隔）
+    #[arg(long, value_enum, value_delimiter = ',', default_values = ["all", "name"])]
+    pub filters: Vec<BenchFilter>,
+
+    /// name 和 iname 过滤器使用的文件名模式
+    #[arg(long, value_name = "PATTERN", default_value = "*.txt")]
+    pub pattern: String,
+
+    /// 每种组合重复运行的次数，报告耗时的中位数
+    #[arg(long, value_name = "NUM", default_value_t = 3)]
+    pub iterations: usize,
+
+    /// 并行遍历时每个线程处理的目录数
+    #[arg(long, value_name = "NUM", default_value_t = 10)]
+    pub dirs_per_thread: usize,
+}
+
+impl BenchArgs {
+    /// 构建基准测试配置
+    pub fn config(&self) -> BenchConfig {
+        let defaults = BenchConfig::default();
+        BenchConfig {
+            threads: if self.threads.is_empty() { defaults.threads } else { self.threads.clone() },
+            filters: self.filters.clone(),
+            pattern: self.pattern.clone(),
+            iterations: self.iterations,
+            dirs_per_thread: self.dirs_per_thread,
+        }
+    }
+}
+
+/// `audit` 子命令的参数
+#[derive(Args, Debug)]
+pub struct AuditArgs {
+    /// 审计的目录
+    #[arg(value_hint = ValueHint::DirPath)]
+    pub path: std::path::PathBuf,
+
+    /// 不进入其他文件系统中的目录
+    #[arg(long)]
+    pub one_file_system: bool,
+
+    /// 最大搜索深度
+    #[arg(long, value_name = "NUM")]
+    pub max_depth: Option<usize>,
+}
+
+impl AuditArgs {
+    /// 构建审计使用的查找选项
+    pub fn options(&self) -> FindOptions {
+        FindOptions::new()
+            .with_max_depth(self.max_depth)
+            .with_same_file_system(self.one_file_system)
+    }
+}
+
+/// `serve` 子命令的参数
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// 监听的地址，默认只接受本机的连接
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:7878")]
+    pub listen: String,
+}
+
+/// `grpc` 子命令的参数
+#[derive(Args, Debug)]
+pub struct GrpcArgs {
+    /// 监听的地址，默认只接受本机的连接
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:50051")]
+    pub listen: std::net::SocketAddr,
+}
+
+/// `index` 子命令的参数
+#[derive(Args, Debug)]
+pub struct IndexArgs {
+    #[command(subcommand)]
+    pub command: IndexCommand,
+}
+
+/// `index` 的子命令
+#[derive(Subcommand, Debug)]
+pub enum IndexCommand {
+    /// 让守护进程从 updatedb 生成的 mlocate 或 plocate 数据库导入目录列表，省去第一次完整读取
+    Import(IndexImportArgs),
+}
+
+/// `index import` 的参数
+#[derive(Args, Debug)]
+pub struct IndexImportArgs {
+    /// 数据库文件，例如 /var/lib/plocate/plocate.db 或 /var/lib/mlocate/mlocate.db
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub database: std::path::PathBuf,
+
+    /// 守护进程的套接字路径（默认 $XDG_RUNTIME_DIR/rust-find.sock）
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub socket: Option<std::path::PathBuf>,
+}
+
+/// `daemon` 子命令的参数
+#[derive(Args, Debug)]
+pub struct DaemonArgs {
+    /// 监听的套接字路径（默认 $XDG_RUNTIME_DIR/rust-find.sock）
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub socket: Option<std::path::PathBuf>,
+
+    /// 启动时缓存的目录树（可多次指定），由文件系统事件保持最新，搜索时不再检查其中目录的修改时间
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub watch: Vec<std::path::PathBuf>,
+}
+
+/// `query` 子命令的参数，过滤选项与搜索时的同名选项相同
+#[derive(Args, Debug)]
+pub struct QueryArgs {
+    /// 搜索路径（默认为当前目录）
+    #[arg(value_hint = ValueHint::DirPath)]
+    pub paths: Vec<std::path::PathBuf>,
+
+    /// 守护进程的套接字路径（默认 $XDG_RUNTIME_DIR/rust-find.sock）
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub socket: Option<std::path::PathBuf>,
+
+    /// 按文件名模式匹配（可多次指定，任一匹配即可）
+    #[arg(short = 'n', long, value_name = "PATTERN")]
+    pub name: Vec<String>,
+
+    /// 不区分大小写的文件名匹配（可多次指定，任一匹配即可）
+    #[arg(short = 'i', long, value_name = "PATTERN")]
+    pub iname: Vec<String>,
+
+    /// 按文件类型匹配（如 f、d、f,l）
+    #[arg(short = 't', long = "type", value_name = "TYPE")]
+    pub file_type: Option<String>,
+
+    /// 按路径匹配 glob 模式（可多次指定，须全部满足）
+    #[arg(long, value_name = "GLOB")]
+    pub path: Vec<String>,
+
+    /// 按查询表达式匹配
+    #[arg(long, value_name = "QUERY")]
+    pub query: Option<String>,
+
+    /// 最小文件大小（如 10M）
+    #[arg(long, value_name = "SIZE")]
+    pub min_size: Option<String>,
+
+    /// 最大文件大小
+    #[arg(long, value_name = "SIZE")]
+    pub max_size: Option<String>,
+
+    /// 文件内容包含的文本（可多次指定，须全部满足）
+    #[arg(long, value_name = "TEXT")]
+    pub contains: Vec<String>,
+
+    /// 最大搜索深度
+    #[arg(short = 'd', long, value_name = "NUM")]
+    pub max_depth: Option<usize>,
+
+    /// 包含隐藏文件
+    #[arg(long)]
+    pub hidden: bool,
+
+    /// 最多输出的结果数
+    #[arg(long, value_name = "NUM")]
+    pub max_results: Option<usize>,
+}
+
+impl QueryArgs {
+    /// 构建发送给守护进程的请求
+    pub fn spec(&self) -> FilterSpec {
+        FilterSpec {
+            paths: self.paths.clone(),
+            name: self.name.clone(),
+            iname: self.iname.clone(),
+            file_type: self.file_type.clone(),
+            path: self.path.clone(),
+            query: self.query.clone(),
+            min_size: self.min_size.clone(),
+            max_size: self.max_size.clone(),
+            contains: self.contains.clone(),
+            max_depth: self.max_depth,
+            hidden: self.hidden,
+            max_results: self.max_results,
+        }
+    }
+}
+
+/// 拆分起始路径列表
+///
+/// 按行拆分时去掉行尾的回车，以兼容 Windows 换行。
+fn parse_start_points(data: &[u8], separator: u8) -> FindResult<Vec<String>> {
+    data.split(|&byte| byte == separator)
+        .map(|item| if separator == b'\n' { item.strip_suffix(b"\r").unwrap_or(item) } else { item })
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            String::from_utf8(item.to_vec()).map_err(|_| {
+                FindError::InvalidPath(std::path::PathBuf::from(String::from_utf8_lossy(item).into_owned()))
+            })
+        })
+        .collect()
+}
+
+/// 保存默认参数的环境变量
+pub const OPTS_ENV_VAR: &str = "RUST_FIND_OPTS";
+
+/// 将默认参数插入到程序名之后、命令行参数之前
+///
+/// 默认参数按 shell 规则拆分（支持引号和转义），同一选项在命令行中再次给出时以命令行为准。
+/// 默认参数是搜索选项，不能与子命令一起使用，第一个参数是子命令时不插入。
+fn prepend_defaults<I>(args: I, defaults: &str) -> Result<Vec<OsString>, String>
+where
+    I: IntoIterator<Item = OsString>,
+{
+    let defaults = shlex::split(defaults)
+        .ok_or_else(|| format!("无法解析环境变量 {} 中的参数: 引号不匹配", OPTS_ENV_VAR))?;
+    let mut args = args.into_iter().peekable();
+    let program = args.next();
+    let subcommand = args
+        .peek()
+        .and_then(|arg| arg.to_str())
+        .is_some_and(|arg| Cli::command().find_subcommand(arg).is_some());
+    let defaults = if subcommand { Vec::new() } else { defaults };
+    Ok(program
+        .into_iter()
+        .chain(defaults.into_iter().map(OsString::from))
+        .chain(args)
+        .collect())
+}
+
+/// 是否为 sftp:// 或 s3:// 远程路径
+fn is_remote_path(path: &str) -> bool {
+    SftpUrl::parse(path).is_some() || S3Url::parse(path).is_some()
+}
+
+/// 解析命令行中的通配符模式
+fn parse_glob(pattern: &str) -> Result<glob::Pattern, String> {
+    glob::Pattern::new(pattern).map_err(|e| format!("无效的模式 '{}': {}", pattern, e))
 }
 
 impl Cli {
+    /// 解析命令行参数，环境变量 `RUST_FIND_OPTS` 中的参数作为默认值
+    ///
+    /// 解析失败时输出错误并退出，与 [`Parser::parse`] 相同。
+    pub fn parse_with_env_defaults() -> Self {
+        let args = std::env::args_os();
+        let defaults = std::env::var(OPTS_ENV_VAR).unwrap_or_default();
+        match prepend_defaults(args, &defaults) {
+            Ok(args) => Self::parse_from(args),
+            Err(message) => Self::command().error(ErrorKind::InvalidValue, message).exit(),
+        }
+    }
+
+    /// 用 --files-from/--files0-from 读取的路径替换起始路径
+    ///
+    /// 列表中的每个路径都作为起始点：目录会被遍历，文件只检查其本身。
+    /// 空行被忽略，列表为空时不搜索任何路径。
+    ///
+    /// # 错误
+    /// 如果无法读取列表或其中的路径不是有效的 UTF-8，返回相应的错误
+    pub fn load_start_points(&mut self) -> FindResult<()> {
+        let (file, separator) = match (&self.files_from, &self.files0_from) {
+            (Some(file), _) => (file, b'\n'),
+            (None, Some(file)) => (file, b'\0'),
+            (None, None) => return Ok(()),
+        };
+
+        let mut data = Vec::new();
+        let read = if file.as_os_str() == "-" {
+            std::io::stdin().lock().read_to_end(&mut data)
+        } else {
+            std::fs::File::open(file).and_then(|mut list| list.read_to_end(&mut data))
+        };
+        read.map_err(|source| FindError::FilesystemError { source, path: file.clone() })?;
+
+        self.paths = parse_start_points(&data, separator)?;
+        Ok(())
+    }
+
+    /// 将指定 shell 的补全脚本写入输出
+    ///
+    /// 取值固定的参数（例如 `--format` 和 `--color`）会补全其可选值，
+    /// 路径参数会补全文件名。
+    pub fn write_completions<W: std::io::Write>(shell: Shell, out: &mut W) {
+        let mut command = Self::command();
+        let name = command.get_name().to_string();
+        clap_complete::generate(shell, &mut command, name, out);
+    }
+
+    /// 每类遍历错误的处理策略
+    ///
+    /// 按类别指定的策略优先，其次是 --ignore-permission-errors、--ignore-io-errors 和 --strict，
+    /// 都未指定时报告错误并继续搜索。
+    pub fn error_policies(&self) -> ErrorPolicies {
+        let fallback = if self.strict { ErrorPolicy::Fail } else { ErrorPolicy::Warn };
+        let policy = |explicit: Option<ErrorPolicy>, ignore: bool| {
+            explicit.unwrap_or(if ignore { ErrorPolicy::Silent } else { fallback })
+        };
+        ErrorPolicies {
+            permission: policy(self.on_permission_error, self.ignore_permission_errors),
+            not_found: policy(self.on_not_found, self.ignore_io_errors),
+            symlink_loop: policy(self.on_symlink_loop, self.ignore_io_errors),
+            other: policy(self.on_io_error, self.ignore_io_errors),
+        }
+    }
+
     /// 构建查找选项
     pub fn build_options(&self) -> FindOptions {
         FindOptions {
-            max_depth: self.max_depth,
-            follow_links: self.follow_links,
-            ignore_permission_errors: self.ignore_permission_errors,
-            ignore_io_errors: self.ignore_io_errors,
+            max_depth: self.effective_max_depth(),
+            symlink_policy: self.symlink_policy(),
+            error_policies: self.error_policies(),
             ignore_hidden: !self.no_ignore_hidden,
+            hidden_attributes: self.hidden_attributes,
             max_threads: self.max_threads.unwrap_or(num_cpus::get()),
             min_threads: self.min_threads.unwrap_or(1),
             dirs_per_thread: self.dirs_per_thread.unwrap_or(10),
             auto_adjust: !self.no_auto_adjust,
+            pool_stats_file: self.pool_stats_file(),
+            max_results: self.max_results,
+            respect_gitignore: self.respect_gitignore,
+            respect_findignore: !self.no_findignore,
+            global_ignore_file: default_global_ignore_file(),
+            exclude: self.exclude.clone(),
+            exclude_dirs: self.exclude_dir.clone(),
+            same_file_system: self.one_file_system,
+            max_symlink_depth: self.max_symlink_depth,
+            skip_reparse_points: self.skip_reparse_points,
+            contents_first: self.depth_first_post,
+            deterministic: self.deterministic,
+            measure_matches: self.stats,
+            error_format: self.error_format,
+            reorder_filters: !self.no_reorder,
+            explain: self.explain,
+            ..FindOptions::new()
+        }
+    }
+
+    /// --size 和 --summarize 使用的大小计算方式
+    pub fn size_measure(&self) -> SizeMeasure {
+        if self.size_on_disk {
+            SizeMeasure::OnDisk
+        } else {
+            SizeMeasure::Apparent
+        }
+    }
+
+    /// 获取实际使用的最大搜索深度
+    pub fn effective_max_depth(&self) -> Option<usize> {
+        if self.no_recurse {
+            Some(1)
+        } else {
+            self.max_depth
+        }
+    }
+
+    /// 获取线程池预热统计文件
+    pub fn pool_stats_file(&self) -> Option<std::path::PathBuf> {
+        if self.no_warm_start {
+            None
+        } else {
+            default_stats_file()
+        }
+    }
+
+    /// 获取符号链接处理策略
+    pub fn symlink_policy(&self) -> SymlinkPolicy {
+        if self.follow_links {
+            SymlinkPolicy::Always
+        } else if self.follow_root_links {
+            SymlinkPolicy::CommandLine
+        } else {
+            SymlinkPolicy::Never
+        }
+    }
+
+    /// 构建命令行指定的过滤器
+    pub fn build_filters(&self) -> FindResult<Vec<Box<dyn FileFilter + Send + Sync>>> {
+        let mut filters: Vec<Box<dyn FileFilter + Send + Sync>> = Vec::new();
+
+        // 多个 --name/--iname 模式中任一匹配即可
+        let (include, exclude) = self.split_name_patterns();
+        if !include.is_empty() {
+            filters.push(Box::new(self.name_filter(&include)?));
+        }
+        if !exclude.is_empty() {
+            filters.push(Box::new(NotFilter::new(self.name_filter(&exclude)?)));
+        }
+        if let Some(type_filter) = &self.file_type {
+            filters.push(Box::new(type_filter.clone()));
+        }
+        for pattern in &self.path {
+            let filter = PathFilter::new(pattern)?
+                .with_roots(self.root_paths())
+                .with_literal_separator(!self.path_wildcard_slash);
+            filters.push(Box::new(filter));
+        }
+        if !self.attr.is_empty() {
+            let provider = std::sync::Arc::new(SidecarProvider::new(&self.attr_sidecar));
+            for spec in &self.attr {
+                filters.push(Box::new(AttrFilter::new(provider.clone(), spec)?));
+            }
+        }
+        for spec in &self.xattr {
+            filters.push(Box::new(XattrFilter::new(spec)?));
+        }
+        if self.has_acl {
+            filters.push(Box::new(HasAclFilter));
+        }
+        for spec in &self.acl_entry {
+            filters.push(Box::new(AclEntryFilter::new(spec)?));
+        }
+        if self.has_caps {
+            filters.push(Box::new(HasCapsFilter));
+        }
+        for name in &self.cap {
+            filters.push(Box::new(CapFilter::new(name)?));
+        }
+        for &flag in &self.flag {
+            filters.push(Box::new(InodeFlagFilter::new(flag)));
+        }
+        if let Some(perm) = self.perm {
+            filters.push(Box::new(PermFilter::new(perm)));
+        }
+        if self.suid {
+            filters.push(Box::new(PermFilter::setuid()));
+        }
+        if self.sgid {
+            filters.push(Box::new(PermFilter::setgid()));
+        }
+        for (enabled, mode) in [
+            (self.executable, AccessMode::Execute),
+            (self.readable, AccessMode::Read),
+            (self.writable, AccessMode::Write),
+        ] {
+            if enabled {
+                filters.push(Box::new(AccessFilter::new(mode)));
+            }
+        }
+        if self.security_audit {
+            filters.push(Box::new(TypeFilter::new("f")?));
+            filters.push(Box::new(PermFilter::new(PermMatch::Any(0o6000))));
+        }
+        if self.broken_symlinks {
+            filters.push(Box::new(BrokenSymlinkFilter));
+        }
+        if let Some(links) = self.links {
+            filters.push(Box::new(LinksFilter::new(links)));
+        }
+        if let Some(length) = self.name_length {
+            filters.push(Box::new(LengthFilter::name(length)));
+        }
+        if let Some(length) = self.path_length {
+            filters.push(Box::new(LengthFilter::path(length)));
+        }
+        if let Some(size) = self.size {
+            filters.push(Box::new(SizeFilter::new(size, self.size_measure())));
+        }
+        if let Some(min) = self.min_size {
+            filters.push(Box::new(SizeFilter::at_least(min, self.size_measure())));
+        }
+        if let Some(max) = self.max_size {
+            filters.push(Box::new(SizeFilter::at_most(max, self.size_measure())));
+        }
+        if let Some(inode) = self.inum {
+            filters.push(Box::new(InodeFilter::new(inode)));
+        }
+        if let Some(reference) = &self.samefile {
+            filters.push(Box::new(SameFileFilter::new(reference)?));
+        }
+        if let Some(query) = &self.query {
+            filters.push(Box::new(QueryFilter::parse(query)?));
+        }
+        if self.git_tracked {
+            filters.push(Box::new(GitTrackedFilter::new(&self.local_root_paths())?));
+        }
+        if self.git_modified {
+            filters.push(Box::new(GitStatusFilter::new(&self.local_root_paths(), GitStatus::Modified)?));
+        }
+        if self.git_untracked {
+            filters.push(Box::new(GitStatusFilter::new(&self.local_root_paths(), GitStatus::Untracked)?));
+        }
+        if let Some(reference) = &self.changed_in_git {
+            filters.push(Box::new(GitChangedFilter::new(&self.local_root_paths(), reference)?));
+        }
+
+        Ok(filters)
+    }
+
+    /// 构建需要读取文件内容的过滤器
+    pub fn build_content_filters(&self) -> FindResult<Vec<Box<dyn FileFilter + Send + Sync>>> {
+        let mut filters: Vec<Box<dyn FileFilter + Send + Sync>> = Vec::new();
+
+        // 只读取文件开头的过滤器放在前面，尽早排除不匹配的文件
+        if !self.mime.is_empty() {
+            filters.push(Box::new(MimeFilter::new(&self.mime)?));
+        }
+        let has_patterns = !self.contains.is_empty() || !self.contains_regex.is_empty();
+        // 搜索文本时会跳过二进制文件，无需单独检查
+        if self.binary_only || (self.text_only && !has_patterns) {
+            filters.push(Box::new(ContentFilter::binary(self.binary_only)));
+        }
+        for filter in self.content_patterns()? {
+            filters.push(Box::new(filter));
+        }
+
+        Ok(filters)
+    }
+
+    /// 构建 `--sort score` 使用的相关性评分器，未按得分排序时返回 None
+    ///
+    /// # 错误
+    /// 如果没有指定内容模式，返回Other错误
+    pub fn build_scorer(&self) -> FindResult<Option<RelevanceScorer>> {
+        if self.sort != Some(SortKey::Score) {
+            return Ok(None);
+        }
+        let patterns = self.content_patterns()?;
+        if patterns.is_empty() {
+            return Err(FindError::Other {
+                message: "--sort score 需要内容匹配条件".to_string(),
+                context: Some("例如 --contains TODO".to_string()),
+                timestamp: std::time::SystemTime::now(),
+            });
+        }
+        Ok(Some(RelevanceScorer::new(patterns)))
+    }
+
+    /// 构建 `--contains` 和 `--contains-regex` 对应的内容过滤器
+    fn content_patterns(&self) -> FindResult<Vec<ContentFilter>> {
+        let literals = self.contains.iter().map(|text| ContentFilter::literal(text));
+        let regexes = self.contains_regex.iter().map(|regex| ContentFilter::regex(regex));
+        literals
+            .chain(regexes)
+            .map(|filter| Ok(filter?.with_skip_binary(self.text_only)))
+            .collect()
+    }
+
+    /// 构建对匹配项执行的操作
+    pub fn build_actions(&self) -> FindResult<Vec<Box<dyn Action>>> {
+        let mut actions: Vec<Box<dyn Action>> = Vec::new();
+
+        // 确认的回答从标准输入读取
+        if (!self.ok.is_empty() || self.interactive)
+            && [&self.files_from, &self.files0_from].into_iter().flatten().any(|file| file.as_os_str() == "-")
+        {
+            return Err(FindError::Other {
+                message: "--ok 和 --interactive 不能与从标准输入读取的 --files-from 一起使用".to_string(),
+                context: None,
+                timestamp: std::time::SystemTime::now(),
+            });
+        }
+
+        // 归档最先写入
+        if let Some(output) = &self.archive {
+            let action = ArchiveAction::new(output.clone(), self.root_paths())?.with_dry_run(self.dry_run);
+            actions.push(Box::new(action));
+        }
+        if !self.exec.is_empty() {
+            let action = ExecAction::new(self.exec.clone())?.with_dry_run(self.dry_run);
+            actions.push(Box::new(action));
+        }
+        if !self.execdir.is_empty() {
+            let action =
+                ExecAction::new_in_parent_dir(self.execdir.clone())?.with_dry_run(self.dry_run);
+            actions.push(Box::new(action));
+        }
+        if !self.ok.is_empty() {
+            let action = ExecAction::new(self.ok.clone())?.with_dry_run(self.dry_run);
+            if action.is_batched() {
+                return Err(FindError::Other {
+                    message: "--ok 不支持 {} + 批量执行".to_string(),
+                    context: None,
+                    timestamp: std::time::SystemTime::now(),
+                });
+            }
+            actions.push(Box::new(ConfirmAction::new(Box::new(action), self.ok.join(" "))));
         }
+        if self.delete {
+            self.require_filters("--delete")?;
+            let action = DeleteAction::new(self.root_paths())
+                .with_include_dirs(self.delete_dirs)
+                .with_dry_run(self.dry_run);
+            if self.interactive {
+                actions.push(Box::new(ConfirmAction::new(Box::new(action), "删除")));
+            } else {
+                actions.push(Box::new(action));
+            }
+        }
+        if self.trash {
+            self.require_filters("--trash")?;
+            let action = TrashAction::new(self.root_paths()).with_dry_run(self.dry_run);
+            actions.push(Box::new(action));
+        }
+        // 权限和属主在重命名或移动之前修改
+        if let Some(mode) = &self.chmod {
+            actions.push(Box::new(ChmodAction::new(mode.clone()).with_dry_run(self.dry_run)));
+        }
+        if let Some(owner) = &self.chown {
+            actions.push(Box::new(ChownAction::new(owner.clone()).with_dry_run(self.dry_run)));
+        }
+        if let Some(template) = &self.rename {
+            let template = RenameTemplate::parse(template)?;
+            let action = RenameAction::new(template, self.root_paths()).with_dry_run(self.dry_run);
+            actions.push(Box::new(action));
+        }
+        let transfer = [(&self.copy_to, TransferMode::Copy), (&self.move_to, TransferMode::Move)];
+        for (target, mode) in transfer {
+            if let Some(target) = target {
+                let action = TransferAction::new(mode, target.clone(), self.root_paths())
+                    .with_preserve_paths(self.preserve_paths)
+                    .with_dry_run(self.dry_run);
+                actions.push(Box::new(action));
+            }
+        }
+
+        Ok(actions)
+    }
+
+    /// 构建输出配置
+    ///
+    /// # 错误
+    /// 如果 `--printf` 格式无效，返回 Other 错误
+    pub fn output_config(&self) -> FindResult<OutputConfig> {
+        let path_style = if self.absolute {
+            PathStyle::Absolute
+        } else if self.relative {
+            PathStyle::Relative
+        } else if self.relative_to_root {
+            PathStyle::RelativeToRoot
+        } else {
+            PathStyle::AsFound
+        };
+
+        let printf = self.printf.as_deref().map(PrintfFormat::parse).transpose()?;
+
+        Ok(OutputConfig {
+            format: self.format,
+            path_style,
+            null_terminated: self.print0,
+            fields: self.fields.clone(),
+            printf,
+            long_listing: self.ls || self.security_audit,
+            colors: self.color.enabled().then(LsColors::from_env),
+        })
     }
 
     /// 验证命令行参数
@@ -100,9 +1262,9 @@ impl Cli {
         Ok(())
     }
 
-    /// 验证所有路径是否存在
+    /// 验证所有本地路径是否存在，远程路径在连接时检查
     fn validate_paths(&self) -> Result<(), FindError> {
-        for path in &self.paths {
+        for path in self.paths.iter().filter(|path| !is_remote_path(path)) {
             if !std::path::Path::new(path).exists() {
                 return Err(FindError::FileNotFound(std::path::PathBuf::from(path)));
             }
@@ -126,13 +1288,13 @@ impl Cli {
 
     /// 验证名称模式
     fn validate_name_patterns(&self) -> Result<(), FindError> {
-        let patterns = if !self.name.is_empty() {
-            &self.name
-        } else {
-            &self.iname
-        };
+        // 字面文本不需要是有效的 glob 模式
+        if self.fixed_strings {
+            return Ok(());
+        }
 
-        for pattern in patterns {
+        let (include, exclude) = self.split_name_patterns();
+        for pattern in include.iter().chain(&exclude) {
             if let Err(e) = glob::Pattern::new(pattern) {
                 return Err(FindError::PatternError {
                     message: format!("无效的模式 '{}': {}", pattern, e),
@@ -142,6 +1304,102 @@ impl Cli {
         Ok(())
     }
 
+    /// 将名称模式分为包含和排除两组
+    ///
+    /// 以 ! 开头的 --name/--iname 模式和 --not-name 的模式属于排除组，
+    /// 以 \! 开头的模式去掉反斜杠后按字面的 ! 匹配。
+    fn split_name_patterns(&self) -> (Vec<String>, Vec<String>) {
+        let mut include = Vec::new();
+        let mut exclude = self.not_name.clone();
+        for pattern in self.name_patterns() {
+            if let Some(negated) = pattern.strip_prefix('!') {
+                exclude.push(negated.to_string());
+            } else if let Some(literal) = pattern.strip_prefix("\\!") {
+                include.push(format!("!{}", literal));
+            } else {
+                include.push(pattern.clone());
+            }
+        }
+        (include, exclude)
+    }
+
+    /// 创建匹配任一名称模式的过滤器
+    fn name_filter(&self, patterns: &[String]) -> FindResult<MultiNameFilter> {
+        if self.fixed_strings {
+            MultiNameFilter::fixed_strings(patterns, self.ignore_case())
+        } else {
+            MultiNameFilter::new(patterns, self.ignore_case())
+        }
+    }
+
+    /// 破坏性操作要求至少指定一个过滤条件
+    fn require_filters(&self, action: &str) -> FindResult<()> {
+        if self.has_filters() {
+            return Ok(());
+        }
+        Err(FindError::Other {
+            message: format!("{} 需要至少一个过滤条件", action),
+            context: Some("例如 --name '*.tmp'".to_string()),
+            timestamp: std::time::SystemTime::now(),
+        })
+    }
+
+    /// 获取搜索根目录列表
+    fn root_paths(&self) -> Vec<std::path::PathBuf> {
+        self.paths.iter().map(std::path::PathBuf::from).collect()
+    }
+
+    /// 获取本地搜索根目录列表，不包括 sftp:// 和 s3:// 远程路径
+    fn local_root_paths(&self) -> Vec<std::path::PathBuf> {
+        self.paths
+            .iter()
+            .filter(|path| !is_remote_path(path))
+            .map(std::path::PathBuf::from)
+            .collect()
+    }
+
+    /// 检查是否指定了任何过滤条件
+    pub fn has_filters(&self) -> bool {
+        !self.name.is_empty()
+            || !self.iname.is_empty()
+            || !self.not_name.is_empty()
+            || self.file_type.is_some()
+            || !self.path.is_empty()
+            || self.query.is_some()
+            || !self.attr.is_empty()
+            || !self.xattr.is_empty()
+            || self.has_acl
+            || !self.acl_entry.is_empty()
+            || self.has_caps
+            || !self.cap.is_empty()
+            || !self.flag.is_empty()
+            || self.perm.is_some()
+            || self.suid
+            || self.sgid
+            || self.executable
+            || self.readable
+            || self.writable
+            || self.security_audit
+            || self.broken_symlinks
+            || self.links.is_some()
+            || self.name_length.is_some()
+            || self.path_length.is_some()
+            || self.size.is_some()
+            || self.min_size.is_some()
+            || self.max_size.is_some()
+            || self.inum.is_some()
+            || self.samefile.is_some()
+            || self.git_tracked
+            || self.git_modified
+            || self.git_untracked
+            || self.changed_in_git.is_some()
+            || !self.contains.is_empty()
+            || !self.contains_regex.is_empty()
+            || self.text_only
+            || self.binary_only
+            || !self.mime.is_empty()
+    }
+
     /// 检查是否忽略大小写
     pub fn ignore_case(&self) -> bool {
         !self.iname.is_empty()
@@ -163,6 +1421,23 @@ impl Cli {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_cli_error_policies() {
+        let cli = Cli::parse_from(["rust-find", "--ignore-io-errors", "--on-symlink-loop", "fail"]);
+        let policies = cli.error_policies();
+        assert_eq!(policies.permission, ErrorPolicy::Warn);
+        assert_eq!(policies.not_found, ErrorPolicy::Silent);
+        assert_eq!(policies.symlink_loop, ErrorPolicy::Fail);
+        assert_eq!(policies.other, ErrorPolicy::Silent);
+
+        let cli = Cli::parse_from(["rust-find", "--ignore-permission-errors", "--on-permission-error", "fail"]);
+        assert_eq!(cli.error_policies().permission, ErrorPolicy::Fail);
+
+        let cli = Cli::parse_from(["rust-find", "--strict", "--on-not-found", "warn"]);
+        assert_eq!(cli.error_policies().permission, ErrorPolicy::Fail);
+        assert_eq!(cli.error_policies().not_found, ErrorPolicy::Warn);
+    }
+
     #[test]
     fn test_cli_validation() {
         let cli = Cli {
@@ -182,6 +1457,7 @@ mod tests {
             min_threads: None,
             dirs_per_thread: None,
             no_auto_adjust: false,
+            ..Default::default()
         };
 
         assert!(cli.validate().is_ok());
@@ -206,6 +1482,7 @@ mod tests {
             min_threads: None,
             dirs_per_thread: None,
             no_auto_adjust: false,
+            ..Default::default()
         };
 
         assert!(cli.validate().is_err());
@@ -230,8 +1507,180 @@ mod tests {
             min_threads: None,
             dirs_per_thread: None,
             no_auto_adjust: false,
+            ..Default::default()
         };
 
         assert!(cli.validate().is_err());
     }
+
+    #[test]
+    fn test_cli_delete_requires_filter() {
+        let cli = Cli {
+            paths: vec![".".to_string()],
+            delete: true,
+            ..Default::default()
+        };
+        assert!(cli.build_actions().is_err());
+
+        let cli = Cli {
+            paths: vec![".".to_string()],
+            name: vec!["*.tmp".to_string()],
+            delete: true,
+            ..Default::default()
+        };
+        assert_eq!(cli.build_actions().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_cli_confirm_actions() {
+        let cli = Cli::parse_from(["rust-find", ".", "--name", "*.tmp", "--delete", "--interactive"]);
+        let actions = cli.build_actions().unwrap();
+        assert_eq!(actions[0].description(), "删除文件（逐项确认）");
+
+        let cli = Cli::parse_from(["rust-find", ".", "--ok", "rm", "{}", ";"]);
+        assert_eq!(cli.build_actions().unwrap()[0].description(), "执行 rm {}（逐项确认）");
+
+        // 批量模式和从标准输入读取起始路径时不能确认
+        let cli = Cli::parse_from(["rust-find", ".", "--ok", "rm", "{}", "+"]);
+        assert!(cli.build_actions().is_err());
+        let cli = Cli::parse_from(["rust-find", "--files-from", "-", "--ok", "rm", ";"]);
+        assert!(cli.build_actions().is_err());
+        assert!(Cli::try_parse_from(["rust-find", ".", "--interactive"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_transfer_actions() {
+        let cli = Cli::parse_from(["rust-find", ".", "--copy-to", "out", "--preserve-paths"]);
+        let actions = cli.build_actions().unwrap();
+        assert_eq!(actions[0].description(), "复制文件到 out，保留目录结构");
+
+        assert!(Cli::try_parse_from(["rust-find", ".", "--copy-to", "a", "--move-to", "b"]).is_err());
+        assert!(Cli::try_parse_from(["rust-find", ".", "--preserve-paths"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_chmod_chown_actions() {
+        let cli = Cli::parse_from(["rust-find", ".", "--chmod", "go-w", "--chown", "0:0", "--dry-run"]);
+        let descriptions: Vec<String> =
+            cli.build_actions().unwrap().iter().map(|action| action.description()).collect();
+        assert_eq!(descriptions, ["修改权限为 go-w（演练）", "修改属主为 0:0（演练）"]);
+
+        assert!(Cli::try_parse_from(["rust-find", ".", "--chmod", "u+q"]).is_err());
+        assert!(Cli::try_parse_from(["rust-find", ".", "--chmod", "644", "--delete"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_type_filter() {
+        let cli = Cli::parse_from(["rust-find", "-t", "d", "."]);
+        assert!(cli.has_filters());
+        let filters = cli.build_filters().unwrap();
+        assert_eq!(filters.len(), 1);
+        assert_eq!(filters[0].description(), "is a directory");
+
+        // 无效的类型代码在解析参数时就被拒绝
+        assert!(Cli::try_parse_from(["rust-find", "--type", "x", "."]).is_err());
+    }
+
+    #[test]
+    fn test_cli_multiple_name_patterns() {
+        let cli = Cli::parse_from(["rust-find", "-n", "*.rs", "-n", "*.toml", "."]);
+        let filters = cli.build_filters().unwrap();
+        assert_eq!(filters.len(), 1);
+        assert!(filters[0].description().contains("*.rs, *.toml"));
+    }
+
+    #[test]
+    fn test_cli_fixed_strings() {
+        let cli = Cli::parse_from(["rust-find", "-F", "-n", "[draft", "."]);
+        assert!(cli.validate().is_ok());
+        let filters = cli.build_filters().unwrap();
+        assert!(filters[0].description().contains("[draft"));
+
+        let cli = Cli::parse_from(["rust-find", "-n", "[draft", "."]);
+        assert!(cli.validate().is_err());
+    }
+
+    #[test]
+    fn test_cli_negated_name_patterns() {
+        let cli = Cli::parse_from(["rust-find", "-n", "*.js", "-n", "!*.min.js", "--not-name", "vendor*", "."]);
+        assert_eq!(
+            cli.split_name_patterns(),
+            (vec!["*.js".to_string()], vec!["vendor*".to_string(), "*.min.js".to_string()])
+        );
+        let filters = cli.build_filters().unwrap();
+        assert_eq!(filters.len(), 2);
+        assert!(filters[1].description().starts_with("not ("));
+
+        // 只有排除模式时匹配其余所有条目
+        let cli = Cli::parse_from(["rust-find", "--not-name", "*.log", "."]);
+        assert!(cli.has_filters());
+        assert_eq!(cli.build_filters().unwrap().len(), 1);
+
+        let cli = Cli::parse_from(["rust-find", "-n", "\\!important", "."]);
+        assert_eq!(cli.split_name_patterns().0, vec!["!important".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_start_points() {
+        assert_eq!(
+            parse_start_points(b"src\r\n\ntests/a b.rs\n", b'\n').unwrap(),
+            vec!["src".to_string(), "tests/a b.rs".to_string()]
+        );
+        assert_eq!(
+            parse_start_points(b"line\nbreak\0other\0", b'\0').unwrap(),
+            vec!["line\nbreak".to_string(), "other".to_string()]
+        );
+        assert!(parse_start_points(b"", b'\0').unwrap().is_empty());
+        assert!(parse_start_points(b"bad\xff\0", b'\0').is_err());
+
+        // 命令行中的路径与路径列表不能同时使用
+        assert!(Cli::try_parse_from(["rust-find", "--files0-from", "-", "src"]).is_err());
+    }
+
+    #[test]
+    fn test_write_completions() {
+        let mut out = Vec::new();
+        Cli::write_completions(Shell::Bash, &mut out);
+        let script = String::from_utf8(out).unwrap();
+        assert!(script.contains("--format"));
+        assert!(script.contains("json"));
+        assert!(script.contains("bench"));
+    }
+
+    #[test]
+    fn test_prepend_defaults() {
+        let args = ["rust-find", "--color", "never", "/tmp"].map(OsString::from);
+        let args = prepend_defaults(args, "--color=always --exclude 'my dir'").unwrap();
+        assert_eq!(
+            args,
+            ["rust-find", "--color=always", "--exclude", "my dir", "--color", "never", "/tmp"]
+                .map(OsString::from)
+        );
+
+        // 命令行中的选项覆盖默认值
+        let cli = Cli::parse_from(args);
+        assert_eq!(cli.color, ColorChoice::Never);
+        assert_eq!(cli.paths, vec!["/tmp".to_string()]);
+
+        assert!(prepend_defaults(Vec::new(), "--name 'unterminated").is_err());
+
+        // 子命令不使用默认参数
+        let args = ["rust-find", "bench", "/tmp"].map(OsString::from);
+        assert_eq!(prepend_defaults(args.clone(), "--color=always").unwrap(), args);
+    }
+
+    #[test]
+    fn test_subcommand_names_shadow_paths() {
+        let cli = Cli::parse_from(["rust-find", "bench", "/tmp"]);
+        assert!(matches!(cli.command, Some(Command::Bench(_))));
+
+        for args in [&["rust-find", "./bench"][..], &["rust-find", "--", "bench"], &["rust-find", "--max-depth", "1", "bench"]] {
+            let cli = Cli::parse_from(args);
+            assert!(cli.command.is_none());
+            assert_eq!(cli.paths.len(), 1);
+            assert!(cli.paths[0].ends_with("bench"));
+        }
+        // 搜索选项不能与子命令一起使用
+        assert!(Cli::try_parse_from(["rust-find", "bench", "/tmp", "--max-depth", "1"]).is_err());
+    }
 }
\ No newline at end of file
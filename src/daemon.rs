@@ -0,0 +1,328 @@
+//! 常驻守护进程
+//!
+//! `rust-find daemon` 在内存中保留目录列表（见 [`ListingCache`]），通过 Unix 域套接字接受搜索请求；
+//! `rust-find query` 是对应的客户端。对同一棵大目录树的重复搜索只需读取发生变化的目录；
+//! 用 `--watch` 指定的目录树由文件系统事件保持最新，搜索时完全不访问其中的目录。
+//!
+//! 协议中的每条消息是一帧：4 字节大端序的长度，后跟该长度的 UTF-8 JSON。
+//! 客户端发送一帧 [`FilterSpec`]，守护进程对每个结果回复一帧 `{"path": "..."}`，
+//! 最后回复 `{"count": N}`；请求无效时只回复 `{"error": "..."}`。同一连接上可以依次发送多个请求。
+//!
+//! 请求 `{"import": "/var/lib/plocate/plocate.db"}` 从 updatedb 的数据库导入目录列表
+//! （见 [`ListingCache::import_locate`]），回复 `{"count": N}` 表示导入的目录数。
+
+use std::io::{self, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::errors::{FindError, FindResult};
+use crate::finder::{ListingCache, ListingWatcher};
+use crate::serve::{FilterSpec, MatchAll};
+
+/// 单帧的最大长度
+pub const MAX_FRAME_BYTES: usize = 1024 * 1024;
+
+/// 默认的套接字路径
+///
+/// 为 `$XDG_RUNTIME_DIR/rust-find.sock`，未设置时为临时目录中按用户区分的文件。
+pub fn default_socket_path() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join("rust-find.sock"),
+        // SAFETY: getuid 总是成功，没有副作用
+        None => std::env::temp_dir().join(format!("rust-find-{}.sock", unsafe { libc::getuid() })),
+    }
+}
+
+/// 写入一帧
+pub fn write_frame(writer: &mut impl Write, payload: &[u8]) -> io::Result<()> {
+    let length = u32::try_from(payload.len())
+        .ok()
+        .filter(|&length| length as usize <= MAX_FRAME_BYTES)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "消息过长"))?;
+    writer.write_all(&length.to_be_bytes())?;
+    writer.write_all(payload)
+}
+
+/// 读取一帧，对方在帧之间关闭连接时返回 None
+pub fn read_frame(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut length = [0; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_FRAME_BYTES {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("消息过长: {} 字节", length)));
+    }
+    let mut payload = vec![0; length];
+    reader.read_exact(&mut payload)?;
+    Ok(Some(payload))
+}
+
+/// 守护进程
+///
+/// 退出时删除套接字文件。
+#[derive(Debug)]
+pub struct Daemon {
+    listener: UnixListener,
+    socket: PathBuf,
+    cache: Arc<ListingCache>,
+    watchers: Vec<ListingWatcher>,
+}
+
+impl Daemon {
+    /// 在指定的套接字路径上监听
+    ///
+    /// 路径上遗留的套接字（没有进程在监听）会被删除。
+    ///
+    /// # 错误
+    /// 如果已有守护进程在该路径上监听，返回 `AddrInUse` 错误；无法创建套接字时返回IO错误
+    pub fn bind(socket: &Path) -> io::Result<Self> {
+        if socket.exists() {
+            if UnixStream::connect(socket).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("已有守护进程在 {} 上运行", socket.display()),
+                ));
+            }
+            debug!("Removing stale socket {}", socket.display());
+            std::fs::remove_file(socket)?;
+        }
+        Ok(Self {
+            listener: UnixListener::bind(socket)?,
+            socket: socket.to_path_buf(),
+            cache: Arc::new(ListingCache::new()),
+            watchers: Vec::new(),
+        })
+    }
+
+    /// 预先缓存目录树并订阅其文件系统事件，直到守护进程退出
+    ///
+    /// # 错误
+    /// 如果目录不存在或无法订阅文件系统事件，返回错误
+    pub fn watch(&mut self, root: &Path) -> FindResult<()> {
+        self.watchers.push(self.cache.watch(root)?);
+        Ok(())
+    }
+
+    /// 守护进程的目录列表缓存
+    pub fn cache(&self) -> &Arc<ListingCache> {
+        &self.cache
+    }
+
+    /// 持续接受连接，每个连接在单独的线程中处理
+    ///
+    /// # 错误
+    /// 只有监听套接字本身出错时返回；单个连接的错误记录到日志后忽略
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    warn!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            };
+            let cache = self.cache.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = handle_connection(stream, &cache) {
+                    debug!("Daemon connection failed: {}", e);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.socket);
+    }
+}
+
+/// 依次处理一个连接上的请求，直到客户端关闭连接
+fn handle_connection(mut stream: UnixStream, cache: &ListingCache) -> io::Result<()> {
+    while let Some(request) = read_frame(&mut stream)? {
+        if let Ok(Import { import }) = serde_json::from_slice::<Import>(&request) {
+            let reply = match cache.import_locate(&import) {
+                Ok(count) => json!({ "count": count }),
+                Err(e) => json!({ "error": e.to_string() }),
+            };
+            write_frame(&mut stream, reply.to_string().as_bytes())?;
+            continue;
+        }
+        let spec = serde_json::from_slice::<FilterSpec>(&request)
+            .map_err(|e| format!("无效的请求: {}", e))
+            .and_then(|spec| Ok((spec.finder().map_err(|e| e.to_string())?, spec)));
+        let (finder, spec) = match spec {
+            Ok(parsed) => parsed,
+            Err(message) => {
+                write_frame(&mut stream, json!({ "error": message }).to_string().as_bytes())?;
+                continue;
+            }
+        };
+
+        let mut writer = io::BufWriter::new(&stream);
+        let mut count = 0;
+        for root in spec.roots() {
+            for path in finder.find_listed(root, MatchAll, cache) {
+                write_frame(&mut writer, json!({ "path": path.to_string_lossy() }).to_string().as_bytes())?;
+                count += 1;
+            }
+        }
+        debug!("Answered query with {} results, {} directories cached", count, cache.len());
+        write_frame(&mut writer, json!({ "count": count }).to_string().as_bytes())?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// 导入 updatedb 数据库的请求
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Import {
+    import: PathBuf,
+}
+
+/// 守护进程的回复
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Reply {
+    Path { path: PathBuf },
+    Count { count: usize },
+    Error { error: String },
+}
+
+/// 向守护进程发送搜索请求，对每个结果调用 `on_result`，返回结果数
+///
+/// 相对的搜索路径按客户端的当前目录解析为绝对路径，未指定时搜索客户端的当前目录。
+///
+/// # 错误
+/// 无法连接守护进程、通信失败或请求无效时返回错误
+pub fn query(socket: &Path, spec: &FilterSpec, on_result: impl FnMut(PathBuf)) -> FindResult<usize> {
+    let mut spec = spec.clone();
+    spec.paths = spec
+        .roots()
+        .iter()
+        .map(|root| std::path::absolute(root).map_err(|e| FindError::from_io(e, root)))
+        .collect::<FindResult<_>>()?;
+    request(socket, &spec, on_result)
+}
+
+/// 让守护进程从 updatedb 生成的 mlocate 或 plocate 数据库导入目录列表，返回导入的目录数
+///
+/// 数据库由守护进程读取，相对路径按客户端的当前目录解析。
+///
+/// # 错误
+/// 无法连接守护进程、通信失败，或守护进程无法读取数据库时返回错误
+pub fn import(socket: &Path, database: &Path) -> FindResult<usize> {
+    let import = std::path::absolute(database).map_err(|e| FindError::from_io(e, database))?;
+    request(socket, &Import { import }, |_| {})
+}
+
+fn other(message: String, context: Option<&str>) -> FindError {
+    FindError::Other {
+        message,
+        context: context.map(str::to_string),
+        timestamp: std::time::SystemTime::now(),
+    }
+}
+
+/// 发送一个请求并读取回复，直到收到结果数或错误
+fn request(socket: &Path, request: &impl Serialize, mut on_result: impl FnMut(PathBuf)) -> FindResult<usize> {
+    let mut stream = UnixStream::connect(socket).map_err(|e| {
+        other(
+            format!("无法连接守护进程 {}: {}", socket.display(), e),
+            Some("请先运行 rust-find daemon"),
+        )
+    })?;
+    let request = serde_json::to_vec(request).map_err(|e| other(format!("无法编码请求: {}", e), None))?;
+    let communication = |e: io::Error| other(format!("与守护进程通信失败: {}", e), None);
+    write_frame(&mut stream, &request).map_err(communication)?;
+
+    let mut stream = io::BufReader::new(stream);
+    loop {
+        let frame = read_frame(&mut stream)
+            .map_err(communication)?
+            .ok_or_else(|| other("守护进程意外关闭了连接".to_string(), None))?;
+        let reply = serde_json::from_slice(&frame).map_err(|e| other(format!("无效的回复: {}", e), None))?;
+        match reply {
+            Reply::Path { path } => on_result(path),
+            Reply::Count { count } => return Ok(count),
+            Reply::Error { error } => return Err(other(error, None)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_frames_round_trip() -> io::Result<()> {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, b"{}")?;
+        write_frame(&mut buffer, b"")?;
+        assert_eq!(&buffer[..6], b"\0\0\0\x02{}");
+
+        let mut reader = buffer.as_slice();
+        assert_eq!(read_frame(&mut reader)?.as_deref(), Some(&b"{}"[..]));
+        assert_eq!(read_frame(&mut reader)?.as_deref(), Some(&b""[..]));
+        assert_eq!(read_frame(&mut reader)?, None);
+
+        let too_long = (MAX_FRAME_BYTES as u32 + 1).to_be_bytes();
+        assert_eq!(read_frame(&mut too_long.as_slice()).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        Ok(())
+    }
+
+    #[test]
+    fn test_daemon_answers_queries() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        fs::create_dir_all(root.join("src"))?;
+        fs::write(root.join("src/lib.rs"), "")?;
+        fs::write(root.join("README.md"), "")?;
+        let socket = dir.path().join("daemon.sock");
+
+        let daemon = Arc::new(Daemon::bind(&socket)?);
+        assert_eq!(Daemon::bind(&socket).unwrap_err().kind(), io::ErrorKind::AddrInUse);
+        let running = daemon.clone();
+        std::thread::spawn(move || running.run());
+
+        let spec = FilterSpec {
+            paths: vec![root.clone()],
+            name: vec!["*.rs".to_string()],
+            ..FilterSpec::default()
+        };
+        let mut results = Vec::new();
+        assert_eq!(query(&socket, &spec, |path| results.push(path))?, 1);
+        assert_eq!(results, [root.join("src/lib.rs")]);
+        assert_eq!(daemon.cache().len(), 2);
+
+        // 新文件出现在下一次搜索中
+        fs::write(root.join("src/main.rs"), "")?;
+        let mut results = Vec::new();
+        query(&socket, &spec, |path| results.push(path))?;
+        assert_eq!(results, [root.join("src/lib.rs"), root.join("src/main.rs")]);
+
+        let invalid = FilterSpec {
+            query: Some("size >".to_string()),
+            ..FilterSpec::default()
+        };
+        let error = query(&socket, &invalid, |_| {}).unwrap_err();
+        assert!(error.to_string().contains("expected a value"), "{}", error);
+
+        let error = import(&socket, &dir.path().join("missing.db")).unwrap_err();
+        assert!(error.to_string().contains("missing.db"), "{}", error);
+        Ok(())
+    }
+}
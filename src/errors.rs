@@ -1,5 +1,5 @@
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 use walkdir;
 
@@ -47,6 +47,78 @@ pub enum FindError {
 
     /// 遍历目录时的错误
     WalkDirError(String),
+
+    /// 对匹配项执行操作失败
+    ActionFailed {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl FindError {
+    /// 根据 I/O 错误的类型创建带路径的错误
+    pub fn from_io(error: std::io::Error, path: &Path) -> Self {
+        let path = path.to_path_buf();
+        match error.kind() {
+            std::io::ErrorKind::NotFound => FindError::FileNotFound(path),
+            std::io::ErrorKind::PermissionDenied => FindError::PermissionDenied(path),
+            _ => FindError::FilesystemError { source: error, path },
+        }
+    }
+
+    /// 错误涉及的路径
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            FindError::FileNotFound(path)
+            | FindError::PermissionDenied(path)
+            | FindError::DirectoryUnreadable(path)
+            | FindError::SymlinkIssue(path)
+            | FindError::FilesystemError { path, .. }
+            | FindError::InvalidPath(path)
+            | FindError::ActionFailed { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// 底层 I/O 错误的系统错误码
+    pub fn raw_os_error(&self) -> Option<i32> {
+        match self {
+            FindError::FilesystemError { source, .. } => source.raw_os_error(),
+            _ => None,
+        }
+    }
+}
+
+/// 复制错误，文件系统错误中的 I/O 错误按错误码或类型和描述重新创建
+impl Clone for FindError {
+    fn clone(&self) -> Self {
+        match self {
+            FindError::FileNotFound(path) => FindError::FileNotFound(path.clone()),
+            FindError::PermissionDenied(path) => FindError::PermissionDenied(path.clone()),
+            FindError::DirectoryUnreadable(path) => FindError::DirectoryUnreadable(path.clone()),
+            FindError::SymlinkIssue(path) => FindError::SymlinkIssue(path.clone()),
+            FindError::FilesystemError { source, path } => FindError::FilesystemError {
+                source: match source.raw_os_error() {
+                    Some(code) => std::io::Error::from_raw_os_error(code),
+                    None => std::io::Error::new(source.kind(), source.to_string()),
+                },
+                path: path.clone(),
+            },
+            FindError::InvalidPath(path) => FindError::InvalidPath(path.clone()),
+            FindError::Other { message, context, timestamp } => FindError::Other {
+                message: message.clone(),
+                context: context.clone(),
+                timestamp: *timestamp,
+            },
+            FindError::PatternError { message } => FindError::PatternError { message: message.clone() },
+            FindError::InvalidFileType(type_code) => FindError::InvalidFileType(type_code.clone()),
+            FindError::WalkDirError(message) => FindError::WalkDirError(message.clone()),
+            FindError::ActionFailed { path, message } => FindError::ActionFailed {
+                path: path.clone(),
+                message: message.clone(),
+            },
+        }
+    }
 }
 
 impl fmt::Display for FindError {
@@ -76,7 +148,9 @@ impl fmt::Display for FindError {
             FindError::InvalidFileType(type_code) => 
                 write!(f, "无效的文件类型: {}", type_code),
             FindError::WalkDirError(message) => 
-                write!(f, "目录遍历错误: {}", message)
+                write!(f, "目录遍历错误: {}", message),
+            FindError::ActionFailed { path, message } => 
+                write!(f, "操作失败 {}: {}", path.display(), message)
         }
     }
 }
@@ -107,6 +181,12 @@ impl From<std::io::Error> for FindError {
 
 impl From<walkdir::Error> for FindError {
     fn from(err: walkdir::Error) -> Self {
+        FindError::from(&err)
+    }
+}
+
+impl From<&walkdir::Error> for FindError {
+    fn from(err: &walkdir::Error) -> Self {
         let path = err.path().map(|p| p.to_path_buf()).unwrap_or_default();
         match err.io_error() {
             Some(io_err) => match io_err.kind() {
@@ -158,9 +238,22 @@ mod tests {
         assert_eq!(find_error.to_string(), "错误: 出现了问题");
     }
 
+    #[test]
+    fn test_from_io_keeps_path() {
+        let path = Path::new("/test/path");
+        let error = FindError::from_io(io::Error::from(io::ErrorKind::PermissionDenied), path);
+        assert!(matches!(error, FindError::PermissionDenied(_)));
+        assert_eq!(error.path(), Some(path));
+
+        let error = FindError::from_io(io::Error::from_raw_os_error(5), path);
+        assert_eq!(error.raw_os_error(), Some(5));
+        assert_eq!(error.path(), Some(path));
+        assert_eq!(error.clone().raw_os_error(), Some(5));
+    }
+
     #[test]
     fn test_from_io_error() {
-        let io_error = io::Error::new(io::ErrorKind::Other, "test error");
+        let io_error = io::Error::other("test error");
         let find_error: FindError = io_error.into();
         match find_error {
             FindError::FilesystemError { source: _, path } => assert_eq!(path, PathBuf::new()),
@@ -0,0 +1,403 @@
+//! 把 zip 和 tar（包括 gzip 和 zstd 压缩的 tar）归档作为文件系统后端
+//!
+//! 打开归档时只读取条目列表和属性，文件内容在需要时（例如内容过滤器）才解压。
+//! 归档中的路径以 `/` 为根，结果以 `归档路径!/内部路径` 的形式给出。
+//! 名称中含有 `..` 或绝对路径的条目按其中的普通部分放置，不会逃出归档的根目录。
+
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use chrono::NaiveDate;
+use flate2::read::GzDecoder;
+use ruzstd::decoding::StreamingDecoder;
+
+use crate::errors::{FindError, FindResult};
+use super::entry::FindEntry;
+use super::filesystem::{FileKind, FileStat, FileSystem};
+use super::filter::{FileFilter, FilterCost};
+use super::Finder;
+
+/// 支持的归档格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+    TarZst,
+}
+
+impl ArchiveFormat {
+    /// 按文件扩展名（不区分大小写）判断归档格式
+    pub fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(ArchiveFormat::TarZst)
+        } else {
+            None
+        }
+    }
+}
+
+/// 归档中的条目
+#[derive(Debug, Clone)]
+struct ArchiveNode {
+    stat: FileStat,
+    /// 条目在归档中的序号，自动补充的目录没有序号
+    index: Option<usize>,
+}
+
+/// 以归档文件为后端的只读文件系统
+///
+/// # 示例
+/// ```no_run
+/// use rust_find::finder::archive::ArchiveFileSystem;
+/// use rust_find::finder::FileSystem;
+/// use std::path::Path;
+///
+/// let fs = ArchiveFileSystem::open(Path::new("backup.tar.gz")).unwrap();
+/// for (name, kind) in fs.read_dir(Path::new("/")).unwrap() {
+///     println!("{:?} {:?}", name, kind);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ArchiveFileSystem {
+    archive: PathBuf,
+    format: ArchiveFormat,
+    nodes: BTreeMap<PathBuf, ArchiveNode>,
+    children: HashMap<PathBuf, Vec<(OsString, FileKind)>>,
+}
+
+impl ArchiveFileSystem {
+    /// 打开归档并读取其中的条目列表，格式按扩展名判断
+    ///
+    /// # 错误
+    /// 如果扩展名不是支持的归档格式，或者无法读取归档，返回 IO 错误
+    pub fn open(archive: &Path) -> io::Result<Self> {
+        let format = ArchiveFormat::detect(archive).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("{} 不是支持的归档格式", archive.display()))
+        })?;
+        let mut fs = Self {
+            archive: archive.to_path_buf(),
+            format,
+            nodes: BTreeMap::new(),
+            children: HashMap::new(),
+        };
+        fs.nodes.insert(PathBuf::from("/"), ArchiveNode { stat: FileStat::new(FileKind::Dir, 0), index: None });
+        match format {
+            ArchiveFormat::Zip => fs.read_zip()?,
+            ArchiveFormat::Tar | ArchiveFormat::TarGz | ArchiveFormat::TarZst => fs.read_tar()?,
+        }
+        for (path, node) in &fs.nodes {
+            if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+                fs.children
+                    .entry(parent.to_path_buf())
+                    .or_default()
+                    .push((name.to_os_string(), node.stat.kind));
+            }
+        }
+        Ok(fs)
+    }
+
+    /// 归档文件的路径
+    pub fn archive(&self) -> &Path {
+        &self.archive
+    }
+
+    fn read_zip(&mut self) -> io::Result<()> {
+        let mut zip = zip::ZipArchive::new(BufReader::new(File::open(&self.archive)?))?;
+        for index in 0..zip.len() {
+            let file = zip.by_index_raw(index)?;
+            let kind = if file.is_dir() {
+                FileKind::Dir
+            } else if file.is_symlink() {
+                FileKind::Symlink
+            } else {
+                FileKind::File
+            };
+            let mut stat = FileStat::new(kind, file.size());
+            stat.modified = file.last_modified().and_then(|time| {
+                let time = NaiveDate::from_ymd_opt(time.year().into(), time.month().into(), time.day().into())?
+                    .and_hms_opt(time.hour().into(), time.minute().into(), time.second().into())?;
+                u64::try_from(time.and_utc().timestamp()).ok().map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+            });
+            stat.mode = file.unix_mode().map(|mode| mode & 0o7777);
+            let name = file.name().to_string();
+            self.insert(Path::new(&name), stat, index);
+        }
+        Ok(())
+    }
+
+    fn read_tar(&mut self) -> io::Result<()> {
+        let mut archive = tar::Archive::new(self.tar_reader()?);
+        for (index, entry) in archive.entries()?.enumerate() {
+            let entry = entry?;
+            let header = entry.header();
+            let entry_type = header.entry_type();
+            let kind = if entry_type.is_dir() {
+                FileKind::Dir
+            } else if entry_type.is_symlink() || entry_type.is_hard_link() {
+                FileKind::Symlink
+            } else if entry_type.is_file() {
+                FileKind::File
+            } else if entry_type.is_block_special() {
+                FileKind::BlockDevice
+            } else if entry_type.is_character_special() {
+                FileKind::CharDevice
+            } else if entry_type.is_fifo() {
+                FileKind::Fifo
+            } else {
+                // 扩展头等元数据条目不是文件
+                continue;
+            };
+            let mut stat = FileStat::new(kind, if kind.is_file() { entry.size() } else { 0 });
+            stat.modified = header.mtime().ok().map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+            stat.mode = header.mode().ok().map(|mode| mode & 0o7777);
+            let path = entry.path()?.into_owned();
+            self.insert(&path, stat, index);
+        }
+        Ok(())
+    }
+
+    /// 从头读取 tar 归档，压缩的归档在读取时解压
+    fn tar_reader(&self) -> io::Result<Box<dyn Read + Send>> {
+        let file = BufReader::new(File::open(&self.archive)?);
+        Ok(match self.format {
+            ArchiveFormat::TarGz => Box::new(GzDecoder::new(file)),
+            ArchiveFormat::TarZst => Box::new(StreamingDecoder::new(file).map_err(io::Error::other)?),
+            _ => Box::new(file),
+        })
+    }
+
+    /// 加入条目并补充其所在的目录，同名条目以后出现的为准
+    fn insert(&mut self, name: &Path, stat: FileStat, index: usize) {
+        let path = Path::new("/").join(
+            name.components()
+                .filter_map(|component| match component {
+                    Component::Normal(part) => Some(part),
+                    _ => None,
+                })
+                .collect::<PathBuf>(),
+        );
+        if path == Path::new("/") {
+            return;
+        }
+        for ancestor in path.ancestors().skip(1) {
+            self.nodes
+                .entry(ancestor.to_path_buf())
+                .or_insert(ArchiveNode { stat: FileStat::new(FileKind::Dir, 0), index: None });
+        }
+        self.nodes.insert(path, ArchiveNode { stat, index: Some(index) });
+    }
+
+    fn node(&self, path: &Path) -> io::Result<&ArchiveNode> {
+        self.nodes.get(path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("{} 中没有 {}", self.archive.display(), path.display()))
+        })
+    }
+
+    /// 解压序号为 `index` 的条目的内容
+    fn extract(&self, index: usize) -> io::Result<Vec<u8>> {
+        let mut contents = Vec::new();
+        match self.format {
+            ArchiveFormat::Zip => {
+                let mut zip = zip::ZipArchive::new(BufReader::new(File::open(&self.archive)?))?;
+                zip.by_index(index)?.read_to_end(&mut contents)?;
+            }
+            ArchiveFormat::Tar | ArchiveFormat::TarGz | ArchiveFormat::TarZst => {
+                let mut archive = tar::Archive::new(self.tar_reader()?);
+                let mut entry = archive
+                    .entries()?
+                    .nth(index)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "归档在读取期间被截断"))??;
+                entry.read_to_end(&mut contents)?;
+            }
+        }
+        Ok(contents)
+    }
+}
+
+impl FileSystem for ArchiveFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<(OsString, FileKind)>> {
+        if !self.node(path)?.stat.kind.is_dir() {
+            return Err(io::Error::new(io::ErrorKind::NotADirectory, format!("{} 不是目录", path.display())));
+        }
+        Ok(self.children.get(path).cloned().unwrap_or_default())
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<FileStat> {
+        Ok(self.node(path)?.stat.clone())
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        match self.node(path)? {
+            ArchiveNode { stat, index: Some(index) } if stat.kind.is_file() => {
+                Ok(Box::new(io::Cursor::new(self.extract(*index)?)))
+            }
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} 不是文件", path.display()))),
+        }
+    }
+}
+
+/// 归档中的条目在结果中的路径：`归档路径!/内部路径`
+pub fn member_path(archive: &Path, member: &Path) -> PathBuf {
+    let mut path = archive.as_os_str().to_os_string();
+    path.push("!");
+    path.push(member.as_os_str());
+    PathBuf::from(path)
+}
+
+/// 匹配扩展名为支持的归档格式的普通文件
+pub struct ArchiveFilter;
+
+impl FileFilter for ArchiveFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        entry.kind().is_file() && ArchiveFormat::detect(entry.path()).is_some()
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
+    fn description(&self) -> String {
+        "is a zip or tar archive".to_string()
+    }
+}
+
+impl Finder {
+    /// 在归档中查找条目，结果为 `归档路径!/内部路径` 形式的路径
+    ///
+    /// 查找器的过滤器、深度和结果数上限作用于归档中的条目。
+    ///
+    /// # 错误
+    /// 如果无法打开归档，返回对应的 FindError
+    pub fn find_in_archive<F>(&self, archive: &Path, filter: F) -> FindResult<Vec<PathBuf>>
+    where
+        F: FileFilter,
+    {
+        let fs = ArchiveFileSystem::open(archive).map_err(|e| FindError::from_io(e, archive))?;
+        Ok(self
+            .find_in(Arc::new(fs), "/", filter)
+            .into_iter()
+            .filter(|member| member != Path::new("/"))
+            .map(|member| member_path(archive, &member))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finder::content::ContentFilter;
+    use crate::finder::filter::{NameFilter, SizeFilter, SizeMeasure};
+    use crate::finder::size::ByteSize;
+    use crate::finder::FindOptions;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    const FILES: [(&str, &str); 3] = [
+        ("docs/readme.txt", "read me\n"),
+        ("src/main.rs", "fn main() {\n    // TODO\n}\n"),
+        ("../escape.rs", "fn escape() {}\n"),
+    ];
+
+    fn write_zip(path: &Path) -> io::Result<()> {
+        let mut zip = zip::ZipWriter::new(File::create(path)?);
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        for (name, contents) in FILES {
+            zip.start_file(name, options)?;
+            zip.write_all(contents.as_bytes())?;
+        }
+        zip.finish()?;
+        Ok(())
+    }
+
+    fn write_tar_gz(path: &Path) -> io::Result<()> {
+        let encoder = flate2::write::GzEncoder::new(File::create(path)?, flate2::Compression::fast());
+        let mut tar = tar::Builder::new(encoder);
+        for (name, contents) in FILES {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(1_700_000_000);
+            header.set_entry_type(tar::EntryType::Regular);
+            // set_path 拒绝含有 `..` 的路径，直接写入名称字段以模拟恶意归档
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_cksum();
+            tar.append(&header, contents.as_bytes())?;
+        }
+        tar.into_inner()?.finish()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_filesystem() -> Result<(), Box<dyn std::error::Error>> {
+        let temp = TempDir::new()?;
+        let zip = temp.path().join("backup.zip");
+        let tar = temp.path().join("backup.TAR.GZ");
+        write_zip(&zip)?;
+        write_tar_gz(&tar)?;
+
+        for archive in [&zip, &tar] {
+            let fs = ArchiveFileSystem::open(archive)?;
+            let mut names = fs.read_dir(Path::new("/"))?;
+            names.sort_by(|a, b| a.0.cmp(&b.0));
+            assert_eq!(
+                names,
+                [
+                    (OsString::from("docs"), FileKind::Dir),
+                    (OsString::from("escape.rs"), FileKind::File),
+                    (OsString::from("src"), FileKind::Dir),
+                ]
+            );
+            let mut contents = String::new();
+            fs.open(Path::new("/src/main.rs"))?.read_to_string(&mut contents)?;
+            assert_eq!(contents, FILES[1].1);
+            assert_eq!(fs.symlink_metadata(Path::new("/docs/readme.txt"))?.len, 8);
+            assert!(fs.open(Path::new("/docs")).is_err());
+        }
+        assert_eq!(
+            ArchiveFileSystem::open(&tar)?.symlink_metadata(Path::new("/escape.rs"))?.modified,
+            Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        );
+        assert!(ArchiveFileSystem::open(&temp.path().join("notes.txt")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_in_archive() -> Result<(), Box<dyn std::error::Error>> {
+        let temp = TempDir::new()?;
+        let zip = temp.path().join("backup.zip");
+        write_zip(&zip)?;
+
+        let finder = Finder::new(FindOptions::new());
+        assert_eq!(
+            finder.find_in_archive(&zip, NameFilter::new("*.rs")?)?,
+            [member_path(&zip, Path::new("/escape.rs")), member_path(&zip, Path::new("/src/main.rs"))]
+        );
+        assert!(finder
+            .find_in_archive(&zip, SizeFilter::at_least(ByteSize::new(100), SizeMeasure::Apparent))?
+            .is_empty());
+
+        let content = Finder::new(FindOptions::new()).with_filter(ContentFilter::literal("TODO")?);
+        assert_eq!(
+            content.find_in_archive(&zip, NameFilter::new("*")?)?,
+            [member_path(&zip, Path::new("/src/main.rs"))]
+        );
+        assert_eq!(
+            member_path(Path::new("a/backup.zip"), Path::new("/src/main.rs")),
+            PathBuf::from("a/backup.zip!/src/main.rs")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+//! 协作式取消支持
+//!
+//! 提供可在线程间共享的取消令牌，用于中途终止长时间运行的搜索。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// 取消令牌
+///
+/// 令牌的所有克隆共享同一个取消状态。任一克隆调用 [`cancel`](Self::cancel) 后，
+/// 正在进行的搜索会在处理下一个条目前停止，并返回已经找到的部分结果。
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::CancellationToken;
+///
+/// let token = CancellationToken::new();
+/// let handle = token.clone();
+/// handle.cancel();
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// 创建新的（未取消的）令牌
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用已有的原子标志创建令牌
+    ///
+    /// 便于与已经使用 `Arc<AtomicBool>` 作为停止信号的应用集成。
+    pub fn from_flag(flag: Arc<AtomicBool>) -> Self {
+        Self { cancelled: flag }
+    }
+
+    /// 请求取消搜索
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// 检查是否已请求取消
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation_token_shared_state() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+
+        clone.cancel();
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn test_cancellation_token_from_flag() {
+        let flag = Arc::new(AtomicBool::new(false));
+        let token = CancellationToken::from_flag(flag.clone());
+
+        flag.store(true, Ordering::Relaxed);
+        assert!(token.is_cancelled());
+    }
+}
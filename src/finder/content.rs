@@ -0,0 +1,688 @@
+//! 文件内容搜索
+//!
+//! 在按名称、类型等条件过滤后的候选文件中搜索内容，相当于在一个进程中完成 find + grep。
+//!
+//! 读取文件内容比遍历目录慢得多，因此内容匹配在独立的工作阶段中进行：
+//! 遍历线程只负责产生候选文件并放入有界队列，由专门的内容工作线程读取和匹配，
+//! 遍历不会因为等待文件 IO 而停顿。
+//!
+//! 二进制文件按启发式规则识别：开头 8 KiB 中含有 NUL 字节的文件视为二进制文件。
+//! MIME 类型根据文件开头的魔数识别，而不是扩展名。
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::Mutex;
+
+use glob::Pattern;
+use log::debug;
+use rayon::prelude::*;
+use regex::bytes::Regex;
+
+use crate::errors::{FindError, FindResult};
+use super::entry::FindEntry;
+use super::filter::{FileFilter, FilterCost};
+
+/// 候选文件队列的容量
+const QUEUE_CAPACITY: usize = 256;
+
+/// 判断是否为二进制文件时检查的字节数
+const BINARY_CHECK_LEN: usize = 8 * 1024;
+
+/// 读取文件开头用于识别类型的部分
+fn read_head(reader: impl Read) -> std::io::Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(BINARY_CHECK_LEN);
+    reader.take(BINARY_CHECK_LEN as u64).read_to_end(&mut head)?;
+    Ok(head)
+}
+
+/// 条目是否为普通文件，符号链接按目标判断
+///
+/// 不是符号链接的条目直接使用遍历时得到的类型（Windows 上来自目录枚举本身，
+/// Unix 上来自 `d_type`），不再为每个候选文件单独读取元数据。
+fn is_regular_file(entry: &FindEntry) -> bool {
+    if entry.path_is_symlink() {
+        entry.target_metadata().is_ok_and(|metadata| metadata.is_file())
+    } else {
+        entry.kind().is_file()
+    }
+}
+
+/// 判断文件是否为二进制文件
+///
+/// 开头 8 KiB 中含有 NUL 字节的文件视为二进制文件，空文件视为文本文件。
+pub fn is_binary(path: &Path) -> std::io::Result<bool> {
+    Ok(read_head(File::open(path)?)?.contains(&0))
+}
+
+/// 根据文件开头的魔数识别 MIME 类型
+///
+/// 无法识别的文件按二进制检测的结果视为 `application/octet-stream` 或 `text/plain`。
+pub fn mime_type(path: &Path) -> std::io::Result<&'static str> {
+    Ok(mime_of(&read_head(File::open(path)?)?))
+}
+
+/// 根据文件开头的部分识别 MIME 类型
+fn mime_of(head: &[u8]) -> &'static str {
+    match infer::get(head) {
+        Some(kind) => kind.mime_type(),
+        None if head.contains(&0) => "application/octet-stream",
+        None => "text/plain",
+    }
+}
+
+/// 文件内容过滤器
+///
+/// 按文件内容匹配普通文件：按行搜索文本或正则表达式，或者按是否为二进制文件过滤。
+/// 按行读取，不会将整个文件读入内存。
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::content::ContentFilter;
+///
+/// let filter = ContentFilter::literal("TODO").unwrap();
+/// let filter = ContentFilter::regex(r"fn\s+main").unwrap().with_skip_binary(true);
+/// let filter = ContentFilter::binary(false);
+/// assert!(ContentFilter::regex("(").is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ContentFilter {
+    matcher: Matcher,
+    /// 搜索文本时是否跳过二进制文件
+    skip_binary: bool,
+}
+
+/// 内容匹配方式
+#[derive(Debug, Clone)]
+enum Matcher {
+    /// 有一行符合模式，`pattern` 为用户给出的原始模式（字面量模式在编译前被转义）
+    Pattern { pattern: String, regex: Regex },
+    /// 是（true）或不是（false）二进制文件
+    Binary(bool),
+}
+
+impl ContentFilter {
+    /// 创建匹配字面量文本的过滤器
+    ///
+    /// # 错误
+    /// 如果模式为空，返回PatternError错误
+    pub fn literal(pattern: &str) -> FindResult<Self> {
+        if pattern.is_empty() {
+            return Err(FindError::PatternError {
+                message: "Content pattern must not be empty".to_string(),
+            });
+        }
+        Self::compile(pattern, &regex::escape(pattern))
+    }
+
+    /// 创建匹配正则表达式的过滤器
+    ///
+    /// # 错误
+    /// 如果正则表达式无效，返回PatternError错误
+    pub fn regex(pattern: &str) -> FindResult<Self> {
+        Self::compile(pattern, pattern)
+    }
+
+    /// 创建按是否为二进制文件过滤的过滤器
+    ///
+    /// # 参数
+    /// - `binary`: true 只匹配二进制文件，false 只匹配文本文件
+    pub fn binary(binary: bool) -> Self {
+        Self {
+            matcher: Matcher::Binary(binary),
+            skip_binary: false,
+        }
+    }
+
+    fn compile(pattern: &str, regex: &str) -> FindResult<Self> {
+        let regex = Regex::new(regex).map_err(|e| FindError::PatternError {
+            message: format!("Invalid content pattern '{}': {}", pattern, e),
+        })?;
+        Ok(Self {
+            matcher: Matcher::Pattern {
+                pattern: pattern.to_string(),
+                regex,
+            },
+            skip_binary: false,
+        })
+    }
+
+    /// 设置搜索文本时是否跳过二进制文件
+    pub fn with_skip_binary(mut self, skip: bool) -> Self {
+        self.skip_binary = skip;
+        self
+    }
+
+    /// 检查文件内容是否匹配
+    ///
+    /// 不是普通文件或无法读取的文件不匹配。
+    pub fn matches_path(&self, path: &Path) -> bool {
+        path.metadata().is_ok_and(|metadata| metadata.is_file()) && self.matches_file(path)
+    }
+
+    /// 检查已知为普通文件的文件内容是否匹配
+    fn matches_file(&self, path: &Path) -> bool {
+        File::open(path).and_then(|file| self.read_matches(file)).unwrap_or_else(|e| {
+            debug!("Failed to read {}: {}", path.display(), e);
+            false
+        })
+    }
+
+    /// 读取普通文件的内容并检查是否匹配
+    fn read_matches(&self, reader: impl Read) -> std::io::Result<bool> {
+        match &self.matcher {
+            Matcher::Pattern { regex, .. } => self.search(reader, regex),
+            Matcher::Binary(binary) => read_head(reader).map(|head| head.contains(&0) == *binary),
+        }
+    }
+
+    /// 统计文件中与模式匹配的次数，用于按相关性排序
+    ///
+    /// 按是否为二进制文件过滤的过滤器、不是普通文件或无法读取的文件返回 0。
+    pub fn count_matches(&self, path: &Path) -> usize {
+        let Matcher::Pattern { regex, .. } = &self.matcher else {
+            return 0;
+        };
+        if !path.metadata().is_ok_and(|metadata| metadata.is_file()) {
+            return 0;
+        }
+        File::open(path).and_then(|file| self.scan(file, regex, false)).unwrap_or_else(|e| {
+            debug!("Failed to read {}: {}", path.display(), e);
+            0
+        })
+    }
+
+    /// 逐行搜索文件
+    fn search(&self, reader: impl Read, regex: &Regex) -> std::io::Result<bool> {
+        Ok(self.scan(reader, regex, true)? > 0)
+    }
+
+    /// 逐行统计匹配次数，`first_only` 为 true 时找到第一个匹配即停止
+    fn scan(&self, reader: impl Read, regex: &Regex, first_only: bool) -> std::io::Result<usize> {
+        let mut reader = BufReader::with_capacity(BINARY_CHECK_LEN, reader);
+        if self.skip_binary && reader.fill_buf()?.contains(&0) {
+            return Ok(0);
+        }
+
+        let mut count = 0;
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                return Ok(count);
+            }
+            if first_only {
+                if regex.is_match(&line) {
+                    return Ok(1);
+                }
+            } else {
+                count += regex.find_iter(&line).count();
+            }
+        }
+    }
+}
+
+impl FileFilter for ContentFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        self.try_matches(entry).unwrap_or_else(|e| {
+            debug!("Failed to read {}: {}", entry.path().display(), e);
+            false
+        })
+    }
+
+    fn try_matches(&self, entry: &FindEntry) -> FindResult<bool> {
+        if !is_regular_file(entry) {
+            return Ok(false);
+        }
+        // 通过条目打开内容，其他文件系统后端中的文件也能搜索
+        self.read_matches(entry.open()?)
+            .map_err(|e| FindError::from_io(e, entry.path()))
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Read
+    }
+
+    fn description(&self) -> String {
+        match &self.matcher {
+            Matcher::Pattern { pattern, .. } => format!("content matches '{}'", pattern),
+            Matcher::Binary(true) => "is a binary file".to_string(),
+            Matcher::Binary(false) => "is a text file".to_string(),
+        }
+    }
+}
+
+/// 按内容匹配的相关性对结果排序
+///
+/// 文件的得分为所有内容模式在文件中的匹配次数之和，得分高的排在前面，
+/// 得分相同时按路径排序。每个文件只评分一次，评分在多个线程中进行。
+///
+/// # 示例
+/// ```no_run
+/// use rust_find::finder::content::{ContentFilter, RelevanceScorer};
+/// use std::path::PathBuf;
+///
+/// let scorer = RelevanceScorer::new(vec![ContentFilter::literal("TODO").unwrap()]);
+/// let mut paths = vec![PathBuf::from("a.rs"), PathBuf::from("b.rs")];
+/// scorer.sort(&mut paths);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RelevanceScorer {
+    filters: Vec<ContentFilter>,
+}
+
+impl RelevanceScorer {
+    /// 使用一组内容过滤器的模式创建评分器
+    pub fn new(filters: Vec<ContentFilter>) -> Self {
+        Self { filters }
+    }
+
+    /// 计算文件的得分
+    pub fn score(&self, path: &Path) -> usize {
+        self.filters.iter().map(|filter| filter.count_matches(path)).sum()
+    }
+
+    /// 按得分从高到低排序
+    pub fn sort(&self, paths: &mut Vec<PathBuf>) {
+        let mut scored: Vec<(usize, PathBuf)> = std::mem::take(paths)
+            .into_par_iter()
+            .map(|path| (self.score(&path), path))
+            .collect();
+        scored.sort_by(|(a_score, a), (b_score, b)| b_score.cmp(a_score).then_with(|| a.cmp(b)));
+        paths.extend(scored.into_iter().map(|(_, path)| path));
+    }
+}
+
+/// MIME 类型过滤器
+///
+/// 根据文件开头的魔数识别类型，匹配 `image/*`、`application/pdf` 等模式，
+/// 给出多个模式时匹配任一模式即可。MIME 类型不区分大小写。
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::content::MimeFilter;
+///
+/// let filter = MimeFilter::new(&["image/*".to_string(), "application/pdf".to_string()]).unwrap();
+/// assert!(MimeFilter::new(&["pdf".to_string()]).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct MimeFilter {
+    patterns: Vec<Pattern>,
+}
+
+impl MimeFilter {
+    /// 创建新的 MIME 类型过滤器
+    ///
+    /// # 错误
+    /// 如果模式不是 `类型/子类型` 形式或不是有效的通配符模式，返回PatternError错误
+    pub fn new(patterns: &[String]) -> FindResult<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                let invalid = |reason: String| FindError::PatternError {
+                    message: format!("Invalid MIME pattern '{}': {}", pattern, reason),
+                };
+                if !pattern.contains('/') {
+                    return Err(invalid("expected TYPE/SUBTYPE".to_string()));
+                }
+                Pattern::new(&pattern.to_ascii_lowercase()).map_err(|e| invalid(e.to_string()))
+            })
+            .collect::<FindResult<Vec<_>>>()?;
+        Ok(Self { patterns })
+    }
+}
+
+impl FileFilter for MimeFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        self.try_matches(entry).unwrap_or_else(|e| {
+            debug!("Failed to read {}: {}", entry.path().display(), e);
+            false
+        })
+    }
+
+    fn try_matches(&self, entry: &FindEntry) -> FindResult<bool> {
+        if !is_regular_file(entry) {
+            return Ok(false);
+        }
+        let head = read_head(entry.open()?).map_err(|e| FindError::from_io(e, entry.path()))?;
+        let mime = mime_of(&head);
+        Ok(self.patterns.iter().any(|pattern| pattern.matches(mime)))
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Read
+    }
+
+    fn description(&self) -> String {
+        let patterns: Vec<&str> = self.patterns.iter().map(Pattern::as_str).collect();
+        format!("MIME type matches [{}]", patterns.join(", "))
+    }
+}
+
+/// 内容匹配阶段中传递的候选条目
+///
+/// 除了条目本身，候选还可以携带调用方需要的其他信息（例如所属的搜索根目录）。
+pub(crate) trait Candidate: Send {
+    /// 需要检查的目录条目
+    fn entry(&self) -> &FindEntry;
+}
+
+impl Candidate for FindEntry {
+    fn entry(&self) -> &FindEntry {
+        self
+    }
+}
+
+impl<T: Send> Candidate for (T, FindEntry) {
+    fn entry(&self) -> &FindEntry {
+        &self.1
+    }
+}
+
+/// 内容匹配阶段
+///
+/// 由若干工作线程组成，从有界队列中取出候选条目并执行需要读取文件的过滤器
+/// （内容过滤器、MIME 类型过滤器等），匹配的条目交给调用方提供的处理函数。
+pub(crate) struct ContentStage<'a> {
+    filters: &'a [Box<dyn FileFilter + Send + Sync>],
+    workers: usize,
+    /// 检查条目的函数，未设置时直接检查过滤器，出错的条目视为不匹配
+    check: Option<&'a (dyn Fn(&FindEntry) -> bool + Sync)>,
+}
+
+impl<'a> ContentStage<'a> {
+    /// 创建内容匹配阶段
+    ///
+    /// # 参数
+    /// - `filters`: 条目必须匹配的所有过滤器
+    /// - `workers`: 工作线程数
+    pub(crate) fn new(filters: &'a [Box<dyn FileFilter + Send + Sync>], workers: usize) -> Self {
+        Self {
+            filters,
+            workers: workers.max(1),
+            check: None,
+        }
+    }
+
+    /// 设置检查条目的函数（例如报告过滤器返回的错误），代替直接检查过滤器
+    pub(crate) fn with_check(mut self, check: &'a (dyn Fn(&FindEntry) -> bool + Sync)) -> Self {
+        self.check = Some(check);
+        self
+    }
+
+    /// 检查条目是否匹配所有过滤器
+    pub(crate) fn matches(filters: &[Box<dyn FileFilter + Send + Sync>], entry: &FindEntry) -> bool {
+        filters.iter().all(|filter| filter.matches(entry))
+    }
+
+    /// 检查条目是否匹配所有过滤器，任一过滤器出错时返回错误
+    pub(crate) fn try_matches(filters: &[Box<dyn FileFilter + Send + Sync>], entry: &FindEntry) -> FindResult<bool> {
+        for filter in filters {
+            if !filter.try_matches(entry)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// 运行流水线
+    ///
+    /// `produce` 在当前线程中运行，通过传入的发送端产生候选条目；
+    /// 发送失败表示所有工作线程都已退出，应当停止产生候选。
+    /// `accept` 在工作线程中对每个匹配的条目调用，返回 false 时停止整个阶段。
+    pub(crate) fn run<T, P, A>(&self, produce: P, accept: A)
+    where
+        T: Candidate,
+        P: FnOnce(SyncSender<T>),
+        A: Fn(T) -> bool + Sync,
+    {
+        let (sender, receiver) = sync_channel(QUEUE_CAPACITY);
+        let receiver = Mutex::new(Some(receiver));
+
+        std::thread::scope(|scope| {
+            for _ in 0..self.workers {
+                scope.spawn(|| self.work(&receiver, &accept));
+            }
+            produce(sender);
+        });
+    }
+
+    /// 单个工作线程的循环
+    ///
+    /// 处理函数要求停止时丢弃接收端，使其他工作线程和产生候选的一方都随之停止。
+    fn work<T, A>(&self, receiver: &Mutex<Option<Receiver<T>>>, accept: &A)
+    where
+        T: Candidate,
+        A: Fn(T) -> bool,
+    {
+        loop {
+            let next = match receiver.lock() {
+                Ok(guard) => match guard.as_ref() {
+                    Some(receiver) => receiver.recv(),
+                    None => return,
+                },
+                Err(_) => return,
+            };
+            let Ok(candidate) = next else {
+                return;
+            };
+            let entry = candidate.entry();
+            let matched = match self.check {
+                Some(check) => check(entry),
+                None => Self::try_matches(self.filters, entry).unwrap_or_else(|e| {
+                    debug!("Failed to check {}: {}", entry.path().display(), e);
+                    false
+                }),
+            };
+            if matched && !accept(candidate) {
+                if let Ok(mut guard) = receiver.lock() {
+                    guard.take();
+                }
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_content_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let source = dir.path().join("main.rs");
+        fs::write(&source, "// TODO: tidy\nfn main() {}\n")?;
+
+        assert!(ContentFilter::literal("TODO")?.matches_path(&source));
+        assert!(!ContentFilter::literal("FIXME")?.matches_path(&source));
+        assert!(ContentFilter::regex(r"^fn\s+main")?.matches_path(&source));
+        // 字面量中的正则元字符按原样匹配
+        assert!(ContentFilter::literal("main()")?.matches_path(&source));
+        assert!(!ContentFilter::literal("m.in")?.matches_path(&source));
+        // 目录不匹配
+        assert!(!ContentFilter::regex(".")?.matches_path(dir.path()));
+        assert!(ContentFilter::literal("").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_content_filter_entries() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("notes.txt"), "TODO\n")?;
+        fs::create_dir(dir.path().join("TODO"))?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path().join("notes.txt"), dir.path().join("link.txt"))?;
+
+        let filter = ContentFilter::literal("TODO")?;
+        let mut found: Vec<String> = walkdir::WalkDir::new(dir.path())
+            .min_depth(1)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(FindEntry::from)
+            .filter(|entry| filter.matches(entry))
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        found.sort();
+        // 目录不匹配，指向普通文件的符号链接按目标匹配
+        #[cfg(unix)]
+        assert_eq!(found, ["link.txt", "notes.txt"]);
+        #[cfg(not(unix))]
+        assert_eq!(found, ["notes.txt"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_matches_reports_read_errors() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let path = dir.path().join("gone.txt");
+        fs::write(&path, "TODO\n")?;
+        let entry = FindEntry::from_path(&path, false)?;
+        fs::remove_file(&path)?;
+
+        let filter = ContentFilter::literal("TODO")?;
+        match filter.try_matches(&entry) {
+            Err(FindError::FileNotFound(missing)) => assert_eq!(missing, path),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(!filter.matches(&entry));
+        // 取反不会把无法读取的文件当作匹配
+        let negated = crate::finder::filter::NotFilter::new(filter);
+        assert!(negated.try_matches(&entry).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_binary_detection() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let text = dir.path().join("notes.txt");
+        let binary = dir.path().join("object.o");
+        let empty = dir.path().join("empty");
+        fs::write(&text, "needle\n")?;
+        fs::write(&binary, b"\x7fELF\x00\x01needle\n")?;
+        fs::write(&empty, "")?;
+
+        assert!(!is_binary(&text)?);
+        assert!(is_binary(&binary)?);
+        assert!(!is_binary(&empty)?);
+
+        assert!(ContentFilter::binary(true).matches_path(&binary));
+        assert!(ContentFilter::binary(false).matches_path(&text));
+        assert!(!ContentFilter::binary(false).matches_path(dir.path()));
+
+        let needle = ContentFilter::literal("needle")?;
+        assert!(needle.matches_path(&binary));
+        let needle = needle.with_skip_binary(true);
+        assert!(!needle.matches_path(&binary));
+        assert!(needle.matches_path(&text));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_relevance_scorer() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let one = dir.path().join("one.txt");
+        let three = dir.path().join("three.txt");
+        let also_one = dir.path().join("also-one.txt");
+        fs::write(&one, "TODO\n")?;
+        fs::write(&three, "TODO TODO\nfixme\n")?;
+        fs::write(&also_one, "fixme\n")?;
+
+        let scorer = RelevanceScorer::new(vec![
+            ContentFilter::literal("TODO")?,
+            ContentFilter::regex("fix(me)?")?,
+        ]);
+        assert_eq!(scorer.score(&three), 3);
+        assert_eq!(scorer.score(dir.path()), 0);
+        assert_eq!(ContentFilter::binary(false).count_matches(&one), 0);
+
+        let mut paths = vec![one.clone(), three.clone(), also_one.clone()];
+        scorer.sort(&mut paths);
+        assert_eq!(paths, vec![three, also_one, one]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mime_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        // 扩展名与内容不符的 PNG 文件
+        fs::write(dir.path().join("photo.txt"), b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR")?;
+        fs::write(dir.path().join("doc.pdf"), b"%PDF-1.7\n")?;
+        fs::write(dir.path().join("notes.md"), "# notes\n")?;
+
+        assert_eq!(mime_type(&dir.path().join("photo.txt"))?, "image/png");
+        assert_eq!(mime_type(&dir.path().join("notes.md"))?, "text/plain");
+
+        let matched = |patterns: &[&str]| -> Result<Vec<String>, Box<dyn std::error::Error>> {
+            let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+            let filter = MimeFilter::new(&patterns)?;
+            Ok(walkdir::WalkDir::new(dir.path())
+                .sort_by_file_name()
+                .into_iter()
+                .filter_map(Result::ok)
+                .map(FindEntry::from)
+                .filter(|entry| filter.matches(entry))
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect())
+        };
+        assert_eq!(matched(&["image/*"])?, vec!["photo.txt"]);
+        assert_eq!(matched(&["Application/PDF", "text/*"])?, vec!["doc.pdf", "notes.md"]);
+        assert!(MimeFilter::new(&["image".to_string()]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_content_stage() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        for i in 0..20 {
+            let path = dir.path().join(format!("{}.txt", i));
+            fs::write(&path, if i % 2 == 0 { "needle\n" } else { "hay\n" })?;
+        }
+        let candidates: Vec<FindEntry> = walkdir::WalkDir::new(dir.path())
+            .min_depth(1)
+            .into_iter()
+            .map(|entry| entry.map(FindEntry::from))
+            .collect::<Result<_, _>>()?;
+
+        let filters: Vec<Box<dyn FileFilter + Send + Sync>> = vec![Box::new(ContentFilter::literal("needle")?)];
+        let found = AtomicUsize::new(0);
+        ContentStage::new(&filters, 3).run(
+            |sender| {
+                for entry in candidates.clone() {
+                    if sender.send(entry).is_err() {
+                        break;
+                    }
+                }
+            },
+            |_| {
+                found.fetch_add(1, Ordering::Relaxed);
+                true
+            },
+        );
+        assert_eq!(found.load(Ordering::Relaxed), 10);
+
+        // 处理函数要求停止后，产生候选的一方会收到发送失败
+        let found = AtomicUsize::new(0);
+        ContentStage::new(&filters, 2).run(
+            |sender| {
+                for entry in candidates.iter().cycle().cloned() {
+                    if sender.send(entry).is_err() {
+                        break;
+                    }
+                }
+            },
+            |_| found.fetch_add(1, Ordering::Relaxed) < 4,
+        );
+        assert!(found.load(Ordering::Relaxed) >= 5);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,360 @@
+//! 查找条目
+//!
+//! [`FindEntry`] 是过滤器和查找器之间传递的条目类型，不依赖具体的遍历实现。
+//! 遍历得到的 `walkdir::DirEntry` 通过 `From` 转换为 `FindEntry`。
+
+use std::ffi::OsStr;
+use std::fs::{self, FileType, Metadata};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::SystemTime;
+
+use crate::errors::{FindError, FindResult};
+use super::filesystem::{FileKind, FileStat, FileSystem};
+
+/// 遍历中发现的条目
+///
+/// 保存路径、文件类型和相对于搜索根目录的深度。元数据（符号链接本身的和目标的）
+/// 在第一次访问时读取并缓存，无论叠加多少个需要元数据的过滤器，每个条目只读取一次。
+/// 不是符号链接的条目两者相同，共用一次读取。
+///
+/// # 示例
+/// ```no_run
+/// use rust_find::finder::FindEntry;
+/// use rust_find::finder::filter::{FileFilter, NameFilter};
+///
+/// let entry = FindEntry::from_path("src/main.rs", false).unwrap();
+/// assert!(NameFilter::new("*.rs").unwrap().matches(&entry));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FindEntry {
+    path: PathBuf,
+    kind: FileKind,
+    depth: usize,
+    /// 条目是否由跟随的符号链接产生（此时文件类型和元数据为目标的）
+    follow_link: bool,
+    /// 符号链接本身的元数据，只用于符号链接
+    link_metadata: OnceLock<Metadata>,
+    /// 跟随符号链接得到的元数据，不是符号链接的条目也保存在这里
+    target_metadata: OnceLock<Metadata>,
+    /// 条目所在的文件系统后端，本地文件系统中的条目为 None
+    backend: Option<Arc<dyn FileSystem>>,
+    /// 后端提供的属性，只用于后端中的条目
+    stat: OnceLock<FileStat>,
+}
+
+impl FindEntry {
+    /// 为单个路径创建深度为 0 的条目
+    ///
+    /// `follow_links` 为 true 时符号链接按目标处理。
+    ///
+    /// # 错误
+    /// 如果无法读取路径的元数据，返回对应的 FindError
+    pub fn from_path(path: impl Into<PathBuf>, follow_links: bool) -> FindResult<Self> {
+        let path = path.into();
+        let link = fs::symlink_metadata(&path).map_err(|e| FindError::from_io(e, &path))?;
+        let mut entry = Self {
+            kind: link.file_type().into(),
+            depth: 0,
+            follow_link: false,
+            link_metadata: OnceLock::new(),
+            target_metadata: OnceLock::new(),
+            backend: None,
+            stat: OnceLock::new(),
+            path,
+        };
+        if !entry.kind.is_symlink() {
+            entry.target_metadata = OnceLock::from(link);
+        } else if follow_links {
+            let target = fs::metadata(&entry.path).map_err(|e| FindError::from_io(e, &entry.path))?;
+            entry.kind = target.file_type().into();
+            entry.follow_link = true;
+            entry.link_metadata = OnceLock::from(link);
+            entry.target_metadata = OnceLock::from(target);
+        } else {
+            entry.link_metadata = OnceLock::from(link);
+        }
+        Ok(entry)
+    }
+
+    /// 条目的完整路径（以搜索根目录为前缀）
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 取出条目的路径
+    pub fn into_path(self) -> PathBuf {
+        self.path
+    }
+
+    /// 条目的文件名，根目录等没有文件名的路径返回整个路径
+    pub fn file_name(&self) -> &OsStr {
+        self.path.file_name().unwrap_or(self.path.as_os_str())
+    }
+
+    /// 条目的类型，跟随的符号链接为目标的类型
+    pub fn kind(&self) -> FileKind {
+        self.kind
+    }
+
+    /// 条目相对于搜索根目录的深度，根目录本身为 0
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// 条目是否由符号链接产生（无论是否被跟随）
+    pub fn path_is_symlink(&self) -> bool {
+        self.kind.is_symlink() || self.follow_link
+    }
+
+    /// 条目的元数据，跟随的符号链接为目标的元数据，未跟随的符号链接为链接本身的元数据
+    ///
+    /// 第一次调用时读取并缓存，读取失败时不缓存。
+    ///
+    /// # 错误
+    /// 如果无法读取元数据，返回对应的 FindError
+    pub fn metadata(&self) -> FindResult<&Metadata> {
+        if self.follow_link {
+            self.target_metadata()
+        } else {
+            self.symlink_metadata()
+        }
+    }
+
+    /// 不跟随符号链接的元数据（`lstat`），缓存方式与 [`metadata`](Self::metadata) 相同
+    ///
+    /// # 错误
+    /// 如果无法读取元数据，返回对应的 FindError
+    pub fn symlink_metadata(&self) -> FindResult<&Metadata> {
+        if self.path_is_symlink() {
+            self.cached(&self.link_metadata, |path| fs::symlink_metadata(path))
+        } else {
+            self.target_metadata()
+        }
+    }
+
+    /// 跟随符号链接的元数据（`stat`），缓存方式与 [`metadata`](Self::metadata) 相同
+    ///
+    /// # 错误
+    /// 如果无法读取元数据（例如符号链接的目标不存在），返回对应的 FindError
+    pub fn target_metadata(&self) -> FindResult<&Metadata> {
+        self.cached(&self.target_metadata, |path| fs::metadata(path))
+    }
+
+    /// 条目的属性，本地文件系统和其他后端中的条目都可用
+    ///
+    /// 本地文件系统中的条目由 [`metadata`](Self::metadata) 转换得到；
+    /// 后端中的条目第一次调用时向后端读取并缓存。
+    ///
+    /// # 错误
+    /// 如果无法读取属性，返回对应的 FindError
+    pub fn stat(&self) -> FindResult<FileStat> {
+        let Some(backend) = &self.backend else {
+            return self.metadata().map(FileStat::from);
+        };
+        if let Some(stat) = self.stat.get() {
+            return Ok(stat.clone());
+        }
+        let stat = backend
+            .symlink_metadata(&self.path)
+            .map_err(|e| FindError::from_io(e, &self.path))?;
+        Ok(self.stat.get_or_init(|| stat).clone())
+    }
+
+    /// 条目是否在本地文件系统中，只有这样的条目可以按路径读取扩展属性等信息
+    pub fn is_local(&self) -> bool {
+        self.backend.as_ref().is_none_or(|backend| backend.is_local())
+    }
+
+    /// 打开条目以读取内容，本地文件系统中的符号链接打开其目标
+    ///
+    /// # 错误
+    /// 如果无法打开条目，返回对应的 FindError
+    pub fn open(&self) -> FindResult<Box<dyn Read + Send>> {
+        let reader = match &self.backend {
+            None => fs::File::open(&self.path).map(|file| Box::new(file) as Box<dyn Read + Send>),
+            Some(backend) => backend.open(&self.path),
+        };
+        reader.map_err(|e| FindError::from_io(e, &self.path))
+    }
+
+    /// 从缓存中取出元数据，没有缓存时读取
+    ///
+    /// 只有本地文件系统中的条目有元数据，其他后端中的条目返回错误。
+    fn cached<'a>(
+        &self,
+        cell: &'a OnceLock<Metadata>,
+        read: fn(&Path) -> io::Result<Metadata>,
+    ) -> FindResult<&'a Metadata> {
+        if let Some(metadata) = cell.get() {
+            return Ok(metadata);
+        }
+        if !self.is_local() {
+            return Err(FindError::Other {
+                message: "只有本地文件系统中的条目有元数据".to_string(),
+                context: Some(self.path.display().to_string()),
+                timestamp: SystemTime::now(),
+            });
+        }
+        let metadata = read(&self.path).map_err(|e| FindError::from_io(e, &self.path))?;
+        Ok(cell.get_or_init(|| metadata))
+    }
+
+    /// 由目录列表中的名称和类型（不跟随符号链接）创建条目，元数据在需要时读取
+    pub(crate) fn listed(path: PathBuf, file_type: FileType, depth: usize) -> Self {
+        Self {
+            path,
+            kind: file_type.into(),
+            depth,
+            follow_link: false,
+            link_metadata: OnceLock::new(),
+            target_metadata: OnceLock::new(),
+            backend: None,
+            stat: OnceLock::new(),
+        }
+    }
+
+    /// 由文件系统后端中的名称和类型创建条目
+    ///
+    /// 枚举目录时已经得到的属性直接缓存在条目中，否则在需要时向后端读取。
+    pub(crate) fn in_filesystem(
+        backend: Arc<dyn FileSystem>,
+        path: PathBuf,
+        kind: FileKind,
+        stat: Option<FileStat>,
+        depth: usize,
+    ) -> Self {
+        Self {
+            path,
+            kind,
+            depth,
+            follow_link: false,
+            link_metadata: OnceLock::new(),
+            target_metadata: OnceLock::new(),
+            backend: Some(backend),
+            stat: stat.map_or_else(OnceLock::new, OnceLock::from),
+        }
+    }
+
+    /// 转换逐个目录遍历得到的条目，深度按相对于搜索根目录的路径计算
+    pub(crate) fn under_root(entry: &walkdir::DirEntry, root: &Path) -> Self {
+        let mut converted = Self::from(entry);
+        converted.depth = entry
+            .path()
+            .strip_prefix(root)
+            .map_or(0, |relative| relative.components().count());
+        converted
+    }
+}
+
+impl From<&walkdir::DirEntry> for FindEntry {
+    fn from(entry: &walkdir::DirEntry) -> Self {
+        let converted = Self {
+            path: entry.path().to_path_buf(),
+            kind: entry.file_type().into(),
+            depth: entry.depth(),
+            follow_link: entry.path_is_symlink() && !entry.file_type().is_symlink(),
+            link_metadata: OnceLock::new(),
+            target_metadata: OnceLock::new(),
+            backend: None,
+            stat: OnceLock::new(),
+        };
+        // Windows 上枚举目录时已经得到未跟随的条目的元数据，直接缓存
+        #[cfg(windows)]
+        if !converted.follow_link {
+            if let Ok(cached) = entry.metadata() {
+                let cell = if converted.kind.is_symlink() {
+                    &converted.link_metadata
+                } else {
+                    &converted.target_metadata
+                };
+                let _ = cell.set(cached);
+            }
+        }
+        converted
+    }
+}
+
+impl From<walkdir::DirEntry> for FindEntry {
+    fn from(entry: walkdir::DirEntry) -> Self {
+        let mut converted = Self::from(&entry);
+        converted.path = entry.into_path();
+        converted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_entry_from_walkdir() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        fs::create_dir(dir.path().join("sub"))?;
+        fs::write(dir.path().join("sub/file.txt"), "hello")?;
+
+        let walked = walkdir::WalkDir::new(dir.path())
+            .into_iter()
+            .filter_map(Result::ok)
+            .find(|entry| entry.file_name() == "file.txt")
+            .unwrap();
+        let entry = FindEntry::from(walked);
+        assert_eq!(entry.path(), dir.path().join("sub/file.txt"));
+        assert_eq!(entry.file_name(), "file.txt");
+        assert_eq!(entry.depth(), 2);
+        assert!(entry.kind().is_file());
+        assert!(!entry.path_is_symlink());
+        assert_eq!(entry.metadata()?.len(), 5);
+
+        // 元数据被缓存，文件删除后仍可读取；不是符号链接的条目共用一份元数据
+        fs::remove_file(entry.path())?;
+        assert_eq!(entry.metadata()?.len(), 5);
+        assert!(std::ptr::eq(entry.symlink_metadata()?, entry.target_metadata()?));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_metadata_filters_share_one_read() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::finder::filter::{FileFilter, LinksFilter, NumericComparison, PermFilter, SizeFilter, SizeMeasure};
+
+        let dir = tempdir()?;
+        let path = dir.path().join("data.bin");
+        fs::write(&path, "12345")?;
+        let entry = FindEntry::from(walkdir::WalkDir::new(&path).into_iter().next().unwrap()?);
+        let size = SizeFilter::new(NumericComparison::Equal(5), SizeMeasure::Apparent);
+        assert!(size.matches(&entry));
+
+        // 第一次读取之后不再访问文件系统，文件删除后其他过滤器仍使用缓存的元数据
+        fs::remove_file(&path)?;
+        assert!(LinksFilter::new(NumericComparison::Equal(1)).matches(&entry));
+        assert!(!PermFilter::setuid().matches(&entry));
+        assert!(size.matches(&entry));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_entry_from_path_follows_links() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        fs::create_dir(dir.path().join("target"))?;
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(dir.path().join("target"), &link)?;
+
+        let entry = FindEntry::from_path(&link, false)?;
+        assert!(entry.kind().is_symlink());
+        assert!(entry.path_is_symlink());
+
+        let followed = FindEntry::from_path(&link, true)?;
+        assert!(followed.kind().is_dir());
+        assert!(followed.path_is_symlink());
+        assert!(followed.metadata()?.is_dir());
+        assert!(followed.symlink_metadata()?.file_type().is_symlink());
+        assert!(entry.target_metadata()?.is_dir());
+
+        assert!(FindEntry::from_path(dir.path().join("missing"), false).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+//! 过滤器解释
+//!
+//! 记录过滤器链中每个过滤器对条目的判断，用于调试复杂的过滤条件（`--explain`、`--explain-path`）。
+//! 过滤器按 [`description`](super::FileFilter::description) 显示。
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::errors::FindResult;
+use super::filter::FileFilter;
+
+/// 单个过滤器对条目的判断
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// 条目通过了过滤器
+    Accepted,
+    /// 条目被过滤器拒绝
+    Rejected,
+    /// 过滤器无法完成检查（例如读取文件失败），条目视为不匹配
+    Failed(String),
+}
+
+/// 过滤器链中的一步
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplainStep {
+    /// 过滤器的描述
+    pub filter: String,
+    /// 过滤器的判断
+    pub verdict: Verdict,
+}
+
+/// 过滤器链对单个条目的判断过程
+///
+/// 过滤器按实际检查的顺序排列（见 [`FileFilter::cost`]）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    /// 条目的路径
+    pub path: PathBuf,
+    /// 每个被检查的过滤器的判断
+    pub steps: Vec<ExplainStep>,
+}
+
+impl Explanation {
+    /// 创建没有任何步骤的解释
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// 记录一个过滤器的检查结果
+    pub fn record(&mut self, filter: &dyn FileFilter, result: &FindResult<bool>) {
+        let verdict = match result {
+            Ok(true) => Verdict::Accepted,
+            Ok(false) => Verdict::Rejected,
+            Err(e) => Verdict::Failed(e.to_string()),
+        };
+        self.steps.push(ExplainStep {
+            filter: filter.description(),
+            verdict,
+        });
+    }
+
+    /// 条目是否通过了所有被检查的过滤器
+    pub fn matched(&self) -> bool {
+        self.steps.iter().all(|step| step.verdict == Verdict::Accepted)
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = if self.matched() { "匹配" } else { "不匹配" };
+        write!(f, "{}: {}", self.path.display(), outcome)?;
+        if self.steps.is_empty() {
+            write!(f, "（没有过滤器）")?;
+        }
+        for step in &self.steps {
+            match &step.verdict {
+                Verdict::Accepted => write!(f, "\n  通过  {}", step.filter)?,
+                Verdict::Rejected => write!(f, "\n  拒绝  {}", step.filter)?,
+                Verdict::Failed(message) => write!(f, "\n  出错  {}: {}", step.filter, message)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::FindError;
+    use crate::finder::filter::NameFilter;
+
+    #[test]
+    fn test_explanation_display() {
+        let name = NameFilter::new("*.rs").unwrap();
+        let mut explanation = Explanation::new(Path::new("src/lib.rs"));
+        explanation.record(&name, &Ok(true));
+        assert!(explanation.matched());
+
+        explanation.record(&name, &Err(FindError::FileNotFound(PathBuf::from("src/lib.rs"))));
+        assert!(!explanation.matched());
+        assert_eq!(
+            explanation.to_string(),
+            format!(
+                "src/lib.rs: 不匹配\n  通过  {0}\n  出错  {0}: 文件未找到: src/lib.rs",
+                name.description()
+            )
+        );
+        assert_eq!(Explanation::new(Path::new("a")).to_string(), "a: 匹配（没有过滤器）");
+    }
+}
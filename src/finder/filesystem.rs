@@ -0,0 +1,508 @@
+//! 可替换的文件系统后端
+//!
+//! [`FileSystem`] 抽象了遍历和过滤需要的三个操作：列出目录、读取条目的属性和打开文件。
+//! [`OsFileSystem`] 直接使用 `std::fs`；[`MemoryFileSystem`] 在内存中保存目录树，
+//! 不需要临时目录即可测试遍历和过滤。压缩包、远程存储等后端实现这个 trait 后
+//! 即可通过 [`Finder::find_in`] 搜索。
+//!
+//! 后端中的条目只提供 [`FileStat`] 中的属性，名称、路径、类型、大小、时间和内容过滤器可以使用；
+//! 依赖本地文件系统特有信息的过滤器（权限、属主、inode、扩展属性等）不匹配这些条目，
+//! 除非后端读取的就是本地文件系统（[`FileSystem::is_local`]），例如 [`native_filesystem`](super::native_filesystem)。
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use log::debug;
+
+use super::entry::FindEntry;
+use super::filter::FileFilter;
+use super::prune::{self, Pruner};
+use super::content::ContentStage;
+use super::Finder;
+
+/// 条目的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileKind {
+    /// 普通文件
+    File,
+    /// 目录
+    Dir,
+    /// 符号链接
+    Symlink,
+    /// 块设备
+    BlockDevice,
+    /// 字符设备
+    CharDevice,
+    /// 命名管道（FIFO）
+    Fifo,
+    /// 套接字
+    Socket,
+    /// 其他类型
+    Other,
+}
+
+impl FileKind {
+    /// 是否为普通文件
+    pub fn is_file(self) -> bool {
+        self == FileKind::File
+    }
+
+    /// 是否为目录
+    pub fn is_dir(self) -> bool {
+        self == FileKind::Dir
+    }
+
+    /// 是否为符号链接
+    pub fn is_symlink(self) -> bool {
+        self == FileKind::Symlink
+    }
+}
+
+impl From<fs::FileType> for FileKind {
+    fn from(file_type: fs::FileType) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+            if file_type.is_block_device() {
+                return FileKind::BlockDevice;
+            }
+            if file_type.is_char_device() {
+                return FileKind::CharDevice;
+            }
+            if file_type.is_fifo() {
+                return FileKind::Fifo;
+            }
+            if file_type.is_socket() {
+                return FileKind::Socket;
+            }
+        }
+        if file_type.is_symlink() {
+            FileKind::Symlink
+        } else if file_type.is_dir() {
+            FileKind::Dir
+        } else if file_type.is_file() {
+            FileKind::File
+        } else {
+            FileKind::Other
+        }
+    }
+}
+
+/// 后端提供的条目属性（不跟随符号链接）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStat {
+    pub kind: FileKind,
+    /// 文件大小（字节）
+    pub len: u64,
+    /// 修改时间，后端不提供时为 None
+    pub modified: Option<SystemTime>,
+    /// 访问时间，后端不提供时为 None
+    pub accessed: Option<SystemTime>,
+    /// Unix 权限位，后端不提供时为 None
+    pub mode: Option<u32>,
+    /// Windows 文件属性（`FILE_ATTRIBUTE_*`），后端不提供时为 None
+    pub attributes: Option<u32>,
+}
+
+impl FileStat {
+    /// 指定类型和大小、其余属性未知的条目
+    pub fn new(kind: FileKind, len: u64) -> Self {
+        Self {
+            kind,
+            len,
+            modified: None,
+            accessed: None,
+            mode: None,
+            attributes: None,
+        }
+    }
+}
+
+impl From<&Metadata> for FileStat {
+    fn from(metadata: &Metadata) -> Self {
+        Self {
+            kind: metadata.file_type().into(),
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            accessed: metadata.accessed().ok(),
+            mode: Some(crate::output::meta::mode_bits(metadata)),
+            #[cfg(windows)]
+            attributes: Some(std::os::windows::fs::MetadataExt::file_attributes(metadata)),
+            #[cfg(not(windows))]
+            attributes: None,
+        }
+    }
+}
+
+/// 文件系统后端
+pub trait FileSystem: Send + Sync + std::fmt::Debug {
+    /// 列出目录中的条目名称和类型（不跟随符号链接），顺序不限
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<(OsString, FileKind)>>;
+
+    /// 列出目录中的条目及其属性（不跟随符号链接），顺序不限
+    ///
+    /// 枚举目录时就能得到属性的后端（如 Windows 上的 `FindFirstFileExW`）覆盖此方法，
+    /// 遍历时条目直接使用这些属性，不再逐个调用 [`symlink_metadata`](Self::symlink_metadata)。
+    /// 默认只返回 [`read_dir`](Self::read_dir) 得到的名称和类型。
+    fn read_dir_stats(&self, path: &Path) -> io::Result<Vec<(OsString, FileKind, Option<FileStat>)>> {
+        Ok(self.read_dir(path)?.into_iter().map(|(name, kind)| (name, kind, None)).collect())
+    }
+
+    /// 读取条目的属性，不跟随符号链接
+    fn symlink_metadata(&self, path: &Path) -> io::Result<FileStat>;
+
+    /// 打开文件以读取内容
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>>;
+
+    /// 后端中的路径是否就是本地文件系统中的路径
+    ///
+    /// 为 true 时后端中的条目与普通搜索的条目相同，可以按路径读取权限、属主、扩展属性等信息。
+    fn is_local(&self) -> bool {
+        false
+    }
+}
+
+/// 本地文件系统
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OsFileSystem;
+
+impl FileSystem for OsFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<(OsString, FileKind)>> {
+        fs::read_dir(path)?
+            .map(|entry| {
+                let entry = entry?;
+                Ok((entry.file_name(), entry.file_type()?.into()))
+            })
+            .collect()
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<FileStat> {
+        fs::symlink_metadata(path).map(|metadata| FileStat::from(&metadata))
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(File::open(path)?))
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+}
+
+/// 内存中的文件系统
+///
+/// 添加条目时自动创建其所在的目录。
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::{Finder, FindOptions, MemoryFileSystem};
+/// use rust_find::finder::filter::NameFilter;
+/// use std::path::PathBuf;
+/// use std::sync::Arc;
+///
+/// let fs = MemoryFileSystem::new()
+///     .with_file("/src/main.rs", "fn main() {}")
+///     .with_file("/README.md", "# demo");
+/// let finder = Finder::new(FindOptions::new());
+/// let results = finder.find_in(Arc::new(fs), "/", NameFilter::new("*.rs").unwrap());
+/// assert_eq!(results, [PathBuf::from("/src/main.rs")]);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MemoryFileSystem {
+    nodes: BTreeMap<PathBuf, MemoryNode>,
+}
+
+#[derive(Debug, Clone)]
+enum MemoryNode {
+    Dir,
+    File { contents: Arc<[u8]>, modified: Option<SystemTime> },
+    Symlink,
+}
+
+impl MemoryFileSystem {
+    /// 创建空的文件系统
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加目录
+    pub fn with_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.insert(path.into(), MemoryNode::Dir);
+        self
+    }
+
+    /// 添加文件
+    pub fn with_file(mut self, path: impl Into<PathBuf>, contents: impl AsRef<[u8]>) -> Self {
+        let node = MemoryNode::File {
+            contents: contents.as_ref().into(),
+            modified: None,
+        };
+        self.insert(path.into(), node);
+        self
+    }
+
+    /// 添加符号链接，链接不能被跟随
+    pub fn with_symlink(mut self, path: impl Into<PathBuf>) -> Self {
+        self.insert(path.into(), MemoryNode::Symlink);
+        self
+    }
+
+    /// 设置已添加的文件的修改时间
+    pub fn with_modified(mut self, path: impl AsRef<Path>, time: SystemTime) -> Self {
+        if let Some(MemoryNode::File { modified, .. }) = self.nodes.get_mut(path.as_ref()) {
+            *modified = Some(time);
+        }
+        self
+    }
+
+    fn insert(&mut self, path: PathBuf, node: MemoryNode) {
+        for ancestor in path.ancestors().skip(1).filter(|ancestor| !ancestor.as_os_str().is_empty()) {
+            self.nodes.entry(ancestor.to_path_buf()).or_insert(MemoryNode::Dir);
+        }
+        self.nodes.insert(path, node);
+    }
+
+    fn node(&self, path: &Path) -> io::Result<&MemoryNode> {
+        self.nodes
+            .get(path)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} 不存在", path.display())))
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<(OsString, FileKind)>> {
+        if !matches!(self.node(path)?, MemoryNode::Dir) {
+            return Err(io::Error::new(io::ErrorKind::NotADirectory, format!("{} 不是目录", path.display())));
+        }
+        Ok(self
+            .nodes
+            .iter()
+            .filter(|(child, _)| child.parent() == Some(path))
+            .filter_map(|(child, node)| Some((child.file_name()?.to_os_string(), node.kind())))
+            .collect())
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<FileStat> {
+        let node = self.node(path)?;
+        let mut stat = FileStat::new(node.kind(), 0);
+        if let MemoryNode::File { contents, modified } = node {
+            stat.len = contents.len() as u64;
+            stat.modified = *modified;
+        }
+        Ok(stat)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        match self.node(path)? {
+            MemoryNode::File { contents, .. } => Ok(Box::new(io::Cursor::new(contents.clone()))),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} 不是文件", path.display()))),
+        }
+    }
+}
+
+impl MemoryNode {
+    fn kind(&self) -> FileKind {
+        match self {
+            MemoryNode::Dir => FileKind::Dir,
+            MemoryNode::File { .. } => FileKind::File,
+            MemoryNode::Symlink => FileKind::Symlink,
+        }
+    }
+}
+
+impl Finder {
+    /// 在指定的文件系统后端中查找文件
+    ///
+    /// 按文件名排序在当前线程中进行深度优先遍历，目录中的匹配项排在其子目录中的匹配项之前。
+    /// 不跟随符号链接；剪枝规则、隐藏文件、深度和结果数上限与普通搜索相同，
+    /// 但不读取后端中的 .gitignore 等忽略文件。
+    pub fn find_in<F>(&self, fs: Arc<dyn FileSystem>, root: impl Into<PathBuf>, filter: F) -> Vec<PathBuf>
+    where
+        F: FileFilter,
+    {
+        let root = root.into();
+        let mut results = Vec::new();
+        let root_stat = match fs.symlink_metadata(&root) {
+            Ok(stat) => stat,
+            Err(e) => {
+                debug!("Cannot read search root {}: {}", root.display(), e);
+                return results;
+            }
+        };
+        let root_kind = root_stat.kind;
+        let root_entry = FindEntry::in_filesystem(fs.clone(), root.clone(), root_kind, Some(root_stat), 0);
+        if self.accepts_in(&root_entry, &root, &filter) {
+            results.push(root.clone());
+        }
+
+        let limit = self.options.max_results.unwrap_or(usize::MAX);
+        let mut pruner = Pruner::new(&self.options, &root);
+        let mut stack = Vec::new();
+        if root_kind.is_dir() && self.within_depth(0) {
+            stack.push((root.clone(), 0));
+        }
+
+        while let Some((dir, depth)) = stack.pop() {
+            if results.len() >= limit || self.options.cancel_token.is_cancelled() {
+                break;
+            }
+            let mut entries = match fs.read_dir_stats(&dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    debug!("Cannot read directory {}: {}", dir.display(), e);
+                    continue;
+                }
+            };
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+            let mut subdirs = Vec::new();
+            for (name, kind, stat) in entries {
+                let path = dir.join(name);
+                if !pruner.keeps_listed(&path, kind.is_dir()) {
+                    continue;
+                }
+                let entry = FindEntry::in_filesystem(fs.clone(), path, kind, stat, depth + 1);
+                if self.accepts_in(&entry, &root, &filter) {
+                    results.push(entry.path().to_path_buf());
+                }
+                if kind.is_dir() {
+                    subdirs.push(entry.into_path());
+                }
+            }
+            if self.within_depth(depth + 1) {
+                stack.extend(subdirs.into_iter().rev().map(|subdir| (subdir, depth + 1)));
+            }
+        }
+
+        results.truncate(limit);
+        if self.options.contents_first {
+            super::sort_contents_first(&mut results);
+        }
+        results
+    }
+
+    /// 检查后端中的条目是否应出现在结果中
+    fn accepts_in<F: FileFilter>(&self, entry: &FindEntry, root: &Path, filter: &F) -> bool {
+        !prune::is_excluded_path(&self.options, root, entry.path())
+            && !prune::is_hidden(&self.options, entry)
+            && self.matches_all(entry, filter)
+            && ContentStage::matches(&self.content_filters, entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finder::content::ContentFilter;
+    use crate::finder::filter::{NameFilter, SizeFilter, SizeMeasure, TypeFilter};
+    use crate::finder::size::ByteSize;
+    use crate::finder::FindOptions;
+
+    fn tree() -> Arc<dyn FileSystem> {
+        Arc::new(
+            MemoryFileSystem::new()
+                .with_file("/project/src/main.rs", "fn main() {\n    // TODO\n}\n")
+                .with_file("/project/src/lib.rs", "pub fn lib() {}\n")
+                .with_file("/project/.hidden.rs", "")
+                .with_file("/project/README.md", "# project\n".repeat(200))
+                .with_dir("/project/empty")
+                .with_symlink("/project/link.rs"),
+        )
+    }
+
+    #[test]
+    fn test_memory_filesystem() -> io::Result<()> {
+        let fs = tree();
+        let mut names = fs.read_dir(Path::new("/project"))?;
+        names.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(names.len(), 5);
+        assert_eq!(names[0], (OsString::from(".hidden.rs"), FileKind::File));
+        assert_eq!(fs.symlink_metadata(Path::new("/project/README.md"))?.len, 2000);
+
+        let mut contents = String::new();
+        fs.open(Path::new("/project/src/lib.rs"))?.read_to_string(&mut contents)?;
+        assert_eq!(contents, "pub fn lib() {}\n");
+        assert_eq!(fs.read_dir(Path::new("/missing")).unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert!(fs.read_dir(Path::new("/project/README.md")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_in_applies_filters() -> Result<(), Box<dyn std::error::Error>> {
+        let finder = Finder::new(FindOptions::new().with_ignore_hidden(true));
+        assert_eq!(
+            finder.find_in(tree(), "/project", NameFilter::new("*.rs")?),
+            [
+                PathBuf::from("/project/link.rs"),
+                PathBuf::from("/project/src/lib.rs"),
+                PathBuf::from("/project/src/main.rs"),
+            ]
+        );
+        assert_eq!(
+            finder.find_in(tree(), "/project", TypeFilter::new("d")?),
+            [PathBuf::from("/project"), PathBuf::from("/project/empty"), PathBuf::from("/project/src")]
+        );
+        assert_eq!(
+            finder.find_in(tree(), "/project", SizeFilter::at_least(ByteSize::new(1000), SizeMeasure::Apparent)),
+            [PathBuf::from("/project/README.md")]
+        );
+
+        let content = Finder::new(FindOptions::new()).with_filter(ContentFilter::literal("TODO")?);
+        assert_eq!(
+            content.find_in(tree(), "/project", NameFilter::new("*")?),
+            [PathBuf::from("/project/src/main.rs")]
+        );
+
+        let shallow = Finder::new(FindOptions::new().with_max_depth(Some(1)));
+        assert_eq!(shallow.find_in(tree(), "/project", NameFilter::new("*.rs")?), [PathBuf::from("/project/link.rs")]);
+        assert!(finder.find_in(tree(), "/missing", NameFilter::new("*")?).is_empty());
+        Ok(())
+    }
+
+    /// 枚举目录时一并返回属性的后端，记录单独读取属性的次数
+    #[derive(Debug)]
+    struct ListedStats {
+        inner: Arc<dyn FileSystem>,
+        lookups: std::sync::atomic::AtomicUsize,
+    }
+
+    impl FileSystem for ListedStats {
+        fn read_dir(&self, path: &Path) -> io::Result<Vec<(OsString, FileKind)>> {
+            self.inner.read_dir(path)
+        }
+
+        fn read_dir_stats(&self, path: &Path) -> io::Result<Vec<(OsString, FileKind, Option<FileStat>)>> {
+            self.inner
+                .read_dir(path)?
+                .into_iter()
+                .map(|(name, kind)| Ok((name.clone(), kind, Some(self.inner.symlink_metadata(&path.join(name))?))))
+                .collect()
+        }
+
+        fn symlink_metadata(&self, path: &Path) -> io::Result<FileStat> {
+            self.lookups.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            self.inner.symlink_metadata(path)
+        }
+
+        fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+            self.inner.open(path)
+        }
+    }
+
+    #[test]
+    fn test_find_in_uses_listed_stats() {
+        let fs = Arc::new(ListedStats { inner: tree(), lookups: Default::default() });
+        let finder = Finder::new(FindOptions::new());
+        assert_eq!(
+            finder.find_in(fs.clone(), "/project", SizeFilter::at_least(ByteSize::new(1000), SizeMeasure::Apparent)),
+            [PathBuf::from("/project/README.md")]
+        );
+        // 只有搜索根目录需要单独读取属性
+        assert_eq!(fs.lookups.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+}
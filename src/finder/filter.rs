@@ -4,11 +4,27 @@
 //! - 文件名模式匹配
 //! - 文件类型过滤
 //! - 路径格式控制
+//! - 自定义属性匹配
+//! - 失效的符号链接
+//! - 硬链接数
+//! - inode 编号和同一文件
+//! - 扩展属性
+//! - 权限位
 
-use walkdir::DirEntry;
-use glob::Pattern;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use glob::{MatchOptions, Pattern};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use log::debug;
 
 use crate::errors::{FindError, FindResult};
+use crate::output::meta;
+use super::entry::FindEntry;
+use super::filesystem::FileKind;
+use super::metadata::MetadataProvider;
+use super::size::ByteSize;
 
 /// 文件过滤器trait
 ///
@@ -18,7 +34,24 @@ pub trait FileFilter {
     ///
     /// # 参数
     /// - `entry`: 待检查的目录条目
-    fn matches(&self, entry: &DirEntry) -> bool;
+    fn matches(&self, entry: &FindEntry) -> bool;
+
+    /// 检查条目是否匹配过滤器，无法完成检查时返回错误
+    ///
+    /// 需要读取文件内容或属性的过滤器覆盖此方法，将读取失败作为错误返回，
+    /// 查找器按错误类别的处理策略报告这些错误，而不是把条目静默地视为不匹配。
+    /// 默认实现调用 [`matches`](Self::matches)，总是成功。
+    fn try_matches(&self, entry: &FindEntry) -> FindResult<bool> {
+        Ok(self.matches(entry))
+    }
+
+    /// 检查一个条目的大致代价
+    ///
+    /// 查找器按代价从低到高依次检查过滤器（见 [`FindOptions::with_reorder_filters`](super::FindOptions::with_reorder_filters)），
+    /// 便宜的过滤器先排除大部分条目。默认为 [`FilterCost::Metadata`]。
+    fn cost(&self) -> FilterCost {
+        FilterCost::Metadata
+    }
     
     /// 获取过滤器描述
     ///
@@ -26,6 +59,17 @@ pub trait FileFilter {
     fn description(&self) -> String;
 }
 
+/// 过滤器检查一个条目的代价，按从低到高排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FilterCost {
+    /// 只使用路径、名称和遍历时得到的文件类型，不访问文件系统
+    Path,
+    /// 需要读取元数据（每个条目最多一次 `stat`，多个过滤器共用）
+    Metadata,
+    /// 需要打开文件或读取扩展属性、内容等额外数据
+    Read,
+}
+
 /// 过滤器工厂，用于从命令行参数创建过滤器
 pub struct FilterFactory;
 
@@ -79,9 +123,12 @@ impl FilterFactory {
 /// let filter = NameFilter::new("*.rs").unwrap();
 /// ```
 pub struct NameFilter {
+    /// 编译后的模式，忽略大小写时为小写形式，只在创建时编译一次
     pattern: Pattern,
     original_pattern: String,
     ignore_case: bool,
+    /// 模式是否为字面子串（`--fixed-strings`）
+    fixed: bool,
 }
 
 impl NameFilter {
@@ -93,16 +140,7 @@ impl NameFilter {
     /// # 错误
     /// 如果模式无效，返回PatternError错误
     pub fn new(pattern: &str) -> FindResult<Self> {
-        let compiled_pattern = Pattern::new(pattern)
-            .map_err(|e| FindError::PatternError {
-                message: format!("Invalid pattern '{}': {}", pattern, e),
-            })?;
-        
-        Ok(Self {
-            pattern: compiled_pattern,
-            original_pattern: pattern.to_string(),
-            ignore_case: false,
-        })
+        Self::build(pattern, false, false)
     }
     
     /// 创建新的文件名过滤器(忽略大小写)
@@ -118,53 +156,162 @@ impl NameFilter {
     /// let filter = NameFilter::new_ignore_case("*.RS").unwrap();
     /// ```
     pub fn new_ignore_case(pattern: &str) -> FindResult<Self> {
-        let mut filter = Self::new(pattern)?;
-        filter.ignore_case = true;
-        Ok(filter)
+        Self::build(pattern, true, false)
     }
-}
 
-impl NameFilter {
-    /// 执行大小写敏感匹配
-    fn matches_case_sensitive(&self, name: &str) -> bool {
-        self.pattern.matches(name)
+    /// 创建按字面子串匹配的文件名过滤器
+    ///
+    /// 文本中的 `*`、`?`、`[` 等字符不作为通配符，文件名包含该文本即匹配。
+    ///
+    /// # 示例
+    /// ```
+    /// use rust_find::finder::filter::NameFilter;
+    ///
+    /// // 匹配名称中包含 "[draft]" 的文件
+    /// let filter = NameFilter::fixed_string("[draft]", false).unwrap();
+    /// ```
+    ///
+    /// # 错误
+    /// 如果文本为空，返回PatternError错误
+    pub fn fixed_string(text: &str, ignore_case: bool) -> FindResult<Self> {
+        if text.is_empty() {
+            return Err(FindError::PatternError {
+                message: "Empty pattern is not allowed".to_string(),
+            });
+        }
+        Self::build(text, ignore_case, true)
     }
 
-    /// 执行大小写不敏感匹配
-    fn matches_case_insensitive(&self, name: &str) -> bool {
-        let name_lower = name.to_lowercase();
-        let pattern_lower = self.original_pattern.to_lowercase();
-        Pattern::new(&pattern_lower)
-            .map(|p| p.matches(&name_lower))
-            .unwrap_or(false)
+    fn build(pattern: &str, ignore_case: bool, fixed: bool) -> FindResult<Self> {
+        let source = if ignore_case { pattern.to_lowercase() } else { pattern.to_string() };
+        let source = if fixed { format!("*{}*", Pattern::escape(&source)) } else { source };
+        let compiled_pattern = Pattern::new(&source)
+            .map_err(|e| FindError::PatternError {
+                message: format!("Invalid pattern '{}': {}", pattern, e),
+            })?;
+        
+        Ok(Self {
+            pattern: compiled_pattern,
+            original_pattern: pattern.to_string(),
+            ignore_case,
+            fixed,
+        })
     }
 }
 
 impl FileFilter for NameFilter {
-    fn matches(&self, entry: &DirEntry) -> bool {
-        if let Some(name) = entry.file_name().to_str() {
-            if self.ignore_case {
-                self.matches_case_insensitive(name)
-            } else {
-                self.matches_case_sensitive(name)
-            }
-        } else {
-            false
+    fn matches(&self, entry: &FindEntry) -> bool {
+        // glob 的忽略大小写选项只处理 ASCII 字母，因此把名称转为小写后与小写的模式比较
+        match entry.file_name().to_str() {
+            Some(name) if self.ignore_case => self.pattern.matches(&name.to_lowercase()),
+            Some(name) => self.pattern.matches(name),
+            None => false,
         }
     }
     
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
     fn description(&self) -> String {
+        let relation = if self.fixed { "contains" } else { "matches" };
         if self.ignore_case {
-            format!("name (ignore case) matches '{}'", self.original_pattern)
+            format!("name (ignore case) {} '{}'", relation, self.original_pattern)
+        } else {
+            format!("name {} '{}'", relation, self.original_pattern)
+        }
+    }
+}
+
+/// 路径模式过滤器
+///
+/// 用 glob 模式匹配条目的路径。默认与 gitignore 相同，`*` 和 `?` 不匹配路径分隔符，
+/// 只有完整的 `**` 组件可以跨越任意层目录；关闭字面分隔符后 `*` 也匹配 `/`，
+/// 与 GNU find 的 `-path` 相同。
+///
+/// 模式既与完整路径比较，也与相对于所在搜索根目录的路径比较，
+/// 因此 `./src/*.rs` 和 `src/**/tests/*.rs` 两种写法都可以使用。
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::filter::PathFilter;
+///
+/// let filter = PathFilter::new("src/**/tests/*.rs").unwrap();
+/// assert!(filter.matches_path("src/tests/a.rs".as_ref()));
+/// assert!(filter.matches_path("src/net/http/tests/a.rs".as_ref()));
+/// assert!(!filter.matches_path("src/tests/fixtures/a.rs".as_ref()));
+/// ```
+pub struct PathFilter {
+    pattern: Pattern,
+    roots: Vec<std::path::PathBuf>,
+    literal_separator: bool,
+}
+
+impl PathFilter {
+    /// 创建新的路径模式过滤器
+    ///
+    /// # 错误
+    /// 如果模式无效，返回PatternError错误
+    pub fn new(pattern: &str) -> FindResult<Self> {
+        let pattern = Pattern::new(pattern).map_err(|e| FindError::PatternError {
+            message: format!("Invalid pattern '{}': {}", pattern, e),
+        })?;
+        Ok(Self { pattern, roots: Vec::new(), literal_separator: true })
+    }
+
+    /// 设置搜索根目录，路径同时以相对于所在根目录的形式参与匹配
+    pub fn with_roots(mut self, roots: Vec<std::path::PathBuf>) -> Self {
+        self.roots = roots;
+        self
+    }
+
+    /// 设置 `*` 和 `?` 是否不匹配路径分隔符（默认 true）
+    pub fn with_literal_separator(mut self, literal: bool) -> Self {
+        self.literal_separator = literal;
+        self
+    }
+
+    /// 检查路径是否匹配
+    pub fn matches_path(&self, path: &Path) -> bool {
+        let options = MatchOptions {
+            require_literal_separator: self.literal_separator,
+            ..MatchOptions::new()
+        };
+        if self.pattern.matches_path_with(path, options) {
+            return true;
+        }
+        // 嵌套的搜索根目录中取最长的前缀
+        self.roots
+            .iter()
+            .filter_map(|root| path.strip_prefix(root).ok())
+            .min_by_key(|relative| relative.components().count())
+            .is_some_and(|relative| self.pattern.matches_path_with(relative, options))
+    }
+}
+
+impl FileFilter for PathFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        self.matches_path(entry.path())
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
+    fn description(&self) -> String {
+        if self.literal_separator {
+            format!("path matches '{}'", self.pattern)
         } else {
-            format!("name matches '{}'", self.original_pattern)
+            format!("path matches '{}' (wildcards match '/')", self.pattern)
         }
     }
 }
 
 /// 多模式文件名过滤器
 ///
-/// 支持同时匹配多个文件名模式，可以使用AND或OR逻辑
+/// 支持同时匹配多个文件名模式，可以使用AND或OR逻辑。
+/// 所有模式被编译为一个 [`GlobSet`]，无论指定了多少个模式，
+/// 检查一个文件名都只需要一次匹配。
 ///
 /// # 示例
 /// ```
@@ -174,7 +321,9 @@ impl FileFilter for NameFilter {
 /// let filter = MultiNameFilter::new(&["*.rs".to_string(), "*.txt".to_string()], false).unwrap();
 /// ```
 pub struct MultiNameFilter {
-    patterns: Vec<NameFilter>,
+    /// 原始模式，用于生成描述
+    patterns: Vec<String>,
+    set: GlobSet,
     any_match: bool,
 }
 
@@ -189,14 +338,31 @@ impl MultiNameFilter {
     /// 如果任何模式无效，返回PatternError错误
     pub fn new(patterns: &[String], ignore_case: bool) -> FindResult<Self> {
         Self::validate_patterns(patterns)?;
-        let patterns = Self::create_filters(patterns, ignore_case)?;
+        let set = Self::compile(patterns.iter().map(String::as_str), ignore_case)?;
         
         Ok(Self {
-            patterns,
+            patterns: patterns.to_vec(),
+            set,
             any_match: true, // Default to OR logic
         })
     }
 
+    /// 创建按字面子串匹配的多模式过滤器（见 [`NameFilter::fixed_string`]）
+    ///
+    /// # 错误
+    /// 如果任何文本为空，返回PatternError错误
+    pub fn fixed_strings(texts: &[String], ignore_case: bool) -> FindResult<Self> {
+        Self::validate_patterns(texts)?;
+        let globs: Vec<String> = texts.iter().map(|text| format!("*{}*", globset::escape(text))).collect();
+        let set = Self::compile(globs.iter().map(String::as_str), ignore_case)?;
+
+        Ok(Self {
+            patterns: texts.to_vec(),
+            set,
+            any_match: true,
+        })
+    }
+
     /// 在创建过滤器之前验证所有模式
     fn validate_patterns(patterns: &[String]) -> FindResult<()> {
         for pattern in patterns {
@@ -209,18 +375,24 @@ impl MultiNameFilter {
         Ok(())
     }
 
-    /// 为每个模式创建单独的文件名过滤器
-    fn create_filters(patterns: &[String], ignore_case: bool) -> FindResult<Vec<NameFilter>> {
-        patterns
-            .iter()
-            .map(|pattern| {
-                if ignore_case {
-                    NameFilter::new_ignore_case(pattern)
-                } else {
-                    NameFilter::new(pattern)
-                }
-            })
-            .collect()
+    /// 将所有模式编译为一个模式集合
+    ///
+    /// 与 [`NameFilter`] 相同，反斜杠不作为转义字符。
+    fn compile<'a>(patterns: impl Iterator<Item = &'a str>, ignore_case: bool) -> FindResult<GlobSet> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let glob = GlobBuilder::new(pattern)
+                .case_insensitive(ignore_case)
+                .backslash_escape(false)
+                .build()
+                .map_err(|e| FindError::PatternError {
+                    message: format!("Invalid pattern '{}': {}", pattern, e.kind()),
+                })?;
+            builder.add(glob);
+        }
+        builder.build().map_err(|e| FindError::PatternError {
+            message: format!("Failed to compile name patterns: {}", e),
+        })
     }
     
     /// 设置模式匹配逻辑
@@ -247,42 +419,87 @@ impl MultiNameFilter {
 
 impl FileFilter for MultiNameFilter {
     /// 检查文件是否匹配任一/所有模式
-    fn matches(&self, entry: &DirEntry) -> bool {
+    fn matches(&self, entry: &FindEntry) -> bool {
         if self.patterns.is_empty() {
             return true;
         }
+        let Some(name) = entry.file_name().to_str() else {
+            return false;
+        };
         
         if self.any_match {
             // OR逻辑 - 任一模式匹配即可
-            self.patterns.iter().any(|filter| filter.matches(entry))
+            self.set.is_match(name)
         } else {
             // AND逻辑 - 所有模式都必须匹配
-            self.patterns.iter().all(|filter| filter.matches(entry))
+            self.set.matches(name).len() == self.patterns.len()
         }
     }
     
     /// 获取过滤器的描述信息
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
     fn description(&self) -> String {
-        let patterns: Vec<String> = self.patterns
-            .iter()
-            .map(|p| p.original_pattern.clone())
-            .collect();
-        
         let logic = if self.any_match { "任一" } else { "所有" };
-        format!("文件名匹配{}模式 [{}]", logic, patterns.join(", "))
+        format!("文件名匹配{}模式 [{}]", logic, self.patterns.join(", "))
+    }
+}
+
+/// 取反过滤器
+///
+/// 匹配内部过滤器不匹配的条目，用于表达“除了……之外”的条件。
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::filter::{NameFilter, NotFilter};
+///
+/// // 匹配所有不是压缩脚本的条目
+/// let filter = NotFilter::new(NameFilter::new("*.min.js").unwrap());
+/// ```
+pub struct NotFilter<F> {
+    inner: F,
+}
+
+impl<F: FileFilter> NotFilter<F> {
+    /// 创建对指定过滤器取反的过滤器
+    pub fn new(inner: F) -> Self {
+        Self { inner }
+    }
+}
+
+impl<F: FileFilter> FileFilter for NotFilter<F> {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        !self.inner.matches(entry)
+    }
+
+    /// 无法检查的条目不视为匹配取反的条件
+    fn try_matches(&self, entry: &FindEntry) -> FindResult<bool> {
+        self.inner.try_matches(entry).map(|matched| !matched)
+    }
+
+    fn cost(&self) -> FilterCost {
+        self.inner.cost()
+    }
+
+    fn description(&self) -> String {
+        format!("not ({})", self.inner.description())
     }
 }
 
 /// 文件类型过滤器
 ///
-/// 用于根据文件类型（普通文件、目录、符号链接）过滤文件
+/// 用于根据文件类型（普通文件、目录、符号链接、设备、FIFO、套接字）过滤文件，
+/// 可以同时指定多个类型，匹配其中任一类型即可
+#[derive(Debug, Clone)]
 pub struct TypeFilter {
-    file_type: FileType,
+    file_types: Vec<FileType>,
 }
 
 /// 支持的文件类型
 ///
-/// 定义了系统支持的基本文件类型
+/// 定义了系统支持的基本文件类型，设备、FIFO 和套接字只在 Unix 平台上存在
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FileType {
     /// 普通文件
@@ -291,46 +508,97 @@ pub enum FileType {
     Directory,
     /// 符号链接
     SymbolicLink,
+    /// 块设备
+    BlockDevice,
+    /// 字符设备
+    CharDevice,
+    /// 命名管道（FIFO）
+    Fifo,
+    /// 套接字
+    Socket,
+}
+
+impl FileType {
+    /// 根据 GNU find 的类型代码获取文件类型
+    fn from_code(code: &str) -> Option<Self> {
+        Some(match code {
+            "f" => FileType::File,
+            "d" => FileType::Directory,
+            "l" => FileType::SymbolicLink,
+            "b" => FileType::BlockDevice,
+            "c" => FileType::CharDevice,
+            "p" => FileType::Fifo,
+            "s" => FileType::Socket,
+            _ => return None,
+        })
+    }
+
+    /// 检查条目类型是否为此类型
+    fn matches(self, kind: FileKind) -> bool {
+        let expected = match self {
+            FileType::File => FileKind::File,
+            FileType::Directory => FileKind::Dir,
+            FileType::SymbolicLink => FileKind::Symlink,
+            FileType::BlockDevice => FileKind::BlockDevice,
+            FileType::CharDevice => FileKind::CharDevice,
+            FileType::Fifo => FileKind::Fifo,
+            FileType::Socket => FileKind::Socket,
+        };
+        kind == expected
+    }
+
+    /// 类型名称（用于过滤器描述）
+    fn name(self) -> &'static str {
+        match self {
+            FileType::File => "a regular file",
+            FileType::Directory => "a directory",
+            FileType::SymbolicLink => "a symbolic link",
+            FileType::BlockDevice => "a block device",
+            FileType::CharDevice => "a character device",
+            FileType::Fifo => "a FIFO",
+            FileType::Socket => "a socket",
+        }
+    }
 }
 
 impl TypeFilter {
     /// 创建新的文件类型过滤器
     ///
     /// # 参数
-    /// - `type_code`: 文件类型代码
+    /// - `type_code`: 文件类型代码，多个类型以逗号分隔（如 "f,l"）
     ///   - "f": 普通文件
     ///   - "d": 目录
     ///   - "l": 符号链接
+    ///   - "b": 块设备
+    ///   - "c": 字符设备
+    ///   - "p": 命名管道（FIFO）
+    ///   - "s": 套接字
     ///
     /// # 错误
     /// 如果类型代码无效，返回InvalidFileType错误
     pub fn new(type_code: &str) -> Result<Self, FindError> {
-        let file_type = match type_code {
-            "f" => FileType::File,
-            "d" => FileType::Directory,
-            "l" => FileType::SymbolicLink,
-            _ => return Err(FindError::InvalidFileType(type_code.to_string())),
-        };
-        
-        Ok(Self { file_type })
+        let file_types = type_code
+            .split(',')
+            .map(|code| FileType::from_code(code).ok_or_else(|| FindError::InvalidFileType(code.to_string())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { file_types })
     }
 }
 
 impl FileFilter for TypeFilter {
-    fn matches(&self, entry: &DirEntry) -> bool {
-        match self.file_type {
-            FileType::File => entry.file_type().is_file(),
-            FileType::Directory => entry.file_type().is_dir(),
-            FileType::SymbolicLink => entry.file_type().is_symlink(),
-        }
+    fn matches(&self, entry: &FindEntry) -> bool {
+        let kind = entry.kind();
+        self.file_types.iter().any(|file_type| file_type.matches(kind))
     }
     
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
     fn description(&self) -> String {
-        match self.file_type {
-            FileType::File => "is a regular file".to_string(),
-            FileType::Directory => "is a directory".to_string(),
-            FileType::SymbolicLink => "is a symbolic link".to_string(),
-        }
+        let names: Vec<&str> = self.file_types.iter().map(|kind| kind.name()).collect();
+        format!("is {}", names.join(" or "))
     }
 }
 
@@ -343,12 +611,16 @@ pub enum PathFormatFilter {
 }
 
 impl FileFilter for PathFormatFilter {
-    fn matches(&self, _entry: &DirEntry) -> bool {
+    fn matches(&self, _entry: &FindEntry) -> bool {
         // This filter doesn't exclude any entries,
         // it just affects how they're displayed
         true
     }
     
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
     fn description(&self) -> String {
         match self {
             PathFormatFilter::Absolute => "output absolute paths".to_string(),
@@ -357,93 +629,1051 @@ impl FileFilter for PathFormatFilter {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs::File;
-    use std::io::Write;
-    use tempfile::TempDir;
-    
-    fn create_test_entry(name: &str) -> Result<(TempDir, DirEntry), Box<dyn std::error::Error>> {
-        let temp_dir = TempDir::new()?;
-        let file_path = temp_dir.path().join(name);
-        File::create(&file_path)?.write_all(b"test")?;
-        
-        let entry = walkdir::WalkDir::new(&file_path)
-            .into_iter()
-            .next()
-            .unwrap()?;
-        
-        Ok((temp_dir, entry))
+/// 自定义属性过滤器
+///
+/// 根据 [`MetadataProvider`] 提供的键值属性过滤文件
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::filter::AttrFilter;
+/// use rust_find::finder::metadata::SidecarProvider;
+/// use std::sync::Arc;
+///
+/// // 匹配旁路文件中 project=alpha 的文件
+/// let filter = AttrFilter::new(Arc::new(SidecarProvider::default()), "project=alpha").unwrap();
+/// ```
+pub struct AttrFilter {
+    provider: Arc<dyn MetadataProvider>,
+    key: String,
+    value: Option<String>,
+}
+
+impl AttrFilter {
+    /// 创建新的属性过滤器
+    ///
+    /// # 参数
+    /// - `provider`: 元数据提供者
+    /// - `spec`: `key=value` 要求属性等于指定值，`key` 只要求属性存在
+    ///
+    /// # 错误
+    /// 如果键为空，返回PatternError错误
+    pub fn new(provider: Arc<dyn MetadataProvider>, spec: &str) -> FindResult<Self> {
+        let (key, value) = match spec.split_once('=') {
+            Some((key, value)) => (key.trim(), Some(value.trim().to_string())),
+            None => (spec.trim(), None),
+        };
+
+        if key.is_empty() {
+            return Err(FindError::PatternError {
+                message: format!("Invalid attribute spec '{}'", spec),
+            });
+        }
+
+        Ok(Self {
+            provider,
+            key: key.to_string(),
+            value,
+        })
     }
-    
-    #[test]
-    fn test_name_filter() -> Result<(), Box<dyn std::error::Error>> {
-        let (_temp_dir, entry) = create_test_entry("test.txt")?;
-        
-        let filter = NameFilter::new("*.txt")?;
-        assert!(filter.matches(&entry));
-        
-        let filter = NameFilter::new("*.rs")?;
-        assert!(!filter.matches(&entry));
-        
-        Ok(())
+}
+
+impl FileFilter for AttrFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        let Some(attrs) = self.provider.attributes(entry.path()) else {
+            return false;
+        };
+        match (attrs.get(&self.key), &self.value) {
+            (Some(actual), Some(expected)) => actual == expected,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
     }
-    
-    #[test]
-    fn test_name_filter_case_insensitive() -> Result<(), Box<dyn std::error::Error>> {
-        let (_temp_dir, entry) = create_test_entry("Test.TXT")?;
-        
-        let filter = NameFilter::new("*.txt")?;
-        assert!(!filter.matches(&entry));
-        
-        let filter = NameFilter::new_ignore_case("*.txt")?;
-        assert!(filter.matches(&entry));
-        
-        Ok(())
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Read
     }
-    
-    #[test]
-    fn test_multi_name_filter() -> Result<(), Box<dyn std::error::Error>> {
-        let (_temp_dir, entry) = create_test_entry("test.txt")?;
-        
-        let filter = MultiNameFilter::new(&["*.txt".to_string(), "*.rs".to_string()], false)?;
-        assert!(filter.matches(&entry));
-        
-        let filter = MultiNameFilter::new(&["*.doc".to_string(), "*.rs".to_string()], false)?;
-        assert!(!filter.matches(&entry));
-        
-        Ok(())
+
+    fn description(&self) -> String {
+        match &self.value {
+            Some(value) => format!("attribute '{}' equals '{}'", self.key, value),
+            None => format!("has attribute '{}'", self.key),
+        }
     }
-    
-    #[test]
-    fn test_type_filter() -> Result<(), Box<dyn std::error::Error>> {
-        let temp_dir = TempDir::new()?;
-        
-        // Create a file
-        let file_path = temp_dir.path().join("test.txt");
-        File::create(&file_path)?.write_all(b"test")?;
-        let file_entry = walkdir::WalkDir::new(&file_path)
-            .into_iter()
-            .next()
-            .unwrap()?;
-        
-        // Create a directory
-        let dir_path = temp_dir.path().join("testdir");
-        std::fs::create_dir(&dir_path)?;
-        let dir_entry = walkdir::WalkDir::new(&dir_path)
-            .into_iter()
-            .next()
-            .unwrap()?;
-        
-        let file_filter = TypeFilter::new("f")?;
-        assert!(file_filter.matches(&file_entry));
-        assert!(!file_filter.matches(&dir_entry));
-        
-        let dir_filter = TypeFilter::new("d")?;
-        assert!(!dir_filter.matches(&file_entry));
-        assert!(dir_filter.matches(&dir_entry));
-        
-        Ok(())
+}
+
+/// 失效符号链接过滤器
+///
+/// 匹配目标不存在的符号链接（链接本身存在，但无法解析到目标），
+/// 与 GNU find 的 `-xtype l` 相同。
+pub struct BrokenSymlinkFilter;
+
+impl FileFilter for BrokenSymlinkFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        entry.path_is_symlink() && entry.target_metadata().is_err()
+    }
+
+    fn description(&self) -> String {
+        "is a broken symbolic link".to_string()
     }
-}
\ No newline at end of file
+}
+
+/// GNU find 风格的数值比较
+///
+/// `+N` 表示大于 N，`-N` 表示小于 N，`N` 表示等于 N。
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::filter::NumericComparison;
+///
+/// let more_than_one: NumericComparison = "+1".parse().unwrap();
+/// assert!(more_than_one.matches(2));
+/// assert!(!more_than_one.matches(1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericComparison {
+    /// 大于
+    GreaterThan(u64),
+    /// 小于
+    LessThan(u64),
+    /// 等于
+    Equal(u64),
+}
+
+impl NumericComparison {
+    /// 检查数值是否满足比较条件
+    pub fn matches(self, value: u64) -> bool {
+        match self {
+            NumericComparison::GreaterThan(n) => value > n,
+            NumericComparison::LessThan(n) => value < n,
+            NumericComparison::Equal(n) => value == n,
+        }
+    }
+
+    /// 解析大小比较，数值部分可以带单位后缀（见 [`ByteSize`]）
+    ///
+    /// # 示例
+    /// ```
+    /// use rust_find::finder::filter::NumericComparison;
+    ///
+    /// let larger = NumericComparison::parse_size("+1.5k").unwrap();
+    /// assert_eq!(larger, NumericComparison::GreaterThan(1536));
+    /// ```
+    pub fn parse_size(spec: &str) -> FindResult<Self> {
+        let (make, size): (fn(u64) -> Self, &str) = match spec.as_bytes().first() {
+            Some(b'+') => (NumericComparison::GreaterThan, &spec[1..]),
+            Some(b'-') => (NumericComparison::LessThan, &spec[1..]),
+            _ => (NumericComparison::Equal, spec),
+        };
+        Ok(make(size.parse::<ByteSize>()?.bytes()))
+    }
+}
+
+impl FromStr for NumericComparison {
+    type Err = FindError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (make, digits): (fn(u64) -> Self, &str) = match spec.as_bytes().first() {
+            Some(b'+') => (NumericComparison::GreaterThan, &spec[1..]),
+            Some(b'-') => (NumericComparison::LessThan, &spec[1..]),
+            _ => (NumericComparison::Equal, spec),
+        };
+        digits.parse().map(make).map_err(|_| FindError::PatternError {
+            message: format!("Invalid numeric comparison '{}', expected N, +N or -N", spec),
+        })
+    }
+}
+
+impl std::fmt::Display for NumericComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumericComparison::GreaterThan(n) => write!(f, "more than {}", n),
+            NumericComparison::LessThan(n) => write!(f, "less than {}", n),
+            NumericComparison::Equal(n) => write!(f, "exactly {}", n),
+        }
+    }
+}
+
+/// 大小的计算方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeMeasure {
+    /// 文件大小（`ls -l` 显示的字节数）
+    #[default]
+    Apparent,
+    /// 实际分配的磁盘空间（`du` 统计的字节数）
+    OnDisk,
+}
+
+impl SizeMeasure {
+    /// 按此方式计算条目的大小
+    pub fn of(self, metadata: &std::fs::Metadata) -> u64 {
+        match self {
+            SizeMeasure::Apparent => metadata.len(),
+            SizeMeasure::OnDisk => meta::disk_usage(metadata),
+        }
+    }
+}
+
+/// 大小过滤器的条件
+#[derive(Debug, Clone, Copy)]
+enum SizeBound {
+    /// `--size` 的比较
+    Compare(NumericComparison),
+    /// 不小于（`--min-size`）
+    AtLeast(u64),
+    /// 不大于（`--max-size`）
+    AtMost(u64),
+}
+
+/// 大小过滤器
+///
+/// 按文件大小或实际占用的磁盘空间（字节）过滤。
+pub struct SizeFilter {
+    bound: SizeBound,
+    measure: SizeMeasure,
+}
+
+impl SizeFilter {
+    /// 创建新的大小过滤器
+    pub fn new(size: NumericComparison, measure: SizeMeasure) -> Self {
+        Self { bound: SizeBound::Compare(size), measure }
+    }
+
+    /// 匹配不小于指定大小的条目
+    pub fn at_least(size: ByteSize, measure: SizeMeasure) -> Self {
+        Self { bound: SizeBound::AtLeast(size.bytes()), measure }
+    }
+
+    /// 匹配不大于指定大小的条目
+    pub fn at_most(size: ByteSize, measure: SizeMeasure) -> Self {
+        Self { bound: SizeBound::AtMost(size.bytes()), measure }
+    }
+}
+
+impl FileFilter for SizeFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        // 表观大小由文件系统后端提供，占用空间只有本地文件系统中的条目才有
+        let size = match self.measure {
+            SizeMeasure::Apparent => entry.stat().map(|stat| stat.len),
+            SizeMeasure::OnDisk => entry.metadata().map(|metadata| self.measure.of(metadata)),
+        };
+        let Ok(size) = size else {
+            return false;
+        };
+        match self.bound {
+            SizeBound::Compare(comparison) => comparison.matches(size),
+            SizeBound::AtLeast(min) => size >= min,
+            SizeBound::AtMost(max) => size <= max,
+        }
+    }
+
+    fn description(&self) -> String {
+        let bound = match self.bound {
+            SizeBound::Compare(comparison) => comparison.to_string(),
+            SizeBound::AtLeast(min) => format!("at least {}", min),
+            SizeBound::AtMost(max) => format!("at most {}", max),
+        };
+        match self.measure {
+            SizeMeasure::Apparent => format!("has size {} bytes", bound),
+            SizeMeasure::OnDisk => format!("uses {} bytes on disk", bound),
+        }
+    }
+}
+
+/// GNU find `-perm` 风格的权限位匹配
+///
+/// `MODE` 要求权限位完全相同，`-MODE` 要求包含所有指定的位，`/MODE` 要求包含任一指定的位。
+/// 模式为八进制数，可以包含 setuid（4000）、setgid（2000）和 sticky（1000）位。
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::filter::PermMatch;
+///
+/// let setuid: PermMatch = "-4000".parse().unwrap();
+/// assert!(setuid.matches(0o4755));
+/// assert!(!setuid.matches(0o755));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermMatch {
+    /// 权限位完全相同
+    Exact(u32),
+    /// 包含所有指定的位
+    All(u32),
+    /// 包含任一指定的位（模式为 0 时匹配所有条目）
+    Any(u32),
+}
+
+impl PermMatch {
+    /// 检查权限位是否满足条件
+    pub fn matches(self, mode: u32) -> bool {
+        let mode = mode & 0o7777;
+        match self {
+            PermMatch::Exact(bits) => mode == bits,
+            PermMatch::All(bits) => mode & bits == bits,
+            PermMatch::Any(0) => true,
+            PermMatch::Any(bits) => mode & bits != 0,
+        }
+    }
+}
+
+impl FromStr for PermMatch {
+    type Err = FindError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (make, digits): (fn(u32) -> Self, &str) = match spec.as_bytes().first() {
+            Some(b'-') => (PermMatch::All, &spec[1..]),
+            Some(b'/') => (PermMatch::Any, &spec[1..]),
+            _ => (PermMatch::Exact, spec),
+        };
+        u32::from_str_radix(digits, 8)
+            .ok()
+            .filter(|bits| *bits <= 0o7777)
+            .map(make)
+            .ok_or_else(|| FindError::PatternError {
+                message: format!("Invalid permission mode '{}', expected octal MODE, -MODE or /MODE", spec),
+            })
+    }
+}
+
+impl std::fmt::Display for PermMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PermMatch::Exact(bits) => write!(f, "exactly {:04o}", bits),
+            PermMatch::All(bits) => write!(f, "all of {:04o}", bits),
+            PermMatch::Any(bits) => write!(f, "any of {:04o}", bits),
+        }
+    }
+}
+
+/// 权限位过滤器
+///
+/// 与 GNU find 的 `-perm` 相同，检查条目本身（不跟随符号链接时为链接本身）的权限位。
+pub struct PermFilter {
+    perm: PermMatch,
+}
+
+impl PermFilter {
+    /// 创建新的权限位过滤器
+    pub fn new(perm: PermMatch) -> Self {
+        Self { perm }
+    }
+
+    /// 匹配设置了 setuid 位的条目
+    pub fn setuid() -> Self {
+        Self::new(PermMatch::All(0o4000))
+    }
+
+    /// 匹配设置了 setgid 位的条目
+    pub fn setgid() -> Self {
+        Self::new(PermMatch::All(0o2000))
+    }
+}
+
+impl FileFilter for PermFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        entry
+            .stat()
+            .is_ok_and(|stat| stat.mode.is_some_and(|mode| self.perm.matches(mode)))
+    }
+
+    fn description(&self) -> String {
+        format!("has permission bits {}", self.perm)
+    }
+}
+
+/// 访问权限检查的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessMode {
+    /// 可读
+    Read,
+    /// 可写
+    Write,
+    /// 可执行（目录为可进入）
+    Execute,
+}
+
+impl AccessMode {
+    fn name(self) -> &'static str {
+        match self {
+            AccessMode::Read => "readable",
+            AccessMode::Write => "writable",
+            AccessMode::Execute => "executable",
+        }
+    }
+}
+
+/// 访问权限过滤器
+///
+/// 与 GNU find 的 `-readable`、`-writable`、`-executable` 相同，检查当前用户
+/// 实际能否访问条目，而不只是比较权限位：Unix 上通过 `faccessat` 按有效用户 ID
+/// 检查，ACL 和只读挂载等因素都会被考虑；符号链接检查其目标。
+/// 非 Unix 平台上根据只读属性和扩展名近似判断。
+pub struct AccessFilter {
+    mode: AccessMode,
+}
+
+impl AccessFilter {
+    /// 创建新的访问权限过滤器
+    pub fn new(mode: AccessMode) -> Self {
+        Self { mode }
+    }
+}
+
+impl FileFilter for AccessFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        has_access(entry.path(), self.mode)
+    }
+
+    fn description(&self) -> String {
+        format!("is {} by the current user", self.mode.name())
+    }
+}
+
+/// 当前用户能否以指定方式访问路径
+#[cfg(unix)]
+fn has_access(path: &Path, mode: AccessMode) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(c_path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mode = match mode {
+        AccessMode::Read => libc::R_OK,
+        AccessMode::Write => libc::W_OK,
+        AccessMode::Execute => libc::X_OK,
+    };
+    // SAFETY: c_path 是以 NUL 结尾的有效字符串，在调用期间存活
+    unsafe { libc::faccessat(libc::AT_FDCWD, c_path.as_ptr(), mode, libc::AT_EACCESS) == 0 }
+}
+
+/// 当前用户能否以指定方式访问路径
+///
+/// 没有按用户检查访问权限的廉价方式：能读取元数据即视为可读，
+/// 没有只读属性即视为可写，目录和可执行扩展名的文件视为可执行。
+#[cfg(not(unix))]
+fn has_access(path: &Path, mode: AccessMode) -> bool {
+    const EXECUTABLE_EXTENSIONS: [&str; 4] = ["exe", "com", "bat", "cmd"];
+
+    let Ok(metadata) = path.metadata() else {
+        return false;
+    };
+    match mode {
+        AccessMode::Read => true,
+        AccessMode::Write => !metadata.permissions().readonly(),
+        AccessMode::Execute => {
+            metadata.is_dir()
+                || path.extension().is_some_and(|extension| {
+                    EXECUTABLE_EXTENSIONS
+                        .iter()
+                        .any(|known| extension.eq_ignore_ascii_case(known))
+                })
+        }
+    }
+}
+
+/// 名称长度过滤器
+///
+/// 按文件名或完整路径的长度（字符数）过滤，用于在打包或复制到 Windows 之前
+/// 找出超过归档格式或路径长度限制的条目。路径长度按遍历得到的形式计算，
+/// 以绝对路径作为搜索路径时即为绝对路径的长度。
+pub struct LengthFilter {
+    length: NumericComparison,
+    /// true 时计算完整路径，否则只计算文件名
+    whole_path: bool,
+}
+
+impl LengthFilter {
+    /// 按文件名长度过滤
+    pub fn name(length: NumericComparison) -> Self {
+        Self { length, whole_path: false }
+    }
+
+    /// 按完整路径长度过滤
+    pub fn path(length: NumericComparison) -> Self {
+        Self { length, whole_path: true }
+    }
+}
+
+impl FileFilter for LengthFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        let text = if self.whole_path {
+            entry.path().as_os_str().to_string_lossy()
+        } else {
+            entry.file_name().to_string_lossy()
+        };
+        self.length.matches(text.chars().count() as u64)
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
+    fn description(&self) -> String {
+        let subject = if self.whole_path { "path" } else { "name" };
+        format!("{} has {} characters", subject, self.length)
+    }
+}
+
+/// 硬链接数过滤器
+///
+/// 按条目的硬链接数（`nlink`）过滤，与 GNU find 的 `-links` 相同。
+/// 非 Unix 平台上硬链接数总是视为 1。
+pub struct LinksFilter {
+    links: NumericComparison,
+}
+
+impl LinksFilter {
+    /// 创建新的硬链接数过滤器
+    pub fn new(links: NumericComparison) -> Self {
+        Self { links }
+    }
+}
+
+impl FileFilter for LinksFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        entry
+            .metadata()
+            .is_ok_and(|metadata| self.links.matches(meta::link_count(metadata)))
+    }
+
+    fn description(&self) -> String {
+        format!("has {} hard links", self.links)
+    }
+}
+
+/// inode 编号过滤器
+///
+/// 与 GNU find 的 `-inum` 相同。非 Unix 平台上没有 inode 编号，不匹配任何条目。
+pub struct InodeFilter {
+    inode: u64,
+}
+
+impl InodeFilter {
+    /// 创建新的 inode 编号过滤器
+    pub fn new(inode: u64) -> Self {
+        Self { inode }
+    }
+}
+
+impl FileFilter for InodeFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        cfg!(unix) && entry.metadata().is_ok_and(|metadata| meta::inode(metadata) == self.inode)
+    }
+
+    fn description(&self) -> String {
+        format!("has inode number {}", self.inode)
+    }
+}
+
+/// 同一文件过滤器
+///
+/// 匹配与参考文件是同一文件的条目（设备号和 inode 都相同），
+/// 即参考文件本身及其所有硬链接，与 GNU find 的 `-samefile` 相同。
+pub struct SameFileFilter {
+    reference: String,
+    device: u64,
+    inode: u64,
+}
+
+impl SameFileFilter {
+    /// 根据参考文件创建过滤器
+    ///
+    /// # 错误
+    /// 如果无法读取参考文件的元数据，返回 InvalidPath 错误
+    pub fn new(reference: &Path) -> FindResult<Self> {
+        let metadata = std::fs::metadata(reference)
+            .map_err(|_| FindError::InvalidPath(reference.to_path_buf()))?;
+        Ok(Self {
+            reference: reference.display().to_string(),
+            device: meta::device(&metadata),
+            inode: meta::inode(&metadata),
+        })
+    }
+}
+
+impl FileFilter for SameFileFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        cfg!(unix)
+            && entry.metadata().is_ok_and(|metadata| {
+                meta::device(metadata) == self.device && meta::inode(metadata) == self.inode
+            })
+    }
+
+    fn description(&self) -> String {
+        format!("is the same file as '{}'", self.reference)
+    }
+}
+
+/// 扩展属性过滤器
+///
+/// 匹配带有指定扩展属性（例如 `user.checksum`、`security.capability`）的条目，
+/// 可以同时要求属性值等于指定的值。不支持扩展属性的文件系统和平台上不匹配任何条目。
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::filter::XattrFilter;
+///
+/// // 匹配 user.checksum 属性为 abc123 的文件
+/// let filter = XattrFilter::new("user.checksum=abc123").unwrap();
+/// ```
+pub struct XattrFilter {
+    name: String,
+    value: Option<Vec<u8>>,
+}
+
+impl XattrFilter {
+    /// 创建新的扩展属性过滤器
+    ///
+    /// # 参数
+    /// - `spec`: `NAME=VALUE` 要求属性等于指定值，`NAME` 只要求属性存在
+    ///
+    /// # 错误
+    /// 如果属性名为空，返回PatternError错误
+    pub fn new(spec: &str) -> FindResult<Self> {
+        let (name, value) = match spec.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.as_bytes().to_vec())),
+            None => (spec.trim(), None),
+        };
+
+        if name.is_empty() {
+            return Err(FindError::PatternError {
+                message: format!("Invalid extended attribute spec '{}'", spec),
+            });
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            value,
+        })
+    }
+}
+
+impl FileFilter for XattrFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        self.try_matches(entry).unwrap_or_else(|e| {
+            debug!("Failed to read extended attribute {} of {}: {}", self.name, entry.path().display(), e);
+            false
+        })
+    }
+
+    fn try_matches(&self, entry: &FindEntry) -> FindResult<bool> {
+        // 跟随符号链接遍历时条目类型为目标的类型，读取目标的属性
+        if !entry.is_local() {
+            return Ok(false);
+        }
+        let follow = !entry.kind().is_symlink();
+        match meta::xattr(entry.path(), &self.name, follow) {
+            Ok(Some(actual)) => Ok(self.value.as_ref().is_none_or(|expected| *expected == actual)),
+            Ok(None) => Ok(false),
+            Err(e) => Err(FindError::from_io(e, entry.path())),
+        }
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Read
+    }
+
+    fn description(&self) -> String {
+        match &self.value {
+            Some(value) => format!(
+                "extended attribute '{}' equals '{}'",
+                self.name,
+                String::from_utf8_lossy(value)
+            ),
+            None => format!("has extended attribute '{}'", self.name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::TempDir;
+    
+    fn create_test_entry(name: &str) -> Result<(TempDir, FindEntry), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let file_path = temp_dir.path().join(name);
+        File::create(&file_path)?.write_all(b"test")?;
+        
+        let entry = FindEntry::from_path(&file_path, false)?;
+        
+        Ok((temp_dir, entry))
+    }
+    
+    #[test]
+    fn test_name_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let (_temp_dir, entry) = create_test_entry("test.txt")?;
+        
+        let filter = NameFilter::new("*.txt")?;
+        assert!(filter.matches(&entry));
+        
+        let filter = NameFilter::new("*.rs")?;
+        assert!(!filter.matches(&entry));
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_name_filter_case_insensitive() -> Result<(), Box<dyn std::error::Error>> {
+        let (_temp_dir, entry) = create_test_entry("Test.TXT")?;
+        
+        let filter = NameFilter::new("*.txt")?;
+        assert!(!filter.matches(&entry));
+        
+        let filter = NameFilter::new_ignore_case("*.txt")?;
+        assert!(filter.matches(&entry));
+        
+        Ok(())
+    }
+
+    #[test]
+    fn test_name_filter_case_insensitive_non_ascii() -> Result<(), Box<dyn std::error::Error>> {
+        let (_temp_dir, entry) = create_test_entry("ÄBC.txt")?;
+
+        assert!(!NameFilter::new("äbc.*")?.matches(&entry));
+        assert!(NameFilter::new_ignore_case("äbc.*")?.matches(&entry));
+        assert!(NameFilter::new_ignore_case("ÄBC.TXT")?.matches(&entry));
+        assert!(NameFilter::fixed_string("äb", true)?.matches(&entry));
+        assert!(!NameFilter::new_ignore_case("abc.*")?.matches(&entry));
+
+        Ok(())
+    }
+    
+    #[test]
+    fn test_multi_name_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let (_temp_dir, entry) = create_test_entry("test.txt")?;
+        
+        let filter = MultiNameFilter::new(&["*.txt".to_string(), "*.rs".to_string()], false)?;
+        assert!(filter.matches(&entry));
+        
+        let filter = MultiNameFilter::new(&["*.doc".to_string(), "*.rs".to_string()], false)?;
+        assert!(!filter.matches(&entry));
+
+        // 大量模式编译为一个集合
+        let mut patterns: Vec<String> = (0..100).map(|i| format!("*.ext{}", i)).collect();
+        assert!(!MultiNameFilter::new(&patterns, false)?.matches(&entry));
+        patterns.push("TEST.*".to_string());
+        assert!(!MultiNameFilter::new(&patterns, false)?.matches(&entry));
+        assert!(MultiNameFilter::new(&patterns, true)?.matches(&entry));
+
+        let all = ["test.*".to_string(), "*.txt".to_string()];
+        assert!(MultiNameFilter::new(&all, false)?.with_any_match(false).matches(&entry));
+        let all = ["test.*".to_string(), "*.rs".to_string()];
+        assert!(!MultiNameFilter::new(&all, false)?.with_any_match(false).matches(&entry));
+
+        assert!(MultiNameFilter::new(&["[".to_string()], false).is_err());
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_type_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        
+        // Create a file
+        let file_path = temp_dir.path().join("test.txt");
+        File::create(&file_path)?.write_all(b"test")?;
+        let file_entry = FindEntry::from_path(&file_path, false)?;
+        
+        // Create a directory
+        let dir_path = temp_dir.path().join("testdir");
+        std::fs::create_dir(&dir_path)?;
+        let dir_entry = FindEntry::from_path(&dir_path, false)?;
+        
+        let file_filter = TypeFilter::new("f")?;
+        assert!(file_filter.matches(&file_entry));
+        assert!(!file_filter.matches(&dir_entry));
+        
+        let dir_filter = TypeFilter::new("d")?;
+        assert!(!dir_filter.matches(&file_entry));
+        assert!(dir_filter.matches(&dir_entry));
+
+        let either = TypeFilter::new("f,d")?;
+        assert!(either.matches(&file_entry) && either.matches(&dir_entry));
+        assert_eq!(either.description(), "is a regular file or a directory");
+        assert!(TypeFilter::new("f,x").is_err());
+        assert!(TypeFilter::new("f,").is_err());
+
+        #[cfg(unix)]
+        {
+            let socket_path = temp_dir.path().join("test.sock");
+            let _listener = std::os::unix::net::UnixListener::bind(&socket_path)?;
+            let socket_entry = FindEntry::from_path(&socket_path, false)?;
+            assert!(TypeFilter::new("s")?.matches(&socket_entry));
+            assert!(!TypeFilter::new("f,p")?.matches(&socket_entry));
+
+            let null_entry = FindEntry::from_path("/dev/null", false)?;
+            assert!(TypeFilter::new("c")?.matches(&null_entry));
+            assert!(!TypeFilter::new("b")?.matches(&null_entry));
+        }
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_attr_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let (_temp_dir, entry) = create_test_entry("report.txt")?;
+        let provider: Arc<dyn MetadataProvider> = Arc::new(|path: &std::path::Path| {
+            let mut attrs = crate::finder::metadata::Attributes::new();
+            if path.ends_with("report.txt") {
+                attrs.insert("project".to_string(), "alpha".to_string());
+            }
+            Some(attrs)
+        });
+        
+        assert!(AttrFilter::new(provider.clone(), "project=alpha")?.matches(&entry));
+        assert!(AttrFilter::new(provider.clone(), "project")?.matches(&entry));
+        assert!(!AttrFilter::new(provider.clone(), "project=beta")?.matches(&entry));
+        assert!(!AttrFilter::new(provider.clone(), "owner")?.matches(&entry));
+        assert!(AttrFilter::new(provider, "=alpha").is_err());
+        
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_broken_symlink_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let target = temp_dir.path().join("target.txt");
+        File::create(&target)?;
+        std::os::unix::fs::symlink(&target, temp_dir.path().join("good"))?;
+        std::os::unix::fs::symlink(temp_dir.path().join("missing"), temp_dir.path().join("dangling"))?;
+
+        let matched: Vec<String> = walkdir::WalkDir::new(temp_dir.path())
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(FindEntry::from)
+            .filter(|entry| BrokenSymlinkFilter.matches(entry))
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(matched, vec!["dangling"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_numeric_comparison() {
+        assert_eq!("+2".parse::<NumericComparison>().unwrap(), NumericComparison::GreaterThan(2));
+        assert_eq!("-2".parse::<NumericComparison>().unwrap(), NumericComparison::LessThan(2));
+        assert_eq!("2".parse::<NumericComparison>().unwrap(), NumericComparison::Equal(2));
+        assert!("".parse::<NumericComparison>().is_err());
+        assert!("+x".parse::<NumericComparison>().is_err());
+        assert!(NumericComparison::LessThan(2).matches(1));
+        assert!(!NumericComparison::Equal(2).matches(3));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_links_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let (temp_dir, entry) = create_test_entry("linked.txt")?;
+        assert!(LinksFilter::new(NumericComparison::Equal(1)).matches(&entry));
+
+        std::fs::hard_link(entry.path(), temp_dir.path().join("second.txt"))?;
+        // 条目缓存了元数据，修改文件后重新读取
+        let entry = FindEntry::from_path(entry.path(), false)?;
+        assert!(LinksFilter::new(NumericComparison::GreaterThan(1)).matches(&entry));
+        assert!(!LinksFilter::new(NumericComparison::LessThan(2)).matches(&entry));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_inode_and_same_file_filters() -> Result<(), Box<dyn std::error::Error>> {
+        let (temp_dir, entry) = create_test_entry("original.txt")?;
+        let link = temp_dir.path().join("link.txt");
+        let other = temp_dir.path().join("other.txt");
+        std::fs::hard_link(entry.path(), &link)?;
+        File::create(&other)?;
+
+        let inode = meta::inode(entry.metadata()?);
+        assert!(InodeFilter::new(inode).matches(&entry));
+        assert!(!InodeFilter::new(inode + 1).matches(&entry));
+
+        let filter = SameFileFilter::new(&link)?;
+        let matched: Vec<String> = walkdir::WalkDir::new(temp_dir.path())
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(FindEntry::from)
+            .filter(|entry| filter.matches(entry))
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(matched, vec!["link.txt", "original.txt"]);
+
+        assert!(SameFileFilter::new(&temp_dir.path().join("missing")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_perm_match() {
+        let exact: PermMatch = "644".parse().unwrap();
+        assert!(exact.matches(0o644));
+        assert!(!exact.matches(0o4644));
+
+        let all: PermMatch = "-4000".parse().unwrap();
+        assert_eq!(all, PermMatch::All(0o4000));
+        assert!(all.matches(0o4755));
+        assert!(!all.matches(0o2755));
+
+        let any: PermMatch = "/6000".parse().unwrap();
+        assert!(any.matches(0o2755));
+        assert!(!any.matches(0o755));
+        assert!("/0".parse::<PermMatch>().unwrap().matches(0o600));
+
+        assert!("u+s".parse::<PermMatch>().is_err());
+        assert!("-9".parse::<PermMatch>().is_err());
+        assert!("17777".parse::<PermMatch>().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_perm_filter() -> Result<(), Box<dyn std::error::Error>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_temp_dir, entry) = create_test_entry("tool")?;
+        std::fs::set_permissions(entry.path(), std::fs::Permissions::from_mode(0o4755))?;
+        let entry = FindEntry::from_path(entry.path(), false)?;
+        assert!(PermFilter::setuid().matches(&entry));
+        assert!(!PermFilter::setgid().matches(&entry));
+        assert!(!PermFilter::new("755".parse()?).matches(&entry));
+        assert!(PermFilter::new("4755".parse()?).matches(&entry));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_access_filter() -> Result<(), Box<dyn std::error::Error>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_temp_dir, entry) = create_test_entry("script.sh")?;
+        std::fs::set_permissions(entry.path(), std::fs::Permissions::from_mode(0o644))?;
+        assert!(AccessFilter::new(AccessMode::Read).matches(&entry));
+        assert!(AccessFilter::new(AccessMode::Write).matches(&entry));
+        // 没有任何执行位时即使是 root 也不能执行
+        assert!(!AccessFilter::new(AccessMode::Execute).matches(&entry));
+
+        std::fs::set_permissions(entry.path(), std::fs::Permissions::from_mode(0o755))?;
+        assert!(AccessFilter::new(AccessMode::Execute).matches(&entry));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_size_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let (_temp_dir, entry) = create_test_entry("sparse.img")?;
+        // 扩展为只有开头几个字节有数据的稀疏文件，文件大小远大于占用的空间
+        let file = std::fs::OpenOptions::new().write(true).open(entry.path())?;
+        file.set_len(64 * 1024 * 1024)?;
+        let entry = FindEntry::from_path(entry.path(), false)?;
+
+        let apparent = SizeFilter::new(NumericComparison::parse_size("+1M")?, SizeMeasure::Apparent);
+        assert!(apparent.matches(&entry));
+        let on_disk = SizeFilter::new(NumericComparison::parse_size("+1M")?, SizeMeasure::OnDisk);
+        assert!(!on_disk.matches(&entry));
+        assert!(SizeFilter::new(NumericComparison::parse_size("64MiB")?, SizeMeasure::Apparent).matches(&entry));
+        assert_ne!(apparent.description(), on_disk.description());
+        assert!(SizeFilter::at_least("64M".parse()?, SizeMeasure::Apparent).matches(&entry));
+        assert!(!SizeFilter::at_most("1M".parse()?, SizeMeasure::Apparent).matches(&entry));
+        assert!(SizeFilter::at_most("1M".parse()?, SizeMeasure::OnDisk).matches(&entry));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixed_string_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let (_temp_dir, entry) = create_test_entry("report [Draft] *v2*.txt")?;
+        assert!(NameFilter::fixed_string("[Draft]", false)?.matches(&entry));
+        assert!(NameFilter::fixed_string("*v2*", false)?.matches(&entry));
+        assert!(!NameFilter::fixed_string("[draft]", false)?.matches(&entry));
+        assert!(NameFilter::fixed_string("[draft]", true)?.matches(&entry));
+        assert!(!NameFilter::fixed_string("r*t", false)?.matches(&entry));
+        assert!(NameFilter::fixed_string("", false).is_err());
+
+        let filter = MultiNameFilter::fixed_strings(&["nope".to_string(), "v2".to_string()], false)?;
+        assert!(filter.matches(&entry));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_not_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let (_temp_dir, entry) = create_test_entry("app.min.js")?;
+        assert!(!NotFilter::new(NameFilter::new("*.min.js")?).matches(&entry));
+        assert!(NotFilter::new(NameFilter::new("*.css")?).matches(&entry));
+        assert_eq!(
+            NotFilter::new(NameFilter::new("*.css")?).description(),
+            "not (name matches '*.css')"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_length_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let (_temp_dir, entry) = create_test_entry("日志-2024.txt")?;
+        assert!(LengthFilter::name("11".parse()?).matches(&entry));
+        assert!(!LengthFilter::name("+11".parse()?).matches(&entry));
+        let path_length = entry.path().to_string_lossy().chars().count() as u64;
+        assert!(LengthFilter::path(NumericComparison::Equal(path_length)).matches(&entry));
+        assert!(LengthFilter::path("+11".parse()?).matches(&entry));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_path_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let filter = PathFilter::new("src/**/tests/*.rs")?.with_roots(vec!["/work/repo".into()]);
+        assert!(filter.matches_path(Path::new("/work/repo/src/tests/a.rs")));
+        assert!(filter.matches_path(Path::new("/work/repo/src/a/b/tests/c.rs")));
+        assert!(!filter.matches_path(Path::new("/work/repo/src/tests/data/c.rs")));
+        assert!(!filter.matches_path(Path::new("/work/repo/lib/src/tests/c.rs")));
+
+        // 与 GNU find -path 相同，* 也匹配路径分隔符
+        let loose = PathFilter::new("./src/*.rs")?.with_literal_separator(false);
+        assert!(loose.matches_path(Path::new("./src/a/b.rs")));
+        let strict = PathFilter::new("./src/*.rs")?;
+        assert!(strict.matches_path(Path::new("./src/b.rs")));
+        assert!(!strict.matches_path(Path::new("./src/a/b.rs")));
+
+        assert!(PathFilter::new("src/[").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_xattr_filter() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(XattrFilter::new("=value").is_err());
+
+        let (_temp_dir, entry) = create_test_entry("tagged.txt")?;
+        assert!(!XattrFilter::new("user.rust_find_test")?.matches(&entry));
+
+        #[cfg(target_os = "linux")]
+        {
+            use std::ffi::CString;
+            use std::os::unix::ffi::OsStrExt;
+
+            let path = CString::new(entry.path().as_os_str().as_bytes())?;
+            let name = CString::new("user.rust_find_test")?;
+            let value = b"alpha";
+            // SAFETY: 参数均为有效的 NUL 结尾字符串和缓冲区
+            let code = unsafe {
+                libc::setxattr(path.as_ptr(), name.as_ptr(), value.as_ptr().cast(), value.len(), 0)
+            };
+            // 临时目录所在的文件系统可能不支持用户扩展属性
+            if code != 0 {
+                return Ok(());
+            }
+
+            assert!(XattrFilter::new("user.rust_find_test")?.matches(&entry));
+            assert!(XattrFilter::new("user.rust_find_test=alpha")?.matches(&entry));
+            assert!(!XattrFilter::new("user.rust_find_test=beta")?.matches(&entry));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+//! 从 FSEvents 的历史事件中找出发生变化的目录（macOS）
+//!
+//! FSEvents 为每个卷保存了带编号的目录级变更记录。增量扫描缓存记录扫描开始时的事件编号，
+//! 下次扫描时读取此后的历史事件：没有出现在事件中的目录可以直接使用缓存而不必检查修改时间，
+//! 出现在事件中的目录总是重新读取，因此原地修改的文件内容也能被发现。
+//!
+//! 事件历史不可用（其他平台、事件编号回绕、事件被丢弃、根目录被移动或历史已被清除）时返回 None，
+//! 调用方退回到按目录修改时间判断。
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// 自上次扫描以来发生变化的目录，路径相对于搜索根目录
+#[derive(Debug, Default)]
+pub(super) struct ChangedDirs {
+    /// 内容发生变化的目录
+    dirs: HashSet<PathBuf>,
+    /// 需要整体重新读取的子树
+    subtrees: Vec<PathBuf>,
+}
+
+impl ChangedDirs {
+    /// 从历史事件构建，事件为（绝对路径，是否需要重新读取整个子树）
+    ///
+    /// 整个根目录都需要重新读取时返回 None，此时按修改时间判断更快。
+    fn from_events(root: &Path, events: Vec<(PathBuf, bool)>) -> Option<Self> {
+        let mut changed = Self::default();
+        for (path, recursive) in events {
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            if !recursive {
+                changed.dirs.insert(relative.to_path_buf());
+            } else if relative.as_os_str().is_empty() {
+                return None;
+            } else {
+                changed.subtrees.push(relative.to_path_buf());
+            }
+        }
+        Some(changed)
+    }
+
+    /// 目录是否在上次扫描后发生了变化
+    pub(super) fn contains(&self, relative: &Path) -> bool {
+        self.dirs.contains(relative) || self.subtrees.iter().any(|subtree| relative.starts_with(subtree))
+    }
+
+    /// 发生变化的目录数和子树数
+    pub(super) fn len(&self) -> usize {
+        self.dirs.len() + self.subtrees.len()
+    }
+}
+
+/// 当前的事件编号，不支持 FSEvents 时返回 None
+pub(super) fn current_event_id() -> Option<u64> {
+    history::current_event_id()
+}
+
+/// 读取规范化的根目录下在事件编号 `since` 之后发生变化的目录
+pub(super) fn changed_dirs(root: &Path, since: u64) -> Option<ChangedDirs> {
+    history::read(root, since).and_then(|events| ChangedDirs::from_events(root, events))
+}
+
+#[cfg(target_os = "macos")]
+mod history {
+    use std::ffi::{c_char, c_void, CStr, CString, OsStr};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, Instant};
+
+    use fsevent_sys as fs;
+    use fsevent_sys::core_foundation as cf;
+    use log::debug;
+
+    /// 等待历史事件读取完成的最长时间
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// 表示历史事件不再可靠的标志
+    const UNRELIABLE: fs::FSEventStreamEventFlags = fs::kFSEventStreamEventFlagUserDropped
+        | fs::kFSEventStreamEventFlagKernelDropped
+        | fs::kFSEventStreamEventFlagEventIdsWrapped
+        | fs::kFSEventStreamEventFlagRootChanged
+        | fs::kFSEventStreamEventFlagMount
+        | fs::kFSEventStreamEventFlagUnmount;
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFRunLoopRunInMode(mode: cf::CFStringRef, seconds: cf::CFTimeInterval, return_after: cf::Boolean) -> i32;
+    }
+
+    /// 回调中收集的事件
+    #[derive(Default)]
+    struct History {
+        events: Vec<(PathBuf, bool)>,
+        reliable: bool,
+        done: bool,
+    }
+
+    extern "C" fn callback(
+        _stream: fs::FSEventStreamRef,
+        info: *mut c_void,
+        count: usize,
+        paths: *mut c_void,
+        flags: *const fs::FSEventStreamEventFlags,
+        _ids: *const fs::FSEventStreamEventId,
+    ) {
+        // SAFETY: info 指向 read 中的 History，paths 和 flags 各有 count 个元素
+        let history = unsafe { &mut *info.cast::<History>() };
+        let paths = paths.cast::<*const c_char>();
+        for i in 0..count {
+            let (flag, path) = unsafe { (*flags.add(i), CStr::from_ptr(*paths.add(i))) };
+            if flag & fs::kFSEventStreamEventFlagHistoryDone != 0 {
+                history.done = true;
+                continue;
+            }
+            if flag & UNRELIABLE != 0 {
+                history.reliable = false;
+            }
+            let path = PathBuf::from(OsStr::from_bytes(path.to_bytes()));
+            history.events.push((path, flag & fs::kFSEventStreamEventFlagMustScanSubDirs != 0));
+        }
+    }
+
+    pub(super) fn current_event_id() -> Option<u64> {
+        // SAFETY: 没有参数，总是成功
+        Some(unsafe { fs::FSEventsGetCurrentEventId() })
+    }
+
+    pub(super) fn read(root: &Path, since: u64) -> Option<Vec<(PathBuf, bool)>> {
+        let root_path = CString::new(root.as_os_str().as_bytes()).ok()?;
+        let mut history = History {
+            reliable: true,
+            ..History::default()
+        };
+        let context = fs::FSEventStreamContext {
+            version: 0,
+            info: (&mut history as *mut History).cast(),
+            retain: None,
+            release: None,
+            copy_description: None,
+        };
+
+        // SAFETY: 所有对象在本函数内创建和释放，回调只在运行循环运行期间被调用
+        unsafe {
+            let paths = cf::CFArrayCreateMutable(cf::kCFAllocatorDefault, 1, &cf::kCFTypeArrayCallBacks);
+            let path = cf::CFStringCreateWithCString(cf::kCFAllocatorDefault, root_path.as_ptr(), cf::kCFStringEncodingUTF8);
+            cf::CFArrayAppendValue(paths, path);
+            cf::CFRelease(path);
+
+            let stream = fs::FSEventStreamCreate(
+                cf::kCFAllocatorDefault,
+                callback,
+                &context,
+                paths,
+                since,
+                0.0,
+                fs::kFSEventStreamCreateFlagWatchRoot | fs::kFSEventStreamCreateFlagNoDefer,
+            );
+            cf::CFRelease(paths);
+            if stream.is_null() {
+                return None;
+            }
+
+            let run_loop = cf::CFRunLoopGetCurrent();
+            fs::FSEventStreamScheduleWithRunLoop(stream, run_loop, cf::kCFRunLoopDefaultMode);
+            if fs::FSEventStreamStart(stream) != 0 {
+                let deadline = Instant::now() + TIMEOUT;
+                while !history.done && Instant::now() < deadline {
+                    CFRunLoopRunInMode(cf::kCFRunLoopDefaultMode, 0.1, 1);
+                }
+                fs::FSEventStreamStop(stream);
+            }
+            fs::FSEventStreamInvalidate(stream);
+            fs::FSEventStreamRelease(stream);
+        }
+
+        debug!(
+            "FSEvents history of {} since {}: {} events, reliable: {}, complete: {}",
+            root.display(),
+            since,
+            history.events.len(),
+            history.reliable,
+            history.done
+        );
+        (history.reliable && history.done).then_some(history.events)
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod history {
+    use std::path::{Path, PathBuf};
+
+    pub(super) fn current_event_id() -> Option<u64> {
+        None
+    }
+
+    pub(super) fn read(_root: &Path, _since: u64) -> Option<Vec<(PathBuf, bool)>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_dirs_from_events() {
+        let root = Path::new("/home/user");
+        let events = vec![
+            (PathBuf::from("/home/user/src/"), false),
+            (PathBuf::from("/home/user/target"), true),
+            (PathBuf::from("/elsewhere"), true),
+        ];
+        let changed = ChangedDirs::from_events(root, events).unwrap();
+        assert_eq!(changed.len(), 2);
+        assert!(changed.contains(Path::new("src")));
+        assert!(!changed.contains(Path::new("src/nested")));
+        assert!(changed.contains(Path::new("target/debug/deps")));
+        assert!(!changed.contains(Path::new("")));
+
+        // 整个根目录都需要重新读取时退回到按修改时间判断
+        assert!(ChangedDirs::from_events(root, vec![(PathBuf::from("/home/user"), true)]).is_none());
+    }
+}
@@ -0,0 +1,344 @@
+//! 按 git 仓库中的信息过滤
+//!
+//! 通过运行 `git` 命令读取仓库信息（不依赖 libgit2），每个搜索路径只在创建过滤器时读取一次，
+//! 之后的匹配只是内存中的查找。路径在 git 的输出中相对于搜索路径，与条目路径去掉搜索路径后的部分比较。
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use crate::errors::{FindError, FindResult};
+use super::entry::FindEntry;
+use super::filter::{FileFilter, FilterCost};
+
+/// 在目录中运行 git 命令并返回标准输出
+fn git(dir: &Path, args: &[&str]) -> FindResult<Vec<u8>> {
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let error = |message: String| FindError::Other {
+        message,
+        context: Some(dir.display().to_string()),
+        timestamp: SystemTime::now(),
+    };
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| error(format!("无法运行 git: {}", e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(error(format!("git {} 失败: {}", args.join(" "), stderr.trim())));
+    }
+    Ok(output.stdout)
+}
+
+/// 拆分 `-z` 输出中以 NUL 结尾的路径
+fn split_paths(output: &[u8]) -> impl Iterator<Item = PathBuf> + '_ {
+    output
+        .split(|&b| b == 0)
+        .filter(|path| !path.is_empty())
+        .map(|path| {
+            #[cfg(unix)]
+            {
+                use std::os::unix::ffi::OsStrExt;
+                PathBuf::from(std::ffi::OsStr::from_bytes(path))
+            }
+            #[cfg(not(unix))]
+            {
+                PathBuf::from(String::from_utf8_lossy(path).into_owned())
+            }
+        })
+}
+
+/// 搜索路径对应的目录：目录本身，或文件所在的目录（可能为空路径，表示当前目录）
+fn base_dir(root: &Path) -> &Path {
+    if root.is_dir() {
+        root
+    } else {
+        root.parent().unwrap_or(Path::new(""))
+    }
+}
+
+/// 每个搜索路径下满足条件的路径集合，路径相对于搜索路径对应的目录
+#[derive(Debug, Default)]
+struct RootSets {
+    roots: Vec<(PathBuf, HashSet<PathBuf>)>,
+}
+
+impl RootSets {
+    /// 在每个搜索路径对应的目录中运行 git 命令并合并输出的路径，搜索路径为单个文件时只列出该文件
+    fn collect(roots: &[PathBuf], commands: &[&[&str]]) -> FindResult<Self> {
+        let mut sets = Self::default();
+        for root in roots {
+            let base = base_dir(root);
+            let name = root.file_name().map(|name| name.to_string_lossy().into_owned());
+            let mut paths = HashSet::new();
+            for command in commands {
+                let mut args = command.to_vec();
+                args.push("--");
+                if base != root.as_path() {
+                    args.push(name.as_deref().unwrap_or("."));
+                }
+                paths.extend(split_paths(&git(base, &args)?));
+            }
+            sets.roots.push((base.to_path_buf(), paths));
+        }
+        Ok(sets)
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        self.roots
+            .iter()
+            .any(|(base, paths)| path.strip_prefix(base).is_ok_and(|relative| paths.contains(relative)))
+    }
+}
+
+/// 只匹配所在 git 仓库中被跟踪的文件
+///
+/// 被跟踪的文件是已加入索引的文件（`git ls-files`，包括子模块中的文件），
+/// 构建产物和未加入索引的新文件都不匹配。目录本身不被跟踪，因此不匹配。
+pub struct GitTrackedFilter {
+    tracked: RootSets,
+}
+
+impl GitTrackedFilter {
+    /// 读取每个搜索路径下被跟踪的文件
+    ///
+    /// # 错误
+    /// 如果无法运行 git，或者搜索路径不在 git 仓库中，返回 FindError::Other
+    pub fn new(roots: &[PathBuf]) -> FindResult<Self> {
+        Ok(Self {
+            tracked: RootSets::collect(roots, &[&["ls-files", "-z", "--recurse-submodules"]])?,
+        })
+    }
+}
+
+impl FileFilter for GitTrackedFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        self.tracked.contains(entry.path())
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
+    fn description(&self) -> String {
+        "is tracked by git".to_string()
+    }
+}
+
+/// 文件在 git 工作区中的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitStatus {
+    /// 被跟踪且有未提交的修改（包括已暂存和新加入索引的文件）
+    Modified,
+    /// 未被跟踪且未被忽略
+    Untracked,
+}
+
+impl GitStatus {
+    /// 列出处于该状态的文件的 git 命令
+    fn commands(self) -> &'static [&'static [&'static str]] {
+        match self {
+            GitStatus::Modified => &[
+                &["ls-files", "-z", "--modified"],
+                &["diff", "--cached", "--name-only", "-z", "--relative"],
+            ],
+            GitStatus::Untracked => &[&["ls-files", "-z", "--others", "--exclude-standard"]],
+        }
+    }
+}
+
+/// 按 git 状态过滤，与 `git status` 的结果一致
+///
+/// 状态在创建过滤器时读取，搜索过程中的修改不会反映到结果中。
+pub struct GitStatusFilter {
+    status: GitStatus,
+    paths: RootSets,
+}
+
+impl GitStatusFilter {
+    /// 读取每个搜索路径下处于指定状态的文件
+    ///
+    /// # 错误
+    /// 如果无法运行 git，或者搜索路径不在 git 仓库中，返回 FindError::Other
+    pub fn new(roots: &[PathBuf], status: GitStatus) -> FindResult<Self> {
+        Ok(Self {
+            status,
+            paths: RootSets::collect(roots, status.commands())?,
+        })
+    }
+}
+
+impl FileFilter for GitStatusFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        self.paths.contains(entry.path())
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
+    fn description(&self) -> String {
+        match self.status {
+            GitStatus::Modified => "is modified in git".to_string(),
+            GitStatus::Untracked => "is untracked by git".to_string(),
+        }
+    }
+}
+
+/// 只匹配在指定提交与工作区之间有改动的文件，相当于 `git diff --name-only <REF>`
+///
+/// 包括已提交、已暂存和未暂存的改动，未被跟踪的文件不匹配。
+pub struct GitChangedFilter {
+    reference: String,
+    changed: RootSets,
+}
+
+impl GitChangedFilter {
+    /// 读取每个搜索路径下自 `reference` 以来改动过的文件
+    ///
+    /// # 错误
+    /// 如果引用以 `-` 开头、无法解析，或者搜索路径不在 git 仓库中，返回 FindError::Other
+    pub fn new(roots: &[PathBuf], reference: &str) -> FindResult<Self> {
+        // 防止引用被 git 当作选项解析
+        if reference.is_empty() || reference.starts_with('-') {
+            return Err(FindError::Other {
+                message: format!("无效的 git 引用: '{}'", reference),
+                context: None,
+                timestamp: SystemTime::now(),
+            });
+        }
+        Ok(Self {
+            reference: reference.to_string(),
+            changed: RootSets::collect(
+                roots,
+                &[&["diff", "--name-only", "-z", "--relative", "--no-renames", reference]],
+            )?,
+        })
+    }
+}
+
+impl FileFilter for GitChangedFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        self.changed.contains(entry.path())
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
+    fn description(&self) -> String {
+        format!("is changed in git since {}", self.reference)
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// 在临时目录中运行 git，测试需要可用的 git 命令
+    pub(in crate::finder) fn run(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_git_tracked_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let temp = TempDir::new()?;
+        let repo = temp.path();
+        run(repo, &["init", "-q"]);
+        fs::create_dir_all(repo.join("src"))?;
+        fs::create_dir_all(repo.join("target"))?;
+        fs::write(repo.join("src/main.rs"), "fn main() {}\n")?;
+        fs::write(repo.join("src/new.rs"), "")?;
+        fs::write(repo.join("target/main.o"), "")?;
+        run(repo, &["add", "src/main.rs"]);
+
+        let filter = GitTrackedFilter::new(&[repo.to_path_buf()])?;
+        assert!(filter.matches(&FindEntry::from_path(repo.join("src/main.rs"), false)?));
+        assert!(!filter.matches(&FindEntry::from_path(repo.join("src/new.rs"), false)?));
+        assert!(!filter.matches(&FindEntry::from_path(repo.join("target/main.o"), false)?));
+        assert!(!filter.matches(&FindEntry::from_path(repo.join("src"), false)?));
+
+        // 搜索路径是子目录或单个文件时路径相对于该目录
+        let filter = GitTrackedFilter::new(&[repo.join("src")])?;
+        assert!(filter.matches(&FindEntry::from_path(repo.join("src/main.rs"), false)?));
+        let filter = GitTrackedFilter::new(&[repo.join("src/main.rs")])?;
+        assert!(filter.matches(&FindEntry::from_path(repo.join("src/main.rs"), false)?));
+
+        let outside = TempDir::new()?;
+        assert!(GitTrackedFilter::new(&[outside.path().to_path_buf()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_git_status_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let temp = TempDir::new()?;
+        let repo = temp.path();
+        run(repo, &["init", "-q"]);
+        fs::write(repo.join(".gitignore"), "*.log\n")?;
+        for name in ["clean.txt", "changed.txt", "staged.txt"] {
+            fs::write(repo.join(name), "old")?;
+        }
+        run(repo, &["add", "."]);
+        run(repo, &["commit", "-qm", "init"]);
+        fs::write(repo.join("changed.txt"), "new")?;
+        fs::write(repo.join("staged.txt"), "new")?;
+        fs::write(repo.join("added.txt"), "")?;
+        run(repo, &["add", "staged.txt", "added.txt"]);
+        fs::write(repo.join("new.txt"), "")?;
+        fs::write(repo.join("debug.log"), "")?;
+
+        let matching = |status| -> Result<Vec<&str>, Box<dyn std::error::Error>> {
+            let filter = GitStatusFilter::new(&[repo.to_path_buf()], status)?;
+            let mut names = Vec::new();
+            for name in ["clean.txt", "changed.txt", "staged.txt", "added.txt", "new.txt", "debug.log"] {
+                if filter.matches(&FindEntry::from_path(repo.join(name), false)?) {
+                    names.push(name);
+                }
+            }
+            Ok(names)
+        };
+        assert_eq!(matching(GitStatus::Modified)?, ["changed.txt", "staged.txt", "added.txt"]);
+        assert_eq!(matching(GitStatus::Untracked)?, ["new.txt"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_git_changed_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let temp = TempDir::new()?;
+        let repo = temp.path();
+        run(repo, &["init", "-q"]);
+        fs::write(repo.join("old.txt"), "")?;
+        fs::write(repo.join("edited.txt"), "")?;
+        run(repo, &["add", "."]);
+        run(repo, &["commit", "-qm", "base"]);
+        run(repo, &["tag", "base"]);
+        fs::write(repo.join("committed.txt"), "")?;
+        run(repo, &["add", "committed.txt"]);
+        run(repo, &["commit", "-qm", "next"]);
+        fs::write(repo.join("edited.txt"), "changed")?;
+        fs::write(repo.join("untracked.txt"), "")?;
+
+        let filter = GitChangedFilter::new(&[repo.to_path_buf()], "base")?;
+        assert!(filter.matches(&FindEntry::from_path(repo.join("committed.txt"), false)?));
+        assert!(filter.matches(&FindEntry::from_path(repo.join("edited.txt"), false)?));
+        assert!(!filter.matches(&FindEntry::from_path(repo.join("old.txt"), false)?));
+        assert!(!filter.matches(&FindEntry::from_path(repo.join("untracked.txt"), false)?));
+        assert_eq!(filter.description(), "is changed in git since base");
+
+        assert!(GitChangedFilter::new(&[repo.to_path_buf()], "no-such-ref").is_err());
+        assert!(GitChangedFilter::new(&[repo.to_path_buf()], "--output=x").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,275 @@
+//! 遍历时使用的忽略规则
+//!
+//! 读取 `.gitignore` 和本工具专用的 `.findignore` 等 gitignore 语法的忽略文件，
+//! 在遍历过程中剪除被忽略的条目，被忽略的目录不会被进入。
+//! 规则按目录逐级叠加：越深的目录中的规则优先级越高，与 git 的行为一致。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use log::debug;
+
+/// 每个目录中的 gitignore 文件名
+const GITIGNORE_FILE: &str = ".gitignore";
+
+/// 每个目录中的本工具专用忽略文件名，不依赖 git
+const FINDIGNORE_FILE: &str = ".findignore";
+
+/// 全局忽略文件名（位于配置目录中）
+const GLOBAL_IGNORE_FILE_NAME: &str = "ignore";
+
+/// 忽略规则
+///
+/// 每次遍历使用一个实例：各目录的规则在首次需要时读取并缓存。
+/// 传入的路径必须是绝对路径。
+#[derive(Debug, Default)]
+pub struct IgnoreRules {
+    /// 每个目录中读取的忽略文件名
+    dir_files: Vec<&'static str>,
+    /// 是否读取 `.git/info/exclude`
+    git_exclude: bool,
+    /// 全局规则，优先级最低
+    global: Vec<Arc<Gitignore>>,
+    /// 读取上级目录规则的最高目录，通常为所在 git 仓库的根目录
+    scope: Option<PathBuf>,
+    /// 每个目录生效的规则栈（从低优先级到高优先级）
+    stacks: HashMap<PathBuf, Arc<Vec<Arc<Gitignore>>>>,
+}
+
+impl IgnoreRules {
+    /// 创建不忽略任何条目的规则
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 启用 gitignore 规则
+    ///
+    /// 读取每个目录中的 `.gitignore`、仓库的 `.git/info/exclude`
+    /// 以及全局 gitignore 文件（`core.excludesFile` 或 `$XDG_CONFIG_HOME/git/ignore`）。
+    pub fn with_gitignore(mut self) -> Self {
+        self.dir_files.push(GITIGNORE_FILE);
+        self.git_exclude = true;
+
+        let (global, error) = Gitignore::global();
+        if let Some(e) = error {
+            debug!("Failed to read global gitignore: {}", e);
+        }
+        if !global.is_empty() {
+            self.global.push(Arc::new(global));
+        }
+        self
+    }
+
+    /// 启用 `.findignore` 规则
+    ///
+    /// 读取每个目录中的 `.findignore`，以及可选的全局忽略文件。
+    /// `.findignore` 的优先级高于同一目录中的 `.gitignore`。
+    ///
+    /// # 参数
+    /// - `global_file`: 全局忽略文件，不存在时忽略
+    pub fn with_findignore(mut self, global_file: Option<&Path>) -> Self {
+        self.dir_files.push(FINDIGNORE_FILE);
+
+        if let Some(file) = global_file.filter(|file| file.is_file()) {
+            let root = std::env::current_dir().unwrap_or_default();
+            let mut builder = GitignoreBuilder::new(root);
+            if let Some(e) = builder.add(file) {
+                debug!("Invalid ignore file {}: {}", file.display(), e);
+            }
+            match builder.build() {
+                Ok(rules) if !rules.is_empty() => self.global.push(Arc::new(rules)),
+                Ok(_) => {}
+                Err(e) => debug!("Invalid ignore file {}: {}", file.display(), e),
+            }
+        }
+        self
+    }
+
+    /// 是否没有启用任何规则
+    pub fn is_empty(&self) -> bool {
+        self.dir_files.is_empty() && self.global.is_empty()
+    }
+
+    /// 设置遍历根目录
+    ///
+    /// 如果根目录位于 git 仓库中，仓库根目录到遍历根目录之间的忽略文件也会生效，
+    /// 与在子目录中运行 git 命令的行为一致。
+    pub fn for_root(mut self, root: &Path) -> Self {
+        let repo_root = if self.git_exclude {
+            root.ancestors().find(|dir| dir.join(".git").exists())
+        } else {
+            None
+        };
+        self.scope = Some(repo_root.unwrap_or(root).to_path_buf());
+        self
+    }
+
+    /// 检查条目是否被忽略
+    ///
+    /// # 参数
+    /// - `path`: 条目的绝对路径
+    /// - `is_dir`: 条目是否为目录
+    pub fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        // git 仓库的内部目录从不属于搜索内容
+        if self.git_exclude && is_dir && path.file_name().is_some_and(|name| name == ".git") {
+            return true;
+        }
+        let Some(parent) = path.parent() else {
+            return false;
+        };
+
+        let stack = self.stack(parent);
+        for rules in stack.iter().rev() {
+            match rules.matched(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+
+    /// 获取目录中生效的规则栈
+    fn stack(&mut self, dir: &Path) -> Arc<Vec<Arc<Gitignore>>> {
+        if let Some(stack) = self.stacks.get(dir) {
+            return stack.clone();
+        }
+
+        let scope = self.scope.clone();
+        let in_scope = |path: &Path| scope.as_deref().is_none_or(|scope| path.starts_with(scope));
+        let mut stack = match dir.parent() {
+            Some(parent) if in_scope(parent) => (*self.stack(parent)).clone(),
+            _ => self.global.clone(),
+        };
+        if in_scope(dir) {
+            stack.extend(self.load_dir(dir));
+        }
+
+        let stack = Arc::new(stack);
+        self.stacks.insert(dir.to_path_buf(), stack.clone());
+        stack
+    }
+
+    /// 读取单个目录中的忽略文件，`.git/info/exclude` 的优先级低于目录中的文件
+    fn load_dir(&self, dir: &Path) -> Vec<Arc<Gitignore>> {
+        let mut files = Vec::new();
+        if self.git_exclude {
+            files.push(dir.join(".git").join("info").join("exclude"));
+        }
+        files.extend(self.dir_files.iter().map(|name| dir.join(name)));
+
+        files
+            .into_iter()
+            .filter(|file| file.is_file())
+            .filter_map(|file| {
+                let mut builder = GitignoreBuilder::new(dir);
+                if let Some(e) = builder.add(&file) {
+                    debug!("Invalid ignore file {}: {}", file.display(), e);
+                }
+                builder.build().ok().filter(|rules| !rules.is_empty())
+            })
+            .map(Arc::new)
+            .collect()
+    }
+}
+
+/// 默认的全局忽略文件位置
+///
+/// Unix 上为 `$XDG_CONFIG_HOME/rust-find/ignore` 或 `~/.config/rust-find/ignore`，
+/// Windows 上为 `%APPDATA%\rust-find\ignore`。
+pub fn default_global_ignore_file() -> Option<PathBuf> {
+    let config_dir = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+
+    config_dir.map(|dir| dir.join("rust-find").join(GLOBAL_IGNORE_FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_gitignore_rules_nest() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root = dir.path().canonicalize()?;
+        fs::create_dir_all(root.join(".git/info"))?;
+        fs::create_dir_all(root.join("sub"))?;
+        fs::write(root.join(".gitignore"), "*.log\ntarget/\n")?;
+        fs::write(root.join(".git/info/exclude"), "secret.txt\n")?;
+        fs::write(root.join("sub/.gitignore"), "!keep.log\n")?;
+
+        let mut rules = IgnoreRules::new().with_gitignore().for_root(&root);
+        assert!(rules.is_ignored(&root.join("a.log"), false));
+        assert!(rules.is_ignored(&root.join("target"), true));
+        assert!(!rules.is_ignored(&root.join("target"), false));
+        assert!(rules.is_ignored(&root.join("secret.txt"), false));
+        assert!(rules.is_ignored(&root.join("sub/b.log"), false));
+        assert!(!rules.is_ignored(&root.join("sub/keep.log"), false));
+        assert!(!rules.is_ignored(&root.join("main.rs"), false));
+        assert!(rules.is_ignored(&root.join(".git"), true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_gitignore_applies_from_repo_root() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let repo = dir.path().canonicalize()?;
+        fs::create_dir_all(repo.join(".git"))?;
+        fs::create_dir_all(repo.join("src/nested"))?;
+        fs::write(repo.join(".gitignore"), "*.tmp\n")?;
+
+        // 从仓库的子目录开始遍历时，仓库根目录的规则仍然生效
+        let src = repo.join("src");
+        let mut rules = IgnoreRules::new().with_gitignore().for_root(&src);
+        assert!(rules.is_ignored(&src.join("nested/x.tmp"), false));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_findignore_without_git() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root = dir.path().canonicalize()?;
+        fs::create_dir_all(root.join("sub"))?;
+        fs::write(root.join(".findignore"), "*.bak\n")?;
+        fs::write(root.join(".gitignore"), "*.txt\n")?;
+        let global = root.join("global-ignore");
+        fs::write(&global, "*.cache\n")?;
+
+        let mut rules = IgnoreRules::new().with_findignore(Some(&global)).for_root(&root);
+        assert!(rules.is_ignored(&root.join("sub/old.bak"), false));
+        assert!(rules.is_ignored(&root.join("sub/x.cache"), false));
+        // 只启用 .findignore 时不读取 .gitignore
+        assert!(!rules.is_ignored(&root.join("notes.txt"), false));
+
+        // .findignore 可以重新包含被 .gitignore 忽略的条目
+        fs::write(root.join(".findignore"), "!keep.txt\n")?;
+        let mut rules = IgnoreRules::new().with_gitignore().with_findignore(None).for_root(&root);
+        assert!(rules.is_ignored(&root.join("notes.txt"), false));
+        assert!(!rules.is_ignored(&root.join("keep.txt"), false));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_rules_ignore_nothing() {
+        let mut rules = IgnoreRules::new();
+        assert!(rules.is_empty());
+        assert!(!rules.is_ignored(Path::new("/tmp/a.log"), false));
+    }
+}
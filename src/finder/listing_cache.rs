@@ -0,0 +1,503 @@
+//! 内存中的目录列表缓存
+//!
+//! 与 [`ScanCache`](super::ScanCache) 不同，缓存的是目录的原始内容（名称和类型）而不是匹配结果，
+//! 因此同一份缓存可以用于任意搜索条件。常驻进程（`rust-find daemon`）在多次搜索之间保留缓存，
+//! 修改时间没有变化的目录不再读取，只按名称和类型过滤的搜索完全在内存中完成。
+//!
+//! 使用缓存的遍历不跟随符号链接（搜索根目录除外）。目录的修改时间只在其中的条目被创建、
+//! 删除或重命名时改变，依赖文件元数据和内容的过滤器在每次搜索时仍读取文件本身，结果不会过时。
+//!
+//! 默认每次使用缓存前检查目录的修改时间。通过 [`ListingCache::watch`] 订阅文件系统事件的目录树中，
+//! 缓存的目录由事件保持最新，搜索时不再访问文件系统；以规范化路径搜索时才能识别这些目录。
+//!
+//! 在已经定期运行 updatedb 的系统上，可以用 [`ListingCache::import_locate`] 从 mlocate 或 plocate
+//! 数据库导入目录列表，省去第一次完整读取。
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{self, FileType};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
+
+use log::{debug, info};
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::errors::{FindError, FindResult};
+
+use super::content::ContentStage;
+use super::entry::FindEntry;
+use super::filter::FileFilter;
+use super::prune::{self, Pruner};
+use super::Finder;
+
+/// 线程安全的目录列表缓存，以目录路径为键
+#[derive(Debug, Default)]
+pub struct ListingCache {
+    dirs: Mutex<HashMap<PathBuf, Arc<Listing>>>,
+    /// 由文件系统事件保持最新的目录树（规范化路径）
+    watched: RwLock<Vec<PathBuf>>,
+    /// 每次因事件使缓存失效时递增，读取目录期间发生变化的结果不写入缓存
+    generation: AtomicU64,
+}
+
+/// 目录树的文件系统事件订阅，被丢弃时停止订阅
+///
+/// 由 [`ListingCache::watch`] 创建。
+pub struct ListingWatcher {
+    _watcher: RecommendedWatcher,
+    cache: Arc<ListingCache>,
+    root: PathBuf,
+}
+
+impl std::fmt::Debug for ListingWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ListingWatcher").field("root", &self.root).finish()
+    }
+}
+
+impl Drop for ListingWatcher {
+    fn drop(&mut self) {
+        let mut watched = self.cache.watched.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(index) = watched.iter().position(|root| *root == self.root) {
+            watched.remove(index);
+        }
+    }
+}
+
+/// 单个目录的内容
+#[derive(Debug)]
+struct Listing {
+    /// 读取时目录的修改时间
+    mtime: SystemTime,
+    /// 目录中的条目名称和类型（不跟随符号链接），按名称排序
+    entries: Vec<(OsString, FileType)>,
+}
+
+impl ListingCache {
+    /// 创建空缓存
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 缓存的目录数
+    pub fn len(&self) -> usize {
+        self.dirs.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// 缓存是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 清空缓存
+    pub fn clear(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.dirs.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// 订阅目录树的文件系统事件，并预先读取其中所有目录的内容
+    ///
+    /// 订阅期间该目录树中缓存的目录由事件保持最新，搜索时不再检查修改时间。
+    /// 事件队列溢出时清空整个缓存。
+    ///
+    /// # 错误
+    /// 如果目录不存在或无法订阅文件系统事件（例如超出 inotify 的监视数量上限），返回错误
+    pub fn watch(self: &Arc<Self>, root: &Path) -> FindResult<ListingWatcher> {
+        let root = root.canonicalize().map_err(|e| FindError::from_io(e, root))?;
+        let cache = Arc::downgrade(self);
+        let watcher = notify::recommended_watcher(move |event| {
+            if let Some(cache) = cache.upgrade() {
+                cache.handle_event(event);
+            }
+        })
+        .and_then(|mut watcher| watcher.watch(&root, RecursiveMode::Recursive).map(|()| watcher))
+        .map_err(|e| FindError::Other {
+            message: format!("无法监视文件系统事件: {}", e),
+            context: Some(root.display().to_string()),
+            timestamp: SystemTime::now(),
+        })?;
+        // 订阅之后再读取，读取期间的变化不会被遗漏
+        self.watched.write().unwrap_or_else(|e| e.into_inner()).push(root.clone());
+        let dirs = self.fill(&root);
+        info!("Watching {}: {} directories cached", root.display(), dirs);
+
+        Ok(ListingWatcher {
+            _watcher: watcher,
+            cache: self.clone(),
+            root,
+        })
+    }
+
+    /// 从 updatedb 生成的 mlocate 或 plocate 数据库导入目录列表，返回导入的目录数
+    ///
+    /// 只导入建库之后没有变化的目录：mlocate 数据库记录了每个目录的时间，与目录当前的时间比较；
+    /// plocate 数据库没有这一信息，目录的时间早于数据库文件的修改时间即视为没有变化，
+    /// 因此在 updatedb 运行期间发生变化的目录可能以旧的内容导入，直到它下一次变化。
+    /// 已经缓存的目录保持不变。
+    ///
+    /// 数据库只区分目录和其他条目，导入的非目录条目（包括符号链接）都按普通文件处理，
+    /// plocate 数据库中的空目录同样如此；目录被重新读取后恢复准确的类型。
+    ///
+    /// # 错误
+    /// 如果无法读取数据库文件或其格式无效，返回错误
+    #[cfg(unix)]
+    pub fn import_locate(&self, database: &Path) -> FindResult<usize> {
+        use std::os::unix::fs::MetadataExt;
+
+        // 状态改变时间和修改时间中较晚者，与 mlocate 记录的一致
+        let changed = |metadata: &fs::Metadata| {
+            (metadata.ctime(), metadata.ctime_nsec()).max((metadata.mtime(), metadata.mtime_nsec()))
+        };
+
+        let data = fs::read(database).map_err(|e| FindError::from_io(e, database))?;
+        let dirs = super::locate_db::read(&data).map_err(|e| FindError::from_io(e, database))?;
+        let metadata = fs::metadata(database).map_err(|e| FindError::from_io(e, database))?;
+        let built = (metadata.mtime(), metadata.mtime_nsec());
+        let file_type = metadata.file_type();
+
+        let generation = self.generation.load(Ordering::SeqCst);
+        let mut imported = Vec::new();
+        for dir in dirs {
+            let Ok(metadata) = fs::symlink_metadata(&dir.path) else {
+                continue;
+            };
+            let fresh = match dir.time {
+                Some(time) => changed(&metadata) == time,
+                None => changed(&metadata) < built,
+            };
+            let Ok(mtime) = metadata.modified() else {
+                continue;
+            };
+            if !metadata.is_dir() || !fresh {
+                continue;
+            }
+            let mut entries: Vec<_> = dir
+                .entries
+                .into_iter()
+                .map(|(name, is_dir)| (name, if is_dir { metadata.file_type() } else { file_type }))
+                .collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            imported.push((dir.path, Arc::new(Listing { mtime, entries })));
+        }
+
+        let mut dirs = self.dirs.lock().unwrap_or_else(|e| e.into_inner());
+        // 导入期间有目录失效时，无法确定哪些导入的内容已经过时
+        if self.generation.load(Ordering::SeqCst) != generation {
+            return Ok(0);
+        }
+        let mut count = 0;
+        for (path, listing) in imported {
+            if let std::collections::hash_map::Entry::Vacant(entry) = dirs.entry(path) {
+                entry.insert(listing);
+                count += 1;
+            }
+        }
+        info!("Imported {} directories from {}", count, database.display());
+        Ok(count)
+    }
+
+    /// 读取目录树中所有目录的内容（不跟随符号链接），返回目录数
+    fn fill(&self, root: &Path) -> usize {
+        let mut stack = vec![root.to_path_buf()];
+        let mut count = 0;
+        while let Some(dir) = stack.pop() {
+            let Some((listing, _)) = self.listing(&dir) else {
+                continue;
+            };
+            count += 1;
+            stack.extend(
+                listing
+                    .entries
+                    .iter()
+                    .filter(|(_, file_type)| file_type.is_dir())
+                    .map(|(name, _)| dir.join(name)),
+            );
+        }
+        count
+    }
+
+    /// 根据文件系统事件使受影响的目录失效
+    fn handle_event(&self, event: notify::Result<Event>) {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                debug!("Watch error: {}", e);
+                self.clear();
+                return;
+            }
+        };
+        if event.need_rescan() {
+            debug!("Watch queue overflowed, clearing listing cache");
+            self.clear();
+            return;
+        }
+        // 只有条目的创建、删除和重命名会改变目录的内容
+        if matches!(event.kind, EventKind::Access(_) | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Metadata(_))) {
+            return;
+        }
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        let mut dirs = self.dirs.lock().unwrap_or_else(|e| e.into_inner());
+        for path in &event.paths {
+            dirs.remove(path);
+            if let Some(parent) = path.parent() {
+                dirs.remove(parent);
+            }
+        }
+    }
+
+    /// 目录是否在订阅了文件系统事件的目录树中
+    fn is_watched(&self, dir: &Path) -> bool {
+        let watched = self.watched.read().unwrap_or_else(|e| e.into_inner());
+        watched.iter().any(|root| dir.starts_with(root))
+    }
+
+    /// 目录的内容，没有缓存或修改时间变化时重新读取
+    ///
+    /// 订阅了文件系统事件的目录直接使用缓存，不检查修改时间。
+    /// 返回的布尔值表示是否使用了缓存。无法读取的目录返回 None；无法读取修改时间的目录不被缓存。
+    fn listing(&self, dir: &Path) -> Option<(Arc<Listing>, bool)> {
+        let generation = self.generation.load(Ordering::SeqCst);
+        if self.is_watched(dir) {
+            let dirs = self.dirs.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(listing) = dirs.get(dir) {
+                return Some((listing.clone(), true));
+            }
+        }
+        let mtime = fs::metadata(dir).and_then(|metadata| metadata.modified()).ok();
+        if let Some(mtime) = mtime {
+            let dirs = self.dirs.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(listing) = dirs.get(dir).filter(|listing| listing.mtime == mtime) {
+                return Some((listing.clone(), true));
+            }
+        }
+
+        let mut entries = fs::read_dir(dir)
+            .ok()?
+            .filter_map(Result::ok)
+            .filter_map(|entry| Some((entry.file_name(), entry.file_type().ok()?)))
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let listing = Arc::new(Listing {
+            mtime: mtime.unwrap_or(SystemTime::UNIX_EPOCH),
+            entries,
+        });
+        let mut dirs = self.dirs.lock().unwrap_or_else(|e| e.into_inner());
+        match mtime {
+            // 读取期间有目录失效时，读到的内容可能已经过时，不写入缓存
+            Some(_) if self.generation.load(Ordering::SeqCst) == generation => {
+                dirs.insert(dir.to_path_buf(), listing.clone())
+            }
+            _ => dirs.remove(dir),
+        };
+        Some((listing, false))
+    }
+}
+
+impl Finder {
+    /// 使用内存中的目录列表缓存查找文件
+    ///
+    /// 按文件名排序在当前线程中进行深度优先遍历，目录中的匹配项排在其子目录中的匹配项之前。
+    /// 修改时间与缓存一致的目录不再读取。
+    /// 同一份缓存可以被多个线程中的多次搜索共享，搜索条件不同也不会使缓存失效。
+    ///
+    /// # 示例
+    /// ```no_run
+    /// use rust_find::finder::{Finder, FindOptions, ListingCache};
+    /// use rust_find::finder::filter::NameFilter;
+    /// use std::path::PathBuf;
+    ///
+    /// let cache = ListingCache::new();
+    /// let finder = Finder::new(FindOptions::new());
+    /// let first = finder.find_listed(PathBuf::from("/usr"), NameFilter::new("*.h").unwrap(), &cache);
+    /// // 第二次搜索只读取发生变化的目录
+    /// let second = finder.find_listed(PathBuf::from("/usr"), NameFilter::new("*.so").unwrap(), &cache);
+    /// ```
+    pub fn find_listed<F>(&self, root: PathBuf, filter: F, cache: &ListingCache) -> Vec<PathBuf>
+    where
+        F: FileFilter,
+    {
+        let mut results = Vec::new();
+        let follow_root = self.options.symlink_policy.follows_root_links();
+        let root_entry = match FindEntry::from_path(&root, follow_root) {
+            Ok(entry) => entry,
+            Err(e) => {
+                debug!("Cannot read search root {}: {}", root.display(), e);
+                return results;
+            }
+        };
+        if self.accepts_listed(&root_entry, &root, &filter) {
+            results.push(root.clone());
+        }
+
+        let limit = self.options.max_results.unwrap_or(usize::MAX);
+        let mut pruner = Pruner::new(&self.options, &root);
+        let mut stack = Vec::new();
+        if root_entry.kind().is_dir() && self.within_depth(0) {
+            stack.push((root.clone(), 0));
+        }
+
+        let (mut reused, mut read) = (0, 0);
+        while let Some((dir, depth)) = stack.pop() {
+            if results.len() >= limit || self.options.cancel_token.is_cancelled() {
+                break;
+            }
+            let Some((listing, cached)) = cache.listing(&dir) else {
+                debug!("Cannot read directory {}", dir.display());
+                continue;
+            };
+            if cached {
+                reused += 1;
+            } else {
+                read += 1;
+            }
+
+            let mut subdirs = Vec::new();
+            for (name, file_type) in &listing.entries {
+                let path = dir.join(name);
+                if !pruner.keeps_listed(&path, file_type.is_dir()) {
+                    continue;
+                }
+                let entry = FindEntry::listed(path, *file_type, depth + 1);
+                if self.accepts_listed(&entry, &root, &filter) {
+                    results.push(entry.path().to_path_buf());
+                }
+                if file_type.is_dir() {
+                    subdirs.push(entry.into_path());
+                }
+            }
+            if self.within_depth(depth + 1) {
+                stack.extend(subdirs.into_iter().rev().map(|subdir| (subdir, depth + 1)));
+            }
+        }
+        debug!("Listed search of {}: {} directories reused, {} read", root.display(), reused, read);
+
+        results.truncate(limit);
+        if self.options.contents_first {
+            super::sort_contents_first(&mut results);
+        }
+        results
+    }
+
+    /// 检查缓存中的条目是否应出现在结果中
+    fn accepts_listed<F: FileFilter>(&self, entry: &FindEntry, root: &Path, filter: &F) -> bool {
+        !prune::is_excluded_path(&self.options, root, entry.path())
+            && !prune::is_hidden(&self.options, entry)
+            && self.matches_all(entry, filter)
+            && ContentStage::matches(&self.content_filters, entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finder::filter::NameFilter;
+    use crate::finder::FindOptions;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_listed_reuses_unchanged_dirs() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        fs::create_dir_all(root.join("a/b"))?;
+        fs::write(root.join("a/one.rs"), "")?;
+        fs::write(root.join("a/b/two.rs"), "")?;
+        fs::write(root.join("a/b/.hidden.rs"), "")?;
+        fs::write(root.join("notes.txt"), "")?;
+
+        let cache = ListingCache::new();
+        let finder = Finder::new(FindOptions::new());
+        let rust = finder.find_listed(root.clone(), NameFilter::new("*.rs")?, &cache);
+        assert_eq!(rust, [root.join("a/one.rs"), root.join("a/b/two.rs")]);
+        assert_eq!(cache.len(), 3);
+
+        // 其他搜索条件使用同一份缓存
+        let text = finder.find_listed(root.clone(), NameFilter::new("*.txt")?, &cache);
+        assert_eq!(text, [root.join("notes.txt")]);
+
+        // 目录内容变化后重新读取该目录
+        fs::remove_file(root.join("a/one.rs"))?;
+        fs::write(root.join("a/three.rs"), "")?;
+        let rust = finder.find_listed(root.clone(), NameFilter::new("*.rs")?, &cache);
+        assert_eq!(rust, [root.join("a/three.rs"), root.join("a/b/two.rs")]);
+
+        let shallow = Finder::new(FindOptions::new().with_max_depth(Some(2)))
+            .find_listed(root.clone(), NameFilter::new("*.rs")?, &cache);
+        assert_eq!(shallow, [root.join("a/three.rs")]);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_watched_listings_follow_events() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root = dir.path().canonicalize()?.join("tree");
+        fs::create_dir_all(root.join("src"))?;
+        fs::write(root.join("src/lib.rs"), "")?;
+
+        let cache = Arc::new(ListingCache::new());
+        let watcher = cache.watch(&root)?;
+        assert_eq!(cache.len(), 2);
+        assert!(cache.is_watched(&root.join("src")));
+
+        // 新文件的事件使所在目录失效，下一次搜索重新读取
+        fs::write(root.join("src/main.rs"), "")?;
+        let finder = Finder::new(FindOptions::new());
+        let expected = [root.join("src/lib.rs"), root.join("src/main.rs")];
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while finder.find_listed(root.clone(), NameFilter::new("*.rs")?, &cache) != expected {
+            assert!(std::time::Instant::now() < deadline, "event was not delivered");
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+
+        drop(watcher);
+        assert!(!cache.is_watched(&root));
+        Ok(())
+    }
+    #[cfg(unix)]
+    #[test]
+    fn test_import_locate_uses_unchanged_dirs() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::finder::locate_db::{tests::mlocate, LocateDir};
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        fs::create_dir_all(root.join("sub"))?;
+        fs::write(root.join("sub/real.rs"), "")?;
+        let time = |path: &Path| -> std::io::Result<(i64, i64)> {
+            let metadata = fs::symlink_metadata(path)?;
+            Ok((metadata.ctime(), metadata.ctime_nsec()).max((metadata.mtime(), metadata.mtime_nsec())))
+        };
+
+        // 数据库中的根目录与磁盘一致（多出的条目证明使用了导入的内容），子目录的时间已经过时
+        let entry = |name: &str, is_dir| (OsString::from(name), is_dir);
+        let dirs = [
+            LocateDir {
+                path: root.clone(),
+                time: Some(time(&root)?),
+                entries: vec![entry("sub", true), entry("ghost.rs", false)],
+            },
+            LocateDir {
+                path: root.join("sub"),
+                time: Some((0, 0)),
+                entries: vec![entry("stale.rs", false)],
+            },
+        ];
+        let database = dir.path().join("mlocate.db");
+        fs::write(&database, mlocate(&dirs))?;
+
+        let cache = ListingCache::new();
+        assert_eq!(cache.import_locate(&database)?, 1);
+        let finder = Finder::new(FindOptions::new());
+        let found = finder.find_listed(root.clone(), NameFilter::new("*.rs")?, &cache);
+        assert_eq!(found, [root.join("ghost.rs"), root.join("sub/real.rs")]);
+
+        assert!(cache.import_locate(&root.join("sub/real.rs")).is_err());
+        Ok(())
+    }
+}
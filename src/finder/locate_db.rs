@@ -0,0 +1,284 @@
+//! 读取 updatedb 生成的 mlocate 和 plocate 数据库
+//!
+//! mlocate 数据库按目录记录其中的条目、条目是否为目录以及建库时目录的修改时间；
+//! plocate 数据库只包含以 zstd 压缩的完整路径，目录由路径之间的父子关系推断，不含修改时间。
+//! 两种格式的说明分别见 mlocate.db(5) 和 plocate 源代码中的 db.h。
+
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::io::{self, Read};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use ruzstd::decoding::{Dictionary, FrameDecoder, StreamingDecoder};
+
+const MLOCATE_MAGIC: &[u8] = b"\0mlocate";
+const PLOCATE_MAGIC: &[u8] = b"\0plocate";
+
+/// 数据库中的一个目录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct LocateDir {
+    pub path: PathBuf,
+    /// 建库时目录的状态改变时间和修改时间中较晚者（秒，纳秒），plocate 数据库中没有
+    pub time: Option<(i64, i64)>,
+    /// 条目名称以及是否为目录
+    pub entries: Vec<(OsString, bool)>,
+}
+
+/// 读取数据库中的所有目录
+pub(super) fn read(data: &[u8]) -> io::Result<Vec<LocateDir>> {
+    if data.starts_with(MLOCATE_MAGIC) {
+        read_mlocate(data)
+    } else if data.starts_with(PLOCATE_MAGIC) {
+        read_plocate(data)
+    } else {
+        Err(invalid("不是 mlocate 或 plocate 数据库"))
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// 按顺序读取数据库内容
+struct Cursor<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, length: usize) -> io::Result<&'a [u8]> {
+        if self.data.len() < length {
+            return Err(invalid("数据库被截断"));
+        }
+        let (head, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32_be(&mut self) -> io::Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64_be(&mut self) -> io::Result<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// 以 NUL 结尾的字符串，不含 NUL
+    fn c_str(&mut self) -> io::Result<&'a [u8]> {
+        let length = self
+            .data
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| invalid("数据库被截断"))?;
+        let value = self.take(length)?;
+        self.take(1)?;
+        Ok(value)
+    }
+}
+
+/// mlocate 格式：文件头、配置块，之后是各个目录及其条目，整数均为大端序
+fn read_mlocate(data: &[u8]) -> io::Result<Vec<LocateDir>> {
+    let mut cursor = Cursor { data };
+    cursor.take(MLOCATE_MAGIC.len())?;
+    let config_size = cursor.u32_be()? as usize;
+    let version = cursor.u8()?;
+    if version != 0 {
+        return Err(invalid(&format!("不支持的 mlocate 数据库版本 {}", version)));
+    }
+    // 可见性标志和填充
+    cursor.take(3)?;
+    cursor.c_str()?;
+    cursor.take(config_size)?;
+
+    let mut dirs = Vec::new();
+    while !cursor.data.is_empty() {
+        let seconds = cursor.u64_be()? as i64;
+        let nanoseconds = i64::from(cursor.u32_be()?);
+        cursor.take(4)?;
+        let path = PathBuf::from(OsStr::from_bytes(cursor.c_str()?));
+        let mut entries = Vec::new();
+        loop {
+            let is_dir = match cursor.u8()? {
+                0 => false,
+                1 => true,
+                2 => break,
+                other => return Err(invalid(&format!("无效的条目类型 {}", other))),
+            };
+            entries.push((OsStr::from_bytes(cursor.c_str()?).to_os_string(), is_dir));
+        }
+        dirs.push(LocateDir {
+            path,
+            time: Some((seconds, nanoseconds)),
+            entries,
+        });
+    }
+    Ok(dirs)
+}
+
+/// plocate 文件头中用到的字段（小端序）的偏移量
+const PLOCATE_VERSION: usize = 8;
+const PLOCATE_NUM_DOCIDS: usize = 20;
+const PLOCATE_FILENAME_INDEX: usize = 32;
+const PLOCATE_DICTIONARY_LENGTH: usize = 44;
+const PLOCATE_DICTIONARY_OFFSET: usize = 48;
+
+/// plocate 格式：文件名按块以 zstd 压缩（可能使用共享字典），文件名索引给出各块的起止偏移量
+fn read_plocate(data: &[u8]) -> io::Result<Vec<LocateDir>> {
+    let field = |offset: usize, length: usize| {
+        data.get(offset..offset + length).ok_or_else(|| invalid("数据库被截断"))
+    };
+    let u32_at = |offset| field(offset, 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize);
+    let u64_at = |offset| field(offset, 8).map(|b| u64::from_le_bytes(b.try_into().unwrap()) as usize);
+
+    let version = u32_at(PLOCATE_VERSION)?;
+    if version > 1 {
+        return Err(invalid(&format!("不支持的 plocate 数据库版本 {}", version)));
+    }
+    let blocks = u32_at(PLOCATE_NUM_DOCIDS)?;
+    let index = u64_at(PLOCATE_FILENAME_INDEX)?;
+
+    let mut decoder = FrameDecoder::new();
+    if version >= 1 {
+        let length = u32_at(PLOCATE_DICTIONARY_LENGTH)?;
+        if length > 0 {
+            let dictionary = field(u64_at(PLOCATE_DICTIONARY_OFFSET)?, length)?;
+            let dictionary =
+                Dictionary::decode_dict(dictionary).map_err(|e| invalid(&format!("无效的 zstd 字典: {}", e)))?;
+            decoder
+                .add_dict(dictionary)
+                .map_err(|e| invalid(&format!("无效的 zstd 字典: {}", e)))?;
+        }
+    }
+
+    // 以目录为键收集条目，之后标记出作为其他路径父目录的条目
+    let mut dirs: BTreeMap<PathBuf, Vec<(OsString, bool)>> = BTreeMap::new();
+    let mut block = Vec::new();
+    for i in 0..blocks {
+        let start = u64_at(index + i * 8)?;
+        let end = u64_at(index + (i + 1) * 8)?;
+        let compressed = data.get(start..end).ok_or_else(|| invalid("数据库被截断"))?;
+        block.clear();
+        StreamingDecoder::new_with_decoder(compressed, &mut decoder)
+            .map_err(|e| invalid(&format!("无法解压文件名: {}", e)))?
+            .read_to_end(&mut block)?;
+
+        for path in block.split(|&b| b == 0).filter(|path| !path.is_empty()) {
+            let path = Path::new(OsStr::from_bytes(path));
+            if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+                dirs.entry(parent.to_path_buf()).or_default().push((name.to_os_string(), false));
+            }
+        }
+    }
+
+    let parents: Vec<PathBuf> = dirs.keys().cloned().collect();
+    for dir in parents {
+        if let (Some(parent), Some(name)) = (dir.parent(), dir.file_name()) {
+            if let Some(entries) = dirs.get_mut(parent) {
+                for entry in entries.iter_mut().filter(|(entry, _)| entry == name) {
+                    entry.1 = true;
+                }
+            }
+        }
+    }
+    Ok(dirs
+        .into_iter()
+        .map(|(path, entries)| LocateDir { path, time: None, entries })
+        .collect())
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use ruzstd::encoding::{compress_to_vec, CompressionLevel};
+
+    /// 按 mlocate 格式编码目录
+    pub(in crate::finder) fn mlocate(dirs: &[LocateDir]) -> Vec<u8> {
+        let mut data = MLOCATE_MAGIC.to_vec();
+        data.extend(0u32.to_be_bytes());
+        data.extend([0, 0, 0, 0]);
+        data.extend(b"/\0");
+        for dir in dirs {
+            let (seconds, nanoseconds) = dir.time.unwrap_or_default();
+            data.extend((seconds as u64).to_be_bytes());
+            data.extend((nanoseconds as u32).to_be_bytes());
+            data.extend([0; 4]);
+            data.extend(dir.path.as_os_str().as_bytes());
+            data.push(0);
+            for (name, is_dir) in &dir.entries {
+                data.push(u8::from(*is_dir));
+                data.extend(name.as_bytes());
+                data.push(0);
+            }
+            data.push(2);
+        }
+        data
+    }
+
+    /// 按 plocate 格式（版本 1，不使用字典）编码路径，每块两个文件名
+    fn plocate(paths: &[&str]) -> Vec<u8> {
+        let blocks: Vec<Vec<u8>> = paths
+            .chunks(2)
+            .map(|chunk| {
+                let names: Vec<u8> = chunk.iter().flat_map(|path| path.bytes().chain([0])).collect();
+                compress_to_vec(names.as_slice(), CompressionLevel::Fastest)
+            })
+            .collect();
+        let mut data = vec![0; 112];
+        data[..8].copy_from_slice(PLOCATE_MAGIC);
+        data[PLOCATE_VERSION..PLOCATE_VERSION + 4].copy_from_slice(&1u32.to_le_bytes());
+        data[PLOCATE_NUM_DOCIDS..PLOCATE_NUM_DOCIDS + 4].copy_from_slice(&(blocks.len() as u32).to_le_bytes());
+        let index = data.len();
+        data[PLOCATE_FILENAME_INDEX..PLOCATE_FILENAME_INDEX + 8].copy_from_slice(&(index as u64).to_le_bytes());
+
+        let mut offset = index + (blocks.len() + 1) * 8;
+        for block in &blocks {
+            data.extend((offset as u64).to_le_bytes());
+            offset += block.len();
+        }
+        data.extend((offset as u64).to_le_bytes());
+        for block in blocks {
+            data.extend(block);
+        }
+        data
+    }
+
+    fn dir(path: &str, time: Option<(i64, i64)>, entries: &[(&str, bool)]) -> LocateDir {
+        LocateDir {
+            path: PathBuf::from(path),
+            time,
+            entries: entries.iter().map(|&(name, is_dir)| (OsString::from(name), is_dir)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_read_mlocate() -> io::Result<()> {
+        let dirs = vec![
+            dir("/srv", Some((1_700_000_000, 5)), &[("data", true), ("notes.txt", false)]),
+            dir("/srv/data", Some((1_700_000_100, 0)), &[]),
+        ];
+        assert_eq!(read(&mlocate(&dirs))?, dirs);
+
+        let truncated = mlocate(&dirs);
+        assert_eq!(read(&truncated[..truncated.len() - 3]).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(read(b"not a database").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_plocate() -> io::Result<()> {
+        let data = plocate(&["/srv", "/srv/data", "/srv/data/a.bin", "/srv/notes.txt", "/srv/empty"]);
+        assert_eq!(
+            read(&data)?,
+            [
+                dir("/", None, &[("srv", true)]),
+                dir("/srv", None, &[("data", true), ("notes.txt", false), ("empty", false)]),
+                dir("/srv/data", None, &[("a.bin", false)]),
+            ]
+        );
+        Ok(())
+    }
+}
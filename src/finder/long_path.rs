@@ -0,0 +1,77 @@
+//! Windows 扩展长度路径（`\\?\`）
+//!
+//! 标准库打开文件、读取元数据和枚举目录时会自动把超过 MAX_PATH 的路径转换为扩展长度形式，
+//! 直接调用 Win32 接口（`FindFirstFileExW`、`GetVolumePathNameW` 等）时不会，
+//! 这些调用点通过 [`to_extended_length`] 得到传给接口的路径。
+
+/// 不需要转换的路径长度上限，与标准库相同（目录路径还要为 8.3 文件名留出 12 个字符）
+#[cfg(any(windows, test))]
+const LEGACY_MAX_PATH: usize = 248;
+
+/// 以 NUL 结尾、可以直接传给 Win32 接口的 UTF-16 路径，超过 MAX_PATH 时转换为扩展长度形式
+///
+/// 扩展长度路径不经过系统的规范化，因此先转换为绝对路径（处理 `.`、`..` 并把 `/` 换为 `\`）。
+#[cfg(windows)]
+pub(crate) fn to_extended_length(path: &std::path::Path) -> std::io::Result<Vec<u16>> {
+    use std::os::windows::ffi::OsStrExt;
+
+    let absolute = std::path::absolute(path)?;
+    let wide: Vec<u16> = absolute.as_os_str().encode_wide().collect();
+    let mut converted = extended_length(&wide);
+    converted.push(0);
+    Ok(converted)
+}
+
+/// 为过长的绝对路径加上扩展长度前缀
+///
+/// `C:\dir` 转换为 `\\?\C:\dir`，`\\server\share\dir` 转换为 `\\?\UNC\server\share\dir`；
+/// 较短的路径、已经是 `\\?\` 或 `\\.\` 形式的路径以及相对路径保持不变。
+#[cfg(any(windows, test))]
+fn extended_length(path: &[u16]) -> Vec<u16> {
+    let starts_with = |prefix: &str| {
+        let prefix: Vec<u16> = prefix.encode_utf16().collect();
+        path.starts_with(&prefix)
+    };
+    let prefixed = |prefix: &str, rest: &[u16]| prefix.encode_utf16().chain(rest.iter().copied()).collect();
+
+    if path.len() < LEGACY_MAX_PATH || starts_with(r"\\?\") || starts_with(r"\\.\") {
+        return path.to_vec();
+    }
+    if starts_with(r"\\") {
+        return prefixed(r"\\?\UNC\", &path[2..]);
+    }
+    let is_drive = matches!(path, [letter, colon, separator, ..]
+        if *colon == u16::from(b':')
+            && *separator == u16::from(b'\\')
+            && u8::try_from(*letter).is_ok_and(|letter| letter.is_ascii_alphabetic()));
+    if is_drive {
+        return prefixed(r"\\?\", path);
+    }
+    path.to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(path: &str) -> String {
+        let wide: Vec<u16> = path.encode_utf16().collect();
+        String::from_utf16(&extended_length(&wide)).unwrap()
+    }
+
+    #[test]
+    fn test_extended_length() {
+        let long = "d".repeat(300);
+        assert_eq!(convert(&format!(r"C:\{}", long)), format!(r"\\?\C:\{}", long));
+        assert_eq!(
+            convert(&format!(r"\\server\share\{}", long)),
+            format!(r"\\?\UNC\server\share\{}", long)
+        );
+        // 已经是扩展长度或设备路径
+        assert_eq!(convert(&format!(r"\\?\C:\{}", long)), format!(r"\\?\C:\{}", long));
+        assert_eq!(convert(&format!(r"\\.\pipe\{}", long)), format!(r"\\.\pipe\{}", long));
+        // 较短的路径和相对路径
+        assert_eq!(convert(r"C:\dir"), r"C:\dir");
+        assert_eq!(convert(&long), long);
+    }
+}
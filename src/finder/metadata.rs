@@ -0,0 +1,110 @@
+//! 自定义元数据提供者
+//!
+//! 允许库的使用者为路径提供额外的键值属性（例如来自数据库或对象存储的旁路文件），
+//! 并通过 [`AttrFilter`](super::filter::AttrFilter) 按这些属性过滤结果。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 路径的键值属性
+pub type Attributes = HashMap<String, String>;
+
+/// 元数据提供者trait
+///
+/// 为路径提供额外的键值属性。闭包 `Fn(&Path) -> Option<Attributes>` 自动实现此trait。
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::metadata::{Attributes, MetadataProvider};
+/// use std::path::Path;
+///
+/// let provider = |path: &Path| -> Option<Attributes> {
+///     let mut attrs = Attributes::new();
+///     if path.extension()? == "rs" {
+///         attrs.insert("lang".to_string(), "rust".to_string());
+///     }
+///     Some(attrs)
+/// };
+/// assert_eq!(provider.attributes(Path::new("main.rs")).unwrap()["lang"], "rust");
+/// ```
+pub trait MetadataProvider: Send + Sync {
+    /// 获取路径的属性，没有属性时返回 None
+    fn attributes(&self, path: &Path) -> Option<Attributes>;
+}
+
+impl<F> MetadataProvider for F
+where
+    F: Fn(&Path) -> Option<Attributes> + Send + Sync,
+{
+    fn attributes(&self, path: &Path) -> Option<Attributes> {
+        self(path)
+    }
+}
+
+/// 从旁路文件读取属性的提供者
+///
+/// 对于路径 `data.csv`，从 `data.csv.<扩展名>` 中读取属性，
+/// 文件中每行一个 `key=value`，空行和以 `#` 开头的行会被忽略。
+#[derive(Debug, Clone)]
+pub struct SidecarProvider {
+    extension: String,
+}
+
+impl SidecarProvider {
+    /// 创建使用指定扩展名的旁路文件提供者
+    pub fn new(extension: &str) -> Self {
+        Self {
+            extension: extension.trim_start_matches('.').to_string(),
+        }
+    }
+
+    /// 获取路径对应的旁路文件路径
+    fn sidecar_path(&self, path: &Path) -> PathBuf {
+        let mut sidecar = path.as_os_str().to_os_string();
+        sidecar.push(".");
+        sidecar.push(&self.extension);
+        PathBuf::from(sidecar)
+    }
+}
+
+impl Default for SidecarProvider {
+    fn default() -> Self {
+        Self::new("meta")
+    }
+}
+
+impl MetadataProvider for SidecarProvider {
+    fn attributes(&self, path: &Path) -> Option<Attributes> {
+        let content = std::fs::read_to_string(self.sidecar_path(path)).ok()?;
+        let attrs = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect();
+        Some(attrs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_sidecar_provider() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let file = dir.path().join("data.csv");
+        std::fs::write(&file, "a,b")?;
+        std::fs::write(dir.path().join("data.csv.meta"), "# comment\nproject = alpha\n\nowner=ops\n")?;
+
+        let provider = SidecarProvider::default();
+        let attrs = provider.attributes(&file).expect("sidecar exists");
+        assert_eq!(attrs["project"], "alpha");
+        assert_eq!(attrs["owner"], "ops");
+        assert!(provider.attributes(&dir.path().join("other.csv")).is_none());
+
+        Ok(())
+    }
+}
@@ -4,26 +4,171 @@
 //! 包括自适应线程池管理和高效的文件过滤机制。
 
 mod thread_pool;
+mod cancel;
+mod filesystem;
+mod fsevents;
+mod ignore_files;
+mod pagination;
+mod parallel;
+mod prune;
+mod scan_cache;
+mod listing_cache;
+mod long_path;
+mod native_fs;
+#[cfg(unix)]
+mod locate_db;
+mod sink;
+mod usn;
+mod warm_start;
+mod watch;
 pub mod options;
 pub mod filter;
+pub mod archive;
+pub mod content;
+pub mod entry;
+pub mod explain;
+pub mod git;
+pub mod metadata;
+pub mod query;
+pub mod s3;
+pub mod security;
+pub mod sftp;
+pub mod size;
+pub mod stats;
 
-use std::path::PathBuf;
-use std::sync::Arc;
-use walkdir::WalkDir;
-use rayon::prelude::*;
-use log::{debug, info};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use walkdir::{DirEntry, WalkDir};
+use log::{debug, info, warn};
+
+use crate::errors::FindError;
 
 pub use thread_pool::{AdaptiveThreadPool, ThreadPoolConfig};
-pub use self::options::FindOptions;
+pub use cancel::CancellationToken;
+pub use ignore_files::{default_global_ignore_file, IgnoreRules};
+pub use pagination::{ContinuationToken, SearchPage};
+pub use scan_cache::ScanCache;
+pub use listing_cache::{ListingCache, ListingWatcher};
+pub use sink::ResultSink;
+pub use filesystem::{FileKind, FileStat, FileSystem, MemoryFileSystem, OsFileSystem};
+pub use native_fs::native_filesystem;
+#[cfg(windows)]
+pub use native_fs::FindFirstFileSystem;
+#[cfg(all(target_os = "linux", feature = "getdents"))]
+pub use native_fs::GetdentsFileSystem;
+pub use usn::UsnChanges;
+pub use warm_start::{default_stats_file, PoolStats, RootStats};
+pub use self::options::{ErrorFormat, ErrorPolicies, ErrorPolicy, FindOptions, HiddenAttributes, SymlinkPolicy};
+pub use self::entry::FindEntry;
+pub use self::explain::{ExplainStep, Explanation, Verdict};
 pub use self::filter::FileFilter;
+pub use self::stats::{ErrorCategory, ErrorCounts, SearchReport, SearchStats};
+use self::content::ContentStage;
+use self::prune::Pruner;
+
+/// 单次遍历中观察到的数量，用于记录预热统计、限制结果数和搜索统计
+#[derive(Debug, Default)]
+struct WalkCounters {
+    dirs: AtomicUsize,
+    entries: AtomicUsize,
+    /// 已占用的结果名额（只在限制结果数时计数，可能超过上限）
+    matches: AtomicUsize,
+    /// 实际交给调用方的结果数
+    delivered: AtomicUsize,
+    hidden: AtomicUsize,
+    ignored: AtomicUsize,
+    matched_bytes: AtomicU64,
+    /// 遍历错误很少出现，加锁记录
+    errors: Mutex<ErrorCounts>,
+    /// 遇到处理策略为 Fail 的错误，搜索已中止
+    aborted: AtomicBool,
+    /// 收集的遍历错误，只在需要返回错误时启用
+    collected: Option<Mutex<Vec<FindError>>>,
+}
+
+impl WalkCounters {
+    /// 为一个匹配项占用结果名额，超出上限时返回 false
+    fn claim_match(&self, limit: Option<usize>) -> bool {
+        match limit {
+            Some(limit) => self.matches.fetch_add(1, Ordering::Relaxed) < limit,
+            None => true,
+        }
+    }
+
+    /// 收集遍历错误的计数器
+    fn collecting_errors() -> Self {
+        Self { collected: Some(Mutex::new(Vec::new())), ..Self::default() }
+    }
+
+    /// 取出收集的遍历错误
+    fn take_collected(&self) -> Vec<FindError> {
+        self.collected
+            .as_ref()
+            .map(|errors| std::mem::take(&mut *errors.lock().unwrap_or_else(|e| e.into_inner())))
+            .unwrap_or_default()
+    }
+
+    /// 搜索是否因错误中止
+    fn aborted(&self) -> bool {
+        self.aborted.load(Ordering::Relaxed)
+    }
+
+    /// 是否已达到结果数上限
+    fn limit_reached(&self, limit: Option<usize>) -> bool {
+        limit.is_some_and(|limit| self.matches.load(Ordering::Relaxed) >= limit)
+    }
+
+    /// 记录一个遍历错误
+    fn record_error(&self, category: ErrorCategory) {
+        self.errors.lock().unwrap_or_else(|e| e.into_inner()).add(category);
+    }
+
+    /// 转换为搜索统计
+    fn to_stats(&self, elapsed: Duration) -> SearchStats {
+        SearchStats {
+            dirs: self.dirs.load(Ordering::Relaxed),
+            entries: self.entries.load(Ordering::Relaxed),
+            matches: self.delivered.load(Ordering::Relaxed),
+            hidden: self.hidden.load(Ordering::Relaxed),
+            ignored: self.ignored.load(Ordering::Relaxed),
+            errors: *self.errors.lock().unwrap_or_else(|e| e.into_inner()),
+            matched_bytes: self.matched_bytes.load(Ordering::Relaxed),
+            elapsed,
+        }
+    }
+}
 
 /// 文件查找器
 /// 
 /// 提供高性能的文件系统遍历和过滤功能，使用自适应线程池进行并行处理。
-#[derive(Debug)]
 pub struct Finder {
     options: FindOptions,
     thread_pool: Arc<AdaptiveThreadPool>,
+    filters: Vec<Box<dyn FileFilter + Send + Sync>>,
+    content_filters: Vec<Box<dyn FileFilter + Send + Sync>>,
+    /// 此查找器所有搜索的累计统计
+    stats: Mutex<SearchStats>,
+    /// 使搜索中止的第一个错误
+    failure: Mutex<Option<FindError>>,
+    /// 并行遍历读取目录使用的后端，None 时通过 walkdir 读取
+    filesystem: Option<Arc<dyn FileSystem>>,
+}
+
+impl std::fmt::Debug for Finder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let filters: Vec<String> = self.filters.iter().map(|filter| filter.description()).collect();
+        let content_filters: Vec<String> =
+            self.content_filters.iter().map(|filter| filter.description()).collect();
+        f.debug_struct("Finder")
+            .field("options", &self.options)
+            .field("thread_pool", &self.thread_pool)
+            .field("filters", &filters)
+            .field("content_filters", &content_filters)
+            .field("filesystem", &self.filesystem.is_some())
+            .finish()
+    }
 }
 
 impl Finder {
@@ -39,18 +184,167 @@ impl Finder {
         Self {
             thread_pool: Arc::new(AdaptiveThreadPool::new(thread_pool_config)),
             options,
+            filters: Vec::new(),
+            content_filters: Vec::new(),
+            stats: Mutex::new(SearchStats::default()),
+            failure: Mutex::new(None),
+            filesystem: None,
         }
     }
 
+    /// 并行遍历时通过指定的后端读取目录，例如 [`native_filesystem`]
+    ///
+    /// 用于 [`find`](Self::find)、[`find_parallel_roots`](Self::find_parallel_roots)、
+    /// [`count_roots`](Self::count_roots) 和 [`find_into`](Self::find_into) 等并行搜索，
+    /// 每个目录仍作为单独的任务在线程池中读取。后端中的符号链接不被跟随；
+    /// .gitignore 等忽略文件从本地磁盘读取，因此只对本地后端有效。
+    pub fn with_filesystem(mut self, filesystem: Arc<dyn FileSystem>) -> Self {
+        self.filesystem = Some(filesystem);
+        self
+    }
+
     /// 添加过滤器
-    pub fn with_filter<F>(self, _filter: F) -> Self
+    ///
+    /// 添加的过滤器会与查找时传入的过滤器一起使用，条目必须匹配所有过滤器。
+    /// 启用 [`FindOptions::with_reorder_filters`] 时（默认），过滤器按
+    /// [`FileFilter::cost`] 从低到高检查，而不是按添加的顺序。
+    pub fn with_filter<F>(mut self, filter: F) -> Self
     where
         F: FileFilter + Send + Sync + 'static,
     {
-        // 这里可以存储过滤器以备后用
+        self.filters.push(Box::new(filter));
+        self.sort_filters();
+        self
+    }
+
+    /// 添加一组过滤器
+    pub fn with_filters(mut self, filters: Vec<Box<dyn FileFilter + Send + Sync>>) -> Self {
+        self.filters.extend(filters);
+        self.sort_filters();
+        self
+    }
+
+    /// 添加内容过滤器
+    ///
+    /// 用于需要读取文件内容的过滤器（例如 [`ContentFilter`](content::ContentFilter)
+    /// 和 [`MimeFilter`](content::MimeFilter)）。内容过滤器在其他过滤器之后、
+    /// 在独立的工作线程中执行，只有通过了其他所有过滤器的文件才会被读取。
+    pub fn with_content_filter<F>(mut self, filter: F) -> Self
+    where
+        F: FileFilter + Send + Sync + 'static,
+    {
+        self.content_filters.push(Box::new(filter));
+        self.sort_filters();
+        self
+    }
+
+    /// 添加一组内容过滤器
+    pub fn with_content_filters(mut self, filters: Vec<Box<dyn FileFilter + Send + Sync>>) -> Self {
+        self.content_filters.extend(filters);
+        self.sort_filters();
         self
     }
 
+    /// 按代价排列已添加的过滤器，代价相同的保持添加的顺序
+    fn sort_filters(&mut self) {
+        if self.options.reorder_filters {
+            self.filters.sort_by_key(|filter| filter.cost());
+            self.content_filters.sort_by_key(|filter| filter.cost());
+        }
+    }
+
+    /// 按检查顺序排列已添加的过滤器和传入的过滤器
+    ///
+    /// 传入的过滤器排在代价不高于它的已添加过滤器之后；不重新排列时排在最后。
+    fn ordered_filters<'a, F: FileFilter>(&'a self, filter: &'a F) -> impl Iterator<Item = &'a dyn FileFilter> {
+        let split = if self.options.reorder_filters {
+            self.filters.partition_point(|added| added.cost() <= filter.cost())
+        } else {
+            self.filters.len()
+        };
+        let (before, after) = self.filters.split_at(split);
+        before
+            .iter()
+            .map(|added| added.as_ref() as &dyn FileFilter)
+            .chain(std::iter::once(filter as &dyn FileFilter))
+            .chain(after.iter().map(|added| added.as_ref() as &dyn FileFilter))
+    }
+
+    /// 检查条目是否匹配所有已添加的过滤器和传入的过滤器
+    fn matches_all<F: FileFilter>(&self, entry: &FindEntry, filter: &F) -> bool {
+        self.ordered_filters(filter).all(|filter| filter.matches(entry))
+    }
+
+    /// 检查条目是否匹配所有已添加的过滤器和传入的过滤器
+    ///
+    /// 过滤器无法完成检查时（例如读取文件失败）按错误类别的处理策略报告错误，条目视为不匹配。
+    fn check_filters<F: FileFilter>(&self, entry: &FindEntry, filter: &F, counters: &WalkCounters) -> bool {
+        self.check_chain(entry, self.ordered_filters(filter), counters)
+    }
+
+    /// 检查条目是否匹配所有内容过滤器，出错时的处理与 [`check_filters`](Self::check_filters) 相同
+    fn check_content_filters(&self, entry: &FindEntry, counters: &WalkCounters) -> bool {
+        let filters = self.content_filters.iter().map(|filter| filter.as_ref() as &dyn FileFilter);
+        self.check_chain(entry, filters, counters)
+    }
+
+    /// 依次检查过滤器直到条目被拒绝，启用解释时输出每个被检查的过滤器的判断
+    fn check_chain<'a>(
+        &self,
+        entry: &FindEntry,
+        filters: impl Iterator<Item = &'a dyn FileFilter>,
+        counters: &WalkCounters,
+    ) -> bool {
+        let mut explanation = self.options.explain.then(|| Explanation::new(entry.path()));
+        let mut matched = true;
+        for filter in filters {
+            let result = filter.try_matches(entry);
+            if let Some(explanation) = explanation.as_mut() {
+                explanation.record(filter, &result);
+            }
+            match result {
+                Ok(true) => {}
+                Ok(false) => matched = false,
+                Err(e) => {
+                    self.report_filter_error(counters, e);
+                    matched = false;
+                }
+            }
+            if !matched {
+                break;
+            }
+        }
+        if let Some(explanation) = explanation.filter(|explanation| !explanation.steps.is_empty()) {
+            eprintln!("{}", explanation);
+        }
+        matched
+    }
+
+    /// 解释条目是否匹配已添加的过滤器、内容过滤器和传入的过滤器
+    ///
+    /// 与搜索时不同，条目被某个过滤器拒绝后仍会检查剩余的过滤器，以便一次看到所有判断。
+    /// 过滤器按搜索时的顺序排列，内容过滤器在最后。不检查隐藏文件和排除规则。
+    ///
+    /// # 示例
+    /// ```no_run
+    /// use rust_find::finder::{FindEntry, Finder, FindOptions};
+    /// use rust_find::finder::filter::{NameFilter, TypeFilter};
+    ///
+    /// let finder = Finder::new(FindOptions::new()).with_filter(TypeFilter::new("f").unwrap());
+    /// let entry = FindEntry::from_path("src/main.rs", false).unwrap();
+    /// let explanation = finder.explain(&entry, &NameFilter::new("*.rs").unwrap());
+    /// assert!(explanation.matched());
+    /// println!("{}", explanation);
+    /// ```
+    pub fn explain<F: FileFilter>(&self, entry: &FindEntry, filter: &F) -> Explanation {
+        let mut explanation = Explanation::new(entry.path());
+        let content_filters = self.content_filters.iter().map(|filter| filter.as_ref() as &dyn FileFilter);
+        for filter in self.ordered_filters(filter).chain(content_filters) {
+            explanation.record(filter, &filter.try_matches(entry));
+        }
+        explanation
+    }
+
     /// 在指定目录中查找符合条件的文件
     pub fn find<F>(&self, root: PathBuf, filter: F) -> Vec<PathBuf>
     where
@@ -59,49 +353,486 @@ impl Finder {
         self.find_parallel(root, filter)
     }
 
+    /// 列出单个目录中符合条件的条目（不递归）
+    ///
+    /// 结果与 `max_depth` 为 1 时的 [`find`](Self::find) 相同，但跳过了目录预统计、
+    /// 线程池调整和并行调度，直接在当前线程中过滤一次目录列表。
+    /// 适用于只需要查看一个目录的常见场景。
+    pub fn list<F>(&self, dir: PathBuf, filter: F) -> Vec<PathBuf>
+    where
+        F: FileFilter,
+    {
+        let cancel = &self.options.cancel_token;
+        let counters = WalkCounters::default();
+        let start = Instant::now();
+        let results = self
+            .walk(&dir, 1)
+            .take_while(|_| !cancel.is_cancelled() && !counters.aborted())
+            .filter_map(|result| result.map_err(|e| self.report_error(&counters, e)).ok())
+            .map(FindEntry::from)
+            .inspect(|entry| {
+                counters.entries.fetch_add(1, Ordering::Relaxed);
+                if entry.kind().is_dir() {
+                    counters.dirs.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+            .filter(|entry| !self.skips_hidden(entry, &counters))
+            .filter(|entry| self.check_filters(entry, &filter, &counters))
+            .filter(|entry| self.check_content_filters(entry, &counters))
+            .take(self.options.max_results.unwrap_or(usize::MAX))
+            .map(|entry| self.deliver(&counters, entry))
+            .collect();
+        self.record_stats(&counters, start.elapsed());
+        results
+    }
+
+    /// 此查找器到目前为止所有搜索的累计统计
+    ///
+    /// 包括 [`find`](Self::find)、[`find_parallel`](Self::find_parallel)、
+    /// [`find_parallel_roots`](Self::find_parallel_roots)、[`count_roots`](Self::count_roots)、
+    /// [`find_with_report`](Self::find_with_report)、[`find_into`](Self::find_into)、[`list`](Self::list) 和监视开始时的搜索，增量扫描和分页搜索不计入。
+    ///
+    /// # 示例
+    /// ```no_run
+    /// use rust_find::finder::{Finder, FindOptions};
+    /// use rust_find::finder::filter::NameFilter;
+    /// use std::path::PathBuf;
+    ///
+    /// let finder = Finder::new(FindOptions::new().with_measure_matches(true));
+    /// let results = finder.find(PathBuf::from("."), NameFilter::new("*.rs").unwrap());
+    /// let stats = finder.stats();
+    /// assert_eq!(stats.matches, results.len());
+    /// eprintln!("{}", stats);
+    /// ```
+    pub fn stats(&self) -> SearchStats {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 将一次搜索的计数累加到统计中
+    fn record_stats(&self, counters: &WalkCounters, elapsed: Duration) {
+        self.stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .merge(&counters.to_stats(elapsed));
+    }
+
+    /// 取出使搜索中止的错误
+    ///
+    /// 处理策略为 [`ErrorPolicy::Fail`] 的错误会中止搜索，搜索返回中止前找到的结果，
+    /// 第一个这样的错误保存在查找器中，直到被取出。
+    pub fn take_error(&self) -> Option<FindError> {
+        self.failure.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+
+    /// 记录一个遍历错误，并按该类错误的处理策略报告或中止搜索
+    fn report_error(&self, counters: &WalkCounters, error: walkdir::Error) {
+        let category = ErrorCategory::of(&error);
+        let errno = error.io_error().and_then(|e| e.raw_os_error());
+        self.report(counters, category, stats::find_error(category, &error), errno);
+    }
+
+    /// 记录一个过滤器返回的错误，处理方式与遍历错误相同
+    fn report_filter_error(&self, counters: &WalkCounters, error: FindError) {
+        let category = ErrorCategory::of_find_error(&error);
+        let errno = error.raw_os_error();
+        self.report(counters, category, error, errno);
+    }
+
+    /// 按错误类别的处理策略报告错误或中止搜索
+    ///
+    /// 文本格式下中止搜索的错误不写入日志，由调用方通过 [`take_error`](Self::take_error) 报告。
+    fn report(&self, counters: &WalkCounters, category: ErrorCategory, error: FindError, errno: Option<i32>) {
+        counters.record_error(category);
+        let policy = self.options.error_policies.get(category);
+        if policy == ErrorPolicy::Silent {
+            debug!("Ignored error: {}", error);
+        } else if self.options.error_format == ErrorFormat::Json {
+            eprintln!("{}", stats::error_json(category, &error, errno));
+        } else if policy == ErrorPolicy::Warn {
+            warn!("{}", stats::error_message(category, &error));
+        }
+
+        if policy == ErrorPolicy::Fail {
+            counters.aborted.store(true, Ordering::Relaxed);
+            let mut failure = self.failure.lock().unwrap_or_else(|e| e.into_inner());
+            if failure.is_none() {
+                *failure = Some(error.clone());
+            }
+        }
+        if let Some(collected) = &counters.collected {
+            collected.lock().unwrap_or_else(|e| e.into_inner()).push(error);
+        }
+    }
+
+    /// 条目是否作为隐藏文件被跳过，跳过时计数
+    fn skips_hidden(&self, entry: &FindEntry, counters: &WalkCounters) -> bool {
+        let hidden = prune::is_hidden(&self.options, entry);
+        if hidden {
+            counters.hidden.fetch_add(1, Ordering::Relaxed);
+        }
+        hidden
+    }
+
+    /// 统计一个通过所有过滤器的结果，返回其路径
+    fn deliver(&self, counters: &WalkCounters, entry: FindEntry) -> PathBuf {
+        counters.delivered.fetch_add(1, Ordering::Relaxed);
+        if self.options.measure_matches {
+            if let Some(metadata) = entry.metadata().ok().filter(|metadata| metadata.is_file()) {
+                counters.matched_bytes.fetch_add(metadata.len(), Ordering::Relaxed);
+            }
+        }
+        entry.into_path()
+    }
+
+    /// 获取此查找器使用的取消令牌
+    ///
+    /// 在其他线程中调用令牌的 `cancel()` 可以中止正在进行的搜索，
+    /// 搜索会返回取消前已找到的结果。
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.options.cancel_token.clone()
+    }
+
     /// 并行查找文件
     pub fn find_parallel<F>(&self, root: PathBuf, filter: F) -> Vec<PathBuf>
     where
         F: FileFilter + Send + Sync,
     {
-        // 首先统计目录数量以优化线程池大小
-        let dir_count = self.count_directories(&root);
-        self.thread_pool.update_directory_count(dir_count);
-        
-        info!("Starting search in {} with {} directories", root.display(), dir_count);
-        debug!("Adjusting thread pool size...");
-        
-        let thread_count = self.thread_pool.adjust_thread_count();
-        info!("Using {} threads for search", thread_count);
+        self.collect_results(root, &filter, &WalkCounters::default())
+    }
 
-        // 创建文件遍历器
-        let walker = WalkDir::new(root)
-            .follow_links(self.options.follow_links)
-            .max_depth(self.options.max_depth.unwrap_or(std::usize::MAX));
+    /// 并行查找文件，同时返回遍历错误和本次搜索的统计
+    ///
+    /// 结果与 [`find_parallel`](Self::find_parallel) 相同。遍历错误按处理策略报告，
+    /// 同时全部收集到返回的报告中；处理策略为 Fail 的错误仍会中止搜索，
+    /// 并可以通过 [`take_error`](Self::take_error) 取出。
+    ///
+    /// # 示例
+    /// ```no_run
+    /// use rust_find::finder::{Finder, FindOptions};
+    /// use rust_find::finder::filter::NameFilter;
+    /// use std::path::PathBuf;
+    ///
+    /// let finder = Finder::new(FindOptions::new());
+    /// let report = finder.find_with_report(PathBuf::from("/var"), NameFilter::new("*.log").unwrap());
+    /// for error in &report.errors {
+    ///     eprintln!("未检查: {}", error);
+    /// }
+    /// println!("{} 个匹配，{} 个错误", report.matches.len(), report.errors.len());
+    /// ```
+    pub fn find_with_report<F>(&self, root: PathBuf, filter: F) -> SearchReport
+    where
+        F: FileFilter + Send + Sync,
+    {
+        let counters = WalkCounters::collecting_errors();
+        let start = Instant::now();
+        let matches = self.collect_results(root, &filter, &counters);
+        SearchReport {
+            matches,
+            errors: counters.take_collected(),
+            stats: counters.to_stats(start.elapsed()),
+        }
+    }
 
-        // 使用 rayon 进行并行处理
-        walker
+    /// 并行查找单个搜索路径并收集排列好的结果
+    fn collect_results<F>(&self, root: PathBuf, filter: &F, counters: &WalkCounters) -> Vec<PathBuf>
+    where
+        F: FileFilter + Send + Sync,
+    {
+        let start = Instant::now();
+        let results = Mutex::new(Vec::new());
+        self.run_search(std::slice::from_ref(&root), filter, counters, |_, path| {
+            if let Ok(mut results) = results.lock() {
+                results.push(path);
+            }
+            true
+        });
+        self.record_warm_start(&root, counters, start.elapsed());
+        let mut results = results.into_inner().unwrap_or_default();
+        self.order_results(&mut results);
+        results
+    }
+
+    /// 在一次并行遍历中查找多个搜索路径，按搜索路径分组返回结果
+    ///
+    /// 所有搜索路径共享同一个线程池、同一组过滤器和结果数上限，
+    /// 线程数按所有路径的目录总数确定。一个路径中的目录很少时，
+    /// 空闲的线程会继续处理其他路径中的目录，而不是等待逐个路径完成。
+    ///
+    /// 返回的第 i 组结果来自第 i 个搜索路径，组内的顺序与 [`find_parallel`](Self::find_parallel) 相同。
+    /// 多个路径的搜索不记录预热统计。
+    pub fn find_parallel_roots<F>(&self, roots: &[PathBuf], filter: F) -> Vec<Vec<PathBuf>>
+    where
+        F: FileFilter + Send + Sync,
+    {
+        if let [root] = roots {
+            return vec![self.find_parallel(root.clone(), filter)];
+        }
+
+        let counters = WalkCounters::default();
+        let results: Vec<Mutex<Vec<PathBuf>>> = roots.iter().map(|_| Mutex::new(Vec::new())).collect();
+        self.run_search(roots, &filter, &counters, |index, path| {
+            if let Ok(mut results) = results[index].lock() {
+                results.push(path);
+            }
+            true
+        });
+        info!(
+            "Searched {} paths: {} entries, {} directories",
+            roots.len(),
+            counters.entries.load(Ordering::Relaxed),
+            counters.dirs.load(Ordering::Relaxed)
+        );
+        results
             .into_iter()
-            .filter_map(Result::ok)
-            .filter(|entry| {
-                !self.options.ignore_hidden || !entry.file_name().to_string_lossy().starts_with('.')
+            .map(|results| {
+                let mut results = results.into_inner().unwrap_or_default();
+                self.order_results(&mut results);
+                results
             })
-            .par_bridge()
-            .filter(|entry| filter.matches(entry))
-            .map(|entry| entry.path().to_owned())
             .collect()
     }
 
-    /// 统计目录中的子目录数量
-    fn count_directories(&self, root: &PathBuf) -> usize {
-        WalkDir::new(root)
-            .follow_links(self.options.follow_links)
-            .max_depth(self.options.max_depth.unwrap_or(std::usize::MAX))
+    /// 统计多个搜索路径中匹配的条目数，第 i 个数量来自第 i 个搜索路径
+    ///
+    /// 与 [`find_parallel_roots`](Self::find_parallel_roots) 使用相同的遍历，
+    /// 但不收集和排序结果路径，适合只需要数量的大目录树。
+    pub fn count_roots<F>(&self, roots: &[PathBuf], filter: F) -> Vec<usize>
+    where
+        F: FileFilter + Send + Sync,
+    {
+        let counters = WalkCounters::default();
+        let counts: Vec<AtomicUsize> = roots.iter().map(|_| AtomicUsize::new(0)).collect();
+        self.run_search(roots, &filter, &counters, |index, _| {
+            counts[index].fetch_add(1, Ordering::Relaxed);
+            true
+        });
+        counts.into_iter().map(AtomicUsize::into_inner).collect()
+    }
+
+    /// 按选项排列收集到的结果
+    fn order_results(&self, results: &mut [PathBuf]) {
+        if self.options.contents_first {
+            sort_contents_first(results);
+        } else if self.options.deterministic {
+            // 路径按组件比较，即按名称排序的深度优先顺序
+            results.sort();
+        }
+    }
+
+    /// 并行查找文件，并将结果发送到调用方提供的通道
+    ///
+    /// 结果一经找到就立即发送，不在内部收集。使用有界通道（`sync_channel`）
+    /// 时，接收方处理较慢会阻塞遍历；接收方关闭后遍历会停止。
+    ///
+    /// # 返回值
+    /// 返回成功发送的结果数量
+    ///
+    /// # 示例
+    /// ```no_run
+    /// use rust_find::finder::{Finder, FindOptions};
+    /// use rust_find::finder::filter::NameFilter;
+    /// use std::path::PathBuf;
+    /// use std::sync::mpsc::sync_channel;
+    ///
+    /// let (sender, receiver) = sync_channel(64);
+    /// std::thread::spawn(move || {
+    ///     let finder = Finder::new(FindOptions::new());
+    ///     let filter = NameFilter::new("*.rs").unwrap();
+    ///     finder.find_into(PathBuf::from("."), filter, sender);
+    /// });
+    /// for path in receiver {
+    ///     println!("{}", path.display());
+    /// }
+    /// ```
+    pub fn find_into<F, S>(&self, root: PathBuf, filter: F, sink: S) -> usize
+    where
+        F: FileFilter + Send + Sync,
+        S: ResultSink,
+    {
+        let sent = AtomicUsize::new(0);
+        let counters = WalkCounters::default();
+        let start = Instant::now();
+        self.run_search(std::slice::from_ref(&root), &filter, &counters, |_, path| {
+            if sink.send_result(path) {
+                sent.fetch_add(1, Ordering::Relaxed);
+                true
+            } else {
+                debug!("Result receiver closed, stopping search");
+                false
+            }
+        });
+        self.record_warm_start(&root, &counters, start.elapsed());
+        sent.into_inner()
+    }
+
+    /// 运行搜索，对每个结果调用 `accept`（参数为所属搜索路径的序号和结果），返回 false 时停止搜索
+    ///
+    /// 有内容过滤器时，遍历和普通过滤器产生的候选文件交给独立的内容匹配阶段，
+    /// 遍历不会等待文件读取。结果数上限在所有过滤器之后计算。
+    fn run_search<F, A>(&self, roots: &[PathBuf], filter: &F, counters: &WalkCounters, accept: A)
+    where
+        F: FileFilter + Send + Sync,
+        A: Fn(usize, PathBuf) -> bool + Sync,
+    {
+        let start = Instant::now();
+        self.prepare_threads(roots);
+        self.thread_pool
+            .install(|| self.run_search_in_pool(roots, filter, counters, &accept));
+        self.record_stats(counters, start.elapsed());
+    }
+
+    /// 根据目录数量确定本次搜索使用的线程数
+    ///
+    /// 优先使用上次运行记录的目录数量（多个搜索路径时为总和）。任一路径没有记录时按大目录树处理，
+    /// 直接使用允许的最大线程数，不预先遍历整个目录树。线程池在搜索开始前创建，遍历中不再改变；
+    /// 本次遍历发现的目录数量记录在预热统计中，供之后的搜索确定线程数。
+    fn prepare_threads(&self, roots: &[PathBuf]) {
+        let warm_count = roots
+            .iter()
+            .map(|root| self.warm_start_dir_count(root))
+            .sum::<Option<usize>>();
+        let paths = roots.iter().map(|root| root.display().to_string()).collect::<Vec<_>>().join(", ");
+        let thread_count = match warm_count {
+            Some(count) => {
+                debug!("Using cached directory count {} for {}", count, paths);
+                info!("Starting search in {} with {} directories", paths, count);
+                self.thread_pool.update_directory_count(count);
+                self.thread_pool.adjust_thread_count()
+            }
+            None => {
+                info!("Starting search in {}", paths);
+                self.thread_pool.use_max_threads()
+            }
+        };
+        info!("Using {} threads for search", thread_count);
+    }
+
+    /// 在线程池中运行搜索
+    fn run_search_in_pool<F, A>(
+        &self,
+        roots: &[PathBuf],
+        filter: &F,
+        counters: &WalkCounters,
+        accept: A,
+    ) where
+        F: FileFilter + Send + Sync,
+        A: Fn(usize, PathBuf) -> bool + Sync,
+    {
+        let limit = self.options.max_results;
+        // 所有搜索路径的遍历同时放入线程池
+        let walk_roots = |emit: &(dyn Fn(usize, FindEntry) -> bool + Sync)| {
+            rayon::scope(|scope| {
+                for (index, root) in roots.iter().enumerate() {
+                    scope.spawn(move |_| {
+                        self.parallel_walk(root, filter, counters, |entry| emit(index, entry));
+                    });
+                }
+            });
+        };
+
+        if self.content_filters.is_empty() {
+            walk_roots(&|index, entry| counters.claim_match(limit) && accept(index, self.deliver(counters, entry)));
+            return;
+        }
+
+        let check = |entry: &FindEntry| self.check_content_filters(entry, counters);
+        let stage = ContentStage::new(&self.content_filters, self.options.max_threads).with_check(&check);
+        stage.run(
+            |sender| walk_roots(&|index, entry| sender.send((index, entry)).is_ok()),
+            |(index, entry)| {
+                !self.options.cancel_token.is_cancelled()
+                    && !counters.aborted()
+                    && counters.claim_match(limit)
+                    && accept(index, self.deliver(counters, entry))
+            },
+        );
+    }
+
+    /// 创建遍历器，应用符号链接策略和剪枝规则
+    ///
+    /// 被剪除的目录（例如被 gitignore 忽略的目录）不会被进入。
+    /// 被 `--exclude` 排除的条目不会产生，但排除的目录仍会被进入。
+    /// 遍历错误（包括符号链接循环）原样产生，由调用方报告。
+    fn walk<'a>(
+        &'a self,
+        root: &Path,
+        max_depth: usize,
+    ) -> impl Iterator<Item = walkdir::Result<DirEntry>> + 'a {
+        let mut pruner = Pruner::new(&self.options, root);
+        let root_path = root.to_path_buf();
+        let walker = WalkDir::new(root)
+            .follow_links(self.options.symlink_policy.follows_links())
+            .follow_root_links(self.options.symlink_policy.follows_root_links())
+            .same_file_system(self.options.same_file_system)
+            .contents_first(self.options.contents_first)
+            .max_depth(max_depth);
+        let walker = if self.options.deterministic {
+            walker.sort_by_file_name()
+        } else {
+            walker
+        };
+        walker
             .into_iter()
-            .filter_map(Result::ok)
-            .filter(|entry| entry.file_type().is_dir())
-            .count()
+            .filter_entry(move |entry| pruner.keeps(entry))
+            .filter(move |result| match result {
+                Ok(entry) => !prune::is_excluded(&self.options, &root_path, entry),
+                Err(_) => true,
+            })
     }
+
+    /// 从预热统计中读取根目录的目录数量
+    fn warm_start_dir_count(&self, root: &Path) -> Option<usize> {
+        let file = self.options.pool_stats_file.as_ref()?;
+        PoolStats::load(file)
+            .get(root, self.options.max_depth)
+            .map(|stats| stats.dir_count)
+    }
+
+    /// 记录本次运行的统计，供下次运行预热线程池
+    fn record_warm_start(&self, root: &Path, counters: &WalkCounters, elapsed: Duration) {
+        let Some(file) = &self.options.pool_stats_file else {
+            return;
+        };
+        // 被取消、中止或提前结束的搜索只遍历了部分目录，不记录
+        if self.options.cancel_token.is_cancelled()
+            || counters.aborted()
+            || counters.limit_reached(self.options.max_results)
+        {
+            return;
+        }
+
+        let mut stats = PoolStats::load(file);
+        stats.record(
+            root,
+            self.options.max_depth,
+            counters.dirs.load(Ordering::Relaxed),
+            counters.entries.load(Ordering::Relaxed),
+            elapsed,
+        );
+        if let Err(e) = stats.save(file) {
+            debug!("Failed to save thread pool stats to {}: {}", file.display(), e);
+        }
+    }
+}
+
+/// 将结果排列为后序：目录中的条目在目录本身之前，同一目录中的条目按名称排序
+///
+/// 并行遍历产生结果的顺序不确定，因此在收集之后统一排序。
+fn sort_contents_first(paths: &mut [PathBuf]) {
+    paths.sort_by(|a, b| {
+        let mut a_components = a.components();
+        let mut b_components = b.components();
+        loop {
+            match (a_components.next(), b_components.next()) {
+                (Some(a), Some(b)) if a == b => continue,
+                (Some(a), Some(b)) => return a.cmp(&b),
+                // 较长的路径位于较短路径的目录中，排在前面
+                (Some(_), None) => return std::cmp::Ordering::Less,
+                (None, Some(_)) => return std::cmp::Ordering::Greater,
+                (None, None) => return std::cmp::Ordering::Equal,
+            }
+        }
+    });
 }
 
 #[cfg(test)]
@@ -140,6 +871,299 @@ mod tests {
         assert!(results.iter().any(|p| p.ends_with("test2.txt")));
     }
 
+    #[test]
+    fn test_finder_list_matches_depth_one_find() {
+        let temp_dir = tempdir().unwrap();
+        let base_path = temp_dir.path();
+        fs::create_dir(base_path.join("sub")).unwrap();
+        File::create(base_path.join("top.txt")).unwrap();
+        File::create(base_path.join("sub/nested.txt")).unwrap();
+
+        let finder = Finder::new(FindOptions::default());
+        let listed = finder.list(base_path.to_path_buf(), NameFilter::new("*.txt").unwrap());
+        assert_eq!(listed, vec![base_path.join("top.txt")]);
+
+        let finder = Finder::new(FindOptions::default().with_max_depth(Some(1)));
+        let mut listed = finder.list(base_path.to_path_buf(), NameFilter::new("*").unwrap());
+        let mut found = finder.find(base_path.to_path_buf(), NameFilter::new("*").unwrap());
+        listed.sort();
+        found.sort();
+        assert_eq!(listed, found);
+    }
+
+    #[test]
+    fn test_finder_max_results() {
+        let temp_dir = tempdir().unwrap();
+        let base_path = temp_dir.path();
+        for dir in 0..5 {
+            let sub = base_path.join(format!("dir{}", dir));
+            fs::create_dir(&sub).unwrap();
+            for file in 0..10 {
+                File::create(sub.join(format!("{}.txt", file))).unwrap();
+            }
+        }
+
+        let finder = Finder::new(FindOptions::default().with_max_results(Some(7)));
+        let results = finder.find(base_path.to_path_buf(), NameFilter::new("*.txt").unwrap());
+        assert_eq!(results.len(), 7);
+
+        let listed = finder.list(base_path.join("dir0"), NameFilter::new("*.txt").unwrap());
+        assert_eq!(listed.len(), 7);
+
+        let finder = Finder::new(FindOptions::default().with_max_results(Some(0)));
+        assert!(finder.find(base_path.to_path_buf(), NameFilter::new("*").unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_finder_parallel_roots_groups_results() {
+        let temp_dir = tempdir().unwrap();
+        let roots: Vec<PathBuf> = ["a", "b", "c"].iter().map(|name| temp_dir.path().join(name)).collect();
+        for (count, root) in roots.iter().enumerate() {
+            fs::create_dir(root).unwrap();
+            for file in 0..=count {
+                File::create(root.join(format!("{}.txt", file))).unwrap();
+            }
+        }
+
+        let finder = Finder::new(FindOptions::default().with_deterministic(true));
+        let results = finder.find_parallel_roots(&roots, NameFilter::new("*.txt").unwrap());
+        assert_eq!(results.len(), 3);
+        for (root, found) in roots.iter().zip(&results) {
+            let expected = finder.find(root.clone(), NameFilter::new("*.txt").unwrap());
+            assert_eq!(found, &expected);
+            assert!(found.iter().all(|path| path.starts_with(root)));
+        }
+        assert_eq!(results[2].len(), 3);
+    }
+
+    #[test]
+    fn test_finder_count_roots() {
+        let temp_dir = tempdir().unwrap();
+        let roots: Vec<PathBuf> = ["a", "b"].iter().map(|name| temp_dir.path().join(name)).collect();
+        for (count, root) in roots.iter().enumerate() {
+            fs::create_dir(root).unwrap();
+            for file in 0..=count {
+                File::create(root.join(format!("{}.txt", file))).unwrap();
+            }
+        }
+
+        let finder = Finder::new(FindOptions::default());
+        assert_eq!(finder.count_roots(&roots, NameFilter::new("*.txt").unwrap()), [1, 2]);
+
+        let finder = Finder::new(FindOptions::default().with_max_results(Some(2)));
+        let counts = finder.count_roots(&roots, NameFilter::new("*.txt").unwrap());
+        assert_eq!(counts.iter().sum::<usize>(), 2);
+    }
+
+    #[test]
+    fn test_finder_records_search_stats() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path().join("tree");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("a.txt"), "hello").unwrap();
+        fs::write(root.join("sub/b.txt"), "hi").unwrap();
+        fs::write(root.join(".hidden.txt"), "x").unwrap();
+        fs::write(root.join("c.log"), "x").unwrap();
+
+        let options = FindOptions::default()
+            .with_measure_matches(true)
+            .with_exclude(vec![glob::Pattern::new("*.log").unwrap()]);
+        let finder = Finder::new(options);
+        let results = finder.find(root.clone(), NameFilter::new("*.txt").unwrap());
+        let stats = finder.stats();
+        assert_eq!(stats.matches, results.len());
+        assert_eq!(stats.matches, 2);
+        assert_eq!(stats.matched_bytes, 7);
+        assert_eq!(stats.dirs, 2);
+        assert_eq!(stats.hidden, 1);
+        assert_eq!(stats.ignored, 1);
+        assert_eq!(stats.errors.total(), 0);
+
+        // 统计在多次搜索之间累加
+        finder.list(root.join("sub"), NameFilter::new("*.txt").unwrap());
+        assert_eq!(finder.stats().matches, 3);
+
+        let finder = Finder::new(FindOptions::default());
+        finder.find(temp_dir.path().join("missing"), NameFilter::new("*").unwrap());
+        assert_eq!(finder.stats().errors.not_found, 1);
+    }
+
+    #[test]
+    fn test_finder_fail_policy_aborts_search() {
+        let temp_dir = tempdir().unwrap();
+        let missing = temp_dir.path().join("missing");
+
+        let finder = Finder::new(FindOptions::default());
+        assert!(finder.find(missing.clone(), NameFilter::new("*").unwrap()).is_empty());
+        assert!(finder.take_error().is_none());
+
+        let options = FindOptions::default().with_error_policy(ErrorCategory::NotFound, ErrorPolicy::Fail);
+        let finder = Finder::new(options);
+        finder.find(missing.clone(), NameFilter::new("*").unwrap());
+        assert!(matches!(finder.take_error(), Some(FindError::FileNotFound(path)) if path == missing));
+        assert!(finder.take_error().is_none());
+        assert_eq!(finder.stats().errors.not_found, 1);
+    }
+
+    #[test]
+    fn test_finder_find_with_report() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path().join("tree");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        File::create(root.join("sub/a.txt")).unwrap();
+
+        let finder = Finder::new(FindOptions::default().with_deterministic(true));
+        let report = finder.find_with_report(root.clone(), NameFilter::new("*.txt").unwrap());
+        assert!(report.is_complete());
+        assert_eq!(report.matches, [root.join("sub/a.txt")]);
+        assert_eq!(report.stats.matches, 1);
+        assert_eq!(report.stats.dirs, 2);
+
+        // 默认静默处理的错误同样会被返回
+        let missing = temp_dir.path().join("missing");
+        let options = FindOptions::default().with_error_policy(ErrorCategory::NotFound, ErrorPolicy::Silent);
+        let report = Finder::new(options).find_with_report(missing.clone(), NameFilter::new("*").unwrap());
+        assert!(!report.is_complete());
+        assert!(report.matches.is_empty());
+        assert!(matches!(&report.errors[..], [FindError::FileNotFound(path)] if *path == missing));
+        assert_eq!(report.stats.errors.not_found, 1);
+    }
+
+    #[test]
+    fn test_finder_content_filter() {
+        let temp_dir = tempdir().unwrap();
+        let base_path = temp_dir.path();
+        for i in 0..30 {
+            let content = if i % 3 == 0 { "needle\n" } else { "hay\n" };
+            fs::write(base_path.join(format!("{}.txt", i)), content).unwrap();
+        }
+
+        let finder = Finder::new(FindOptions::default())
+            .with_content_filter(content::ContentFilter::literal("needle").unwrap());
+        assert_eq!(finder.find(base_path.to_path_buf(), NameFilter::new("*.txt").unwrap()).len(), 10);
+        assert_eq!(finder.list(base_path.to_path_buf(), NameFilter::new("*").unwrap()).len(), 10);
+
+        // 结果数上限在内容匹配之后计算
+        let finder = Finder::new(FindOptions::default().with_max_results(Some(4)))
+            .with_content_filter(content::ContentFilter::literal("needle").unwrap());
+        let results = finder.find(base_path.to_path_buf(), NameFilter::new("*").unwrap());
+        assert_eq!(results.len(), 4);
+        assert!(results.iter().all(|path| fs::read_to_string(path).unwrap() == "needle\n"));
+    }
+
+    #[test]
+    fn test_finder_contents_first() {
+        let temp_dir = tempdir().unwrap();
+        let base_path = temp_dir.path().join("tree");
+        fs::create_dir_all(base_path.join("a/b")).unwrap();
+        fs::create_dir(base_path.join("c")).unwrap();
+        File::create(base_path.join("a/b/1.txt")).unwrap();
+        File::create(base_path.join("a/2.txt")).unwrap();
+        File::create(base_path.join("c/3.txt")).unwrap();
+
+        let finder = Finder::new(FindOptions::default().with_contents_first(true));
+        let results = finder.find(base_path.clone(), NameFilter::new("*").unwrap());
+        let expected: Vec<PathBuf> = ["a/2.txt", "a/b/1.txt", "a/b", "a", "c/3.txt", "c", ""]
+            .iter()
+            .map(|name| if name.is_empty() { base_path.clone() } else { base_path.join(name) })
+            .collect();
+        assert_eq!(results, expected);
+
+        let listed = finder.list(base_path.clone(), NameFilter::new("*").unwrap());
+        assert_eq!(listed.last(), Some(&base_path));
+    }
+
+    #[test]
+    fn test_finder_deterministic() {
+        let temp_dir = tempdir().unwrap();
+        let base_path = temp_dir.path().join("tree");
+        for dir in ["b", "a", "a/z", "c"] {
+            fs::create_dir_all(base_path.join(dir)).unwrap();
+            for i in 0..5 {
+                File::create(base_path.join(dir).join(format!("{}.txt", i))).unwrap();
+            }
+        }
+
+        let finder = Finder::new(FindOptions::default().with_deterministic(true));
+        let results = finder.find(base_path.clone(), NameFilter::new("*").unwrap());
+        let mut sorted = results.clone();
+        sorted.sort();
+        assert_eq!(results, sorted);
+        assert_eq!(results[0], base_path);
+        assert_eq!(results[1], base_path.join("a"));
+        assert_eq!(results[2], base_path.join("a/0.txt"));
+
+        let listed = finder.list(base_path.clone(), NameFilter::new("*").unwrap());
+        let mut sorted = listed.clone();
+        sorted.sort();
+        assert_eq!(listed, sorted);
+    }
+
+    #[test]
+    fn test_finder_runs_on_own_thread_pool() {
+        struct ThreadCountFilter(Arc<AtomicUsize>);
+        impl FileFilter for ThreadCountFilter {
+            fn matches(&self, _: &FindEntry) -> bool {
+                self.0.fetch_max(rayon::current_num_threads(), Ordering::Relaxed);
+                true
+            }
+
+            fn description(&self) -> String {
+                "records the number of worker threads".to_string()
+            }
+        }
+
+        let temp_dir = tempdir().unwrap();
+        File::create(temp_dir.path().join("a.txt")).unwrap();
+        let options = FindOptions::default().with_min_threads(2).with_max_threads(2);
+        let finder = Finder::new(options);
+        let threads = Arc::new(AtomicUsize::new(0));
+        let filter = ThreadCountFilter(Arc::clone(&threads));
+        assert!(!finder.find(temp_dir.path().to_path_buf(), filter).is_empty());
+        assert_eq!(threads.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_finder_respects_gitignore() {
+        let temp_dir = tempdir().unwrap();
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("target/debug")).unwrap();
+        fs::create_dir(base_path.join("src")).unwrap();
+        File::create(base_path.join("target/debug/out.rs")).unwrap();
+        File::create(base_path.join("src/main.rs")).unwrap();
+        fs::write(base_path.join(".gitignore"), "target/\n").unwrap();
+
+        let options = FindOptions::default().with_respect_gitignore(true);
+        let finder = Finder::new(options);
+        let results = finder.find(base_path.to_path_buf(), NameFilter::new("*.rs").unwrap());
+        assert_eq!(results, vec![base_path.join("src/main.rs")]);
+
+        // 分页搜索使用相同的剪枝规则
+        let page = finder
+            .find_page(base_path.to_path_buf(), NameFilter::new("*.rs").unwrap(), 10, None)
+            .unwrap();
+        assert_eq!(page.results, vec![base_path.join("src/main.rs")]);
+
+        let finder = Finder::new(FindOptions::default());
+        let results = finder.find(base_path.to_path_buf(), NameFilter::new("*.rs").unwrap());
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_finder_with_filter_is_applied() {
+        let temp_dir = tempdir().unwrap();
+        let base_path = temp_dir.path();
+        File::create(base_path.join("keep.txt")).unwrap();
+        File::create(base_path.join("skip.log")).unwrap();
+
+        let finder = Finder::new(FindOptions::default())
+            .with_filter(NameFilter::new("*.txt").unwrap());
+        let results = finder.find(base_path.to_path_buf(), NameFilter::new("*").unwrap());
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].ends_with("keep.txt"));
+    }
+
     #[test]
     fn test_finder_hidden_files() {
         let temp_dir = tempdir().unwrap();
@@ -161,11 +1185,145 @@ mod tests {
         assert!(results[0].ends_with("normal.txt"));
 
         // 测试包含隐藏文件的情况
-        let mut options = FindOptions::default();
-        options.ignore_hidden = false;
+        let options = FindOptions {
+            ignore_hidden: false,
+            ..Default::default()
+        };
         let finder = Finder::new(options);
         let filter = NameFilter::new("*.txt").unwrap();
         let results = finder.find(base_path.to_path_buf(), filter);
         assert_eq!(results.len(), 2);
     }
+
+    #[test]
+    fn test_finder_cancellation() {
+        let temp_dir = tempdir().unwrap();
+        let base_path = temp_dir.path();
+        for i in 0..500 {
+            File::create(base_path.join(format!("file{}.txt", i))).unwrap();
+        }
+
+        // 已取消的令牌不会产生任何结果
+        let token = CancellationToken::new();
+        token.cancel();
+        let finder = Finder::new(FindOptions::new().with_cancel_token(token));
+        let filter = NameFilter::new("*.txt").unwrap();
+        assert!(finder.find(base_path.to_path_buf(), filter).is_empty());
+
+        // 搜索过程中取消，返回部分结果
+        struct CancelOnMatch(CancellationToken);
+        impl FileFilter for CancelOnMatch {
+            fn matches(&self, _: &FindEntry) -> bool {
+                self.0.cancel();
+                true
+            }
+
+            fn description(&self) -> String {
+                "cancel on first match".to_string()
+            }
+        }
+
+        let finder = Finder::new(FindOptions::new());
+        let filter = CancelOnMatch(finder.cancellation_token());
+        let results = finder.find(base_path.to_path_buf(), filter);
+        assert!(!results.is_empty());
+        assert!(results.len() < 500);
+    }
+
+    #[test]
+    fn test_finder_records_warm_start_stats() {
+        let temp_dir = tempdir().unwrap();
+        let base_path = temp_dir.path().join("tree");
+        fs::create_dir_all(base_path.join("a/b")).unwrap();
+        File::create(base_path.join("a/b/file.txt")).unwrap();
+        let stats_file = temp_dir.path().join("stats.json");
+
+        let options = FindOptions::new().with_pool_stats_file(Some(stats_file.clone()));
+        let finder = Finder::new(options);
+        let filter = NameFilter::new("*.txt").unwrap();
+        assert_eq!(finder.find(base_path.clone(), filter).len(), 1);
+
+        // 根目录、a 和 a/b 三个目录
+        let stats = PoolStats::load(&stats_file).get(&base_path, None).unwrap();
+        assert_eq!(stats.dir_count, 3);
+        assert_eq!(finder.warm_start_dir_count(&base_path), Some(3));
+
+        // 使用缓存的统计再次搜索，结果不变
+        let filter = NameFilter::new("*.txt").unwrap();
+        assert_eq!(finder.find(base_path, filter).len(), 1);
+    }
+
+    #[test]
+    fn test_finder_find_into_channel() {
+        let temp_dir = tempdir().unwrap();
+        let base_path = temp_dir.path();
+        for i in 0..20 {
+            File::create(base_path.join(format!("file{}.txt", i))).unwrap();
+        }
+
+        let finder = Finder::new(FindOptions::default());
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let filter = NameFilter::new("*.txt").unwrap();
+        let sent = finder.find_into(base_path.to_path_buf(), filter, sender);
+
+        let received: Vec<PathBuf> = receiver.iter().collect();
+        assert_eq!(sent, 20);
+        assert_eq!(received.len(), 20);
+    }
+
+    #[test]
+    fn test_finder_find_into_stops_when_receiver_dropped() {
+        let temp_dir = tempdir().unwrap();
+        let base_path = temp_dir.path();
+        for i in 0..200 {
+            File::create(base_path.join(format!("file{}.txt", i))).unwrap();
+        }
+
+        let finder = Finder::new(FindOptions::default());
+        let (sender, receiver) = std::sync::mpsc::sync_channel(1);
+        drop(receiver);
+        let filter = NameFilter::new("*.txt").unwrap();
+        assert_eq!(finder.find_into(base_path.to_path_buf(), filter, sender), 0);
+    }
+
+    #[test]
+    fn test_filters_checked_by_cost() {
+        use crate::finder::filter::FilterCost;
+
+        struct Recorder {
+            name: &'static str,
+            cost: FilterCost,
+            log: Arc<Mutex<Vec<&'static str>>>,
+        }
+        impl FileFilter for Recorder {
+            fn matches(&self, _: &FindEntry) -> bool {
+                self.log.lock().unwrap().push(self.name);
+                true
+            }
+            fn cost(&self) -> FilterCost {
+                self.cost
+            }
+            fn description(&self) -> String {
+                self.name.to_string()
+            }
+        }
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let recorder = |name, cost| Recorder { name, cost, log: log.clone() };
+        let entry = FindEntry::from_path(env!("CARGO_MANIFEST_DIR"), false).unwrap();
+        let checked = |options: FindOptions| {
+            let finder = Finder::new(options)
+                .with_filter(recorder("xattr", FilterCost::Read))
+                .with_filter(recorder("size", FilterCost::Metadata))
+                .with_filter(recorder("name", FilterCost::Path));
+            assert!(finder.matches_all(&entry, &recorder("passed", FilterCost::Metadata)));
+            std::mem::take(&mut *log.lock().unwrap())
+        };
+
+        assert_eq!(checked(FindOptions::new()), ["name", "size", "passed", "xattr"]);
+        assert_eq!(
+            checked(FindOptions::new().with_reorder_filters(false)),
+            ["xattr", "size", "name", "passed"]
+        );
+    }
 }
\ No newline at end of file
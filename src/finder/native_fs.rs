@@ -0,0 +1,437 @@
+//! 使用平台原生接口读取目录的本地文件系统后端（`--native-readdir`）
+//!
+//! Linux 上启用 `getdents` 特性时，`GetdentsFileSystem` 以较大的缓冲区直接调用 `getdents64`，
+//! 一次系统调用取回尽可能多的目录项，条目类型来自目录项本身的 `d_type`，
+//! 不需要为每个条目调用 `stat`；只有文件系统不提供类型（`DT_UNKNOWN`）时才对该条目调用 `fstatat`。
+//! Windows 上的 `FindFirstFileSystem` 以 `FindFirstFileExW` 枚举目录（`FindExInfoBasic` 不查询短文件名，
+//! `FIND_FIRST_EX_LARGE_FETCH` 使用更大的枚举缓冲区），条目的大小、时间戳和文件属性随枚举一起返回，
+//! 遍历时直接缓存在条目中。
+//! 其他平台使用 [`OsFileSystem`](super::OsFileSystem)。
+//!
+//! 这些后端读取的就是本地文件系统，条目与普通搜索的条目一样可以使用权限、属主等过滤器。
+
+use std::sync::Arc;
+
+use super::filesystem::FileSystem;
+#[cfg(not(any(windows, all(target_os = "linux", feature = "getdents"))))]
+use super::filesystem::OsFileSystem;
+
+#[cfg(windows)]
+pub use self::find_first::FindFirstFileSystem;
+#[cfg(all(target_os = "linux", feature = "getdents"))]
+pub use self::getdents::GetdentsFileSystem;
+
+/// 当前平台上可用的原生目录读取后端，没有时为 [`OsFileSystem`](super::OsFileSystem)
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::{native_filesystem, Finder, FindOptions};
+/// use rust_find::finder::filter::NameFilter;
+///
+/// let finder = Finder::new(FindOptions::new()).with_filesystem(native_filesystem());
+/// let results = finder.find("src".into(), NameFilter::new("*.rs").unwrap());
+/// assert!(results.iter().any(|path| path.ends_with("lib.rs")));
+/// ```
+pub fn native_filesystem() -> Arc<dyn FileSystem> {
+    #[cfg(windows)]
+    return Arc::new(FindFirstFileSystem);
+    #[cfg(all(target_os = "linux", feature = "getdents"))]
+    return Arc::new(GetdentsFileSystem);
+    #[cfg(not(any(windows, all(target_os = "linux", feature = "getdents"))))]
+    Arc::new(OsFileSystem)
+}
+
+#[cfg(windows)]
+mod find_first {
+    //! 通过 FindFirstFileExW 枚举目录
+
+    use std::ffi::OsString;
+    use std::fs::{self, File};
+    use std::io::{self, Read};
+    use std::mem::MaybeUninit;
+    use std::os::windows::ffi::OsStringExt;
+    use std::path::Path;
+    use std::ptr;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use windows_sys::Win32::Foundation::{ERROR_FILE_NOT_FOUND, ERROR_NO_MORE_FILES, FILETIME, HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::{
+        FindClose, FindExInfoBasic, FindExSearchNameMatch, FindFirstFileExW, FindNextFileW, FILE_ATTRIBUTE_DIRECTORY,
+        FILE_ATTRIBUTE_READONLY, FILE_ATTRIBUTE_REPARSE_POINT, FIND_FIRST_EX_LARGE_FETCH, WIN32_FIND_DATAW,
+    };
+
+    use crate::finder::filesystem::{FileKind, FileStat, FileSystem};
+    use crate::finder::long_path::to_extended_length;
+
+    /// 重解析标记中表示名称代理（符号链接、目录联接）的位，与标准库判断符号链接的方式相同
+    const NAME_SURROGATE_BIT: u32 = 0x2000_0000;
+
+    /// FILETIME（1601 年起的 100 纳秒间隔数）中 Unix 纪元对应的值
+    const UNIX_EPOCH_TICKS: u64 = 116_444_736_000_000_000;
+
+    /// 以 FindFirstFileExW 枚举目录的本地文件系统
+    ///
+    /// 条目的属性随枚举一起返回，单独读取属性和文件内容时与 [`OsFileSystem`](crate::finder::OsFileSystem) 相同。
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct FindFirstFileSystem;
+
+    /// 目录枚举句柄，离开作用域时关闭
+    struct FindHandle(HANDLE);
+
+    impl Drop for FindHandle {
+        fn drop(&mut self) {
+            // SAFETY: 句柄由 FindFirstFileExW 返回，只在这里关闭一次
+            unsafe { FindClose(self.0) };
+        }
+    }
+
+    impl FileSystem for FindFirstFileSystem {
+        fn read_dir(&self, path: &Path) -> io::Result<Vec<(OsString, FileKind)>> {
+            Ok(self
+                .read_dir_stats(path)?
+                .into_iter()
+                .map(|(name, kind, _)| (name, kind))
+                .collect())
+        }
+
+        fn read_dir_stats(&self, path: &Path) -> io::Result<Vec<(OsString, FileKind, Option<FileStat>)>> {
+            // 与标准库不同，FindFirstFileExW 不会自动转换超过 MAX_PATH 的路径
+            let pattern = to_extended_length(&path.join("*"))?;
+            let mut data = MaybeUninit::<WIN32_FIND_DATAW>::uninit();
+            // SAFETY: pattern 以 NUL 结尾，data 指向足够大的 WIN32_FIND_DATAW
+            let handle = unsafe {
+                FindFirstFileExW(
+                    pattern.as_ptr(),
+                    FindExInfoBasic,
+                    data.as_mut_ptr().cast(),
+                    FindExSearchNameMatch,
+                    ptr::null(),
+                    FIND_FIRST_EX_LARGE_FETCH,
+                )
+            };
+            if handle == INVALID_HANDLE_VALUE {
+                let error = io::Error::last_os_error();
+                // 没有任何条目（例如空的驱动器根目录）
+                if error.raw_os_error() == Some(ERROR_FILE_NOT_FOUND as i32) {
+                    return Ok(Vec::new());
+                }
+                return Err(error);
+            }
+            let handle = FindHandle(handle);
+
+            let mut entries = Vec::new();
+            loop {
+                // SAFETY: FindFirstFileExW 或上一次 FindNextFileW 成功时已填充 data
+                if let Some(entry) = entry(unsafe { data.assume_init_ref() }) {
+                    entries.push(entry);
+                }
+                // SAFETY: handle 是有效的枚举句柄，data 指向足够大的 WIN32_FIND_DATAW
+                if unsafe { FindNextFileW(handle.0, data.as_mut_ptr()) } == 0 {
+                    let error = io::Error::last_os_error();
+                    if error.raw_os_error() == Some(ERROR_NO_MORE_FILES as i32) {
+                        break;
+                    }
+                    return Err(error);
+                }
+            }
+            Ok(entries)
+        }
+
+        fn symlink_metadata(&self, path: &Path) -> io::Result<FileStat> {
+            fs::symlink_metadata(path).map(|metadata| FileStat::from(&metadata))
+        }
+
+        fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+            Ok(Box::new(File::open(path)?))
+        }
+
+        fn is_local(&self) -> bool {
+            true
+        }
+    }
+
+    /// 枚举得到的条目名称、类型和属性，跳过 `.` 和 `..`
+    fn entry(data: &WIN32_FIND_DATAW) -> Option<(OsString, FileKind, Option<FileStat>)> {
+        let len = data.cFileName.iter().position(|&c| c == 0).unwrap_or(data.cFileName.len());
+        let name = &data.cFileName[..len];
+        if name == [u16::from(b'.')] || name == [u16::from(b'.'); 2] {
+            return None;
+        }
+
+        let attributes = data.dwFileAttributes;
+        let kind = if attributes & FILE_ATTRIBUTE_REPARSE_POINT != 0 && data.dwReserved0 & NAME_SURROGATE_BIT != 0 {
+            FileKind::Symlink
+        } else if attributes & FILE_ATTRIBUTE_DIRECTORY != 0 {
+            FileKind::Dir
+        } else {
+            FileKind::File
+        };
+        // 与 meta::mode_bits 在非 Unix 平台上的推算方式相同
+        let mut mode = if attributes & FILE_ATTRIBUTE_READONLY != 0 { 0o444 } else { 0o644 };
+        if kind == FileKind::Dir {
+            mode |= 0o111;
+        }
+        let stat = FileStat {
+            kind,
+            len: (u64::from(data.nFileSizeHigh) << 32) | u64::from(data.nFileSizeLow),
+            modified: system_time(data.ftLastWriteTime),
+            accessed: system_time(data.ftLastAccessTime),
+            mode: Some(mode),
+            attributes: Some(attributes),
+        };
+        Some((OsString::from_wide(name), kind, Some(stat)))
+    }
+
+    /// FILETIME 对应的时间，为 0 时表示文件系统不记录该时间
+    fn system_time(time: FILETIME) -> Option<SystemTime> {
+        let ticks = (u64::from(time.dwHighDateTime) << 32) | u64::from(time.dwLowDateTime);
+        if ticks == 0 {
+            return None;
+        }
+        let duration = |ticks: u64| Duration::new(ticks / 10_000_000, (ticks % 10_000_000) as u32 * 100);
+        if ticks >= UNIX_EPOCH_TICKS {
+            UNIX_EPOCH.checked_add(duration(ticks - UNIX_EPOCH_TICKS))
+        } else {
+            UNIX_EPOCH.checked_sub(duration(UNIX_EPOCH_TICKS - ticks))
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "getdents"))]
+mod getdents {
+    //! 通过 getdents64 系统调用读取目录
+
+    use std::ffi::{CString, OsStr, OsString};
+    use std::fs::{self, File};
+    use std::io::{self, Read};
+    use std::mem::MaybeUninit;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    use log::debug;
+
+    use crate::finder::filesystem::{FileKind, FileStat, FileSystem};
+
+    /// 每次调用 getdents64 的缓冲区大小
+    const BUFFER_SIZE: usize = 64 * 1024;
+
+    /// `linux_dirent64` 中名称之前的固定部分：d_ino（8）、d_off（8）、d_reclen（2）、d_type（1）
+    const NAME_OFFSET: usize = 19;
+
+    /// 以 getdents64 读取目录的本地文件系统
+    ///
+    /// 属性和文件内容与 [`OsFileSystem`](crate::finder::OsFileSystem) 相同，通过标准库读取。
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct GetdentsFileSystem;
+
+    impl FileSystem for GetdentsFileSystem {
+        fn read_dir(&self, path: &Path) -> io::Result<Vec<(OsString, FileKind)>> {
+            let c_path = CString::new(path.as_os_str().as_bytes())?;
+            // SAFETY: c_path 是以 NUL 结尾的有效字符串
+            let fd = unsafe { libc::open(c_path.as_ptr(), libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC) };
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            // SAFETY: fd 刚刚打开，由 OwnedFd 负责关闭
+            let dir = unsafe { OwnedFd::from_raw_fd(fd) };
+
+            let mut buffer = vec![0u8; BUFFER_SIZE];
+            let mut entries = Vec::new();
+            loop {
+                // SAFETY: 缓冲区在调用期间有效，长度与传入的大小一致
+                let read = unsafe {
+                    libc::syscall(libc::SYS_getdents64, dir.as_raw_fd(), buffer.as_mut_ptr(), buffer.len())
+                };
+                if read < 0 {
+                    let error = io::Error::last_os_error();
+                    if error.kind() == io::ErrorKind::Interrupted {
+                        continue;
+                    }
+                    return Err(error);
+                }
+                if read == 0 {
+                    break;
+                }
+
+                for (name, d_type) in parse_entries(&buffer[..read as usize]) {
+                    let kind = match d_type {
+                        libc::DT_UNKNOWN => match kind_at(&dir, &name) {
+                            Ok(kind) => kind,
+                            // 条目在读取目录之后被删除
+                            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                            Err(e) => {
+                                debug!("Cannot read type of {}: {}", path.join(&name).display(), e);
+                                FileKind::Other
+                            }
+                        },
+                        d_type => kind_from_d_type(d_type),
+                    };
+                    entries.push((name, kind));
+                }
+            }
+            Ok(entries)
+        }
+
+        fn symlink_metadata(&self, path: &Path) -> io::Result<FileStat> {
+            fs::symlink_metadata(path).map(|metadata| FileStat::from(&metadata))
+        }
+
+        fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+            Ok(Box::new(File::open(path)?))
+        }
+
+        fn is_local(&self) -> bool {
+            true
+        }
+    }
+
+    /// 解析 getdents64 填充的缓冲区，返回名称和 d_type，跳过 `.` 和 `..`
+    pub(super) fn parse_entries(buffer: &[u8]) -> Vec<(OsString, u8)> {
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        while offset + NAME_OFFSET <= buffer.len() {
+            let record = &buffer[offset..];
+            let reclen = u16::from_ne_bytes([record[16], record[17]]) as usize;
+            if reclen < NAME_OFFSET || reclen > record.len() {
+                break;
+            }
+            let name = &record[NAME_OFFSET..reclen];
+            let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(name.len())];
+            if name != b"." && name != b".." {
+                entries.push((OsStr::from_bytes(name).to_os_string(), record[18]));
+            }
+            offset += reclen;
+        }
+        entries
+    }
+
+    /// 目录项中的 d_type 对应的类型
+    fn kind_from_d_type(d_type: u8) -> FileKind {
+        match d_type {
+            libc::DT_REG => FileKind::File,
+            libc::DT_DIR => FileKind::Dir,
+            libc::DT_LNK => FileKind::Symlink,
+            libc::DT_BLK => FileKind::BlockDevice,
+            libc::DT_CHR => FileKind::CharDevice,
+            libc::DT_FIFO => FileKind::Fifo,
+            libc::DT_SOCK => FileKind::Socket,
+            _ => FileKind::Other,
+        }
+    }
+
+    /// 文件系统不提供 d_type 时，以 fstatat 读取目录中条目的类型（不跟随符号链接）
+    fn kind_at(dir: &OwnedFd, name: &OsStr) -> io::Result<FileKind> {
+        let c_name = CString::new(name.as_bytes())?;
+        let mut stat = MaybeUninit::<libc::stat>::uninit();
+        // SAFETY: dir 是打开的目录，c_name 以 NUL 结尾，stat 指向足够大的缓冲区
+        let result = unsafe {
+            libc::fstatat(dir.as_raw_fd(), c_name.as_ptr(), stat.as_mut_ptr(), libc::AT_SYMLINK_NOFOLLOW)
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: fstatat 成功时已填充 stat
+        let mode = unsafe { stat.assume_init() }.st_mode & libc::S_IFMT;
+        Ok(match mode {
+            libc::S_IFREG => FileKind::File,
+            libc::S_IFDIR => FileKind::Dir,
+            libc::S_IFLNK => FileKind::Symlink,
+            libc::S_IFBLK => FileKind::BlockDevice,
+            libc::S_IFCHR => FileKind::CharDevice,
+            libc::S_IFIFO => FileKind::Fifo,
+            libc::S_IFSOCK => FileKind::Socket,
+            _ => FileKind::Other,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use crate::finder::filesystem::FileKind;
+    use crate::finder::filter::{NameFilter, SameFileFilter, TypeFilter};
+    use crate::finder::{FindOptions, Finder};
+
+    #[test]
+    fn test_native_read_dir_matches_std() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join("sub"))?;
+        // 名称较长的大量条目需要多次调用才能读完
+        for i in 0..2000 {
+            fs::write(dir.path().join(format!("{:0>100}.txt", i)), "")?;
+        }
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("sub", dir.path().join("link"))?;
+
+        let mut native = native_filesystem().read_dir(dir.path())?;
+        native.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut expected: Vec<_> = fs::read_dir(dir.path())?
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (entry.file_name(), FileKind::from(entry.file_type().unwrap()))
+            })
+            .collect();
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(native, expected);
+        assert!(native_filesystem().read_dir(&dir.path().join("missing")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_native_entries_are_local() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join("src"))?;
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}")?;
+        fs::write(dir.path().join("README.md"), "")?;
+
+        // 临时目录的名称以点开头
+        let finder = Finder::new(FindOptions::new().with_ignore_hidden(false));
+        let fs = native_filesystem();
+        assert!(fs.is_local());
+        assert_eq!(
+            finder.find_in(fs.clone(), dir.path(), NameFilter::new("*.rs")?),
+            [dir.path().join("src/main.rs")]
+        );
+        assert_eq!(
+            finder.find_in(fs.clone(), dir.path(), TypeFilter::new("d")?),
+            [dir.path().to_path_buf(), dir.path().join("src")]
+        );
+        // 需要本地元数据（设备号和 inode）的过滤器同样可用
+        assert_eq!(
+            finder.find_in(fs, dir.path(), SameFileFilter::new(&dir.path().join("README.md"))?),
+            [dir.path().join("README.md")]
+        );
+        Ok(())
+    }
+
+    #[cfg(all(target_os = "linux", feature = "getdents"))]
+    #[test]
+    fn test_parse_getdents_buffer() {
+        /// 按 linux_dirent64 的布局构造一个目录项，长度按 8 字节对齐
+        fn record(name: &str, d_type: u8) -> Vec<u8> {
+            let len = (19 + name.len() + 1).div_ceil(8) * 8;
+            let mut record = vec![0u8; len];
+            record[16..18].copy_from_slice(&(len as u16).to_ne_bytes());
+            record[18] = d_type;
+            record[19..19 + name.len()].copy_from_slice(name.as_bytes());
+            record
+        }
+
+        let buffer: Vec<u8> = [
+            record(".", libc::DT_DIR),
+            record("..", libc::DT_DIR),
+            record("a.txt", libc::DT_REG),
+            record("a-much-longer-directory-name", libc::DT_DIR),
+        ]
+        .concat();
+        assert_eq!(
+            getdents::parse_entries(&buffer),
+            [("a.txt".into(), libc::DT_REG), ("a-much-longer-directory-name".into(), libc::DT_DIR)]
+        );
+        // 截断的记录被忽略
+        assert!(getdents::parse_entries(&buffer[..10]).is_empty());
+    }
+}
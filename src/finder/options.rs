@@ -3,9 +3,131 @@
 //! 提供用于配置文件查找过程的选项，包括：
 //! - 搜索深度控制
 //! - 符号链接处理
-//! - 错误处理策略
+//! - 按错误类别的处理策略（中止、警告、静默）
+//! - 搜索取消
+//! - 结果数量限制
+//! - 忽略文件（gitignore、.findignore）
+//! - 排除模式（`--exclude` / `--exclude-dir`）
+//! - 文件系统边界
+
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use glob::Pattern;
 
 use crate::cli::Cli;
+use super::cancel::CancellationToken;
+use super::stats::ErrorCategory;
+use super::ignore_files::default_global_ignore_file;
+
+/// 符号链接处理策略
+///
+/// 对应 GNU find 的 `-P`、`-H` 和 `-L` 选项。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    /// 从不跟随符号链接（`-P`，默认）
+    #[default]
+    Never,
+    /// 仅跟随作为搜索路径给出的符号链接（`-H`）
+    CommandLine,
+    /// 始终跟随符号链接（`-L`）
+    Always,
+}
+
+impl SymlinkPolicy {
+    /// 遍历过程中是否跟随符号链接
+    pub fn follows_links(self) -> bool {
+        self == SymlinkPolicy::Always
+    }
+
+    /// 是否跟随作为搜索根目录的符号链接
+    pub fn follows_root_links(self) -> bool {
+        self != SymlinkPolicy::Never
+    }
+}
+
+/// Windows 上除以点开头的名称外，哪些文件属性也表示隐藏文件
+///
+/// 其他平台上没有这些属性，只按名称判断。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum HiddenAttributes {
+    /// 只按名称判断
+    None,
+    /// 带有 `FILE_ATTRIBUTE_HIDDEN` 的条目（默认）
+    #[default]
+    Hidden,
+    /// 带有 `FILE_ATTRIBUTE_HIDDEN` 或 `FILE_ATTRIBUTE_SYSTEM` 的条目
+    System,
+}
+
+/// 遍历错误的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ErrorPolicy {
+    /// 报告错误并中止搜索
+    Fail,
+    /// 报告错误并继续搜索
+    #[default]
+    Warn,
+    /// 不报告错误（仍计入搜索统计），继续搜索
+    Silent,
+}
+
+/// 每类遍历错误的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorPolicies {
+    /// 没有读取权限
+    pub permission: ErrorPolicy,
+    /// 路径不存在
+    pub not_found: ErrorPolicy,
+    /// 符号链接循环
+    pub symlink_loop: ErrorPolicy,
+    /// 其他 I/O 错误
+    pub other: ErrorPolicy,
+}
+
+impl ErrorPolicies {
+    /// 所有类别使用同一策略
+    pub fn all(policy: ErrorPolicy) -> Self {
+        Self { permission: policy, not_found: policy, symlink_loop: policy, other: policy }
+    }
+
+    /// 指定类别的策略
+    pub fn get(&self, category: ErrorCategory) -> ErrorPolicy {
+        match category {
+            ErrorCategory::PermissionDenied => self.permission,
+            ErrorCategory::NotFound => self.not_found,
+            ErrorCategory::SymlinkLoop => self.symlink_loop,
+            ErrorCategory::Other => self.other,
+        }
+    }
+
+    /// 设置指定类别的策略
+    pub fn set(&mut self, category: ErrorCategory, policy: ErrorPolicy) {
+        match category {
+            ErrorCategory::PermissionDenied => self.permission = policy,
+            ErrorCategory::NotFound => self.not_found = policy,
+            ErrorCategory::SymlinkLoop => self.symlink_loop = policy,
+            ErrorCategory::Other => self.other = policy,
+        }
+    }
+}
+
+/// 默认静默跳过权限错误，其他错误输出警告
+impl Default for ErrorPolicies {
+    fn default() -> Self {
+        Self { permission: ErrorPolicy::Silent, ..Self::all(ErrorPolicy::Warn) }
+    }
+}
+
+/// 遍历错误的报告格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ErrorFormat {
+    /// 作为警告写入日志（默认）
+    #[default]
+    Text,
+    /// 每个错误输出一行 JSON 对象到标准错误：code、path、errno 和 message
+    Json,
+}
 
 /// 文件查找配置选项
 ///
@@ -24,17 +146,17 @@ pub struct FindOptions {
     /// 最大搜索深度，None表示不限制
     pub max_depth: Option<usize>,
     
-    /// 是否跟随符号链接，默认为false
-    pub follow_links: bool,
-    
-    /// 是否忽略权限错误，默认为true
-    pub ignore_permission_errors: bool,
+    /// 符号链接处理策略，默认为不跟随
+    pub symlink_policy: SymlinkPolicy,
     
-    /// 是否忽略I/O错误，默认为false
-    pub ignore_io_errors: bool,
+    /// 每类遍历错误的处理策略，默认静默跳过权限错误、其他错误输出警告
+    pub error_policies: ErrorPolicies,
     
     /// 是否忽略隐藏文件，默认为true
     pub ignore_hidden: bool,
+
+    /// Windows 上视为隐藏的文件属性，默认为 Hidden
+    pub hidden_attributes: HiddenAttributes,
     
     /// 线程池最大线程数，默认为CPU核心数
     pub max_threads: usize,
@@ -47,6 +169,57 @@ pub struct FindOptions {
     
     /// 是否自动调整线程数，默认为true
     pub auto_adjust: bool,
+
+    /// 取消令牌，用于中途终止搜索
+    pub cancel_token: CancellationToken,
+    
+    /// 线程池预热统计文件，None表示不使用预热
+    pub pool_stats_file: Option<PathBuf>,
+
+    /// 最多返回的结果数，达到后停止遍历，None表示不限制
+    pub max_results: Option<usize>,
+
+    /// 是否遵循 gitignore 规则剪除被忽略的条目，默认为false
+    pub respect_gitignore: bool,
+
+    /// 是否遵循 `.findignore` 和全局忽略文件，默认为true
+    pub respect_findignore: bool,
+
+    /// 全局忽略文件，None表示不使用
+    pub global_ignore_file: Option<PathBuf>,
+
+    /// 跳过匹配的条目（不影响是否进入目录）
+    pub exclude: Vec<Pattern>,
+
+    /// 不进入匹配的目录
+    pub exclude_dirs: Vec<Pattern>,
+
+    /// 是否只搜索根目录所在的文件系统（不跨越挂载点），默认为false
+    pub same_file_system: bool,
+
+    /// 跟随符号链接时，一条路径上最多跟随的符号链接数，None表示不限制
+    pub max_symlink_depth: Option<usize>,
+
+    /// 是否跳过重解析点（Windows 上的联接、挂载点和符号链接，其他平台上的符号链接），默认为false
+    pub skip_reparse_points: bool,
+
+    /// 是否按后序输出（目录中的内容在目录本身之前），默认为false
+    pub contents_first: bool,
+
+    /// 是否按稳定的顺序返回结果（按名称排序的遍历顺序），默认为false
+    pub deterministic: bool,
+
+    /// 是否在搜索统计中记录匹配文件的总大小（需要读取每个匹配项的元数据），默认为false
+    pub measure_matches: bool,
+
+    /// 遍历错误的报告格式，默认为Text
+    pub error_format: ErrorFormat,
+
+    /// 是否按代价从低到高检查过滤器（而不是按添加的顺序），默认为true
+    pub reorder_filters: bool,
+
+    /// 是否向标准错误输出每个候选条目经过的过滤器及其判断，默认为false
+    pub explain: bool,
 }
 
 impl FindOptions {
@@ -54,21 +227,37 @@ impl FindOptions {
     ///
     /// 默认值：
     /// - max_depth: None (不限制深度)
-    /// - follow_links: false
-    /// - ignore_permission_errors: true
-    /// - ignore_io_errors: false
+    /// - symlink_policy: Never
+    /// - error_policies: 权限错误 Silent，其他错误 Warn
     pub fn new() -> Self {
         let num_cpus = num_cpus::get();
         Self {
             max_depth: None,
-            follow_links: false,
-            ignore_permission_errors: true,
-            ignore_io_errors: false,
+            symlink_policy: SymlinkPolicy::Never,
+            error_policies: ErrorPolicies::default(),
             ignore_hidden: true,
+            hidden_attributes: HiddenAttributes::Hidden,
             max_threads: num_cpus,
             min_threads: 1,
             dirs_per_thread: 10,
             auto_adjust: true,
+            cancel_token: CancellationToken::new(),
+            pool_stats_file: None,
+            max_results: None,
+            respect_gitignore: false,
+            respect_findignore: true,
+            global_ignore_file: None,
+            exclude: Vec::new(),
+            exclude_dirs: Vec::new(),
+            same_file_system: false,
+            max_symlink_depth: None,
+            skip_reparse_points: false,
+            contents_first: false,
+            deterministic: false,
+            measure_matches: false,
+            error_format: ErrorFormat::Text,
+            reorder_filters: true,
+            explain: false,
         }
     }
     
@@ -84,27 +273,50 @@ impl FindOptions {
     /// 设置是否跟随符号链接
     ///
     /// # 参数
-    /// - `follow_links`: true表示跟随符号链接
+    /// - `follow_links`: true表示始终跟随符号链接，false表示从不跟随
     pub fn with_follow_links(mut self, follow_links: bool) -> Self {
-        self.follow_links = follow_links;
+        self.symlink_policy = if follow_links {
+            SymlinkPolicy::Always
+        } else {
+            SymlinkPolicy::Never
+        };
+        self
+    }
+    
+    /// 设置符号链接处理策略
+    ///
+    /// # 参数
+    /// - `policy`: 符号链接处理策略
+    pub fn with_symlink_policy(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlink_policy = policy;
         self
     }
     
-    /// 设置是否忽略权限错误
+    /// 设置一类遍历错误的处理策略
     ///
     /// # 参数
-    /// - `ignore`: true表示忽略权限错误
-    pub fn with_ignore_permission_errors(mut self, ignore: bool) -> Self {
-        self.ignore_permission_errors = ignore;
+    /// - `category`: 错误类别
+    /// - `policy`: 处理策略，Fail 表示遇到该类错误时中止搜索
+    ///
+    /// # 示例
+    /// ```
+    /// use rust_find::finder::{ErrorCategory, ErrorPolicy, FindOptions};
+    ///
+    /// let options = FindOptions::new()
+    ///     .with_error_policy(ErrorCategory::PermissionDenied, ErrorPolicy::Fail);
+    /// assert_eq!(options.error_policies.permission, ErrorPolicy::Fail);
+    /// ```
+    pub fn with_error_policy(mut self, category: ErrorCategory, policy: ErrorPolicy) -> Self {
+        self.error_policies.set(category, policy);
         self
     }
     
-    /// 设置是否忽略I/O错误
+    /// 设置所有类别遍历错误的处理策略
     ///
     /// # 参数
-    /// - `ignore`: true表示忽略I/O错误
-    pub fn with_ignore_io_errors(mut self, ignore: bool) -> Self {
-        self.ignore_io_errors = ignore;
+    /// - `policies`: 每类错误的处理策略
+    pub fn with_error_policies(mut self, policies: ErrorPolicies) -> Self {
+        self.error_policies = policies;
         self
     }
     
@@ -117,6 +329,15 @@ impl FindOptions {
         self
     }
     
+    /// 设置 Windows 上视为隐藏的文件属性
+    ///
+    /// # 参数
+    /// - `attributes`: 除以点开头的名称外，表示隐藏文件的属性
+    pub fn with_hidden_attributes(mut self, attributes: HiddenAttributes) -> Self {
+        self.hidden_attributes = attributes;
+        self
+    }
+    
     /// 设置线程池最大线程数
     ///
     /// # 参数
@@ -153,21 +374,213 @@ impl FindOptions {
         self
     }
     
+    /// 设置取消令牌
+    ///
+    /// # 参数
+    /// - `token`: 与调用方共享的取消令牌
+    pub fn with_cancel_token(mut self, token: CancellationToken) -> Self {
+        self.cancel_token = token;
+        self
+    }
+    
+    /// 设置线程池预热统计文件
+    ///
+    /// # 参数
+    /// - `file`: 统计文件路径，None表示不使用预热
+    pub fn with_pool_stats_file(mut self, file: Option<PathBuf>) -> Self {
+        self.pool_stats_file = file;
+        self
+    }
+    
+    /// 设置最多返回的结果数
+    ///
+    /// # 参数
+    /// - `max_results`: 结果数上限，达到后停止遍历，None表示不限制
+    pub fn with_max_results(mut self, max_results: Option<usize>) -> Self {
+        self.max_results = max_results;
+        self
+    }
+    
+    /// 设置是否遵循 gitignore 规则
+    ///
+    /// # 参数
+    /// - `respect`: true表示读取 `.gitignore`、`.git/info/exclude` 和全局 gitignore，
+    ///   被忽略的目录不会被进入
+    pub fn with_respect_gitignore(mut self, respect: bool) -> Self {
+        self.respect_gitignore = respect;
+        self
+    }
+    
+    /// 设置是否遵循 `.findignore` 规则
+    ///
+    /// # 参数
+    /// - `respect`: true表示读取每个目录中的 `.findignore` 和全局忽略文件
+    pub fn with_respect_findignore(mut self, respect: bool) -> Self {
+        self.respect_findignore = respect;
+        self
+    }
+    
+    /// 设置全局忽略文件
+    ///
+    /// # 参数
+    /// - `file`: 全局忽略文件路径，None表示不使用
+    pub fn with_global_ignore_file(mut self, file: Option<PathBuf>) -> Self {
+        self.global_ignore_file = file;
+        self
+    }
+    
+    /// 设置排除模式
+    ///
+    /// # 参数
+    /// - `patterns`: 匹配的条目不出现在结果中；不含 `/` 的模式匹配文件名，
+    ///   含 `/` 的模式匹配相对于搜索根目录的路径
+    pub fn with_exclude(mut self, patterns: Vec<Pattern>) -> Self {
+        self.exclude = patterns;
+        self
+    }
+    
+    /// 设置排除目录模式
+    ///
+    /// # 参数
+    /// - `patterns`: 匹配的目录及其全部内容都不会被遍历，匹配规则同 [`with_exclude`](Self::with_exclude)
+    pub fn with_exclude_dirs(mut self, patterns: Vec<Pattern>) -> Self {
+        self.exclude_dirs = patterns;
+        self
+    }
+    
+    /// 设置是否只搜索根目录所在的文件系统
+    ///
+    /// # 参数
+    /// - `same`: true表示不进入位于其他文件系统上的目录（比较设备号）
+    pub fn with_same_file_system(mut self, same: bool) -> Self {
+        self.same_file_system = same;
+        self
+    }
+    
+    /// 设置一条路径上最多跟随的符号链接数
+    ///
+    /// 只在跟随符号链接时生效，超过限制的目录不会被进入。
+    ///
+    /// # 参数
+    /// - `depth`: 最大符号链接数，None表示不限制
+    pub fn with_max_symlink_depth(mut self, depth: Option<usize>) -> Self {
+        self.max_symlink_depth = depth;
+        self
+    }
+    
+    /// 设置是否跳过重解析点
+    ///
+    /// 启用后重解析点既不出现在结果中，也不会被进入。
+    ///
+    /// # 参数
+    /// - `skip`: true表示跳过重解析点
+    pub fn with_skip_reparse_points(mut self, skip: bool) -> Self {
+        self.skip_reparse_points = skip;
+        self
+    }
+    
+    /// 设置是否按后序输出（GNU find 的 `-depth`）
+    ///
+    /// 启用后目录中的所有匹配项都在目录本身之前返回，同一目录中的条目按名称排序。
+    /// 通过 [`Finder::find_into`](super::Finder::find_into) 流式发送的结果不保证该顺序。
+    ///
+    /// # 参数
+    /// - `contents_first`: true表示先返回目录中的内容
+    pub fn with_contents_first(mut self, contents_first: bool) -> Self {
+        self.contents_first = contents_first;
+        self
+    }
+    
+    /// 设置是否按稳定的顺序返回结果
+    ///
+    /// 启用后结果按名称排序的深度优先顺序返回，即使使用并行遍历，
+    /// 对同一目录树的两次搜索也会得到相同的顺序。结果在遍历完成后统一排序。
+    ///
+    /// # 参数
+    /// - `deterministic`: true表示按稳定的顺序返回结果
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+    
+    /// 设置是否统计匹配文件的总大小
+    ///
+    /// 启用后 [`Finder::stats`](super::Finder::stats) 中的 `matched_bytes` 为匹配的普通文件的大小之和。
+    ///
+    /// # 参数
+    /// - `measure`: true表示统计匹配文件的总大小
+    pub fn with_measure_matches(mut self, measure: bool) -> Self {
+        self.measure_matches = measure;
+        self
+    }
+    
+    /// 设置遍历错误的报告格式
+    ///
+    /// 处理策略为 Silent 的错误不会被报告，但仍计入搜索统计。
+    ///
+    /// # 参数
+    /// - `format`: 报告格式
+    pub fn with_error_format(mut self, format: ErrorFormat) -> Self {
+        self.error_format = format;
+        self
+    }
+    
+    /// 设置是否按代价重新排列过滤器
+    ///
+    /// 启用时查找器按 [`FileFilter::cost`](super::FileFilter::cost) 从低到高检查过滤器，
+    /// 代价相同的过滤器保持添加的顺序；禁用时严格按添加的顺序检查。
+    ///
+    /// # 参数
+    /// - `reorder`: true表示按代价重新排列过滤器
+    pub fn with_reorder_filters(mut self, reorder: bool) -> Self {
+        self.reorder_filters = reorder;
+        self
+    }
+    
+    /// 设置是否解释过滤器的判断
+    ///
+    /// 启用后每个候选条目检查完过滤器时，向标准错误输出一个 [`Explanation`](super::explain::Explanation)，
+    /// 列出被检查的过滤器及其判断。条目被拒绝后不再检查剩余的过滤器；通过普通过滤器的条目
+    /// 在检查内容过滤器时再输出一次。增量扫描、分页搜索和监视模式不输出解释。
+    ///
+    /// # 参数
+    /// - `explain`: true表示输出过滤器的判断
+    pub fn with_explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+    
     /// 从命令行参数创建配置选项
     ///
     /// # 参数
     /// - `cli`: 命令行参数解析结果
     pub fn from_cli(cli: &Cli) -> Self {
         Self::new()
-            .with_max_depth(cli.max_depth)
-            .with_follow_links(cli.follow_links)
-            .with_ignore_permission_errors(cli.ignore_permission_errors)
-            .with_ignore_io_errors(cli.ignore_io_errors)
+            .with_max_depth(cli.effective_max_depth())
+            .with_symlink_policy(cli.symlink_policy())
+            .with_error_policies(cli.error_policies())
             .with_ignore_hidden(!cli.no_ignore_hidden)
+            .with_hidden_attributes(cli.hidden_attributes)
             .with_max_threads(cli.max_threads.unwrap_or(num_cpus::get()))
             .with_min_threads(cli.min_threads.unwrap_or(1))
             .with_dirs_per_thread(cli.dirs_per_thread.unwrap_or(10))
             .with_auto_adjust(!cli.no_auto_adjust)
+            .with_pool_stats_file(cli.pool_stats_file())
+            .with_max_results(cli.max_results)
+            .with_respect_gitignore(cli.respect_gitignore)
+            .with_respect_findignore(!cli.no_findignore)
+            .with_global_ignore_file(default_global_ignore_file())
+            .with_exclude(cli.exclude.clone())
+            .with_exclude_dirs(cli.exclude_dir.clone())
+            .with_same_file_system(cli.one_file_system)
+            .with_max_symlink_depth(cli.max_symlink_depth)
+            .with_skip_reparse_points(cli.skip_reparse_points)
+            .with_contents_first(cli.depth_first_post)
+            .with_deterministic(cli.deterministic)
+            .with_measure_matches(cli.stats)
+            .with_error_format(cli.error_format)
+            .with_reorder_filters(!cli.no_reorder)
+            .with_explain(cli.explain)
     }
 }
 
@@ -185,9 +598,9 @@ mod tests {
     fn test_find_options_defaults() {
         let options = FindOptions::new();
         assert_eq!(options.max_depth, None);
-        assert_eq!(options.follow_links, false);
-        assert_eq!(options.ignore_permission_errors, true);
-        assert_eq!(options.ignore_io_errors, false);
+        assert_eq!(options.symlink_policy, SymlinkPolicy::Never);
+        assert_eq!(options.error_policies.permission, ErrorPolicy::Silent);
+        assert_eq!(options.error_policies.get(ErrorCategory::NotFound), ErrorPolicy::Warn);
     }
     
     #[test]
@@ -199,6 +612,45 @@ mod tests {
     #[test]
     fn test_find_options_with_follow_links() {
         let options = FindOptions::new().with_follow_links(true);
-        assert_eq!(options.follow_links, true);
+        assert_eq!(options.symlink_policy, SymlinkPolicy::Always);
+    }
+    
+    #[test]
+    fn test_find_options_with_same_file_system() {
+        assert!(!FindOptions::new().same_file_system);
+        assert!(FindOptions::new().with_same_file_system(true).same_file_system);
+    }
+    
+    #[test]
+    fn test_find_options_with_skip_reparse_points() {
+        assert!(!FindOptions::new().skip_reparse_points);
+        assert!(FindOptions::new().with_skip_reparse_points(true).skip_reparse_points);
+    }
+    
+    #[test]
+    fn test_find_options_with_contents_first() {
+        assert!(!FindOptions::new().contents_first);
+        assert!(FindOptions::new().with_contents_first(true).contents_first);
+    }
+    
+    #[test]
+    fn test_find_options_with_deterministic() {
+        assert!(!FindOptions::new().deterministic);
+        assert!(FindOptions::new().with_deterministic(true).deterministic);
+    }
+    
+    #[test]
+    fn test_find_options_with_hidden_attributes() {
+        assert_eq!(FindOptions::new().hidden_attributes, HiddenAttributes::Hidden);
+        let options = FindOptions::new().with_hidden_attributes(HiddenAttributes::System);
+        assert_eq!(options.hidden_attributes, HiddenAttributes::System);
+    }
+    
+    #[test]
+    fn test_symlink_policy() {
+        assert!(!SymlinkPolicy::Never.follows_root_links());
+        assert!(SymlinkPolicy::CommandLine.follows_root_links());
+        assert!(!SymlinkPolicy::CommandLine.follows_links());
+        assert!(SymlinkPolicy::Always.follows_links());
     }
 }
\ No newline at end of file
@@ -0,0 +1,334 @@
+//! 分页搜索
+//!
+//! 提供可恢复的分页搜索：每次返回最多 N 个结果以及一个不透明的续传令牌，
+//! 令牌编码了遍历的前沿（尚未处理完的目录及其中的位置），
+//! 之后的请求可以从该位置继续，而无需保持一次扫描一直打开。
+//!
+//! 分页遍历按文件名排序进行深度优先遍历，因此结果顺序是确定的。
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use walkdir::{DirEntry, WalkDir};
+
+use crate::errors::{FindError, FindResult};
+use super::content::ContentStage;
+use super::entry::FindEntry;
+use super::filter::FileFilter;
+use super::prune::{self, Pruner};
+use super::Finder;
+
+/// 分页搜索的续传令牌
+///
+/// 令牌内容对调用方是不透明的，只应原样传回 [`Finder::find_page`]。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContinuationToken(String);
+
+impl ContinuationToken {
+    /// 从字符串解析令牌（例如来自 HTTP 请求参数）
+    ///
+    /// # 错误
+    /// 如果字符串不是有效的令牌，返回 Other 错误
+    pub fn parse(token: &str) -> FindResult<Self> {
+        decode_frontier(token)?;
+        Ok(Self(token.to_string()))
+    }
+
+    /// 获取令牌的字符串形式
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for ContinuationToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// 一页搜索结果
+#[derive(Debug, Clone, Default)]
+pub struct SearchPage {
+    /// 本页的匹配结果
+    pub results: Vec<PathBuf>,
+    /// 续传令牌，None 表示遍历已经完成
+    pub continuation: Option<ContinuationToken>,
+}
+
+/// 遍历前沿：尚未处理完的目录栈
+#[derive(Debug, Serialize, Deserialize)]
+struct Frontier {
+    root: PathBuf,
+    frames: Vec<FrameState>,
+}
+
+/// 前沿中单个目录的状态
+#[derive(Debug, Serialize, Deserialize)]
+struct FrameState {
+    dir: PathBuf,
+    depth: usize,
+    /// 最后一个已处理的子条目名称
+    after: Option<PathBuf>,
+}
+
+/// 遍历中的目录帧，缓存了排序后的子条目
+struct Frame {
+    state: FrameState,
+    entries: Vec<DirEntry>,
+    next: usize,
+}
+
+impl Finder {
+    /// 分页查找文件
+    ///
+    /// # 参数
+    /// - `root`: 搜索根目录
+    /// - `filter`: 文件过滤器
+    /// - `limit`: 本页最多返回的结果数
+    /// - `token`: 上一页返回的续传令牌，首次请求时为 None
+    ///
+    /// # 错误
+    /// 如果令牌无效或不属于该根目录，返回 Other 错误
+    pub fn find_page<F>(
+        &self,
+        root: PathBuf,
+        filter: F,
+        limit: usize,
+        token: Option<&ContinuationToken>,
+    ) -> FindResult<SearchPage>
+    where
+        F: FileFilter,
+    {
+        let mut page = SearchPage::default();
+        let mut pruner = Pruner::new(&self.options, &root);
+
+        let mut stack = match token {
+            Some(token) => {
+                let frontier = decode_frontier(token.as_str())?;
+                if frontier.root != root {
+                    return Err(invalid_token("令牌不属于该搜索路径"));
+                }
+                // 前沿中的目录都是当前路径上已经进入的目录
+                for state in &frontier.frames {
+                    pruner.visit_dir(&state.dir);
+                }
+                frontier
+                    .frames
+                    .into_iter()
+                    .map(|state| self.load_frame(state, &mut pruner))
+                    .collect()
+            }
+            None => {
+                let root_entry = WalkDir::new(&root)
+                    .follow_links(self.options.symlink_policy.follows_links())
+                    .follow_root_links(self.options.symlink_policy.follows_root_links())
+                    .max_depth(0)
+                    .into_iter()
+                    .next()
+                    .and_then(Result::ok);
+
+                let mut stack = Vec::new();
+                if let Some(entry) = root_entry {
+                    if self.accepts(&entry, &root, &filter) {
+                        page.results.push(entry.path().to_path_buf());
+                    }
+                    if entry.file_type().is_dir() && self.within_depth(0) {
+                        stack.push(self.load_frame(
+                            FrameState {
+                                dir: root.clone(),
+                                depth: 0,
+                                after: None,
+                            },
+                            &mut pruner,
+                        ));
+                    }
+                }
+                stack
+            }
+        };
+
+        while let Some(frame) = stack.last_mut() {
+            if page.results.len() >= limit || self.options.cancel_token.is_cancelled() {
+                break;
+            }
+
+            let Some(entry) = frame.entries.get(frame.next).cloned() else {
+                stack.pop();
+                continue;
+            };
+            frame.next += 1;
+            frame.state.after = Some(PathBuf::from(entry.file_name()));
+            let depth = frame.state.depth + 1;
+
+            if self.accepts(&entry, &root, &filter) {
+                page.results.push(entry.path().to_path_buf());
+            }
+
+            if entry.file_type().is_dir() && self.within_depth(depth) {
+                stack.push(self.load_frame(
+                    FrameState {
+                        dir: entry.path().to_path_buf(),
+                        depth,
+                        after: None,
+                    },
+                    &mut pruner,
+                ));
+            }
+        }
+
+        // 丢弃已经处理完的帧，剩余部分即为续传前沿
+        stack.retain(|frame| frame.next < frame.entries.len());
+        if !stack.is_empty() {
+            let frontier = Frontier {
+                root,
+                frames: stack.into_iter().map(|frame| frame.state).collect(),
+            };
+            page.continuation = Some(ContinuationToken(encode_frontier(&frontier)?));
+        }
+
+        Ok(page)
+    }
+
+    /// 读取目录的排序子条目，并跳过已处理的部分和被剪除的条目
+    fn load_frame(&self, state: FrameState, pruner: &mut Pruner) -> Frame {
+        let entries: Vec<DirEntry> = WalkDir::new(&state.dir)
+            .follow_links(self.options.symlink_policy.follows_links())
+            .follow_root_links(self.options.symlink_policy.follows_root_links())
+            .same_file_system(self.options.same_file_system)
+            .min_depth(1)
+            .max_depth(1)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| pruner.keeps(entry))
+            .collect();
+
+        let next = match &state.after {
+            Some(after) => entries
+                .iter()
+                .position(|entry| Path::new(entry.file_name()) > after.as_path())
+                .unwrap_or(entries.len()),
+            None => 0,
+        };
+
+        Frame { state, entries, next }
+    }
+
+    /// 检查是否应该进入指定深度的目录
+    pub(super) fn within_depth(&self, depth: usize) -> bool {
+        self.options.max_depth.is_none_or(|max| depth < max)
+    }
+
+    /// 检查条目是否应出现在结果中
+    pub(super) fn accepts<F: FileFilter>(&self, entry: &DirEntry, root: &Path, filter: &F) -> bool {
+        if prune::is_excluded(&self.options, root, entry) {
+            return false;
+        }
+        let entry = FindEntry::under_root(entry, root);
+        !prune::is_hidden(&self.options, &entry)
+            && self.matches_all(&entry, filter)
+            && ContentStage::matches(&self.content_filters, &entry)
+    }
+}
+
+/// 将前沿编码为十六进制字符串
+fn encode_frontier(frontier: &Frontier) -> FindResult<String> {
+    let json = serde_json::to_vec(frontier)
+        .map_err(|e| invalid_token(&format!("无法编码遍历前沿: {}", e)))?;
+    Ok(json.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 从十六进制字符串解码前沿
+fn decode_frontier(token: &str) -> FindResult<Frontier> {
+    if !token.len().is_multiple_of(2) || !token.is_ascii() {
+        return Err(invalid_token("令牌格式错误"));
+    }
+    let bytes = (0..token.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&token[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| invalid_token("令牌格式错误"))?;
+
+    serde_json::from_slice(&bytes).map_err(|_| invalid_token("令牌内容无效"))
+}
+
+/// 构造无效令牌错误
+fn invalid_token(message: &str) -> FindError {
+    FindError::Other {
+        message: format!("无效的续传令牌: {}", message),
+        context: None,
+        timestamp: SystemTime::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finder::filter::NameFilter;
+    use crate::finder::FindOptions;
+    use std::fs::{self, File};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_page_resumes_from_token() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempdir()?;
+        let base = temp_dir.path();
+        fs::create_dir(base.join("a"))?;
+        fs::create_dir(base.join("b"))?;
+        for name in ["a/1.txt", "a/2.txt", "b/3.txt", "b/4.txt", "5.txt"] {
+            File::create(base.join(name))?;
+        }
+
+        let finder = Finder::new(FindOptions::new());
+        let mut all = Vec::new();
+        let mut token = None;
+        let mut pages = 0;
+        loop {
+            let filter = NameFilter::new("*.txt")?;
+            let page = finder.find_page(base.to_path_buf(), filter, 2, token.as_ref())?;
+            assert!(page.results.len() <= 2);
+            all.extend(page.results);
+            pages += 1;
+            match page.continuation {
+                Some(next) => token = Some(ContinuationToken::parse(next.as_str())?),
+                None => break,
+            }
+        }
+
+        assert_eq!(pages, 3);
+        let expected: Vec<PathBuf> = ["5.txt", "a/1.txt", "a/2.txt", "b/3.txt", "b/4.txt"]
+            .iter()
+            .map(|name| base.join(name))
+            .collect();
+        let mut sorted = all.clone();
+        sorted.sort();
+        assert_eq!(sorted, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_page_rejects_invalid_token() {
+        assert!(ContinuationToken::parse("not a token").is_err());
+        assert!(ContinuationToken::parse("abcd").is_err());
+    }
+
+    #[test]
+    fn test_find_page_rejects_foreign_root() -> Result<(), Box<dyn std::error::Error>> {
+        let dir1 = tempdir()?;
+        let dir2 = tempdir()?;
+        for i in 0..3 {
+            File::create(dir1.path().join(format!("{}.txt", i)))?;
+        }
+
+        let finder = Finder::new(FindOptions::new());
+        let page = finder.find_page(dir1.path().to_path_buf(), NameFilter::new("*")?, 1, None)?;
+        let token = page.continuation.expect("more results");
+
+        let result = finder.find_page(dir2.path().to_path_buf(), NameFilter::new("*")?, 1, Some(&token));
+        assert!(result.is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+//! 并行目录遍历
+//!
+//! 每个目录作为一个任务交给线程池：工作线程读取目录中的条目，在当前线程中
+//! 应用剪枝规则和过滤器，再把其中的子目录作为新的任务放回线程池。
+//! 空闲的线程从其他线程的任务队列中窃取目录，读取目录本身也在多个线程中同时进行，
+//! 而不是由单个线程遍历、其余线程只负责过滤。
+//! 查找器设置了文件系统后端时，目录通过后端读取，否则通过 walkdir 读取。
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use walkdir::{DirEntry, WalkDir};
+
+use crate::errors::FindError;
+use crate::output::meta;
+use super::entry::FindEntry;
+use super::filesystem::FileSystem;
+use super::filter::FileFilter;
+use super::prune::{self, Pruner};
+use super::{Finder, WalkCounters};
+
+/// 单次并行遍历中各个任务共享的状态
+struct ParallelWalk<'a, F, E> {
+    finder: &'a Finder,
+    root: &'a Path,
+    filter: &'a F,
+    counters: &'a WalkCounters,
+    /// 接收候选条目的回调，返回 false 时停止遍历
+    emit: E,
+    /// 读取目录使用的后端，None 时通过 walkdir 读取
+    filesystem: Option<&'a Arc<dyn FileSystem>>,
+    /// 剪枝规则，读取一个目录后加锁一次，批量检查其中的条目
+    pruner: Mutex<Pruner>,
+    /// 根目录所在的设备号，只在限制于同一文件系统时记录
+    root_device: Option<u64>,
+    max_depth: usize,
+    /// 回调要求停止遍历
+    stopped: AtomicBool,
+}
+
+impl Finder {
+    /// 在当前线程池中并行遍历目录树，对每个通过普通过滤器的条目调用 `emit`
+    ///
+    /// 剪枝规则、`--exclude`、隐藏文件和深度限制与串行遍历相同。
+    /// `emit` 返回 false、取消或达到结果数上限后不再读取新的目录。
+    /// 条目产生的顺序不确定。
+    pub(super) fn parallel_walk<F, E>(
+        &self,
+        root: &Path,
+        filter: &F,
+        counters: &WalkCounters,
+        emit: E,
+    ) where
+        F: FileFilter + Send + Sync,
+        E: Fn(FindEntry) -> bool + Sync,
+    {
+        let root_entry = match &self.filesystem {
+            Some(fs) => match fs.symlink_metadata(root) {
+                Ok(stat) => FindEntry::in_filesystem(fs.clone(), root.to_path_buf(), stat.kind, Some(stat), 0),
+                Err(e) => {
+                    self.report_filter_error(counters, FindError::from_io(e, root));
+                    return;
+                }
+            },
+            None => {
+                let root_entry = WalkDir::new(root)
+                    .follow_links(self.options.symlink_policy.follows_links())
+                    .follow_root_links(self.options.symlink_policy.follows_root_links())
+                    .max_depth(0)
+                    .into_iter()
+                    .next();
+                match root_entry {
+                    Some(Ok(entry)) => FindEntry::under_root(&entry, root),
+                    Some(Err(e)) => {
+                        self.report_error(counters, e);
+                        return;
+                    }
+                    None => return,
+                }
+            }
+        };
+
+        let root_device = if self.options.same_file_system {
+            root_entry.metadata().ok().map(meta::device)
+        } else {
+            None
+        };
+        let walk = ParallelWalk {
+            finder: self,
+            root,
+            filter,
+            counters,
+            emit,
+            filesystem: self.filesystem.as_ref(),
+            pruner: Mutex::new(Pruner::new(&self.options, root)),
+            root_device,
+            max_depth: self.options.max_depth.unwrap_or(usize::MAX),
+            stopped: AtomicBool::new(false),
+        };
+        rayon::scope(|scope| walk.visit(scope, root_entry, 0));
+    }
+}
+
+impl<F, E> ParallelWalk<'_, F, E>
+where
+    F: FileFilter + Send + Sync,
+    E: Fn(FindEntry) -> bool + Sync,
+{
+    /// 是否应停止遍历
+    fn should_stop(&self) -> bool {
+        let options = &self.finder.options;
+        self.stopped.load(Ordering::Relaxed)
+            || options.cancel_token.is_cancelled()
+            || self.counters.aborted()
+            || self.counters.limit_reached(options.max_results)
+    }
+
+    /// 处理单个已被剪枝规则保留的条目，需要进入的目录作为新任务放回线程池
+    fn visit<'s>(&'s self, scope: &rayon::Scope<'s>, entry: FindEntry, depth: usize) {
+        if self.should_stop() {
+            return;
+        }
+
+        let descend = self.is_dir(&entry, depth) && depth < self.max_depth && self.on_root_device(&entry);
+        let dir = descend.then(|| entry.path().to_path_buf());
+
+        // 被排除的条目不计数也不产生结果，但排除的目录仍会被进入
+        if prune::is_excluded_path(&self.finder.options, self.root, entry.path()) {
+            self.counters.ignored.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.count(&entry);
+            if self.produces(&entry) && !(self.emit)(entry) {
+                self.stopped.store(true, Ordering::Relaxed);
+                return;
+            }
+        }
+
+        if let Some(dir) = dir {
+            scope.spawn(move |scope| self.read_dir(scope, &dir, depth + 1));
+        }
+    }
+
+    /// 读取目录中的条目，剪枝后逐个处理
+    fn read_dir<'s>(&'s self, scope: &rayon::Scope<'s>, dir: &Path, depth: usize) {
+        if self.should_stop() {
+            return;
+        }
+
+        let entries = match self.filesystem {
+            Some(fs) => self.read_listed(fs, dir, depth),
+            None => self.read_walked(dir),
+        };
+        for entry in entries {
+            self.visit(scope, entry, depth);
+        }
+    }
+
+    /// 通过 walkdir 读取目录，返回被剪枝规则保留的条目
+    fn read_walked(&self, dir: &Path) -> Vec<FindEntry> {
+        let entries: Vec<DirEntry> = WalkDir::new(dir)
+            .follow_links(self.finder.options.symlink_policy.follows_links())
+            .min_depth(1)
+            .max_depth(1)
+            .into_iter()
+            .filter_map(|result| result.map_err(|e| self.finder.report_error(self.counters, e)).ok())
+            .collect();
+        let read = entries.len();
+        let entries: Vec<DirEntry> = {
+            let mut pruner = self.pruner.lock().unwrap_or_else(PoisonError::into_inner);
+            entries.into_iter().filter(|entry| pruner.keeps(entry)).collect()
+        };
+        self.counters.ignored.fetch_add(read - entries.len(), Ordering::Relaxed);
+        entries.iter().map(|entry| FindEntry::under_root(entry, self.root)).collect()
+    }
+
+    /// 通过文件系统后端读取目录，返回被剪枝规则保留的条目
+    ///
+    /// 后端枚举目录时返回的属性直接缓存在条目中。
+    fn read_listed(&self, fs: &Arc<dyn FileSystem>, dir: &Path, depth: usize) -> Vec<FindEntry> {
+        let listed = match fs.read_dir_stats(dir) {
+            Ok(listed) => listed,
+            Err(e) => {
+                self.finder.report_filter_error(self.counters, FindError::from_io(e, dir));
+                return Vec::new();
+            }
+        };
+        let read = listed.len();
+        let entries: Vec<FindEntry> = {
+            let mut pruner = self.pruner.lock().unwrap_or_else(PoisonError::into_inner);
+            listed
+                .into_iter()
+                .map(|(name, kind, stat)| (dir.join(name), kind, stat))
+                .filter(|(path, kind, _)| pruner.keeps_listed(path, kind.is_dir()))
+                .map(|(path, kind, stat)| FindEntry::in_filesystem(fs.clone(), path, kind, stat, depth))
+                .collect()
+        };
+        self.counters.ignored.fetch_add(read - entries.len(), Ordering::Relaxed);
+        entries
+    }
+
+    /// 条目是否为需要进入的目录
+    ///
+    /// 只跟随根目录的符号链接时，根目录条目的类型仍是符号链接，需要按目标判断。
+    fn is_dir(&self, entry: &FindEntry, depth: usize) -> bool {
+        if depth == 0 && self.finder.options.symlink_policy.follows_root_links() {
+            entry.path().is_dir()
+        } else {
+            entry.kind().is_dir()
+        }
+    }
+
+    /// 统计遍历的条目和目录
+    fn count(&self, entry: &FindEntry) {
+        self.counters.entries.fetch_add(1, Ordering::Relaxed);
+        if entry.kind().is_dir() {
+            self.counters.dirs.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 条目是否通过隐藏文件规则和普通过滤器
+    fn produces(&self, entry: &FindEntry) -> bool {
+        !self.finder.skips_hidden(entry, self.counters)
+            && self.finder.check_filters(entry, self.filter, self.counters)
+    }
+
+    /// 目录是否与根目录位于同一文件系统（未限制时总是 true）
+    ///
+    /// 位于其他文件系统的目录本身仍会产生，但不会被进入。
+    fn on_root_device(&self, entry: &FindEntry) -> bool {
+        match self.root_device {
+            Some(device) => entry.metadata().is_ok_and(|metadata| meta::device(metadata) == device),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finder::filter::NameFilter;
+    use crate::finder::FindOptions;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    fn collect<F: FileFilter + Send + Sync>(finder: &Finder, root: &Path, filter: &F) -> Vec<PathBuf> {
+        let found = Mutex::new(Vec::new());
+        let counters = WalkCounters::default();
+        finder.parallel_walk(root, filter, &counters, |entry| {
+            found.lock().unwrap().push(entry.into_path());
+            true
+        });
+        let mut found = found.into_inner().unwrap();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn test_parallel_walk_visits_whole_tree() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        for i in 0..20 {
+            let sub = root.join(format!("d{}", i)).join("nested");
+            fs::create_dir_all(&sub)?;
+            fs::write(sub.join("a.txt"), "x")?;
+            fs::write(sub.join("b.log"), "x")?;
+        }
+
+        let finder = Finder::new(FindOptions::new());
+        let found = collect(&finder, &root, &NameFilter::new("*.txt")?);
+        assert_eq!(found.len(), 20);
+        assert!(found.contains(&root.join("d7/nested/a.txt")));
+
+        let finder = Finder::new(FindOptions::new().with_max_depth(Some(2)));
+        let found = collect(&finder, &root, &NameFilter::new("*")?);
+        // 根目录、20 个子目录及其中的 nested 目录
+        assert_eq!(found.len(), 41);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parallel_walk_reads_through_filesystem() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::finder::filter::{SizeFilter, SizeMeasure};
+        use crate::finder::size::ByteSize;
+        use crate::finder::MemoryFileSystem;
+
+        let fs: Arc<dyn FileSystem> = Arc::new(
+            MemoryFileSystem::new()
+                .with_file("/project/src/main.rs", "fn main() {}\n")
+                .with_file("/project/src/nested/lib.rs", "")
+                .with_file("/project/target/build.rs", "")
+                .with_file("/project/.hidden.rs", ""),
+        );
+        let root = Path::new("/project");
+        let options = FindOptions::new()
+            .with_exclude_dirs(vec![glob::Pattern::new("target")?])
+            .with_ignore_hidden(true);
+        let finder = Finder::new(options).with_filesystem(fs);
+
+        let found = collect(&finder, root, &NameFilter::new("*.rs")?);
+        assert_eq!(found, [PathBuf::from("/project/src/main.rs"), PathBuf::from("/project/src/nested/lib.rs")]);
+
+        // 大小来自后端枚举时返回的属性
+        let found = collect(&finder, root, &SizeFilter::at_least(ByteSize::new(1), SizeMeasure::Apparent));
+        assert_eq!(found, [PathBuf::from("/project/src/main.rs")]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parallel_walk_stops_when_emit_refuses() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        for i in 0..50 {
+            fs::create_dir_all(root.join(format!("d{}", i)))?;
+        }
+
+        let finder = Finder::new(FindOptions::new());
+        let counters = WalkCounters::default();
+        let emitted = std::sync::atomic::AtomicUsize::new(0);
+        finder.parallel_walk(&root, &NameFilter::new("*")?, &counters, |_| {
+            emitted.fetch_add(1, Ordering::Relaxed);
+            false
+        });
+        // 根目录本身被拒绝后不再进入任何目录
+        assert_eq!(emitted.load(Ordering::Relaxed), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,453 @@
+//! 遍历剪枝
+//!
+//! 在遍历过程中决定是否保留条目：被剪除的目录不会被进入，
+//! 因此其中的条目既不会被访问，也不会出现在结果中。
+//!
+//! 排除模式在这里统一匹配：不含 `/` 的模式匹配文件名，
+//! 含 `/` 的模式匹配相对于遍历根目录的路径。
+//!
+//! 跟随符号链接时，已经访问过的目录（按设备号和 inode 判断）不会被再次进入，
+//! 从而避免符号链接循环导致的无限遍历。
+//!
+//! Windows 上的 NTFS 联接（junction）和挂载点被标准库视为符号链接，
+//! 与符号链接一样遵循跟随策略和循环检测。
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use glob::{MatchOptions, Pattern};
+use log::warn;
+use walkdir::DirEntry;
+
+use crate::errors::FindError;
+use super::entry::FindEntry;
+use super::ignore_files::IgnoreRules;
+use super::options::{FindOptions, HiddenAttributes};
+
+/// 单次遍历的剪枝规则
+#[derive(Debug)]
+pub(crate) struct Pruner {
+    /// 遍历根目录（按传入形式）
+    root: PathBuf,
+    /// 遍历根目录的绝对路径，用于匹配忽略规则
+    absolute_root: PathBuf,
+    /// 忽略文件规则
+    ignore: IgnoreRules,
+    /// 整个子树被剪除的目录模式（`--exclude-dir`）
+    exclude_dirs: Vec<Pattern>,
+    /// 符号链接循环和深度检测，只在跟随符号链接时启用
+    links: Option<LinkGuard>,
+    /// 是否剪除重解析点（`--skip-reparse-points`）
+    skip_reparse_points: bool,
+}
+
+impl Pruner {
+    /// 根据查找选项为指定根目录创建剪枝规则
+    pub(crate) fn new(options: &FindOptions, root: &Path) -> Self {
+        let absolute_root = root
+            .canonicalize()
+            .or_else(|_| std::env::current_dir().map(|cwd| cwd.join(root)))
+            .unwrap_or_else(|_| root.to_path_buf());
+
+        let mut ignore = IgnoreRules::new();
+        if options.respect_gitignore {
+            ignore = ignore.with_gitignore();
+        }
+        if options.respect_findignore {
+            ignore = ignore.with_findignore(options.global_ignore_file.as_deref());
+        }
+        let ignore = ignore.for_root(&absolute_root);
+
+        let links = options.symlink_policy.follows_links().then(|| {
+            let mut guard = LinkGuard::new(root, options.max_symlink_depth);
+            guard.visit(root);
+            guard
+        });
+
+        Self {
+            root: root.to_path_buf(),
+            absolute_root,
+            ignore,
+            exclude_dirs: options.exclude_dirs.clone(),
+            links,
+            skip_reparse_points: options.skip_reparse_points,
+        }
+    }
+
+    /// 记录已经进入的目录，用于从续传位置恢复遍历时重建访问记录
+    pub(crate) fn visit_dir(&mut self, dir: &Path) {
+        if let Some(links) = &mut self.links {
+            links.visit(dir);
+        }
+    }
+
+    /// 是否保留条目（对目录而言，同时决定是否进入）
+    ///
+    /// 遍历根目录本身总是保留。
+    pub(crate) fn keeps(&mut self, entry: &DirEntry) -> bool {
+        if entry.depth() == 0 {
+            return true;
+        }
+        let is_dir = entry.file_type().is_dir();
+        if !self.keeps_one(entry.path(), is_dir) {
+            return false;
+        }
+        match &mut self.links {
+            Some(links) if is_dir => links.admits(entry),
+            _ => true,
+        }
+    }
+
+    /// 是否保留不是由遍历产生的路径（例如文件系统事件中的路径）
+    ///
+    /// 路径本身及其在遍历根目录之下的所有上级目录都必须被保留。
+    /// 不在遍历根目录之下的路径不被保留。
+    pub(crate) fn keeps_path(&mut self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let components: Vec<_> = relative.components().collect();
+        let mut current = self.root.clone();
+        for (index, component) in components.iter().enumerate() {
+            current.push(component);
+            let is_dir = index + 1 < components.len() || current.is_dir();
+            if !self.keeps_one(&current, is_dir) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// 是否保留从目录列表缓存中得到的条目（不跟随符号链接，无需检测循环）
+    pub(crate) fn keeps_listed(&mut self, path: &Path, is_dir: bool) -> bool {
+        self.keeps_one(path, is_dir)
+    }
+
+    /// 检查单个条目的重解析点、排除目录和忽略规则
+    fn keeps_one(&mut self, path: &Path, is_dir: bool) -> bool {
+        if self.skip_reparse_points && is_reparse_point(path) {
+            return false;
+        }
+        if is_dir && matches_any(&self.exclude_dirs, &self.root, path) {
+            return false;
+        }
+        if self.ignore.is_empty() {
+            return true;
+        }
+
+        let absolute = match path.strip_prefix(&self.root) {
+            Ok(relative) => self.absolute_root.join(relative),
+            Err(_) => path.to_path_buf(),
+        };
+        !self.ignore.is_ignored(&absolute, is_dir)
+    }
+}
+
+/// 跟随符号链接时的循环和深度检测
+#[derive(Debug)]
+struct LinkGuard {
+    /// 遍历根目录
+    root: PathBuf,
+    /// 已经进入过的目录
+    visited: HashSet<DirId>,
+    /// 一条路径上最多跟随的符号链接数
+    max_depth: Option<usize>,
+    /// 已进入目录的路径上跟随的符号链接数（只在限制深度时记录）
+    link_depths: HashMap<PathBuf, usize>,
+}
+
+impl LinkGuard {
+    fn new(root: &Path, max_depth: Option<usize>) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            visited: HashSet::new(),
+            max_depth,
+            link_depths: HashMap::new(),
+        }
+    }
+
+    /// 记录目录已被访问，返回该目录此前是否未被访问过
+    fn visit(&mut self, dir: &Path) -> bool {
+        match dir_id(dir) {
+            Some(id) => self.visited.insert(id),
+            None => true,
+        }
+    }
+
+    /// 检查是否可以进入目录，不能进入时输出警告
+    fn admits(&mut self, entry: &DirEntry) -> bool {
+        let path = entry.path();
+        if let Some(max_depth) = self.max_depth {
+            let depth = self.link_depth(path);
+            if depth > max_depth {
+                warn!("{} (超过最大符号链接深度 {})", FindError::SymlinkIssue(path.to_path_buf()), max_depth);
+                return false;
+            }
+            self.link_depths.insert(path.to_path_buf(), depth);
+        }
+
+        if dir_id(path).is_some_and(|id| !self.visited.insert(id)) {
+            warn!("{} (目录已访问过，可能存在循环，已跳过)", FindError::SymlinkIssue(path.to_path_buf()));
+            return false;
+        }
+        true
+    }
+
+    /// 从遍历根目录到该路径之间跟随的符号链接数
+    fn link_depth(&self, path: &Path) -> usize {
+        let own = usize::from(is_symlink(path));
+        let Some(parent) = path.parent() else {
+            return own;
+        };
+        if !parent.starts_with(&self.root) || parent == self.root {
+            return own;
+        }
+        let parent_depth = match self.link_depths.get(parent) {
+            Some(depth) => *depth,
+            // 从续传位置恢复时没有上级目录的记录，逐级检查
+            None => parent
+                .ancestors()
+                .take_while(|dir| *dir != self.root)
+                .filter(|dir| is_symlink(dir))
+                .count(),
+        };
+        parent_depth + own
+    }
+}
+
+/// 路径本身是否为符号链接
+fn is_symlink(path: &Path) -> bool {
+    path.symlink_metadata().is_ok_and(|metadata| metadata.file_type().is_symlink())
+}
+
+/// 路径本身是否为重解析点
+///
+/// Windows 上检查 `FILE_ATTRIBUTE_REPARSE_POINT`，包括符号链接、联接、挂载点和云存储占位文件。
+/// 其他平台上没有重解析点，对应的是符号链接。
+fn is_reparse_point(path: &Path) -> bool {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+
+        const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+        path.symlink_metadata()
+            .is_ok_and(|metadata| metadata.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0)
+    }
+
+    #[cfg(not(windows))]
+    {
+        is_symlink(path)
+    }
+}
+
+/// 目录的唯一标识
+///
+/// Unix 上为（设备号, inode）。Windows 上标准库没有稳定的文件索引，使用规范化路径：
+/// 经过符号链接或联接到达同一目录时，规范化路径相同。
+#[cfg(unix)]
+type DirId = (u64, u64);
+#[cfg(not(unix))]
+type DirId = PathBuf;
+
+/// 获取目录的唯一标识（跟随符号链接），无法读取时返回 None
+fn dir_id(dir: &Path) -> Option<DirId> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        std::fs::metadata(dir).ok().map(|metadata| (metadata.dev(), metadata.ino()))
+    }
+
+    #[cfg(not(unix))]
+    {
+        dir.canonicalize().ok()
+    }
+}
+
+/// 条目是否被 `--exclude` 排除（只跳过条目本身，目录仍会被进入）
+///
+/// 遍历根目录本身从不被排除。
+pub(crate) fn is_excluded(options: &FindOptions, root: &Path, entry: &DirEntry) -> bool {
+    is_excluded_path(options, root, entry.path())
+}
+
+/// 路径是否被 `--exclude` 排除，用于不是由遍历产生的条目
+pub(crate) fn is_excluded_path(options: &FindOptions, root: &Path, path: &Path) -> bool {
+    path != root && matches_any(&options.exclude, root, path)
+}
+
+/// 条目是否作为隐藏文件被跳过（只影响输出，隐藏的目录仍会被进入）
+///
+/// 以点开头的名称总是视为隐藏；Windows 上还按 `hidden_attributes` 检查文件属性。
+pub(crate) fn is_hidden(options: &FindOptions, entry: &FindEntry) -> bool {
+    options.ignore_hidden
+        && (entry.file_name().to_string_lossy().starts_with('.')
+            || has_hidden_attributes(entry, options.hidden_attributes))
+}
+
+/// 条目是否带有表示隐藏的 Windows 文件属性
+#[cfg(windows)]
+fn has_hidden_attributes(entry: &FindEntry, attributes: HiddenAttributes) -> bool {
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+    let mask = match attributes {
+        HiddenAttributes::None => return false,
+        HiddenAttributes::Hidden => FILE_ATTRIBUTE_HIDDEN,
+        HiddenAttributes::System => FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM,
+    };
+    // 枚举目录时已经得到属性并缓存在条目中，这里不会再访问文件系统
+    entry
+        .stat()
+        .is_ok_and(|stat| stat.attributes.is_some_and(|attributes| attributes & mask != 0))
+}
+
+/// 条目是否带有表示隐藏的 Windows 文件属性（其他平台上没有这些属性）
+#[cfg(not(windows))]
+fn has_hidden_attributes(_entry: &FindEntry, _attributes: HiddenAttributes) -> bool {
+    false
+}
+
+/// 路径是否匹配任一排除模式
+fn matches_any(patterns: &[Pattern], root: &Path, path: &Path) -> bool {
+    if patterns.is_empty() {
+        return false;
+    }
+
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let path_options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+
+    patterns.iter().any(|pattern| {
+        if pattern.as_str().contains('/') {
+            pattern.matches_path_with(relative, path_options)
+        } else {
+            pattern.matches(&name)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+    use walkdir::WalkDir;
+
+    fn walk_with(options: &FindOptions, root: &Path) -> Vec<PathBuf> {
+        let mut pruner = Pruner::new(options, root);
+        let mut paths: Vec<PathBuf> = WalkDir::new(root)
+            .into_iter()
+            .filter_entry(|entry| pruner.keeps(entry))
+            .filter_map(Result::ok)
+            .filter(|entry| !is_excluded(options, root, entry))
+            .map(|entry| entry.path().strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_exclude_and_exclude_dir() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/gen"))?;
+        fs::create_dir_all(root.join("vendor/lib"))?;
+        for file in ["src/a.rs", "src/a.bak", "src/gen/b.rs", "vendor/lib/c.rs"] {
+            fs::write(root.join(file), "x")?;
+        }
+
+        let options = FindOptions::new()
+            .with_exclude(vec![Pattern::new("*.bak")?, Pattern::new("src/gen")?])
+            .with_exclude_dirs(vec![Pattern::new("vendor")?]);
+        let paths = walk_with(&options, root);
+
+        let expected: Vec<PathBuf> = ["", "src", "src/a.rs", "src/gen/b.rs"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(paths, expected);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_cycles_and_depth() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::finder::SymlinkPolicy;
+        use std::os::unix::fs::symlink;
+
+        let dir = tempdir()?;
+        let root = dir.path();
+        fs::create_dir_all(root.join("a/b"))?;
+        fs::create_dir(root.join("target"))?;
+        fs::write(root.join("target/file"), "x")?;
+        // 指向上级目录的循环，以及两级符号链接
+        symlink(root, root.join("a/b/up"))?;
+        symlink(root.join("target"), root.join("link1"))?;
+        symlink(root.join("link1"), root.join("a/link2"))?;
+
+        let walk = |options: &FindOptions| -> Vec<PathBuf> {
+            let mut pruner = Pruner::new(options, root);
+            let mut paths: Vec<PathBuf> = WalkDir::new(root)
+                .follow_links(true)
+                .into_iter()
+                .filter_entry(|entry| pruner.keeps(entry))
+                .filter_map(Result::ok)
+                .map(|entry| entry.path().strip_prefix(root).unwrap().to_path_buf())
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        let options = FindOptions::new().with_symlink_policy(SymlinkPolicy::Always);
+        let paths = walk(&options);
+        // 循环链接被跳过，同一目录只进入一次
+        assert!(!paths.iter().any(|path| path.starts_with("a/b/up")));
+        let files = paths.iter().filter(|path| path.ends_with("file")).count();
+        assert_eq!(files, 1);
+
+        let options = options.with_max_symlink_depth(Some(0));
+        let paths = walk(&options);
+        assert!(paths.contains(&PathBuf::from("target/file")));
+        assert!(!paths.iter().any(|path| path.starts_with("link1") || path.starts_with("a/link2")));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_skip_reparse_points() -> Result<(), Box<dyn std::error::Error>> {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempdir()?;
+        let root = dir.path();
+        fs::create_dir(root.join("target"))?;
+        fs::write(root.join("target/file"), "x")?;
+        symlink(root.join("target"), root.join("link"))?;
+        symlink(root.join("target/file"), root.join("file_link"))?;
+
+        let options = FindOptions::new().with_skip_reparse_points(true);
+        let paths = walk_with(&options, root);
+        assert_eq!(paths, ["", "target", "target/file"].map(PathBuf::from));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_keeps_path() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root = dir.path();
+        fs::create_dir_all(root.join("vendor/lib"))?;
+        fs::create_dir_all(root.join("src"))?;
+
+        let options = FindOptions::new().with_exclude_dirs(vec![Pattern::new("vendor")?]);
+        let mut pruner = Pruner::new(&options, root);
+        assert!(pruner.keeps_path(&root.join("src/new.rs")));
+        assert!(!pruner.keeps_path(&root.join("vendor/lib/new.rs")));
+        assert!(!pruner.keeps_path(Path::new("/elsewhere/new.rs")));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,723 @@
+//! 类 SQL 的查询语言
+//!
+//! `--query` 接受一个由比较、`AND`、`OR`、`NOT` 和括号组成的表达式，
+//! 解析为过滤器树，代替一组命令行过滤选项：
+//!
+//! ```text
+//! type = file AND size > 10M AND (name LIKE '%.log' OR mtime < '30d')
+//! ```
+//!
+//! 支持的字段和运算符：
+//!
+//! | 字段 | 运算符 | 值 |
+//! |------|--------|----|
+//! | `name`、`path` | `=`、`!=`、`LIKE`、`NOT LIKE`、`ILIKE`、`NOT ILIKE` | 字符串（`LIKE` 中 `%` 匹配任意字符，`_` 匹配单个字符） |
+//! | `type` | `=`、`!=` | `file`、`dir`、`symlink`、`block`、`char`、`fifo`、`socket` 或 GNU find 的类型代码 |
+//! | `size` | `=`、`!=`、`<`、`<=`、`>`、`>=` | 带可选单位的大小，例如 `10M`、`1.5k` |
+//! | `links`、`depth` | 同上 | 整数 |
+//! | `mtime`、`atime` | 同上 | 时长，例如 `30d`、`12h`；比较的是条目的年龄，`mtime < 30d` 表示 30 天内修改过 |
+//!
+//! 关键字和字段名不区分大小写，`AND` 的优先级高于 `OR`。字符串用单引号或双引号括起，
+//! 引号内连续两个引号表示引号本身；不含空白和括号的值可以省略引号。
+
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use glob::Pattern;
+
+use crate::errors::{FindError, FindResult};
+use super::entry::FindEntry;
+use super::filter::{FileFilter, FilterCost, NameFilter, PathFilter, TypeFilter};
+use super::size::ByteSize;
+
+/// 由查询表达式构建的过滤器
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::query::QueryFilter;
+///
+/// let filter = QueryFilter::parse("type = file AND (name LIKE '%.log' OR size > 10M)").unwrap();
+/// assert!(QueryFilter::parse("size >").is_err());
+/// ```
+pub struct QueryFilter {
+    expr: Expr,
+    query: String,
+}
+
+impl QueryFilter {
+    /// 解析查询表达式
+    ///
+    /// # 错误
+    /// 如果表达式无效，返回PatternError错误，消息中标出出错的位置
+    pub fn parse(query: &str) -> FindResult<Self> {
+        let tokens = tokenize(query)?;
+        let mut parser = Parser { query, tokens, next: 0 };
+        let expr = parser.expression()?;
+        if let Some(token) = parser.peek() {
+            return Err(parser.error_at(token.start, format!("unexpected {}", token.kind)));
+        }
+        Ok(Self {
+            expr,
+            query: query.trim().to_string(),
+        })
+    }
+}
+
+impl FileFilter for QueryFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        self.expr.evaluate(entry).unwrap_or(false)
+    }
+
+    fn try_matches(&self, entry: &FindEntry) -> FindResult<bool> {
+        self.expr.evaluate(entry)
+    }
+
+    fn cost(&self) -> FilterCost {
+        self.expr.cost()
+    }
+
+    fn description(&self) -> String {
+        format!("matches query \"{}\"", self.query)
+    }
+}
+
+/// 过滤器树的节点
+enum Expr {
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Test(Box<dyn FileFilter + Send + Sync>),
+}
+
+impl Expr {
+    /// 对条目求值
+    ///
+    /// `OR` 中的某个分支出错时，只有其他分支都不匹配才返回该错误。
+    fn evaluate(&self, entry: &FindEntry) -> FindResult<bool> {
+        match self {
+            Expr::And(operands) => {
+                for operand in operands {
+                    if !operand.evaluate(entry)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Expr::Or(operands) => {
+                let mut failure = None;
+                for operand in operands {
+                    match operand.evaluate(entry) {
+                        Ok(true) => return Ok(true),
+                        Ok(false) => {}
+                        Err(e) => failure = failure.or(Some(e)),
+                    }
+                }
+                failure.map_or(Ok(false), Err)
+            }
+            Expr::Not(operand) => operand.evaluate(entry).map(|matched| !matched),
+            Expr::Test(filter) => filter.try_matches(entry),
+        }
+    }
+
+    /// 最昂贵的叶子过滤器的代价
+    fn cost(&self) -> FilterCost {
+        match self {
+            Expr::And(operands) | Expr::Or(operands) => {
+                operands.iter().map(Expr::cost).max().unwrap_or(FilterCost::Path)
+            }
+            Expr::Not(operand) => operand.cost(),
+            Expr::Test(filter) => filter.cost(),
+        }
+    }
+}
+
+/// 比较运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    /// 按运算符比较两个值
+    fn compare<T: PartialOrd>(self, left: T, right: T) -> bool {
+        match self {
+            CompareOp::Eq => left == right,
+            CompareOp::Ne => left != right,
+            CompareOp::Lt => left < right,
+            CompareOp::Le => left <= right,
+            CompareOp::Gt => left > right,
+            CompareOp::Ge => left >= right,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            CompareOp::Eq => "=",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        }
+    }
+}
+
+/// 按数值比较的字段
+#[derive(Debug, Clone, Copy)]
+enum NumericField {
+    Size,
+    Links,
+    Depth,
+}
+
+/// 数值字段的比较
+struct NumericTest {
+    field: NumericField,
+    op: CompareOp,
+    value: u64,
+}
+
+impl FileFilter for NumericTest {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        self.try_matches(entry).unwrap_or(false)
+    }
+
+    fn try_matches(&self, entry: &FindEntry) -> FindResult<bool> {
+        let actual = match self.field {
+            NumericField::Size => entry.stat()?.len,
+            NumericField::Links => crate::output::meta::link_count(entry.metadata()?),
+            NumericField::Depth => entry.depth() as u64,
+        };
+        Ok(self.op.compare(actual, self.value))
+    }
+
+    fn cost(&self) -> FilterCost {
+        match self.field {
+            NumericField::Depth => FilterCost::Path,
+            NumericField::Size | NumericField::Links => FilterCost::Metadata,
+        }
+    }
+
+    fn description(&self) -> String {
+        let field = match self.field {
+            NumericField::Size => "size",
+            NumericField::Links => "links",
+            NumericField::Depth => "depth",
+        };
+        format!("{} {} {}", field, self.op.symbol(), self.value)
+    }
+}
+
+/// 按年龄比较的时间字段
+#[derive(Debug, Clone, Copy)]
+enum TimeField {
+    Modified,
+    Accessed,
+}
+
+/// 时间字段的比较，比较的是条目的年龄（当前时间减去该时间）
+struct AgeTest {
+    field: TimeField,
+    op: CompareOp,
+    age: Duration,
+    /// 解析查询时的时间，所有条目的年龄都相对于同一时刻计算
+    now: SystemTime,
+}
+
+impl FileFilter for AgeTest {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        self.try_matches(entry).unwrap_or(false)
+    }
+
+    fn try_matches(&self, entry: &FindEntry) -> FindResult<bool> {
+        let stat = entry.stat()?;
+        let time = match self.field {
+            TimeField::Modified => stat.modified,
+            TimeField::Accessed => stat.accessed,
+        };
+        // 后端不提供此时间的条目不匹配
+        let Some(time) = time else {
+            return Ok(false);
+        };
+        // 时间在未来的条目年龄为 0
+        let age = self.now.duration_since(time).unwrap_or_default();
+        Ok(self.op.compare(age, self.age))
+    }
+
+    fn description(&self) -> String {
+        let field = match self.field {
+            TimeField::Modified => "mtime",
+            TimeField::Accessed => "atime",
+        };
+        format!("{} age {} {:?}", field, self.op.symbol(), self.age)
+    }
+}
+
+/// 词法单元的类型
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    /// 标识符、关键字或不带引号的值
+    Word(String),
+    /// 带引号的字符串
+    Str(String),
+    Op(CompareOp),
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::Word(word) => write!(f, "'{}'", word),
+            TokenKind::Str(text) => write!(f, "string '{}'", text),
+            TokenKind::Op(op) => write!(f, "'{}'", op.symbol()),
+            TokenKind::LParen => write!(f, "'('"),
+            TokenKind::RParen => write!(f, "')'"),
+        }
+    }
+}
+
+/// 词法单元及其在查询中的字节偏移
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+}
+
+/// 不带引号的值和标识符中允许的字符
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '+' | '%' | '*' | '?' | '/' | ':' | '~')
+}
+
+/// 将查询拆分为词法单元
+fn tokenize(query: &str) -> FindResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        let kind = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => {
+                chars.next();
+                TokenKind::LParen
+            }
+            ')' => {
+                chars.next();
+                TokenKind::RParen
+            }
+            '=' | '!' | '<' | '>' => {
+                chars.next();
+                let second = chars.peek().map(|&(_, c)| c);
+                let (op, consumed) = match (c, second) {
+                    ('=', _) => (CompareOp::Eq, false),
+                    ('!', Some('=')) => (CompareOp::Ne, true),
+                    ('<', Some('>')) => (CompareOp::Ne, true),
+                    ('<', Some('=')) => (CompareOp::Le, true),
+                    ('<', _) => (CompareOp::Lt, false),
+                    ('>', Some('=')) => (CompareOp::Ge, true),
+                    ('>', _) => (CompareOp::Gt, false),
+                    _ => return Err(syntax_error(query, start, "expected '!='".to_string())),
+                };
+                if consumed {
+                    chars.next();
+                }
+                TokenKind::Op(op)
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, q)) if q == c => {
+                            // 连续两个引号表示引号本身
+                            if chars.peek().is_some_and(|&(_, next)| next == c) {
+                                chars.next();
+                                text.push(c);
+                            } else {
+                                break;
+                            }
+                        }
+                        Some((_, other)) => text.push(other),
+                        None => return Err(syntax_error(query, start, "unterminated string".to_string())),
+                    }
+                }
+                TokenKind::Str(text)
+            }
+            c if is_word_char(c) => {
+                let mut word = String::new();
+                while let Some(&(_, c)) = chars.peek().filter(|&&(_, c)| is_word_char(c)) {
+                    word.push(c);
+                    chars.next();
+                }
+                TokenKind::Word(word)
+            }
+            other => return Err(syntax_error(query, start, format!("unexpected character '{}'", other))),
+        };
+        tokens.push(Token { kind, start });
+    }
+    Ok(tokens)
+}
+
+/// 构造标出出错位置的解析错误
+fn syntax_error(query: &str, offset: usize, message: String) -> FindError {
+    let column = query[..offset].chars().count();
+    FindError::PatternError {
+        message: format!(
+            "Invalid query: {} at column {}\n  {}\n  {}^",
+            message,
+            column + 1,
+            query,
+            " ".repeat(column)
+        ),
+    }
+}
+
+/// 递归下降解析器
+struct Parser<'a> {
+    query: &'a str,
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.next).cloned();
+        self.next += 1;
+        token
+    }
+
+    /// 下一个词法单元是否为指定的关键字（不区分大小写），是则消耗它
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(
+            self.peek(),
+            Some(Token { kind: TokenKind::Word(word), .. }) if word.eq_ignore_ascii_case(keyword)
+        );
+        if found {
+            self.next += 1;
+        }
+        found
+    }
+
+    fn error_at(&self, offset: usize, message: String) -> FindError {
+        syntax_error(self.query, offset, message)
+    }
+
+    /// 在下一个词法单元处（或查询末尾）报告错误
+    fn error_here(&self, expected: &str) -> FindError {
+        match self.peek() {
+            Some(token) => self.error_at(token.start, format!("expected {}, found {}", expected, token.kind)),
+            None => self.error_at(self.query.len(), format!("expected {}, found end of query", expected)),
+        }
+    }
+
+    /// expression := and ( OR and )*
+    fn expression(&mut self) -> FindResult<Expr> {
+        let mut operands = vec![self.conjunction()?];
+        while self.keyword("OR") {
+            operands.push(self.conjunction()?);
+        }
+        Ok(if operands.len() == 1 { operands.remove(0) } else { Expr::Or(operands) })
+    }
+
+    /// and := unary ( AND unary )*
+    fn conjunction(&mut self) -> FindResult<Expr> {
+        let mut operands = vec![self.unary()?];
+        while self.keyword("AND") {
+            operands.push(self.unary()?);
+        }
+        Ok(if operands.len() == 1 { operands.remove(0) } else { Expr::And(operands) })
+    }
+
+    /// unary := NOT unary | '(' expression ')' | comparison
+    fn unary(&mut self) -> FindResult<Expr> {
+        if self.keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if matches!(self.peek(), Some(Token { kind: TokenKind::LParen, .. })) {
+            self.next += 1;
+            let expr = self.expression()?;
+            match self.peek() {
+                Some(Token { kind: TokenKind::RParen, .. }) => {
+                    self.next += 1;
+                    Ok(expr)
+                }
+                _ => Err(self.error_here("')'")),
+            }
+        } else {
+            self.comparison()
+        }
+    }
+
+    /// comparison := field operator value
+    fn comparison(&mut self) -> FindResult<Expr> {
+        let (field, field_start) = match self.peek() {
+            Some(Token { kind: TokenKind::Word(word), start }) => (word.to_ascii_lowercase(), *start),
+            _ => return Err(self.error_here("a field name")),
+        };
+        self.next += 1;
+
+        let operator_start = self.peek().map_or(self.query.len(), |token| token.start);
+        let operator = if let Some(Token { kind: TokenKind::Op(op), .. }) = self.peek() {
+            let op = *op;
+            self.next += 1;
+            Operator::Compare(op)
+        } else {
+            let negated = self.keyword("NOT");
+            if self.keyword("LIKE") {
+                Operator::Like { ignore_case: false, negated }
+            } else if self.keyword("ILIKE") {
+                Operator::Like { ignore_case: true, negated }
+            } else {
+                return Err(self.error_here("a comparison operator (=, !=, <, <=, >, >=, LIKE, ILIKE)"));
+            }
+        };
+
+        let (value, value_start) = match self.advance() {
+            Some(Token { kind: TokenKind::Word(text) | TokenKind::Str(text), start }) => (text, start),
+            Some(token) => {
+                return Err(self.error_at(token.start, format!("expected a value, found {}", token.kind)))
+            }
+            None => return Err(self.error_at(self.query.len(), "expected a value, found end of query".to_string())),
+        };
+
+        let unsupported = || {
+            self.error_at(
+                operator_start,
+                format!("operator {} is not supported for field '{}'", operator, field),
+            )
+        };
+        let invalid_value = |e: FindError| self.error_at(value_start, invalid_value_message(&field, &value, e));
+
+        let test: Box<dyn FileFilter + Send + Sync> = match field.as_str() {
+            "name" | "path" => {
+                let (pattern, ignore_case, negated) = match operator {
+                    Operator::Compare(CompareOp::Eq) => (Pattern::escape(&value), false, false),
+                    Operator::Compare(CompareOp::Ne) => (Pattern::escape(&value), false, true),
+                    Operator::Like { ignore_case, negated } => (like_to_glob(&value), ignore_case, negated),
+                    Operator::Compare(_) => return Err(unsupported()),
+                };
+                let test = if field == "name" {
+                    if ignore_case {
+                        NameFilter::new_ignore_case(&pattern)
+                    } else {
+                        NameFilter::new(&pattern)
+                    }
+                    .map(|filter| Box::new(filter) as Box<dyn FileFilter + Send + Sync>)
+                } else if ignore_case {
+                    return Err(unsupported());
+                } else {
+                    PathFilter::new(&pattern)
+                        .map(|filter| Box::new(filter.with_literal_separator(false)) as Box<dyn FileFilter + Send + Sync>)
+                };
+                let test = Expr::Test(test.map_err(invalid_value)?);
+                return Ok(if negated { Expr::Not(Box::new(test)) } else { test });
+            }
+            "type" => {
+                let negated = match operator {
+                    Operator::Compare(CompareOp::Eq) => false,
+                    Operator::Compare(CompareOp::Ne) => true,
+                    _ => return Err(unsupported()),
+                };
+                let code = type_code(&value).ok_or_else(|| {
+                    self.error_at(
+                        value_start,
+                        format!(
+                            "unknown file type '{}', expected file, dir, symlink, block, char, fifo or socket",
+                            value
+                        ),
+                    )
+                })?;
+                let test = Expr::Test(Box::new(TypeFilter::new(code).map_err(invalid_value)?));
+                return Ok(if negated { Expr::Not(Box::new(test)) } else { test });
+            }
+            "size" | "links" | "depth" => {
+                let Operator::Compare(op) = operator else {
+                    return Err(unsupported());
+                };
+                let (field, value) = match field.as_str() {
+                    "size" => (NumericField::Size, value.parse::<ByteSize>().map_err(invalid_value)?.bytes()),
+                    "links" => (NumericField::Links, parse_number(&value).map_err(invalid_value)?),
+                    _ => (NumericField::Depth, parse_number(&value).map_err(invalid_value)?),
+                };
+                Box::new(NumericTest { field, op, value })
+            }
+            "mtime" | "atime" => {
+                let Operator::Compare(op) = operator else {
+                    return Err(unsupported());
+                };
+                let field = if field == "mtime" { TimeField::Modified } else { TimeField::Accessed };
+                let age = parse_age(&value).map_err(invalid_value)?;
+                Box::new(AgeTest { field, op, age, now: SystemTime::now() })
+            }
+            _ => {
+                return Err(self.error_at(
+                    field_start,
+                    format!(
+                        "unknown field '{}', expected name, path, type, size, links, depth, mtime or atime",
+                        field
+                    ),
+                ))
+            }
+        };
+        Ok(Expr::Test(test))
+    }
+}
+
+/// 比较中的运算符
+#[derive(Debug, Clone, Copy)]
+enum Operator {
+    Compare(CompareOp),
+    Like { ignore_case: bool, negated: bool },
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operator::Compare(op) => write!(f, "'{}'", op.symbol()),
+            Operator::Like { ignore_case, negated } => {
+                let not = if *negated { "NOT " } else { "" };
+                let like = if *ignore_case { "ILIKE" } else { "LIKE" };
+                write!(f, "'{}{}'", not, like)
+            }
+        }
+    }
+}
+
+/// 值无效时的错误消息，保留原始错误中的说明
+fn invalid_value_message(field: &str, value: &str, error: FindError) -> String {
+    let reason = match error {
+        FindError::PatternError { message } => message,
+        other => other.to_string(),
+    };
+    format!("invalid value '{}' for field '{}' ({})", value, field, reason)
+}
+
+/// 将 SQL LIKE 模式转换为 glob 模式：`%` 对应 `*`，`_` 对应 `?`，其他字符按字面匹配
+fn like_to_glob(like: &str) -> String {
+    let mut glob = String::new();
+    for c in like.chars() {
+        match c {
+            '%' => glob.push('*'),
+            '_' => glob.push('?'),
+            '*' | '?' | '[' | ']' => {
+                glob.push('[');
+                glob.push(c);
+                glob.push(']');
+            }
+            c => glob.push(c),
+        }
+    }
+    glob
+}
+
+/// 查询中的类型名称对应的 GNU find 类型代码
+fn type_code(name: &str) -> Option<&'static str> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "f" | "file" => "f",
+        "d" | "dir" | "directory" => "d",
+        "l" | "link" | "symlink" => "l",
+        "b" | "block" => "b",
+        "c" | "char" => "c",
+        "p" | "fifo" => "p",
+        "s" | "socket" => "s",
+        _ => return None,
+    })
+}
+
+/// 解析非负整数
+fn parse_number(value: &str) -> FindResult<u64> {
+    value.parse().map_err(|_| FindError::PatternError {
+        message: "expected a non-negative integer".to_string(),
+    })
+}
+
+/// 解析时长，单位为 s、m（分钟）、h、d、w，没有单位时按天计算
+fn parse_age(value: &str) -> FindResult<Duration> {
+    let invalid = || FindError::PatternError {
+        message: "expected a duration such as 30d, 12h, 15m or 2w".to_string(),
+    };
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    let seconds = match unit.to_ascii_lowercase().as_str() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "" | "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    number.checked_mul(seconds).map(Duration::from_secs).ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn matched(query: &str, root: &std::path::Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let filter = QueryFilter::parse(query)?;
+        Ok(walkdir::WalkDir::new(root)
+            .min_depth(1)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(FindEntry::from)
+            .filter(|entry| filter.matches(entry))
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect())
+    }
+
+    #[test]
+    fn test_query_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        fs::create_dir(dir.path().join("logs"))?;
+        fs::write(dir.path().join("logs/app.log"), "x".repeat(2048))?;
+        fs::write(dir.path().join("logs/small.log"), "x")?;
+        fs::write(dir.path().join("notes_1.txt"), "x".repeat(4096))?;
+
+        assert_eq!(matched("type = file AND size > 1k", dir.path())?, ["app.log", "notes_1.txt"]);
+        assert_eq!(
+            matched("type = file AND (name LIKE '%.log' OR size >= 4K) AND NOT name = 'app.log'", dir.path())?,
+            ["small.log", "notes_1.txt"]
+        );
+        assert_eq!(matched("name like 'NOTES\\_%' or type != f", dir.path())?, ["logs"]);
+        assert_eq!(matched("name ILIKE 'NOTES%' AND depth = 1", dir.path())?, ["notes_1.txt"]);
+        assert_eq!(matched("path LIKE '%/logs/%' AND mtime < 1d", dir.path())?, ["app.log", "small.log"]);
+        assert!(matched("mtime > 30d", dir.path())?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_errors() {
+        let message = |query: &str| match QueryFilter::parse(query) {
+            Err(FindError::PatternError { message }) => message,
+            other => panic!("expected an error for {:?}, got {:?}", query, other.map(|f| f.description())),
+        };
+
+        assert_eq!(
+            message("type = file AND size >"),
+            "Invalid query: expected a value, found end of query at column 23\n  type = file AND size >\n                        ^"
+        );
+        assert!(message("colour = red").starts_with("Invalid query: unknown field 'colour'"));
+        assert!(message("size LIKE '1%'").contains("operator 'LIKE' is not supported for field 'size' at column 6"));
+        assert!(message("type = folder").contains("unknown file type 'folder'"));
+        assert!(message("size > 10Q").contains("invalid value '10Q' for field 'size'"));
+        assert!(message("(name = a").contains("expected ')', found end of query"));
+        assert!(message("name = 'a").contains("unterminated string at column 8"));
+        assert!(message("name = a b").contains("unexpected 'b' at column 10"));
+        assert!(message("name a").contains("expected a comparison operator"));
+    }
+}
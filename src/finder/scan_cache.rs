@@ -0,0 +1,368 @@
+//! 增量扫描缓存
+//!
+//! 记录每个目录的修改时间，以及上次扫描时该目录中的匹配项和子目录。
+//! 再次扫描同一根目录时，修改时间没有变化的目录直接使用缓存的结果，
+//! 不再读取目录内容，适合定期扫描（例如 cron 任务）变化很少的目录树。
+//!
+//! 目录的修改时间只在其中的条目被创建、删除或重命名时改变。
+//! 原地修改文件的内容或属性不会使缓存失效，因此依赖文件大小、时间或内容的过滤条件
+//! 可能返回过时的结果；文件系统时间戳精度较低时，同一时间单位内的修改也可能被遗漏。
+//!
+//! 在 macOS 上，缓存还记录扫描开始时的 FSEvents 事件编号。再次扫描时先读取此后的历史事件，
+//! 只重新读取发生了变化的目录，其余目录连修改时间也不再检查；原地修改文件内容的目录同样会被重新读取。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use super::filter::FileFilter;
+use super::fsevents;
+use super::prune::Pruner;
+use super::Finder;
+
+/// 保存在缓存文件中的扫描结果
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ScanCache {
+    roots: HashMap<String, CachedRoot>,
+}
+
+/// 单个搜索根目录的缓存
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CachedRoot {
+    /// 搜索条件的指纹，条件变化后缓存失效
+    fingerprint: String,
+    /// 以相对于根目录的路径为键的目录缓存
+    dirs: HashMap<PathBuf, CachedDir>,
+    /// 上次扫描开始时的 FSEvents 事件编号（macOS）
+    #[serde(default)]
+    event_id: Option<u64>,
+}
+
+/// 单个目录的缓存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedDir {
+    /// 目录的修改时间（自 UNIX 纪元起的秒数和纳秒数）
+    mtime: (u64, u32),
+    /// 目录中匹配的条目名称
+    matches: Vec<PathBuf>,
+    /// 目录中需要进入的子目录名称
+    subdirs: Vec<PathBuf>,
+}
+
+impl ScanCache {
+    /// 从文件加载缓存，文件不存在或无效时返回空缓存
+    pub fn load(path: &Path) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// 将缓存保存到文件
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_vec(self).map_err(std::io::Error::other)?;
+        fs::write(path, data)
+    }
+
+    /// 取出根目录的目录缓存和记录的事件编号，指纹不一致时返回空缓存
+    fn take_dirs(&mut self, root: &Path, fingerprint: &str) -> (HashMap<PathBuf, CachedDir>, Option<u64>) {
+        match self.roots.remove(&Self::key(root)) {
+            Some(cached) if cached.fingerprint == fingerprint => (cached.dirs, cached.event_id),
+            _ => (HashMap::new(), None),
+        }
+    }
+
+    /// 记录根目录的目录缓存
+    fn store_dirs(
+        &mut self,
+        root: &Path,
+        fingerprint: String,
+        dirs: HashMap<PathBuf, CachedDir>,
+        event_id: Option<u64>,
+    ) {
+        self.roots.insert(Self::key(root), CachedRoot { fingerprint, dirs, event_id });
+    }
+
+    /// 缓存的键：规范化的根目录
+    fn key(root: &Path) -> String {
+        canonical(root).display().to_string()
+    }
+}
+
+impl Finder {
+    /// 使用增量扫描缓存查找文件
+    ///
+    /// 按文件名排序在当前线程中进行深度优先遍历。修改时间与缓存一致的目录
+    /// 直接使用缓存的匹配项和子目录，其余目录被重新读取，结果写回缓存。
+    /// 在 macOS 上可以从 FSEvents 的历史事件得知哪些目录发生了变化时，只重新读取这些目录。
+    /// 搜索条件（过滤器和影响遍历的选项）变化后，该根目录的缓存整体失效。
+    ///
+    /// # 示例
+    /// ```no_run
+    /// use rust_find::finder::{Finder, FindOptions, ScanCache};
+    /// use rust_find::finder::filter::NameFilter;
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// let file = Path::new("scan-cache.json");
+    /// let mut cache = ScanCache::load(file);
+    /// let finder = Finder::new(FindOptions::new());
+    /// let filter = NameFilter::new("*.log").unwrap();
+    /// let results = finder.find_incremental(PathBuf::from("/var/log"), filter, &mut cache);
+    /// cache.save(file).unwrap();
+    /// ```
+    pub fn find_incremental<F>(&self, root: PathBuf, filter: F, cache: &mut ScanCache) -> Vec<PathBuf>
+    where
+        F: FileFilter,
+    {
+        let fingerprint = self.scan_fingerprint(&filter);
+        let (mut cached, since) = cache.take_dirs(&root, &fingerprint);
+        // 在扫描开始前记录事件编号，扫描期间的变化在下次扫描时被发现
+        let event_id = fsevents::current_event_id();
+        let changed = since.and_then(|since| fsevents::changed_dirs(&canonical(&root), since));
+        if let Some(changed) = &changed {
+            debug!("{} directories changed under {} since the last scan", changed.len(), root.display());
+        }
+        let mut scanned = HashMap::new();
+        let mut results = Vec::new();
+        let limit = self.options.max_results.unwrap_or(usize::MAX);
+        let mut pruner = Pruner::new(&self.options, &root);
+
+        let root_entry = WalkDir::new(&root)
+            .follow_links(self.options.symlink_policy.follows_links())
+            .follow_root_links(self.options.symlink_policy.follows_root_links())
+            .max_depth(0)
+            .into_iter()
+            .next()
+            .and_then(Result::ok);
+        let mut stack = Vec::new();
+        if let Some(entry) = root_entry {
+            if self.accepts(&entry, &root, &filter) {
+                results.push(root.clone());
+            }
+            if entry.file_type().is_dir() && self.within_depth(0) {
+                stack.push((PathBuf::new(), 0));
+            }
+        }
+
+        let (mut reused, mut read) = (0, 0);
+        let mut complete = true;
+        while let Some((relative, depth)) = stack.pop() {
+            if results.len() >= limit || self.options.cancel_token.is_cancelled() {
+                complete = false;
+                break;
+            }
+
+            let dir = root.join(&relative);
+            // 有历史事件时，事件中没有出现的目录不再检查修改时间，出现的目录总是重新读取
+            let unchanged = changed.as_ref().is_some_and(|changed| !changed.contains(&relative));
+            let mtime = match cached.get(&relative) {
+                Some(entry) if unchanged => Some(entry.mtime),
+                _ => dir_mtime(&dir),
+            };
+            let entry = match cached.remove(&relative) {
+                Some(entry) if Some(entry.mtime) == mtime && (unchanged || changed.is_none()) => {
+                    reused += 1;
+                    for subdir in &entry.subdirs {
+                        pruner.visit_dir(&dir.join(subdir));
+                    }
+                    entry
+                }
+                _ => {
+                    read += 1;
+                    self.scan_dir(&dir, &root, &filter, &mut pruner, mtime.unwrap_or_default())
+                }
+            };
+
+            results.extend(entry.matches.iter().map(|name| dir.join(name)));
+            if self.within_depth(depth + 1) {
+                for subdir in entry.subdirs.iter().rev() {
+                    stack.push((relative.join(subdir), depth + 1));
+                }
+            }
+            // 无法读取修改时间的目录每次都重新读取
+            if mtime.is_some() {
+                scanned.insert(relative, entry);
+            }
+        }
+        debug!("Incremental scan of {}: {} directories reused, {} read", root.display(), reused, read);
+
+        // 提前结束时保留未访问目录的缓存，完整扫描后丢弃已不存在的目录
+        if !complete {
+            for (relative, entry) in cached {
+                scanned.entry(relative).or_insert(entry);
+            }
+        }
+        cache.store_dirs(&root, fingerprint, scanned, event_id);
+
+        results.truncate(limit);
+        if self.options.contents_first {
+            super::sort_contents_first(&mut results);
+        }
+        results
+    }
+
+    /// 读取目录内容，得到匹配项和需要进入的子目录
+    fn scan_dir<F: FileFilter>(
+        &self,
+        dir: &Path,
+        root: &Path,
+        filter: &F,
+        pruner: &mut Pruner,
+        mtime: (u64, u32),
+    ) -> CachedDir {
+        let mut cached = CachedDir {
+            mtime,
+            matches: Vec::new(),
+            subdirs: Vec::new(),
+        };
+        let entries = WalkDir::new(dir)
+            .follow_links(self.options.symlink_policy.follows_links())
+            .same_file_system(self.options.same_file_system)
+            .min_depth(1)
+            .max_depth(1)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| pruner.keeps(entry));
+
+        for entry in entries {
+            let name = PathBuf::from(entry.file_name());
+            if self.accepts(&entry, root, filter) {
+                cached.matches.push(name.clone());
+            }
+            if entry.file_type().is_dir() {
+                cached.subdirs.push(name);
+            }
+        }
+        cached
+    }
+
+    /// 搜索条件的指纹：过滤器描述和影响遍历结果的选项
+    fn scan_fingerprint<F: FileFilter>(&self, filter: &F) -> String {
+        let options = &self.options;
+        let filters: Vec<String> = self
+            .filters
+            .iter()
+            .chain(&self.content_filters)
+            .map(|filter| filter.description())
+            .chain(std::iter::once(filter.description()))
+            .collect();
+        format!(
+            "{:?}|{:?}|{}|{:?}|{}|{}|{:?}|{:?}|{}|{:?}|{}|{:?}",
+            options.max_depth,
+            options.symlink_policy,
+            options.ignore_hidden,
+            options.hidden_attributes,
+            options.respect_gitignore,
+            options.respect_findignore,
+            options.exclude,
+            options.exclude_dirs,
+            options.same_file_system,
+            options.max_symlink_depth,
+            options.skip_reparse_points,
+            filters,
+        )
+    }
+}
+
+/// 规范化的根目录，无法规范化时原样返回
+fn canonical(root: &Path) -> PathBuf {
+    root.canonicalize().unwrap_or_else(|_| root.to_path_buf())
+}
+
+/// 目录的修改时间
+fn dir_mtime(dir: &Path) -> Option<(u64, u32)> {
+    let modified = fs::metadata(dir).and_then(|metadata| metadata.modified()).ok()?;
+    let since_epoch = modified.duration_since(UNIX_EPOCH).ok()?;
+    Some((since_epoch.as_secs(), since_epoch.subsec_nanos()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finder::filter::NameFilter;
+    use crate::finder::FindOptions;
+    use std::fs::File;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_incremental_reuses_unchanged_dirs() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        fs::create_dir_all(root.join("sub"))?;
+        fs::write(root.join("a.log"), "x")?;
+        fs::write(root.join("sub/b.log"), "x")?;
+        fs::write(root.join("sub/c.txt"), "x")?;
+
+        let finder = Finder::new(FindOptions::new());
+        let mut cache = ScanCache::default();
+        let results = finder.find_incremental(root.clone(), NameFilter::new("*.log")?, &mut cache);
+        assert_eq!(results, vec![root.join("a.log"), root.join("sub/b.log")]);
+
+        // 恢复修改时间后目录被视为未变化，新文件不会被发现
+        let sub_modified = fs::metadata(root.join("sub"))?.modified()?;
+        fs::write(root.join("sub/d.log"), "x")?;
+        File::open(root.join("sub"))?.set_modified(sub_modified)?;
+        let results = finder.find_incremental(root.clone(), NameFilter::new("*.log")?, &mut cache);
+        assert_eq!(results, vec![root.join("a.log"), root.join("sub/b.log")]);
+
+        // 修改时间变化后目录被重新读取
+        fs::remove_file(root.join("sub/c.txt"))?;
+        let results = finder.find_incremental(root.clone(), NameFilter::new("*.log")?, &mut cache);
+        assert_eq!(
+            results,
+            vec![root.join("a.log"), root.join("sub/b.log"), root.join("sub/d.log")]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_incremental_invalidates_on_new_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        fs::create_dir(&root)?;
+        fs::write(root.join("a.log"), "x")?;
+        fs::write(root.join("b.txt"), "x")?;
+
+        let finder = Finder::new(FindOptions::new());
+        let mut cache = ScanCache::default();
+        let results = finder.find_incremental(root.clone(), NameFilter::new("*.log")?, &mut cache);
+        assert_eq!(results, vec![root.join("a.log")]);
+        let results = finder.find_incremental(root.clone(), NameFilter::new("*.txt")?, &mut cache);
+        assert_eq!(results, vec![root.join("b.txt")]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_cache_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        fs::create_dir(&root)?;
+        fs::write(root.join("a.log"), "x")?;
+        let file = dir.path().join("cache").join("scan.json");
+
+        let finder = Finder::new(FindOptions::new());
+        let mut cache = ScanCache::load(&file);
+        finder.find_incremental(root.clone(), NameFilter::new("*.log")?, &mut cache);
+        cache.save(&file)?;
+
+        let mut loaded = ScanCache::load(&file);
+        let fingerprint = finder.scan_fingerprint(&NameFilter::new("*.log")?);
+        assert!(loaded.take_dirs(&root, &fingerprint).0.contains_key(Path::new("")));
+
+        fs::write(&file, "not json")?;
+        assert!(ScanCache::load(&file).roots.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,599 @@
+//! 安全相关的文件属性过滤器
+//!
+//! 读取 Linux 上以扩展属性形式保存的安全信息：
+//! - POSIX ACL（`system.posix_acl_access` / `system.posix_acl_default`）
+//! - 文件能力（`security.capability`）
+//! - chattr 风格的 inode 标志（`FS_IOC_GETFLAGS`）
+//!
+//! 不支持扩展属性的平台和文件系统上，条目视为没有这些信息。
+
+use clap::ValueEnum;
+use log::debug;
+
+use crate::errors::{FindError, FindResult};
+use crate::output::meta;
+use super::entry::FindEntry;
+use super::filter::{FileFilter, FilterCost};
+
+/// 保存访问 ACL 的扩展属性
+const ACL_ACCESS_XATTR: &str = "system.posix_acl_access";
+
+/// 保存目录默认 ACL 的扩展属性
+const ACL_DEFAULT_XATTR: &str = "system.posix_acl_default";
+
+/// 扩展属性中 ACL 格式的版本号
+const ACL_XATTR_VERSION: u32 = 2;
+
+/// 保存文件能力的扩展属性
+const CAPABILITY_XATTR: &str = "security.capability";
+
+/// 文件能力格式的版本号掩码（`magic_etc` 的高 8 位）
+const VFS_CAP_REVISION_MASK: u32 = 0xFF00_0000;
+
+/// 能力在执行时直接生效的标记
+const VFS_CAP_FLAGS_EFFECTIVE: u32 = 0x0000_0001;
+
+/// 按编号排列的能力名称（不含 `cap_` 前缀）
+const CAPABILITY_NAMES: [&str; 41] = [
+    "chown", "dac_override", "dac_read_search", "fowner", "fsetid", "kill", "setgid", "setuid",
+    "setpcap", "linux_immutable", "net_bind_service", "net_broadcast", "net_admin", "net_raw",
+    "ipc_lock", "ipc_owner", "sys_module", "sys_rawio", "sys_chroot", "sys_ptrace", "sys_pacct",
+    "sys_admin", "sys_boot", "sys_nice", "sys_resource", "sys_time", "sys_tty_config", "mknod",
+    "lease", "audit_write", "audit_control", "setfcap", "mac_override", "mac_admin", "syslog",
+    "wake_alarm", "block_suspend", "audit_read", "perfmon", "bpf", "checkpoint_restore",
+];
+
+/// ACL 条目的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AclTag {
+    /// 文件属主（`user::`）
+    UserObj,
+    /// 指定用户（`user:NAME:`）
+    User,
+    /// 文件属组（`group::`）
+    GroupObj,
+    /// 指定组（`group:NAME:`）
+    Group,
+    /// 权限上限（`mask::`）
+    Mask,
+    /// 其他用户（`other::`）
+    Other,
+}
+
+impl AclTag {
+    /// 从扩展属性中的标记值解析
+    fn from_raw(tag: u16) -> Option<Self> {
+        Some(match tag {
+            0x01 => AclTag::UserObj,
+            0x02 => AclTag::User,
+            0x04 => AclTag::GroupObj,
+            0x08 => AclTag::Group,
+            0x10 => AclTag::Mask,
+            0x20 => AclTag::Other,
+            _ => return None,
+        })
+    }
+
+    /// 条目是否带有用户或组 ID
+    fn has_qualifier(self) -> bool {
+        matches!(self, AclTag::User | AclTag::Group)
+    }
+}
+
+/// 单个 ACL 条目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AclEntry {
+    /// 条目类型
+    pub tag: AclTag,
+    /// 指定用户或组的 ID，其他类型为 None
+    pub id: Option<u32>,
+    /// 权限位：读 4、写 2、执行 1
+    pub perms: u8,
+}
+
+/// 解析扩展属性中保存的 ACL
+///
+/// 格式为 4 字节的版本号，之后每个条目 8 字节（类型、权限各 2 字节，ID 4 字节），
+/// 均为小端序。格式不正确时返回 None。
+pub fn parse_acl(bytes: &[u8]) -> Option<Vec<AclEntry>> {
+    let (header, entries) = bytes.split_first_chunk::<4>()?;
+    if u32::from_le_bytes(*header) != ACL_XATTR_VERSION || !entries.len().is_multiple_of(8) {
+        return None;
+    }
+    entries
+        .chunks_exact(8)
+        .map(|chunk| {
+            let tag = AclTag::from_raw(u16::from_le_bytes([chunk[0], chunk[1]]))?;
+            let perms = (u16::from_le_bytes([chunk[2], chunk[3]]) & 0o7) as u8;
+            let id = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+            Some(AclEntry { tag, id: tag.has_qualifier().then_some(id), perms })
+        })
+        .collect()
+}
+
+/// 读取条目保存在扩展属性中的 ACL，没有时返回 None
+fn read_acl(entry: &FindEntry, name: &str) -> Option<Vec<AclEntry>> {
+    if !entry.is_local() {
+        return None;
+    }
+    let follow = !entry.kind().is_symlink();
+    let bytes = meta::xattr(entry.path(), name, follow).ok().flatten()?;
+    parse_acl(&bytes)
+}
+
+/// 条目的访问 ACL
+///
+/// 没有保存 ACL 的条目按权限位得到最小 ACL（属主、属组和其他用户三个条目）。
+fn access_acl(entry: &FindEntry) -> Option<Vec<AclEntry>> {
+    if let Some(acl) = read_acl(entry, ACL_ACCESS_XATTR) {
+        return Some(acl);
+    }
+    let mode = meta::mode_bits(entry.metadata().ok()?);
+    let base = |tag, shift: u32| AclEntry { tag, id: None, perms: ((mode >> shift) & 0o7) as u8 };
+    Some(vec![
+        base(AclTag::UserObj, 6),
+        base(AclTag::GroupObj, 3),
+        base(AclTag::Other, 0),
+    ])
+}
+
+/// 扩展 ACL 过滤器
+///
+/// 匹配带有扩展 ACL 的条目：访问 ACL 中有指定用户、指定组或权限上限条目，
+/// 或者目录带有默认 ACL。这些条目的实际权限可能与权限位显示的不同，
+/// 即 `ls -l` 在权限后显示 `+` 的条目。
+pub struct HasAclFilter;
+
+impl FileFilter for HasAclFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        let extended = read_acl(entry, ACL_ACCESS_XATTR).is_some_and(|acl| {
+            acl.iter()
+                .any(|entry| matches!(entry.tag, AclTag::User | AclTag::Group | AclTag::Mask))
+        });
+        extended
+            || (entry.kind().is_dir()
+                && read_acl(entry, ACL_DEFAULT_XATTR).is_some_and(|acl| !acl.is_empty()))
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Read
+    }
+
+    fn description(&self) -> String {
+        "has an extended POSIX ACL".to_string()
+    }
+}
+
+/// ACL 条目过滤器
+///
+/// 按 `setfacl` 的条目格式匹配访问 ACL 中的条目：`user:bob:rwx`、`group:dev:r-x`、
+/// `user::rw-`、`mask::r-x`、`other::---`。类型可以简写为 `u`、`g`、`m`、`o`；
+/// 省略权限时只要求条目存在，给出权限时要求权限完全相同。
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::security::AclEntryFilter;
+///
+/// // 匹配 ACL 中给予 UID 1000 读写权限的文件
+/// let filter = AclEntryFilter::new("user:1000:rw-").unwrap();
+/// ```
+pub struct AclEntryFilter {
+    spec: String,
+    tag: AclTag,
+    id: Option<u32>,
+    perms: Option<u8>,
+}
+
+impl AclEntryFilter {
+    /// 根据条目格式创建过滤器
+    ///
+    /// # 错误
+    /// 如果格式不正确或用户名、组名不存在，返回PatternError错误
+    pub fn new(spec: &str) -> FindResult<Self> {
+        let invalid = |reason: &str| FindError::PatternError {
+            message: format!("Invalid ACL entry '{}': {}", spec, reason),
+        };
+
+        let mut parts = spec.splitn(3, ':');
+        let tag = parts.next().unwrap_or_default();
+        let qualifier = parts.next().ok_or_else(|| invalid("expected TYPE:QUALIFIER[:PERMS]"))?;
+        let perms = parts.next();
+
+        let (obj_tag, named_tag) = match tag {
+            "user" | "u" => (AclTag::UserObj, Some(AclTag::User)),
+            "group" | "g" => (AclTag::GroupObj, Some(AclTag::Group)),
+            "mask" | "m" => (AclTag::Mask, None),
+            "other" | "o" => (AclTag::Other, None),
+            _ => return Err(invalid("type must be user, group, mask or other")),
+        };
+        let (tag, id) = match (qualifier, named_tag) {
+            ("", _) => (obj_tag, None),
+            (_, None) => return Err(invalid("mask and other entries take no qualifier")),
+            (name, Some(AclTag::User)) => {
+                let uid = meta::user_id(name).ok_or_else(|| invalid("unknown user"))?;
+                (AclTag::User, Some(uid))
+            }
+            (name, Some(named)) => {
+                let gid = meta::group_id(name).ok_or_else(|| invalid("unknown group"))?;
+                (named, Some(gid))
+            }
+        };
+
+        let perms = perms.map(parse_perms).transpose().map_err(|_| invalid("permissions must use r, w, x and -"))?;
+        Ok(Self { spec: spec.to_string(), tag, id, perms })
+    }
+}
+
+impl FileFilter for AclEntryFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        access_acl(entry).is_some_and(|acl| {
+            acl.iter().any(|acl_entry| {
+                acl_entry.tag == self.tag
+                    && acl_entry.id == self.id
+                    && self.perms.is_none_or(|perms| perms == acl_entry.perms)
+            })
+        })
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Read
+    }
+
+    fn description(&self) -> String {
+        format!("has ACL entry '{}'", self.spec)
+    }
+}
+
+/// 文件能力
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FileCapabilities {
+    /// 允许集合，按能力编号的位图
+    pub permitted: u64,
+    /// 可继承集合，按能力编号的位图
+    pub inheritable: u64,
+    /// 执行时是否直接生效（`setcap` 中的 `+e`）
+    pub effective: bool,
+    /// 用户命名空间中的根用户 ID（第 3 版格式）
+    pub root_id: Option<u32>,
+}
+
+impl FileCapabilities {
+    /// 是否没有任何能力（允许和可继承集合都为空）
+    pub fn is_empty(&self) -> bool {
+        self.permitted == 0 && self.inheritable == 0
+    }
+
+    /// 是否在允许或可继承集合中带有指定编号的能力
+    pub fn contains(&self, capability: u32) -> bool {
+        let bit = 1u64.checked_shl(capability).unwrap_or(0);
+        (self.permitted | self.inheritable) & bit != 0
+    }
+}
+
+/// 解析 `security.capability` 扩展属性
+///
+/// 支持第 1 版（32 位集合）、第 2 版（64 位集合）和第 3 版（额外带有根用户 ID）格式，
+/// 格式不正确时返回 None。
+pub fn parse_capabilities(bytes: &[u8]) -> Option<FileCapabilities> {
+    if !bytes.len().is_multiple_of(4) {
+        return None;
+    }
+    let words: Vec<u32> = bytes
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect();
+    let (&magic, data) = words.split_first()?;
+    let (sets, root_id) = match (magic & VFS_CAP_REVISION_MASK, data.len()) {
+        (0x0100_0000, 2) => (1, None),
+        (0x0200_0000, 4) => (2, None),
+        (0x0300_0000, 5) => (2, Some(data[4])),
+        _ => return None,
+    };
+
+    let mut caps = FileCapabilities {
+        effective: magic & VFS_CAP_FLAGS_EFFECTIVE != 0,
+        root_id,
+        ..FileCapabilities::default()
+    };
+    for index in 0..sets {
+        caps.permitted |= u64::from(data[index * 2]) << (32 * index);
+        caps.inheritable |= u64::from(data[index * 2 + 1]) << (32 * index);
+    }
+    Some(caps)
+}
+
+/// 按名称查找能力编号
+///
+/// 接受 `cap_net_raw`、`net_raw`（不区分大小写）或数字形式。
+pub fn capability_number(name: &str) -> Option<u32> {
+    let name = name.to_ascii_lowercase();
+    if let Ok(number) = name.parse::<u32>() {
+        return (number < 64).then_some(number);
+    }
+    let name = name.strip_prefix("cap_").unwrap_or(&name);
+    CAPABILITY_NAMES
+        .iter()
+        .position(|known| *known == name)
+        .map(|index| index as u32)
+}
+
+/// 读取条目的文件能力，没有时返回 None
+fn read_capabilities(entry: &FindEntry) -> Option<FileCapabilities> {
+    // 符号链接本身不能带有能力
+    if entry.kind().is_symlink() || !entry.is_local() {
+        return None;
+    }
+    let bytes = meta::xattr(entry.path(), CAPABILITY_XATTR, true).ok().flatten()?;
+    parse_capabilities(&bytes)
+}
+
+/// 带有文件能力的条目过滤器
+///
+/// 匹配设置了文件能力（`setcap`）的文件，这些程序执行时无需 setuid 即可获得特权。
+pub struct HasCapsFilter;
+
+impl FileFilter for HasCapsFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        read_capabilities(entry).is_some_and(|caps| !caps.is_empty())
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Read
+    }
+
+    fn description(&self) -> String {
+        "has file capabilities".to_string()
+    }
+}
+
+/// 指定能力过滤器
+///
+/// 匹配允许或可继承集合中带有指定能力的文件。
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::security::CapFilter;
+///
+/// let filter = CapFilter::new("cap_net_raw").unwrap();
+/// assert!(CapFilter::new("cap_unknown").is_err());
+/// ```
+pub struct CapFilter {
+    capability: u32,
+}
+
+impl CapFilter {
+    /// 按能力名称创建过滤器
+    ///
+    /// # 错误
+    /// 如果能力名称未知，返回PatternError错误
+    pub fn new(name: &str) -> FindResult<Self> {
+        let capability = capability_number(name).ok_or_else(|| FindError::PatternError {
+            message: format!("Unknown capability '{}'", name),
+        })?;
+        Ok(Self { capability })
+    }
+}
+
+impl FileFilter for CapFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        read_capabilities(entry).is_some_and(|caps| caps.contains(self.capability))
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Read
+    }
+
+    fn description(&self) -> String {
+        match CAPABILITY_NAMES.get(self.capability as usize) {
+            Some(name) => format!("has capability cap_{}", name),
+            None => format!("has capability {}", self.capability),
+        }
+    }
+}
+
+/// chattr 风格的 inode 标志
+///
+/// 可以使用 `chattr` 的单字母形式，例如 `i` 表示 immutable。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InodeFlag {
+    /// 不可修改、删除或重命名（`chattr +i`）
+    #[value(alias = "i")]
+    Immutable,
+    /// 只能追加写入（`chattr +a`）
+    #[value(alias = "append", alias = "a")]
+    AppendOnly,
+    /// 不被 dump 备份（`chattr +d`）
+    #[value(alias = "d")]
+    Nodump,
+    /// 访问时不更新访问时间（`chattr +A`）
+    #[value(alias = "A")]
+    Noatime,
+    /// 同步写入（`chattr +S`）
+    #[value(alias = "S")]
+    Sync,
+    /// 目录同步更新（`chattr +D`）
+    #[value(alias = "D")]
+    Dirsync,
+    /// 透明压缩（`chattr +c`）
+    #[value(alias = "c")]
+    Compressed,
+    /// 禁用写时复制（`chattr +C`）
+    #[value(alias = "C")]
+    Nocow,
+    /// 目录中的文件名不区分大小写（`chattr +F`）
+    #[value(alias = "F")]
+    Casefold,
+}
+
+impl InodeFlag {
+    /// 对应的 `FS_*_FL` 标志位
+    fn mask(self) -> u32 {
+        match self {
+            InodeFlag::Compressed => 0x0000_0004,
+            InodeFlag::Sync => 0x0000_0008,
+            InodeFlag::Immutable => 0x0000_0010,
+            InodeFlag::AppendOnly => 0x0000_0020,
+            InodeFlag::Nodump => 0x0000_0040,
+            InodeFlag::Noatime => 0x0000_0080,
+            InodeFlag::Dirsync => 0x0001_0000,
+            InodeFlag::Nocow => 0x0080_0000,
+            InodeFlag::Casefold => 0x4000_0000,
+        }
+    }
+}
+
+/// inode 标志过滤器
+///
+/// 匹配设置了指定 inode 标志的普通文件和目录，例如查找无法删除的不可修改文件。
+/// 读取标志需要打开文件，没有读取权限的文件不匹配。只支持 Linux。
+pub struct InodeFlagFilter {
+    flag: InodeFlag,
+}
+
+impl InodeFlagFilter {
+    /// 创建新的 inode 标志过滤器
+    pub fn new(flag: InodeFlag) -> Self {
+        Self { flag }
+    }
+}
+
+impl FileFilter for InodeFlagFilter {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        // 打开设备文件或管道可能产生副作用，只检查普通文件和目录
+        let kind = entry.kind();
+        if (!kind.is_file() && !kind.is_dir()) || !entry.is_local() {
+            return false;
+        }
+        match meta::inode_flags(entry.path()) {
+            Ok(flags) => flags.is_some_and(|flags| flags & self.flag.mask() != 0),
+            Err(e) => {
+                debug!("Failed to read inode flags of {}: {}", entry.path().display(), e);
+                false
+            }
+        }
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Read
+    }
+
+    fn description(&self) -> String {
+        let name = self
+            .flag
+            .to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default();
+        format!("has inode flag {}", name)
+    }
+}
+
+/// 解析 `rwx` 形式的权限，`-` 表示没有对应权限
+fn parse_perms(perms: &str) -> Result<u8, ()> {
+    perms.chars().try_fold(0, |bits, c| match c {
+        'r' => Ok(bits | 4),
+        'w' => Ok(bits | 2),
+        'x' => Ok(bits | 1),
+        '-' => Ok(bits),
+        _ => Err(()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    /// 按扩展属性格式编码 ACL
+    fn encode(entries: &[(u16, u16, u32)]) -> Vec<u8> {
+        let mut bytes = ACL_XATTR_VERSION.to_le_bytes().to_vec();
+        for (tag, perms, id) in entries {
+            bytes.extend(tag.to_le_bytes());
+            bytes.extend(perms.to_le_bytes());
+            bytes.extend(id.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_parse_acl() {
+        let bytes = encode(&[(0x01, 6, u32::MAX), (0x02, 7, 1000), (0x10, 5, u32::MAX), (0x20, 4, u32::MAX)]);
+        let acl = parse_acl(&bytes).unwrap();
+        assert_eq!(acl.len(), 4);
+        assert_eq!(acl[0], AclEntry { tag: AclTag::UserObj, id: None, perms: 6 });
+        assert_eq!(acl[1], AclEntry { tag: AclTag::User, id: Some(1000), perms: 7 });
+
+        assert!(parse_acl(&bytes[..bytes.len() - 1]).is_none());
+        assert!(parse_acl(&[1, 0, 0, 0]).is_none());
+    }
+
+    #[test]
+    fn test_acl_entry_spec() {
+        assert!(AclEntryFilter::new("user:1000:rwx").is_ok());
+        assert!(AclEntryFilter::new("g::r-x").is_ok());
+        assert!(AclEntryFilter::new("mask:1000:r").is_err());
+        assert!(AclEntryFilter::new("owner::rwx").is_err());
+        assert!(AclEntryFilter::new("user::rwz").is_err());
+        assert!(AclEntryFilter::new("user").is_err());
+        assert_eq!(parse_perms("r-x"), Ok(5));
+    }
+
+    #[test]
+    fn test_parse_capabilities() {
+        // setcap cap_net_raw,cap_net_admin+ep 的第 2 版格式
+        let words = [0x0200_0001u32, (1 << 13) | (1 << 12), 0, 0, 0];
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        let caps = parse_capabilities(&bytes).unwrap();
+        assert!(caps.effective);
+        assert!(caps.contains(capability_number("cap_net_raw").unwrap()));
+        assert!(caps.contains(capability_number("NET_ADMIN").unwrap()));
+        assert!(!caps.contains(capability_number("sys_admin").unwrap()));
+        assert_eq!(caps.root_id, None);
+
+        // 第 3 版格式带有根用户 ID，高 32 位的能力位于第二组
+        let words = [0x0300_0000u32, 0, 0, 1 << (38 - 32), 0, 100_000];
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        let caps = parse_capabilities(&bytes).unwrap();
+        assert!(caps.contains(capability_number("perfmon").unwrap()));
+        assert_eq!(caps.root_id, Some(100_000));
+
+        assert!(parse_capabilities(&bytes[..bytes.len() - 4]).is_none());
+        assert_eq!(capability_number("38"), Some(38));
+        assert_eq!(capability_number("cap_bogus"), None);
+    }
+
+    #[test]
+    fn test_inode_flag_names() {
+        assert_eq!(InodeFlag::from_str("i", false), Ok(InodeFlag::Immutable));
+        assert_eq!(InodeFlag::from_str("append", false), Ok(InodeFlag::AppendOnly));
+        assert_eq!(InodeFlag::from_str("nodump", false), Ok(InodeFlag::Nodump));
+        assert_eq!(
+            InodeFlagFilter::new(InodeFlag::AppendOnly).description(),
+            "has inode flag append-only"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_acl_from_mode_bits() -> Result<(), Box<dyn std::error::Error>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir()?;
+        let file = dir.path().join("plain.txt");
+        fs::write(&file, "x")?;
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o640))?;
+        let entry = FindEntry::from_path(&file, false)?;
+
+        // 没有扩展 ACL 的文件按权限位匹配基本条目
+        assert!(!HasAclFilter.matches(&entry));
+        assert!(AclEntryFilter::new("user::rw-")?.matches(&entry));
+        assert!(AclEntryFilter::new("group::r")?.matches(&entry));
+        assert!(AclEntryFilter::new("other::---")?.matches(&entry));
+        assert!(!AclEntryFilter::new("other::r--")?.matches(&entry));
+        assert!(!AclEntryFilter::new("user:0")?.matches(&entry));
+        assert!(!HasCapsFilter.matches(&entry));
+        assert!(!InodeFlagFilter::new(InodeFlag::Immutable).matches(&entry));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,355 @@
+//! 通过 SFTP 搜索远程目录（`sftp://user@host:port/path`）
+//!
+//! 远程服务器上只需要 SSH 服务，不需要安装任何程序。目录列表和文件属性通过 SFTP 读取，
+//! 内容过滤器需要时才下载文件内容。连接需要 sftp 特性（`cargo build --features sftp`）。
+//!
+//! 服务器的主机密钥必须已记录在 `~/.ssh/known_hosts` 中；依次尝试 ssh-agent、
+//! `~/.ssh` 中的默认私钥和环境变量 `RUST_FIND_SFTP_PASSWORD` 中的密码进行认证。
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// SFTP 地址前缀
+const SCHEME: &str = "sftp://";
+
+/// 默认的 SSH 端口
+const DEFAULT_PORT: u16 = 22;
+
+/// 解析后的 SFTP 地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SftpUrl {
+    user: Option<String>,
+    host: String,
+    port: u16,
+    path: PathBuf,
+}
+
+impl SftpUrl {
+    /// 解析 `sftp://[user@]host[:port][/path]`，不是 SFTP 地址或地址无效时返回 None
+    ///
+    /// 以 `/~` 开头的路径相对于远程用户的主目录，没有路径时搜索主目录。
+    /// IPv6 地址需要写在方括号中。
+    ///
+    /// # 示例
+    /// ```
+    /// use rust_find::finder::sftp::SftpUrl;
+    /// use std::path::Path;
+    ///
+    /// let url = SftpUrl::parse("sftp://admin@example.com:2222/var/log").unwrap();
+    /// assert_eq!(url.host(), "example.com");
+    /// assert_eq!(url.port(), 2222);
+    /// assert_eq!(url.path(), Path::new("/var/log"));
+    /// assert!(SftpUrl::parse("/var/log").is_none());
+    /// ```
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix(SCHEME)?;
+        let (authority, path) = match rest.find('/') {
+            Some(slash) => (&rest[..slash], &rest[slash..]),
+            None => (rest, "/~"),
+        };
+        // SFTP 服务器按用户的主目录解析相对路径
+        let path = match path.strip_prefix("/~") {
+            Some("") | Some("/") => PathBuf::from("."),
+            Some(relative) if relative.starts_with('/') => PathBuf::from(&relative[1..]),
+            _ => PathBuf::from(path),
+        };
+        let (user, address) = match authority.rsplit_once('@') {
+            Some((user, address)) => (Some(user.to_string()).filter(|user| !user.is_empty()), address),
+            None => (None, authority),
+        };
+        let (host, port) = if let Some(bracketed) = address.strip_prefix('[') {
+            let (host, rest) = bracketed.split_once(']')?;
+            (host, rest.strip_prefix(':'))
+        } else {
+            match address.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (address, None),
+            }
+        };
+        let port = match port {
+            Some(port) => port.parse().ok()?,
+            None => DEFAULT_PORT,
+        };
+        if host.is_empty() {
+            return None;
+        }
+        Some(Self {
+            user,
+            host: host.to_string(),
+            port,
+            path,
+        })
+    }
+
+    /// 登录用户，地址中没有给出时为 None（使用本地用户名）
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    /// 主机名或地址
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// SSH 端口
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// 远程搜索路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 远程路径在结果中的形式：完整的 SFTP 地址，相对路径写作 `/~/…`
+    pub fn join(&self, remote: &Path) -> PathBuf {
+        let mut url = std::ffi::OsString::from(self.authority());
+        if remote.has_root() {
+            url.push(remote.as_os_str());
+        } else {
+            url.push("/~");
+            let relative = remote.strip_prefix(".").unwrap_or(remote);
+            if !relative.as_os_str().is_empty() {
+                url.push("/");
+                url.push(relative.as_os_str());
+            }
+        }
+        PathBuf::from(url)
+    }
+
+    /// `sftp://[user@]host[:port]`
+    fn authority(&self) -> String {
+        let mut authority = SCHEME.to_string();
+        if let Some(user) = &self.user {
+            authority.push_str(user);
+            authority.push('@');
+        }
+        if self.host.contains(':') {
+            authority.push_str(&format!("[{}]", self.host));
+        } else {
+            authority.push_str(&self.host);
+        }
+        if self.port != DEFAULT_PORT {
+            authority.push_str(&format!(":{}", self.port));
+        }
+        authority
+    }
+}
+
+impl fmt::Display for SftpUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.join(&self.path).display())
+    }
+}
+
+#[cfg(feature = "sftp")]
+pub use self::remote::SftpFileSystem;
+
+#[cfg(feature = "sftp")]
+mod remote {
+    use std::collections::HashMap;
+    use std::ffi::OsString;
+    use std::io::{self, Read};
+    use std::net::TcpStream;
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use log::debug;
+    use ssh2::{CheckResult, KnownHostFileKind, Session, Sftp};
+
+    use super::SftpUrl;
+    use crate::finder::filesystem::{FileKind, FileStat, FileSystem};
+
+    /// 连接和每次请求的超时时间
+    const TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// 依次尝试的默认私钥
+    const DEFAULT_KEYS: [&str; 3] = ["id_ed25519", "id_ecdsa", "id_rsa"];
+
+    /// 通过 SFTP 访问的远程文件系统
+    ///
+    /// 列出目录时服务器返回的属性被缓存，过滤器读取属性时不再发出额外的请求。
+    pub struct SftpFileSystem {
+        url: SftpUrl,
+        // 会话需要在 SFTP 通道之后释放
+        sftp: Sftp,
+        _session: Session,
+        stats: Mutex<HashMap<PathBuf, FileStat>>,
+    }
+
+    impl std::fmt::Debug for SftpFileSystem {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("SftpFileSystem").field("url", &self.url).finish_non_exhaustive()
+        }
+    }
+
+    impl SftpFileSystem {
+        /// 连接到服务器、校验主机密钥、认证并打开 SFTP 通道
+        ///
+        /// # 错误
+        /// 如果无法连接、主机密钥未知或不匹配、认证失败，返回 IO 错误
+        pub fn connect(url: &SftpUrl) -> io::Result<Self> {
+            let tcp = TcpStream::connect((url.host(), url.port()))?;
+            tcp.set_read_timeout(Some(TIMEOUT))?;
+            let mut session = Session::new()?;
+            session.set_timeout(TIMEOUT.as_millis() as u32);
+            session.set_tcp_stream(tcp);
+            session.handshake()?;
+            verify_host_key(&session, url)?;
+
+            let user = match url.user() {
+                Some(user) => user.to_string(),
+                None => std::env::var("USER")
+                    .or_else(|_| std::env::var("USERNAME"))
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "地址中没有用户名，且无法确定本地用户名"))?,
+            };
+            authenticate(&session, &user)?;
+            debug!("Connected to {} as {}", url, user);
+
+            Ok(Self {
+                url: url.clone(),
+                sftp: session.sftp()?,
+                _session: session,
+                stats: Mutex::new(HashMap::new()),
+            })
+        }
+    }
+
+    /// 服务器的主机密钥必须与 known_hosts 中的记录一致，不自动信任未知的主机
+    fn verify_host_key(session: &Session, url: &SftpUrl) -> io::Result<()> {
+        let (key, _) = session
+            .host_key()
+            .ok_or_else(|| io::Error::other("服务器没有提供主机密钥"))?;
+        let mut known_hosts = session.known_hosts()?;
+        if let Some(home) = home_dir() {
+            let file = home.join(".ssh").join("known_hosts");
+            if file.exists() {
+                known_hosts.read_file(&file, KnownHostFileKind::OpenSSH)?;
+            }
+        }
+        match known_hosts.check_port(url.host(), url.port(), key) {
+            CheckResult::Match => Ok(()),
+            CheckResult::Mismatch => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} 的主机密钥与 known_hosts 中的记录不一致", url.host()),
+            )),
+            CheckResult::NotFound => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} 的主机密钥不在 known_hosts 中，请先用 ssh 连接一次以确认主机密钥", url.host()),
+            )),
+            CheckResult::Failure => Err(io::Error::other("无法校验主机密钥")),
+        }
+    }
+
+    /// 依次尝试 ssh-agent、默认私钥和环境变量中的密码
+    fn authenticate(session: &Session, user: &str) -> io::Result<()> {
+        if let Err(e) = session.userauth_agent(user) {
+            debug!("SSH agent authentication failed: {}", e);
+        }
+        if let Some(ssh_dir) = home_dir().map(|home| home.join(".ssh")) {
+            for key in DEFAULT_KEYS.iter().map(|name| ssh_dir.join(name)) {
+                if session.authenticated() {
+                    break;
+                }
+                if key.exists() {
+                    if let Err(e) = session.userauth_pubkey_file(user, None, &key, None) {
+                        debug!("Key {} rejected: {}", key.display(), e);
+                    }
+                }
+            }
+        }
+        if !session.authenticated() {
+            if let Ok(password) = std::env::var("RUST_FIND_SFTP_PASSWORD") {
+                session.userauth_password(user, &password)?;
+            }
+        }
+        if session.authenticated() {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("无法以 {} 的身份认证", user)))
+        }
+    }
+
+    fn home_dir() -> Option<PathBuf> {
+        std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")).map(PathBuf::from)
+    }
+
+    fn file_stat(stat: &ssh2::FileStat) -> FileStat {
+        let kind = match stat.file_type() {
+            ssh2::FileType::RegularFile => FileKind::File,
+            ssh2::FileType::Directory => FileKind::Dir,
+            ssh2::FileType::Symlink => FileKind::Symlink,
+            ssh2::FileType::BlockDevice => FileKind::BlockDevice,
+            ssh2::FileType::CharDevice => FileKind::CharDevice,
+            ssh2::FileType::NamedPipe => FileKind::Fifo,
+            ssh2::FileType::Socket => FileKind::Socket,
+            ssh2::FileType::Other(_) => FileKind::Other,
+        };
+        let time = |secs: Option<u64>| secs.map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+        FileStat {
+            kind,
+            len: stat.size.unwrap_or(0),
+            modified: time(stat.mtime),
+            accessed: time(stat.atime),
+            mode: stat.perm.map(|perm| perm & 0o7777),
+            attributes: None,
+        }
+    }
+
+    impl FileSystem for SftpFileSystem {
+        fn read_dir(&self, path: &Path) -> io::Result<Vec<(OsString, FileKind)>> {
+            let entries = self.sftp.readdir(path)?;
+            let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+            Ok(entries
+                .into_iter()
+                .filter_map(|(child, stat)| {
+                    let stat = file_stat(&stat);
+                    let entry = (child.file_name()?.to_os_string(), stat.kind);
+                    stats.insert(child, stat);
+                    Some(entry)
+                })
+                .collect())
+        }
+
+        fn symlink_metadata(&self, path: &Path) -> io::Result<FileStat> {
+            if let Some(stat) = self.stats.lock().unwrap_or_else(|e| e.into_inner()).get(path) {
+                return Ok(stat.clone());
+            }
+            Ok(file_stat(&self.sftp.lstat(path)?))
+        }
+
+        fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+            Ok(Box::new(self.sftp.open(path)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sftp_url() {
+        let url = SftpUrl::parse("sftp://admin@example.com/srv/data").unwrap();
+        assert_eq!(url.user(), Some("admin"));
+        assert_eq!((url.host(), url.port()), ("example.com", 22));
+        assert_eq!(url.path(), Path::new("/srv/data"));
+        assert_eq!(url.to_string(), "sftp://admin@example.com/srv/data");
+        assert_eq!(
+            url.join(Path::new("/srv/data/a.log")),
+            PathBuf::from("sftp://admin@example.com/srv/data/a.log")
+        );
+
+        let url = SftpUrl::parse("sftp://[::1]:2222").unwrap();
+        assert_eq!((url.user(), url.host(), url.port()), (None, "::1", 2222));
+        assert_eq!(url.path(), Path::new("."));
+        assert_eq!(url.to_string(), "sftp://[::1]:2222/~");
+        assert_eq!(url.join(Path::new("./notes.txt")), PathBuf::from("sftp://[::1]:2222/~/notes.txt"));
+        assert_eq!(SftpUrl::parse("sftp://host/~/logs").unwrap().path(), Path::new("logs"));
+
+        assert!(SftpUrl::parse("sftp://host:port/").is_none());
+        assert!(SftpUrl::parse("sftp:///srv").is_none());
+        assert!(SftpUrl::parse("ssh://host/srv").is_none());
+    }
+}
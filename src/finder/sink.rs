@@ -0,0 +1,31 @@
+//! 搜索结果接收端
+//!
+//! 允许将搜索结果直接发送到调用方提供的通道中，
+//! 以便接入应用自己的处理流水线（界面刷新、批量上传等）。
+
+use std::path::PathBuf;
+use std::sync::mpsc::{Sender, SyncSender};
+
+/// 搜索结果接收端trait
+///
+/// 为 `std::sync::mpsc` 的 `Sender` 和 `SyncSender` 提供了实现；
+/// 其他通道（例如 crossbeam）可以通过实现此trait接入。
+pub trait ResultSink: Sync {
+    /// 发送一个结果
+    ///
+    /// 返回 false 表示接收方已关闭，搜索应当停止。
+    /// 有界通道在满时阻塞，从而对遍历施加背压。
+    fn send_result(&self, path: PathBuf) -> bool;
+}
+
+impl ResultSink for Sender<PathBuf> {
+    fn send_result(&self, path: PathBuf) -> bool {
+        self.send(path).is_ok()
+    }
+}
+
+impl ResultSink for SyncSender<PathBuf> {
+    fn send_result(&self, path: PathBuf) -> bool {
+        self.send(path).is_ok()
+    }
+}
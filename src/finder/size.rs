@@ -0,0 +1,129 @@
+//! 带单位后缀的大小解析
+//!
+//! 所有接受字节数的选项共用同一种写法：数字（可以带小数）后跟可选的单位。
+//! - 无后缀、`b` 或 `c`：字节
+//! - `k`、`M`、`G`、`T`、`P` 以及 `KiB`、`MiB` 等：1024 的幂
+//! - `kB`、`MB`、`GB`、`TB`、`PB`：1000 的幂
+//!
+//! 单位不区分大小写，与 GNU find 和 `du` 一样，单个字母表示二进制单位。
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::errors::FindError;
+
+/// 单位前缀，按指数排列
+const PREFIXES: [char; 5] = ['k', 'm', 'g', 't', 'p'];
+
+/// 以字节为单位的大小
+///
+/// # 示例
+/// ```
+/// use rust_find::finder::size::ByteSize;
+///
+/// let size: ByteSize = "1.5G".parse().unwrap();
+/// assert_eq!(size.bytes(), 1_610_612_736);
+/// assert_eq!("10MiB".parse::<ByteSize>().unwrap().bytes(), 10 * 1024 * 1024);
+/// assert_eq!("2kB".parse::<ByteSize>().unwrap().bytes(), 2000);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct ByteSize(u64);
+
+impl ByteSize {
+    /// 创建指定字节数的大小
+    pub fn new(bytes: u64) -> Self {
+        Self(bytes)
+    }
+
+    /// 字节数
+    pub fn bytes(self) -> u64 {
+        self.0
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = FindError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| FindError::PatternError {
+            message: format!("Invalid size '{}': {}", spec, reason),
+        };
+
+        let split = spec
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(spec.len());
+        let (number, unit) = spec.split_at(split);
+        if number.is_empty() {
+            return Err(invalid("expected a number such as 500, 1.5G or 10MiB"));
+        }
+        let multiplier = unit_multiplier(unit).ok_or_else(|| {
+            invalid("unknown unit, expected b, k, M, G, T, P, KiB..PiB or kB..PB")
+        })?;
+
+        // 整数直接计算，避免大数经过浮点数时丢失精度
+        let bytes = if let Ok(integer) = number.parse::<u64>() {
+            integer.checked_mul(multiplier)
+        } else {
+            let value: f64 = number.parse().map_err(|_| invalid("malformed number"))?;
+            let bytes = (value * multiplier as f64).round();
+            // u64::MAX 转换为浮点数后向上取整为 2^64，因此用 < 判断
+            (bytes < u64::MAX as f64).then_some(bytes as u64)
+        };
+        bytes.map(Self).ok_or_else(|| invalid("value is too large"))
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// 单位对应的字节数，无法识别时返回 None
+fn unit_multiplier(unit: &str) -> Option<u64> {
+    let unit = unit.to_ascii_lowercase();
+    if unit.is_empty() || unit == "b" || unit == "c" {
+        return Some(1);
+    }
+
+    let mut chars = unit.chars();
+    let prefix = chars.next()?;
+    let exponent = PREFIXES.iter().position(|&known| known == prefix)? as u32 + 1;
+    match chars.as_str() {
+        "" | "ib" => Some(1024u64.pow(exponent)),
+        "b" => Some(1000u64.pow(exponent)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(spec: &str) -> u64 {
+        spec.parse::<ByteSize>().unwrap().bytes()
+    }
+
+    #[test]
+    fn test_parse_byte_size() {
+        assert_eq!(bytes("0"), 0);
+        assert_eq!(bytes("500"), 500);
+        assert_eq!(bytes("500c"), 500);
+        assert_eq!(bytes("500k"), 512_000);
+        assert_eq!(bytes("500K"), 512_000);
+        assert_eq!(bytes("1.5G"), 1_610_612_736);
+        assert_eq!(bytes("10MiB"), 10_485_760);
+        assert_eq!(bytes("10mib"), 10_485_760);
+        assert_eq!(bytes("10MB"), 10_000_000);
+        assert_eq!(bytes("0.5kB"), 500);
+        assert_eq!(bytes("1P"), 1 << 50);
+    }
+
+    #[test]
+    fn test_parse_byte_size_errors() {
+        for spec in ["", "G", "1.5.G", "10XB", "10MiBs", "-5", "99999999999P", "1e3"] {
+            let error = spec.parse::<ByteSize>().unwrap_err();
+            assert!(error.to_string().contains(spec), "{}: {}", spec, error);
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! 搜索统计和报告
+//!
+//! 查找器在遍历过程中记录遍历的目录和条目、匹配数、被跳过的条目和遍历错误，
+//! 通过 [`Finder::stats`](super::Finder::stats) 提供给调用方（`--stats`）。
+//! [`Finder::find_with_report`](super::Finder::find_with_report) 还会返回遍历错误本身。
+
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::errors::FindError;
+
+/// 遍历错误的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// 没有读取权限
+    PermissionDenied,
+    /// 路径不存在（例如遍历过程中被删除）
+    NotFound,
+    /// 符号链接循环
+    SymlinkLoop,
+    /// 其他 I/O 错误
+    Other,
+}
+
+impl ErrorCategory {
+    /// 遍历错误所属的类别
+    pub fn of(error: &walkdir::Error) -> Self {
+        if error.loop_ancestor().is_some() {
+            return ErrorCategory::SymlinkLoop;
+        }
+        match error.io_error().map(|e| e.kind()) {
+            Some(ErrorKind::PermissionDenied) => ErrorCategory::PermissionDenied,
+            Some(ErrorKind::NotFound) => ErrorCategory::NotFound,
+            _ => ErrorCategory::Other,
+        }
+    }
+
+    /// 过滤器等返回的 FindError 所属的类别
+    pub fn of_find_error(error: &FindError) -> Self {
+        match error {
+            FindError::PermissionDenied(_) => ErrorCategory::PermissionDenied,
+            FindError::FileNotFound(_) => ErrorCategory::NotFound,
+            FindError::SymlinkIssue(_) => ErrorCategory::SymlinkLoop,
+            _ => ErrorCategory::Other,
+        }
+    }
+
+    /// 机器可读的错误代码
+    pub fn code(self) -> &'static str {
+        match self {
+            ErrorCategory::PermissionDenied => "permission_denied",
+            ErrorCategory::NotFound => "not_found",
+            ErrorCategory::SymlinkLoop => "symlink_loop",
+            ErrorCategory::Other => "io_error",
+        }
+    }
+}
+
+/// 将遍历错误转换为 FindError，符号链接循环转换为 SymlinkIssue
+pub(crate) fn find_error(category: ErrorCategory, error: &walkdir::Error) -> FindError {
+    match (category, error.path()) {
+        (ErrorCategory::SymlinkLoop, Some(path)) => FindError::SymlinkIssue(path.to_path_buf()),
+        _ => FindError::from(error),
+    }
+}
+
+/// 遍历错误的文本描述
+pub(crate) fn error_message(category: ErrorCategory, error: &FindError) -> String {
+    if category == ErrorCategory::SymlinkLoop {
+        format!("{} (链接指向其上级目录，已跳过)", error)
+    } else {
+        error.to_string()
+    }
+}
+
+/// 遍历错误的 JSON 描述，没有对应的路径或系统错误码时为 null
+pub(crate) fn error_json(category: ErrorCategory, error: &FindError, errno: Option<i32>) -> serde_json::Value {
+    json!({
+        "code": category.code(),
+        "path": error.path().map(|path| path.to_string_lossy()),
+        "errno": errno,
+        "message": error.to_string(),
+    })
+}
+
+/// 按类别统计的遍历错误数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ErrorCounts {
+    /// 没有读取权限
+    pub permission_denied: usize,
+    /// 路径不存在
+    pub not_found: usize,
+    /// 符号链接循环
+    pub symlink_loop: usize,
+    /// 其他错误
+    pub other: usize,
+}
+
+impl ErrorCounts {
+    /// 记录一个错误
+    pub fn add(&mut self, category: ErrorCategory) {
+        *self.get_mut(category) += 1;
+    }
+
+    /// 指定类别的错误数
+    pub fn get(&self, category: ErrorCategory) -> usize {
+        match category {
+            ErrorCategory::PermissionDenied => self.permission_denied,
+            ErrorCategory::NotFound => self.not_found,
+            ErrorCategory::SymlinkLoop => self.symlink_loop,
+            ErrorCategory::Other => self.other,
+        }
+    }
+
+    fn get_mut(&mut self, category: ErrorCategory) -> &mut usize {
+        match category {
+            ErrorCategory::PermissionDenied => &mut self.permission_denied,
+            ErrorCategory::NotFound => &mut self.not_found,
+            ErrorCategory::SymlinkLoop => &mut self.symlink_loop,
+            ErrorCategory::Other => &mut self.other,
+        }
+    }
+
+    /// 错误总数
+    pub fn total(&self) -> usize {
+        self.permission_denied + self.not_found + self.symlink_loop + self.other
+    }
+}
+
+/// 一次或多次搜索的统计
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// 遍历的目录数
+    pub dirs: usize,
+    /// 检查的条目数（包括目录）
+    pub entries: usize,
+    /// 匹配的条目数
+    pub matches: usize,
+    /// 作为隐藏文件跳过的条目数
+    pub hidden: usize,
+    /// 被忽略文件、`--exclude` 和 `--exclude-dir` 等规则跳过的条目数
+    pub ignored: usize,
+    /// 遍历错误
+    pub errors: ErrorCounts,
+    /// 匹配的文件的总大小（字节），只在 [`FindOptions::with_measure_matches`](super::FindOptions::with_measure_matches) 启用时统计
+    pub matched_bytes: u64,
+    /// 搜索耗时
+    pub elapsed: Duration,
+}
+
+impl SearchStats {
+    /// 被跳过的条目总数
+    pub fn skipped(&self) -> usize {
+        self.hidden + self.ignored
+    }
+
+    /// 累加另一组统计
+    pub fn merge(&mut self, other: &SearchStats) {
+        self.dirs += other.dirs;
+        self.entries += other.entries;
+        self.matches += other.matches;
+        self.hidden += other.hidden;
+        self.ignored += other.ignored;
+        self.errors.permission_denied += other.errors.permission_denied;
+        self.errors.not_found += other.errors.not_found;
+        self.errors.symlink_loop += other.errors.symlink_loop;
+        self.errors.other += other.errors.other;
+        self.matched_bytes += other.matched_bytes;
+        self.elapsed += other.elapsed;
+    }
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "遍历的目录: {}", self.dirs)?;
+        writeln!(f, "检查的条目: {}", self.entries)?;
+        writeln!(f, "匹配的条目: {}", self.matches)?;
+        writeln!(f, "匹配文件的大小: {} 字节", self.matched_bytes)?;
+        writeln!(f, "跳过的条目: {}（隐藏 {}，忽略 {}）", self.skipped(), self.hidden, self.ignored)?;
+        writeln!(
+            f,
+            "错误: {}（权限不足 {}，不存在 {}，符号链接循环 {}，其他 {}）",
+            self.errors.total(),
+            self.errors.permission_denied,
+            self.errors.not_found,
+            self.errors.symlink_loop,
+            self.errors.other
+        )?;
+        write!(f, "耗时: {:.2?}", self.elapsed)
+    }
+}
+
+/// 单次搜索的结果、遍历错误和统计
+///
+/// 与只返回路径的 [`Finder::find`](super::Finder::find) 不同，无法读取的目录等错误不会被静默丢弃，
+/// 调用方可以据此区分“没有匹配的文件”和“文件因权限不足等原因未被检查”。
+#[derive(Debug, Default)]
+pub struct SearchReport {
+    /// 匹配的路径，顺序与 [`Finder::find_parallel`](super::Finder::find_parallel) 相同
+    pub matches: Vec<PathBuf>,
+    /// 遍历中遇到的所有错误（包括处理策略为 Silent 的错误），按出现的顺序排列
+    pub errors: Vec<FindError>,
+    /// 本次搜索的统计
+    pub stats: SearchStats,
+}
+
+impl SearchReport {
+    /// 搜索是否没有遇到任何错误
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_json() {
+        let error = walkdir::WalkDir::new("/nonexistent/rust-find-stats")
+            .into_iter()
+            .next()
+            .unwrap()
+            .unwrap_err();
+        let category = ErrorCategory::of(&error);
+        assert_eq!(category, ErrorCategory::NotFound);
+
+        let errno = error.io_error().and_then(|e| e.raw_os_error());
+        let error = find_error(category, &error);
+        assert_eq!(error_message(category, &error), "文件未找到: /nonexistent/rust-find-stats");
+        let value = error_json(category, &error, errno);
+        assert_eq!(value["code"], "not_found");
+        assert_eq!(value["path"], "/nonexistent/rust-find-stats");
+        assert!(value["errno"].is_i64());
+        assert!(value["message"].is_string());
+    }
+
+    #[test]
+    fn test_search_stats_merge_and_display() {
+        let mut stats = SearchStats { dirs: 2, entries: 10, hidden: 1, ..SearchStats::default() };
+        stats.errors.add(ErrorCategory::PermissionDenied);
+        let mut other = SearchStats { dirs: 1, matches: 3, ignored: 4, ..SearchStats::default() };
+        other.errors.add(ErrorCategory::PermissionDenied);
+        other.errors.add(ErrorCategory::Other);
+
+        stats.merge(&other);
+        assert_eq!(stats.dirs, 3);
+        assert_eq!(stats.skipped(), 5);
+        assert_eq!(stats.errors.get(ErrorCategory::PermissionDenied), 2);
+        assert_eq!(stats.errors.total(), 3);
+
+        let text = stats.to_string();
+        assert!(text.contains("跳过的条目: 5（隐藏 1，忽略 4）"));
+        assert!(text.contains("错误: 3（权限不足 2"));
+    }
+}
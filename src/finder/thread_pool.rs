@@ -1,9 +1,12 @@
 //! 自适应线程池模块
 //! 
 //! 提供根据系统资源和工作负载自动调整线程数量的线程池实现。
+//! 线程池持有专用的 rayon 线程池，搜索中的并行任务在其中执行，
+//! 因此配置的最大线程数真正限制了并发度。
 
 use std::sync::atomic::{AtomicUsize, Ordering};
-use log::{debug, info};
+use std::sync::{Arc, Mutex};
+use log::{debug, info, warn};
 use num_cpus;
 
 /// 线程池配置选项
@@ -41,6 +44,8 @@ pub struct AdaptiveThreadPool {
     directory_count: AtomicUsize,
     /// 当前线程数量
     current_threads: AtomicUsize,
+    /// 执行并行任务的线程池，线程数变化后在下次执行时重建
+    executor: Mutex<Option<Arc<rayon::ThreadPool>>>,
 }
 
 impl AdaptiveThreadPool {
@@ -52,6 +57,7 @@ impl AdaptiveThreadPool {
             config,
             directory_count: AtomicUsize::new(0),
             current_threads: AtomicUsize::new(initial_threads),
+            executor: Mutex::new(None),
         }
     }
     
@@ -70,6 +76,36 @@ impl AdaptiveThreadPool {
         }
         
         let dir_count = self.directory_count.load(Ordering::Relaxed);
+        let new_threads = self.ideal_thread_count(dir_count);
+        
+        // 更新并返回新的线程数
+        self.current_threads.store(new_threads, Ordering::Relaxed);
+        info!("Adjusted thread count to {} (directories: {}, CPUs: {})", 
+              new_threads, dir_count, num_cpus::get());
+        
+        new_threads
+    }
+    
+    /// 没有目录数量可以参考时使用允许的最大线程数并返回
+    ///
+    /// 禁用自动调整时保持当前线程数。
+    pub fn use_max_threads(&self) -> usize {
+        if !self.config.auto_adjust {
+            return self.get_thread_count();
+        }
+        let cpu_count = num_cpus::get();
+        let threads = self
+            .config
+            .max_threads
+            .min(cpu_count)
+            .max(self.config.min_threads);
+        self.current_threads.store(threads, Ordering::Relaxed);
+        info!("Using maximum thread count {} (no cached directory count, CPUs: {})", threads, cpu_count);
+        threads
+    }
+    
+    /// 根据目录数量计算理想的线程数
+    fn ideal_thread_count(&self, dir_count: usize) -> usize {
         let cpu_count = num_cpus::get();
         
         debug!("Adjusting thread count - dirs: {}, min: {}, max: {}, per_thread: {}, cpus: {}",
@@ -77,7 +113,7 @@ impl AdaptiveThreadPool {
               self.config.dirs_per_thread, cpu_count);
         
         // 计算理想线程数
-        let new_threads = if dir_count == 0 {
+        if dir_count == 0 {
             self.config.min_threads
         } else {
             let ideal_threads = (dir_count as f64 / self.config.dirs_per_thread as f64).ceil() as usize;
@@ -86,14 +122,48 @@ impl AdaptiveThreadPool {
                 .max(self.config.min_threads)  // 至少使用min_threads
                 .min(self.config.max_threads)   // 不超过max_threads
                 .min(cpu_count.max(self.config.min_threads)) // 不超过CPU核心数，但至少使用min_threads
-        };
-        
-        // 更新并返回新的线程数
-        self.current_threads.store(new_threads, Ordering::Relaxed);
-        info!("Adjusted thread count to {} (directories: {}, CPUs: {})", 
-              new_threads, dir_count, cpu_count);
+        }
+    }
+    
+    /// 在线程池中执行操作，操作中的 rayon 并行迭代器使用当前线程数的工作线程
+    ///
+    /// 线程数在上次执行后发生了变化时先按新的线程数重建线程池。
+    /// 无法创建线程池时在当前线程中直接执行（使用 rayon 的全局线程池）。
+    pub fn install<OP, R>(&self, op: OP) -> R
+    where
+        OP: FnOnce() -> R + Send,
+        R: Send,
+    {
+        match self.executor() {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+    
+    /// 获取与当前线程数一致的线程池，必要时重建
+    fn executor(&self) -> Option<Arc<rayon::ThreadPool>> {
+        let threads = self.get_thread_count().max(1);
+        let mut executor = self.executor.lock().ok()?;
+        if let Some(pool) = executor.as_ref().filter(|pool| pool.current_num_threads() == threads) {
+            return Some(Arc::clone(pool));
+        }
         
-        new_threads
+        debug!("Building thread pool with {} threads", threads);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|index| format!("rust-find-{}", index))
+            .build();
+        match pool {
+            Ok(pool) => {
+                let pool = Arc::new(pool);
+                *executor = Some(Arc::clone(&pool));
+                Some(pool)
+            }
+            Err(e) => {
+                warn!("无法创建线程池，使用默认线程池: {}", e);
+                None
+            }
+        }
     }
     
     /// 获取当前线程数
@@ -172,4 +242,36 @@ mod tests {
         pool.update_directory_count(1000);
         assert_eq!(pool.adjust_thread_count(), 3);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_use_max_threads() {
+        let config = ThreadPoolConfig {
+            min_threads: 1,
+            max_threads: 4,
+            dirs_per_thread: 10,
+            auto_adjust: true,
+        };
+        let pool = AdaptiveThreadPool::new(config.clone());
+        let expected = 4.min(num_cpus::get());
+        assert_eq!(pool.use_max_threads(), expected);
+        assert_eq!(pool.get_thread_count(), expected);
+
+        let pool = AdaptiveThreadPool::new(ThreadPoolConfig { auto_adjust: false, ..config });
+        assert_eq!(pool.use_max_threads(), 1);
+    }
+    
+    #[test]
+    fn test_install_limits_threads() {
+        let pool = AdaptiveThreadPool::new(ThreadPoolConfig {
+            min_threads: 2,
+            max_threads: 2,
+            dirs_per_thread: 10,
+            auto_adjust: false,
+        });
+        assert_eq!(pool.install(rayon::current_num_threads), 2);
+        
+        // 线程数变化后重建线程池
+        pool.current_threads.store(3, Ordering::Relaxed);
+        assert_eq!(pool.install(rayon::current_num_threads), 3);
+    }
+}
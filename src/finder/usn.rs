@@ -0,0 +1,348 @@
+//! NTFS 的 USN 变更日志
+//!
+//! 读取卷的变更日志，直接得到某个 USN 之后创建、修改或重命名的条目，不遍历目录树（`--changed-since-usn`）。
+//! 只在 Windows 上启用了变更日志的 NTFS 或 ReFS 卷上可用，打开卷通常需要管理员权限。
+//!
+//! 结果只包含仍然存在的条目，并经过与遍历相同的剪枝规则和过滤器。日志大小有限，
+//! 较早的记录会被覆盖；请求的 USN 早于日志中最早的记录时返回错误，此时需要完整搜索一次。
+
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::errors::FindResult;
+use super::filter::FileFilter;
+use super::prune::Pruner;
+use super::Finder;
+
+/// 变更查询的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsnChanges {
+    /// 发生变化且仍然存在的匹配条目，按路径排序
+    pub paths: Vec<PathBuf>,
+    /// 查询时日志的下一个 USN，下次以它查询即可得到此后的变化
+    pub next_usn: i64,
+}
+
+impl Finder {
+    /// 查找搜索根目录下在指定 USN 之后发生变化的匹配条目
+    ///
+    /// # 错误
+    /// 不是 Windows、卷不支持或没有启用变更日志、没有权限读取日志，
+    /// 或请求的 USN 已经不在日志中时返回错误
+    ///
+    /// # 示例
+    /// ```no_run
+    /// use rust_find::finder::{Finder, FindOptions};
+    /// use rust_find::finder::filter::NameFilter;
+    /// use std::path::Path;
+    ///
+    /// let finder = Finder::new(FindOptions::new());
+    /// let changes = finder
+    ///     .changed_since_usn(Path::new(r"C:\projects"), 0, NameFilter::new("*.rs").unwrap())
+    ///     .unwrap();
+    /// println!("{} 个文件发生了变化，下次从 USN {} 开始查询", changes.paths.len(), changes.next_usn);
+    /// ```
+    pub fn changed_since_usn<F: FileFilter>(&self, root: &Path, usn: i64, filter: F) -> FindResult<UsnChanges> {
+        let (changed, next_usn) = journal::read_changes(root, usn)?;
+        let canonical = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let max_depth = self.options.max_depth.unwrap_or(usize::MAX);
+        let mut pruner = Pruner::new(&self.options, root);
+
+        let mut paths = Vec::new();
+        for path in changed {
+            // 日志中的路径是规范化的绝对路径，转换为以传入的根目录为前缀的形式
+            let Ok(relative) = path.strip_prefix(&canonical) else {
+                continue;
+            };
+            let path = root.join(relative);
+            if relative.components().count() > max_depth || !pruner.keeps_path(&path) {
+                continue;
+            }
+            let entry = WalkDir::new(&path).max_depth(0).into_iter().next().and_then(Result::ok);
+            if let Some(entry) = entry.filter(|entry| self.accepts(entry, root, &filter)) {
+                paths.push(entry.into_path());
+            }
+        }
+        paths.sort();
+        Ok(UsnChanges { paths, next_usn })
+    }
+}
+
+#[cfg(windows)]
+mod journal {
+    //! 通过 DeviceIoControl 读取变更日志
+
+    use std::collections::HashMap;
+    use std::ffi::OsString;
+    use std::io;
+    use std::mem::size_of;
+    use std::os::windows::ffi::OsStringExt;
+    use std::path::{Path, PathBuf};
+    use std::time::SystemTime;
+
+    use windows_sys::Win32::Foundation::{CloseHandle, GENERIC_READ, HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::{
+        CreateFileW, FileIdType, GetFinalPathNameByHandleW, GetVolumeNameForVolumeMountPointW, GetVolumePathNameW,
+        OpenFileById, FILE_FLAG_BACKUP_SEMANTICS, FILE_ID_DESCRIPTOR, FILE_ID_DESCRIPTOR_0, FILE_NAME_NORMALIZED,
+        FILE_READ_ATTRIBUTES, FILE_SHARE_DELETE, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING, VOLUME_NAME_DOS,
+    };
+    use windows_sys::Win32::System::Ioctl::{
+        FSCTL_QUERY_USN_JOURNAL, FSCTL_READ_USN_JOURNAL, READ_USN_JOURNAL_DATA_V0, USN_JOURNAL_DATA_V0,
+        USN_REASON_FILE_DELETE, USN_RECORD_V2,
+    };
+    use windows_sys::Win32::System::IO::DeviceIoControl;
+
+    use crate::errors::{FindError, FindResult};
+    use crate::finder::long_path::to_extended_length;
+
+    /// 每次读取日志的缓冲区大小（以 u64 计，保证记录按 8 字节对齐）
+    const BUFFER_WORDS: usize = 8 * 1024;
+
+    /// 自动关闭的句柄
+    struct Handle(HANDLE);
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            // SAFETY: 句柄由 CreateFileW 或 OpenFileById 打开，只关闭一次
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+
+    /// 读取 `since` 之后发生变化且仍然存在的条目的路径，以及日志的下一个 USN
+    pub(super) fn read_changes(root: &Path, since: i64) -> FindResult<(Vec<PathBuf>, i64)> {
+        let volume = open_volume(root).map_err(|e| FindError::from_io(e, root))?;
+        let journal = query_journal(&volume).map_err(|e| FindError::from_io(e, root))?;
+        if since < journal.FirstUsn || since > journal.NextUsn {
+            return Err(FindError::Other {
+                message: format!(
+                    "USN {} 不在变更日志的范围内（{} 到 {}），需要完整搜索一次",
+                    since, journal.FirstUsn, journal.NextUsn
+                ),
+                context: Some(root.display().to_string()),
+                timestamp: SystemTime::now(),
+            });
+        }
+
+        // 每个文件只保留最后一条记录的原因
+        let mut changes = HashMap::new();
+        let mut request = READ_USN_JOURNAL_DATA_V0 {
+            StartUsn: since,
+            ReasonMask: u32::MAX,
+            ReturnOnlyOnClose: 0,
+            Timeout: 0,
+            BytesToWaitFor: 0,
+            UsnJournalID: journal.UsnJournalID,
+        };
+        let mut buffer = vec![0u64; BUFFER_WORDS];
+        while request.StartUsn < journal.NextUsn {
+            let mut returned = 0;
+            // SAFETY: 输入和输出缓冲区在调用期间有效，大小与传入的一致
+            let ok = unsafe {
+                DeviceIoControl(
+                    volume.0,
+                    FSCTL_READ_USN_JOURNAL,
+                    (&request as *const READ_USN_JOURNAL_DATA_V0).cast(),
+                    size_of::<READ_USN_JOURNAL_DATA_V0>() as u32,
+                    buffer.as_mut_ptr().cast(),
+                    (buffer.len() * size_of::<u64>()) as u32,
+                    &mut returned,
+                    std::ptr::null_mut(),
+                )
+            };
+            if ok == 0 {
+                return Err(FindError::from_io(io::Error::last_os_error(), root));
+            }
+            let returned = returned as usize;
+            if returned < size_of::<i64>() {
+                break;
+            }
+            let next = buffer[0] as i64;
+
+            let bytes = &as_bytes(&buffer)[..returned];
+            let mut offset = size_of::<i64>();
+            while offset + size_of::<USN_RECORD_V2>() <= bytes.len() {
+                // SAFETY: 记录完整地位于缓冲区内，read_unaligned 不要求对齐
+                let record: USN_RECORD_V2 = unsafe { std::ptr::read_unaligned(bytes[offset..].as_ptr().cast()) };
+                if record.RecordLength == 0 {
+                    break;
+                }
+                // 版本 3 的记录使用 128 位文件标识（ReFS），这里只处理版本 2
+                if record.MajorVersion == 2 {
+                    changes.insert(record.FileReferenceNumber, record.Reason);
+                }
+                offset += record.RecordLength as usize;
+            }
+
+            if next <= request.StartUsn {
+                break;
+            }
+            request.StartUsn = next;
+        }
+
+        let paths = changes
+            .into_iter()
+            .filter(|&(_, reason)| reason & USN_REASON_FILE_DELETE == 0)
+            .filter_map(|(file, _)| path_by_id(&volume, file))
+            .collect();
+        Ok((paths, journal.NextUsn))
+    }
+
+    /// 将缓冲区视为字节切片
+    fn as_bytes(buffer: &[u64]) -> &[u8] {
+        // SAFETY: u64 切片的内存可以按字节读取，长度按字节数计算
+        unsafe { std::slice::from_raw_parts(buffer.as_ptr().cast(), std::mem::size_of_val(buffer)) }
+    }
+
+    /// 打开路径所在的卷
+    fn open_volume(root: &Path) -> io::Result<Handle> {
+        let path = to_extended_length(root)?;
+        let mut mount_point = vec![0u16; 1024];
+        let mut volume_name = vec![0u16; 64];
+        // SAFETY: 缓冲区长度与传入的一致
+        let ok = unsafe {
+            GetVolumePathNameW(path.as_ptr(), mount_point.as_mut_ptr(), mount_point.len() as u32) != 0
+                && GetVolumeNameForVolumeMountPointW(
+                    mount_point.as_ptr(),
+                    volume_name.as_mut_ptr(),
+                    volume_name.len() as u32,
+                ) != 0
+        };
+        if !ok {
+            return Err(io::Error::last_os_error());
+        }
+
+        // 卷名形如 \\?\Volume{GUID}\，打开卷设备时去掉结尾的反斜杠
+        let length = volume_name.iter().position(|&c| c == 0).unwrap_or(volume_name.len());
+        let mut device = volume_name[..length].to_vec();
+        if device.last() == Some(&(b'\\' as u16)) {
+            device.pop();
+        }
+        device.push(0);
+
+        // SAFETY: 路径以 NUL 结尾
+        let handle = unsafe {
+            CreateFileW(
+                device.as_ptr(),
+                GENERIC_READ,
+                FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+                std::ptr::null(),
+                OPEN_EXISTING,
+                0,
+                std::ptr::null_mut(),
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Handle(handle))
+    }
+
+    /// 查询卷的变更日志信息
+    fn query_journal(volume: &Handle) -> io::Result<USN_JOURNAL_DATA_V0> {
+        // SAFETY: USN_JOURNAL_DATA_V0 只包含整数字段，全零是有效值
+        let mut journal: USN_JOURNAL_DATA_V0 = unsafe { std::mem::zeroed() };
+        let mut returned = 0;
+        // SAFETY: 输出缓冲区在调用期间有效，大小与传入的一致
+        let ok = unsafe {
+            DeviceIoControl(
+                volume.0,
+                FSCTL_QUERY_USN_JOURNAL,
+                std::ptr::null(),
+                0,
+                (&mut journal as *mut USN_JOURNAL_DATA_V0).cast(),
+                size_of::<USN_JOURNAL_DATA_V0>() as u32,
+                &mut returned,
+                std::ptr::null_mut(),
+            )
+        };
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(journal)
+    }
+
+    /// 按文件标识得到文件当前的路径，文件已不存在时返回 None
+    fn path_by_id(volume: &Handle, file: u64) -> Option<PathBuf> {
+        let descriptor = FILE_ID_DESCRIPTOR {
+            dwSize: size_of::<FILE_ID_DESCRIPTOR>() as u32,
+            Type: FileIdType,
+            Anonymous: FILE_ID_DESCRIPTOR_0 { FileId: file as i64 },
+        };
+        // SAFETY: 描述符在调用期间有效
+        let handle = unsafe {
+            OpenFileById(
+                volume.0,
+                &descriptor,
+                FILE_READ_ATTRIBUTES,
+                FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+                std::ptr::null(),
+                FILE_FLAG_BACKUP_SEMANTICS,
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return None;
+        }
+        let handle = Handle(handle);
+
+        let mut buffer = vec![0u16; 512];
+        loop {
+            // SAFETY: 缓冲区长度与传入的一致
+            let length = unsafe {
+                GetFinalPathNameByHandleW(
+                    handle.0,
+                    buffer.as_mut_ptr(),
+                    buffer.len() as u32,
+                    FILE_NAME_NORMALIZED | VOLUME_NAME_DOS,
+                )
+            } as usize;
+            if length == 0 {
+                return None;
+            }
+            // 缓冲区不足时返回所需的长度（含结尾的 NUL）
+            if length >= buffer.len() {
+                buffer.resize(length + 1, 0);
+                continue;
+            }
+            let path = PathBuf::from(OsString::from_wide(&buffer[..length]));
+            // 去掉 \\?\ 前缀，与规范化的搜索根目录一致
+            return Some(match path.to_str().and_then(|path| path.strip_prefix(r"\\?\")) {
+                Some(stripped) => PathBuf::from(stripped),
+                None => path,
+            });
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod journal {
+    use std::path::{Path, PathBuf};
+    use std::time::SystemTime;
+
+    use crate::errors::{FindError, FindResult};
+
+    pub(super) fn read_changes(root: &Path, _since: i64) -> FindResult<(Vec<PathBuf>, i64)> {
+        Err(FindError::Other {
+            message: "USN 变更日志只在 Windows 的 NTFS 卷上可用".to_string(),
+            context: Some(root.display().to_string()),
+            timestamp: SystemTime::now(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finder::FindOptions;
+    use crate::finder::filter::NameFilter;
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_changed_since_usn_requires_windows() {
+        let finder = Finder::new(FindOptions::new());
+        let error = finder
+            .changed_since_usn(Path::new("."), 0, NameFilter::new("*").unwrap())
+            .unwrap_err();
+        assert!(error.to_string().contains("Windows"), "{}", error);
+    }
+}
@@ -38,7 +38,8 @@ impl<'a> FileWalker<'a> {
     /// 使用配置的选项初始化目录遍历器
     fn init_walker(&self, path: &Path) -> walkdir::WalkDir {
         let mut walker = WalkDir::new(path)
-            .follow_links(self.options.follow_links);
+            .follow_links(self.options.symlink_policy.follows_links())
+            .follow_root_links(self.options.symlink_policy.follows_root_links());
         
         if let Some(depth) = self.options.max_depth {
             walker = walker.max_depth(depth);
@@ -106,7 +107,8 @@ impl<'a> FileWalkerIterator<'a> {
     pub fn new<P: AsRef<Path>>(path: P, options: &'a FindOptions) -> Self {
         let root_path = path.as_ref().to_path_buf();
         let mut walker = WalkDir::new(&root_path)
-            .follow_links(options.follow_links);
+            .follow_links(options.symlink_policy.follows_links())
+            .follow_root_links(options.symlink_policy.follows_root_links());
         
         if let Some(depth) = options.max_depth {
             walker = walker.max_depth(depth);
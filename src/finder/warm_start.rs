@@ -0,0 +1,146 @@
+//! 线程池预热统计
+//!
+//! 记录每个搜索根目录上次运行时观察到的目录数量和吞吐量，
+//! 后续对同一根目录的搜索可以直接据此确定线程池大小，
+//! 省去目录预统计的遍历。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+/// 统计文件名
+const STATS_FILE_NAME: &str = "pool-stats.json";
+
+/// 单个搜索根目录的运行统计
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RootStats {
+    /// 观察到的目录数量
+    pub dir_count: usize,
+    /// 每秒处理的条目数
+    pub entries_per_sec: f64,
+    /// 记录时间（自 UNIX 纪元起的秒数）
+    pub updated: u64,
+}
+
+/// 保存在缓存目录中的统计数据
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PoolStats {
+    roots: HashMap<String, RootStats>,
+}
+
+impl PoolStats {
+    /// 从文件加载统计数据，文件不存在或无效时返回空数据
+    pub fn load(path: &Path) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// 将统计数据保存到文件
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_vec(self).map_err(std::io::Error::other)?;
+        fs::write(path, data)
+    }
+
+    /// 获取根目录的统计数据
+    pub fn get(&self, root: &Path, max_depth: Option<usize>) -> Option<RootStats> {
+        self.roots.get(&Self::key(root, max_depth)).copied()
+    }
+
+    /// 记录一次完整运行的结果
+    pub fn record(
+        &mut self,
+        root: &Path,
+        max_depth: Option<usize>,
+        dir_count: usize,
+        entry_count: usize,
+        elapsed: Duration,
+    ) {
+        let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+        let updated = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        self.roots.insert(
+            Self::key(root, max_depth),
+            RootStats {
+                dir_count,
+                entries_per_sec: entry_count as f64 / seconds,
+                updated,
+            },
+        );
+    }
+
+    /// 统计数据的键：规范化的根目录和最大深度
+    fn key(root: &Path, max_depth: Option<usize>) -> String {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        match max_depth {
+            Some(depth) => format!("{}|{}", root.display(), depth),
+            None => root.display().to_string(),
+        }
+    }
+}
+
+/// 默认的统计文件位置
+///
+/// Unix 上为 `$XDG_CACHE_HOME/rust-find` 或 `~/.cache/rust-find`，
+/// Windows 上为 `%LOCALAPPDATA%\rust-find`。
+pub fn default_stats_file() -> Option<PathBuf> {
+    let cache_dir = if cfg!(windows) {
+        std::env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+    };
+
+    let file = cache_dir.map(|dir| dir.join("rust-find").join(STATS_FILE_NAME));
+    debug!("Thread pool stats file: {:?}", file);
+    file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_pool_stats_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let file = dir.path().join("cache").join(STATS_FILE_NAME);
+
+        let mut stats = PoolStats::load(&file);
+        assert!(stats.get(dir.path(), None).is_none());
+
+        stats.record(dir.path(), None, 42, 1000, Duration::from_secs(2));
+        stats.save(&file)?;
+
+        let loaded = PoolStats::load(&file);
+        let root_stats = loaded.get(dir.path(), None).expect("stats recorded");
+        assert_eq!(root_stats.dir_count, 42);
+        assert_eq!(root_stats.entries_per_sec, 500.0);
+        // 不同的最大深度单独记录
+        assert!(loaded.get(dir.path(), Some(1)).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pool_stats_invalid_file() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let file = dir.path().join(STATS_FILE_NAME);
+        fs::write(&file, "not json")?;
+        assert!(PoolStats::load(&file).get(dir.path(), None).is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+//! 监视模式
+//!
+//! 完成一次初始搜索后订阅文件系统事件（Linux 上为 inotify，macOS 上为 FSEvents，
+//! Windows 上为 ReadDirectoryChangesW），持续报告新出现或被修改的匹配条目。
+//! 事件中的路径经过与遍历相同的剪枝规则和过滤器。
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant, SystemTime};
+
+use log::debug;
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use walkdir::{DirEntry, WalkDir};
+
+use crate::errors::{FindError, FindResult};
+use super::content::ContentStage;
+use super::entry::FindEntry;
+use super::filter::FileFilter;
+use super::prune::{self, Pruner};
+use super::sink::ResultSink;
+use super::{Finder, WalkCounters};
+
+/// 收集一批事件的时间窗口，窗口内同一路径的多个事件只报告一次
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// 等待事件时检查取消令牌的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 被监视的搜索根目录
+struct WatchedRoot {
+    /// 按传入形式的根目录，报告的路径以它为前缀
+    root: PathBuf,
+    /// 规范化的根目录，用于识别事件中的绝对路径
+    canonical: PathBuf,
+    pruner: Pruner,
+}
+
+impl WatchedRoot {
+    /// 将事件中的路径转换为以传入的根目录为前缀的形式
+    fn localize(&self, path: &Path) -> Option<PathBuf> {
+        if let Ok(relative) = path.strip_prefix(&self.canonical) {
+            return Some(self.root.join(relative));
+        }
+        path.starts_with(&self.root).then(|| path.to_path_buf())
+    }
+}
+
+impl Finder {
+    /// 监视目录中的匹配条目
+    ///
+    /// 先订阅文件系统事件，再对每个根目录执行一次完整搜索并发送结果，
+    /// 之后持续发送新出现或被修改的匹配条目，直到取消令牌被取消、
+    /// 接收方关闭或达到结果数上限。新出现的目录中已有的条目也会被报告。
+    ///
+    /// # 错误
+    /// 如果无法订阅文件系统事件，返回Other错误；初始搜索因处理策略为 Fail 的遍历错误中止时返回该错误
+    ///
+    /// # 示例
+    /// ```no_run
+    /// use rust_find::finder::{Finder, FindOptions};
+    /// use rust_find::finder::filter::NameFilter;
+    /// use std::path::PathBuf;
+    /// use std::sync::mpsc::channel;
+    ///
+    /// let (sender, receiver) = channel();
+    /// std::thread::spawn(move || {
+    ///     let finder = Finder::new(FindOptions::new());
+    ///     let filter = NameFilter::new("*.log").unwrap();
+    ///     finder.watch(&[PathBuf::from("/var/log")], filter, sender)
+    /// });
+    /// for path in receiver {
+    ///     println!("{}", path.display());
+    /// }
+    /// ```
+    pub fn watch<F, S>(&self, roots: &[PathBuf], filter: F, sink: S) -> FindResult<()>
+    where
+        F: FileFilter + Send + Sync,
+        S: ResultSink,
+    {
+        let (sender, receiver) = channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(|e| watch_error(None, e))?;
+        for root in roots {
+            watcher
+                .watch(root, RecursiveMode::Recursive)
+                .map_err(|e| watch_error(Some(root), e))?;
+        }
+
+        // 订阅之后再搜索，搜索期间出现的条目不会被遗漏
+        let counters = WalkCounters::default();
+        let open = AtomicBool::new(true);
+        for root in roots {
+            self.run_search(std::slice::from_ref(root), &filter, &counters, |_, path| {
+                let sent = sink.send_result(path);
+                if !sent {
+                    open.store(false, Ordering::Relaxed);
+                }
+                sent
+            });
+            if let Some(e) = self.take_error() {
+                return Err(e);
+            }
+            if !open.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+        }
+
+        let mut watched: Vec<WatchedRoot> = roots
+            .iter()
+            .map(|root| WatchedRoot {
+                root: root.clone(),
+                canonical: root.canonicalize().unwrap_or_else(|_| root.clone()),
+                pruner: Pruner::new(&self.options, root),
+            })
+            .collect();
+
+        let limit = self.options.max_results;
+        while !self.options.cancel_token.is_cancelled() && !counters.limit_reached(limit) {
+            let changes = match next_changes(&receiver) {
+                Ok(changes) => changes,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            };
+
+            // 新目录中的条目可能同时由目录遍历和自身的事件产生
+            let mut reported = HashSet::new();
+            for (path, created) in changes {
+                let Some(watched) = watched
+                    .iter_mut()
+                    .filter(|watched| watched.localize(&path).is_some())
+                    .max_by_key(|watched| watched.root.components().count())
+                else {
+                    continue;
+                };
+                let Some(path) = watched.localize(&path) else {
+                    continue;
+                };
+
+                for entry in self.changed_entries(watched, &path, created) {
+                    if !reported.insert(entry.path().to_path_buf())
+                        || !self.watch_accepts(&watched.root, &entry, &filter)
+                        || !counters.claim_match(limit)
+                    {
+                        continue;
+                    }
+                    if !sink.send_result(entry.into_path()) {
+                        debug!("Result receiver closed, stopping watch");
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 获取发生变化的路径对应的条目
+    ///
+    /// 新出现的目录（创建或移入）中已有的条目不会产生单独的事件，因此一并遍历。
+    fn changed_entries(&self, watched: &mut WatchedRoot, path: &Path, created: bool) -> Vec<DirEntry> {
+        if !watched.pruner.keeps_path(path) {
+            return Vec::new();
+        }
+        let depth = path
+            .strip_prefix(&watched.root)
+            .map(|relative| relative.components().count())
+            .unwrap_or(0);
+        let max_depth = self.options.max_depth.unwrap_or(usize::MAX);
+        if depth > max_depth {
+            return Vec::new();
+        }
+
+        let walk_depth = if created { max_depth - depth } else { 0 };
+        let pruner = &mut watched.pruner;
+        WalkDir::new(path)
+            .follow_links(self.options.symlink_policy.follows_links())
+            .max_depth(walk_depth)
+            .into_iter()
+            .filter_entry(|entry| pruner.keeps(entry))
+            .filter_map(Result::ok)
+            .collect()
+    }
+
+    /// 检查发生变化的条目是否应被报告
+    fn watch_accepts<F: FileFilter>(&self, root: &Path, entry: &DirEntry, filter: &F) -> bool {
+        if prune::is_excluded(&self.options, root, entry) {
+            return false;
+        }
+        let entry = FindEntry::under_root(entry, root);
+        !prune::is_hidden(&self.options, &entry)
+            && self.matches_all(&entry, filter)
+            && ContentStage::matches(&self.content_filters, &entry)
+    }
+}
+
+/// 等待下一批变化，返回发生变化的路径及其是否为新出现的条目
+///
+/// 在轮询间隔内没有事件时返回 Timeout 错误。
+fn next_changes(
+    receiver: &Receiver<notify::Result<Event>>,
+) -> Result<BTreeMap<PathBuf, bool>, RecvTimeoutError> {
+    let first = receiver.recv_timeout(POLL_INTERVAL)?;
+
+    let mut changes = BTreeMap::new();
+    record_event(first, &mut changes);
+    let deadline = Instant::now() + DEBOUNCE;
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        match receiver.recv_timeout(remaining) {
+            Ok(event) => record_event(event, &mut changes),
+            Err(_) => break,
+        }
+    }
+    Ok(changes)
+}
+
+/// 记录单个事件中发生变化的路径
+fn record_event(event: notify::Result<Event>, changes: &mut BTreeMap<PathBuf, bool>) {
+    let event = match event {
+        Ok(event) => event,
+        Err(e) => {
+            debug!("Watch error: {}", e);
+            return;
+        }
+    };
+    let created = match event.kind {
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_)) => true,
+        EventKind::Modify(_) => false,
+        _ => return,
+    };
+    for path in event.paths {
+        // 已经被删除或移走的路径不再报告
+        if path.symlink_metadata().is_ok() {
+            *changes.entry(path).or_insert(false) |= created;
+        }
+    }
+}
+
+/// 构造订阅文件系统事件失败的错误
+fn watch_error(root: Option<&Path>, error: notify::Error) -> FindError {
+    FindError::Other {
+        message: format!("无法监视文件系统事件: {}", error),
+        context: root.map(|root| root.display().to_string()),
+        timestamp: SystemTime::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finder::filter::NameFilter;
+    use crate::finder::FindOptions;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_watch_reports_existing_and_new_matches() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root = dir.path().to_path_buf();
+        fs::write(root.join("existing.log"), "x")?;
+        fs::write(root.join("ignored.txt"), "x")?;
+
+        let finder = Finder::new(FindOptions::new());
+        let cancel = finder.cancellation_token();
+        let (sender, receiver) = channel();
+        let watch_root = root.clone();
+        let handle = std::thread::spawn(move || {
+            finder.watch(&[watch_root], NameFilter::new("*.log").unwrap(), sender)
+        });
+
+        let timeout = Duration::from_secs(10);
+        assert_eq!(receiver.recv_timeout(timeout)?, root.join("existing.log"));
+
+        fs::write(root.join("ignored.txt"), "changed")?;
+        fs::create_dir_all(root.join("nested/deeper"))?;
+        fs::write(root.join("nested/deeper/new.log"), "x")?;
+        assert_eq!(receiver.recv_timeout(timeout)?, root.join("nested/deeper/new.log"));
+        assert!(receiver.recv_timeout(Duration::from_millis(500)).is_err());
+
+        cancel.cancel();
+        handle.join().unwrap()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+//! gRPC 搜索服务（grpc 特性）
+//!
+//! 与 HTTP 接口（[`serve`](crate::serve)）提供相同的搜索，接口定义见 `proto/search.proto`：
+//! `Search` 以服务端流的形式返回结果，`Cancel` 按请求中的 `search_id` 取消正在进行的搜索。
+//! 请求中的过滤条件转换为 [`serve::FilterSpec`] 后构建查找器，校验和错误与 HTTP 接口一致。
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use log::debug;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+use crate::finder::{CancellationToken, ResultSink};
+use crate::serve::{self, MatchAll};
+
+include!(concat!(env!("OUT_DIR"), "/rust_find.Search.rs"));
+
+pub use search_client::SearchClient;
+pub use search_server::{Search, SearchServer};
+
+/// 结果通道的容量，客户端读取较慢时对搜索施加背压
+const RESULT_BUFFER: usize = 256;
+
+/// 过滤条件
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FilterSpec {
+    #[prost(string, repeated, tag = "1")]
+    pub paths: Vec<String>,
+    #[prost(string, repeated, tag = "2")]
+    pub name: Vec<String>,
+    #[prost(string, repeated, tag = "3")]
+    pub iname: Vec<String>,
+    #[prost(string, optional, tag = "4")]
+    pub file_type: Option<String>,
+    #[prost(string, repeated, tag = "5")]
+    pub path: Vec<String>,
+    #[prost(string, optional, tag = "6")]
+    pub query: Option<String>,
+    #[prost(string, optional, tag = "7")]
+    pub min_size: Option<String>,
+    #[prost(string, optional, tag = "8")]
+    pub max_size: Option<String>,
+    #[prost(string, repeated, tag = "9")]
+    pub contains: Vec<String>,
+    #[prost(uint64, optional, tag = "10")]
+    pub max_depth: Option<u64>,
+    #[prost(bool, tag = "11")]
+    pub hidden: bool,
+    #[prost(uint64, optional, tag = "12")]
+    pub max_results: Option<u64>,
+}
+
+impl From<FilterSpec> for serve::FilterSpec {
+    fn from(spec: FilterSpec) -> Self {
+        let to_usize = |value: u64| usize::try_from(value).unwrap_or(usize::MAX);
+        Self {
+            paths: spec.paths.into_iter().map(PathBuf::from).collect(),
+            name: spec.name,
+            iname: spec.iname,
+            file_type: spec.file_type,
+            path: spec.path,
+            query: spec.query,
+            min_size: spec.min_size,
+            max_size: spec.max_size,
+            contains: spec.contains,
+            max_depth: spec.max_depth.map(to_usize),
+            hidden: spec.hidden,
+            max_results: spec.max_results.map(to_usize),
+        }
+    }
+}
+
+/// 搜索请求
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SearchRequest {
+    /// 用于取消搜索的标识，为空时只能通过取消调用停止
+    #[prost(string, tag = "1")]
+    pub search_id: String,
+    #[prost(message, optional, tag = "2")]
+    pub spec: Option<FilterSpec>,
+}
+
+/// 单个搜索结果
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SearchResult {
+    #[prost(string, tag = "1")]
+    pub path: String,
+}
+
+/// 取消请求
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CancelRequest {
+    #[prost(string, tag = "1")]
+    pub search_id: String,
+}
+
+/// 取消的结果
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CancelReply {
+    /// 是否找到了正在进行的搜索
+    #[prost(bool, tag = "1")]
+    pub cancelled: bool,
+}
+
+/// 将结果发送到响应流
+struct StreamSink(mpsc::Sender<Result<SearchResult, Status>>);
+
+impl ResultSink for StreamSink {
+    fn send_result(&self, path: PathBuf) -> bool {
+        let result = SearchResult {
+            path: path.to_string_lossy().into_owned(),
+        };
+        self.0.blocking_send(Ok(result)).is_ok()
+    }
+}
+
+/// 搜索服务的实现
+#[derive(Debug, Default, Clone)]
+pub struct SearchService {
+    /// 正在进行的带标识的搜索
+    searches: Arc<Mutex<HashMap<String, CancellationToken>>>,
+}
+
+impl SearchService {
+    /// 搜索结束后移除其标识
+    fn finish(&self, search_id: &str) {
+        if !search_id.is_empty() {
+            self.searches.lock().unwrap_or_else(|e| e.into_inner()).remove(search_id);
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Search for SearchService {
+    type SearchStream = ReceiverStream<Result<SearchResult, Status>>;
+
+    async fn search(&self, request: Request<SearchRequest>) -> Result<Response<Self::SearchStream>, Status> {
+        let request = request.into_inner();
+        let spec = serve::FilterSpec::from(request.spec.unwrap_or_default());
+        let finder = spec.finder().map_err(|e| Status::invalid_argument(e.to_string()))?;
+        if let Some(missing) = spec.roots().into_iter().find(|root| !root.exists()) {
+            return Err(Status::not_found(format!("文件未找到: {}", missing.display())));
+        }
+
+        let search_id = request.search_id;
+        if !search_id.is_empty() {
+            let mut searches = self.searches.lock().unwrap_or_else(|e| e.into_inner());
+            if searches.contains_key(&search_id) {
+                return Err(Status::already_exists(format!("搜索 {} 正在进行", search_id)));
+            }
+            searches.insert(search_id.clone(), finder.cancellation_token());
+        }
+
+        let (sender, receiver) = mpsc::channel(RESULT_BUFFER);
+        let service = self.clone();
+        tokio::task::spawn_blocking(move || {
+            for root in spec.roots() {
+                if sender.is_closed() || finder.cancellation_token().is_cancelled() {
+                    break;
+                }
+                finder.find_into(root, MatchAll, StreamSink(sender.clone()));
+            }
+            debug!("gRPC search {:?} finished", search_id);
+            service.finish(&search_id);
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn cancel(&self, request: Request<CancelRequest>) -> Result<Response<CancelReply>, Status> {
+        let search_id = request.into_inner().search_id;
+        let token = self.searches.lock().unwrap_or_else(|e| e.into_inner()).remove(&search_id);
+        if let Some(token) = &token {
+            token.cancel();
+        }
+        Ok(Response::new(CancelReply {
+            cancelled: token.is_some(),
+        }))
+    }
+}
+
+/// 在指定地址上运行 gRPC 服务，直到出错
+///
+/// # 错误
+/// 如果无法监听地址或服务出错，返回传输层错误
+pub async fn run(addr: SocketAddr) -> Result<(), tonic::transport::Error> {
+    tonic::transport::Server::builder()
+        .add_service(SearchServer::new(SearchService::default()))
+        .serve(addr)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+    use tokio_stream::wrappers::TcpListenerStream;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_search_and_cancel() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        fs::create_dir_all(root.join("src"))?;
+        fs::write(root.join("src/lib.rs"), "")?;
+        fs::write(root.join("README.md"), "")?;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(SearchServer::new(SearchService::default()))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        let mut client = SearchClient::connect(format!("http://{}", addr)).await?;
+        let spec = FilterSpec {
+            paths: vec![root.to_string_lossy().into_owned()],
+            name: vec!["*.rs".to_string()],
+            ..FilterSpec::default()
+        };
+        let request = SearchRequest {
+            search_id: "first".to_string(),
+            spec: Some(spec),
+        };
+        let mut stream = client.search(request).await?.into_inner();
+        let mut paths = Vec::new();
+        while let Some(result) = stream.message().await? {
+            paths.push(result.path);
+        }
+        assert_eq!(paths, [root.join("src/lib.rs").to_string_lossy()]);
+
+        let invalid = SearchRequest {
+            search_id: String::new(),
+            spec: Some(FilterSpec {
+                query: Some("size >".to_string()),
+                ..FilterSpec::default()
+            }),
+        };
+        let status = client.search(invalid).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let reply = client.cancel(CancelRequest { search_id: "unknown".to_string() }).await?;
+        assert!(!reply.into_inner().cancelled);
+        Ok(())
+    }
+}
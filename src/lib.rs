@@ -41,9 +41,19 @@
 //!
 //! 更多用法请参考各模块文档。
 
+pub mod actions;
+pub mod audit;
+pub mod bench;
 pub mod cli;
+#[cfg(unix)]
+pub mod daemon;
 pub mod errors;
 pub mod finder;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod output;
+pub mod serve;
+pub mod snapshot;
 
 // Re-export main types for convenience
 pub use errors::{FindError, FindResult};
@@ -1,89 +1,662 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, sync_channel};
 use std::time::Instant;
 use anyhow::{Result, Context};
-use rust_find::finder::filter::FileFilter;
-use walkdir::DirEntry;
-use log::{info, debug};
-use clap::Parser;
+use rust_find::finder::archive::ArchiveFilter;
+use rust_find::finder::s3::S3Url;
+use rust_find::finder::sftp::SftpUrl;
+use rust_find::finder::filter::{FileFilter, FilterCost};
+use log::{info, debug, error};
 
-use rust_find::cli::Cli;
-use rust_find::finder::{Finder, filter::NameFilter};
+use rust_find::{audit, bench};
+use rust_find::actions::Action;
+use rust_find::cli::{AuditArgs, BenchArgs, Cli, Command, DaemonArgs, GrpcArgs, IndexArgs, QueryArgs, ServeArgs};
+use rust_find::serve::Server;
+use rust_find::finder::{native_filesystem, FindEntry, Finder};
+use rust_find::output::{Dedup, Printer, Summary};
+use rust_find::finder::{FindOptions, ScanCache, SearchStats};
+use rust_find::snapshot::Snapshot;
+
+/// 边搜索边执行操作时，等待处理的结果数上限，操作较慢时对遍历施加背压
+const STREAM_BUFFER: usize = 256;
+
+/// 不做额外限制的过滤器，实际条件都已加入查找器
+struct AlwaysTrueFilter;
+
+impl FileFilter for AlwaysTrueFilter {
+    fn matches(&self, _: &FindEntry) -> bool {
+        true
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
+    fn description(&self) -> String {
+        "始终匹配所有文件".to_string()
+    }
+}
+
+/// 根据命令行参数创建查找器并添加过滤器
+fn build_finder(cli: &Cli, options: FindOptions) -> Result<Finder> {
+    let finder = Finder::new(options)
+        .with_filters(cli.build_filters().with_context(|| "创建过滤器失败")?)
+        .with_content_filters(cli.build_content_filters().with_context(|| "创建内容过滤器失败")?);
+    Ok(if cli.native_readdir {
+        finder.with_filesystem(native_filesystem())
+    } else {
+        finder
+    })
+}
+
+/// --explain-path：列出每个过滤器对指定路径的判断，匹配时以 0 退出，否则以 1 退出
+fn explain_path(cli: &Cli, path: &Path) -> Result<()> {
+    let options = cli.build_options();
+    let entry = FindEntry::from_path(path, options.symlink_policy.follows_root_links())
+        .with_context(|| format!("无法读取路径: {}", path.display()))?;
+    let finder = build_finder(cli, options)?;
+    let explanation = finder.explain(&entry, &AlwaysTrueFilter);
+    println!("{}", explanation);
+    if !explanation.matched() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// 搜索因处理策略为 fail 的错误中止时返回该错误
+fn check_aborted(finder: &Finder) -> Result<()> {
+    match finder.take_error() {
+        Some(e) => Err(e).with_context(|| "遍历出错，搜索已中止"),
+        None => Ok(()),
+    }
+}
+
+/// 遍历中出现过错误（包括被忽略的错误）时以 1 退出，与 GNU find 相同
+fn exit_on_errors(cli: &Cli, stats: &SearchStats) {
+    let errors = stats.errors.total();
+    if errors > 0 && !cli.no_fail_on_error {
+        debug!("遍历中出现 {} 个错误", errors);
+        std::process::exit(1);
+    }
+}
+
+/// 逐个路径搜索（非递归列出或增量扫描），--max-results 限制所有路径的结果总数
+///
+/// 返回每个路径的结果和所有搜索的累计统计。
+fn search_each(
+    cli: &Cli,
+    roots: &[PathBuf],
+    mut scan_cache: Option<&mut ScanCache>,
+) -> Result<(Vec<Vec<PathBuf>>, SearchStats)> {
+    let mut remaining = cli.max_results;
+    let mut searches = Vec::new();
+    let mut stats = SearchStats::default();
+    for root in roots {
+        if remaining == Some(0) {
+            debug!("已达到结果数上限，跳过剩余路径");
+            break;
+        }
+        debug!("在路径中搜索: {}", root.display());
+
+        let finder = build_finder(cli, cli.build_options().with_max_results(remaining))?;
+        let results = if cli.no_recurse {
+            finder.list(root.clone(), AlwaysTrueFilter)
+        } else if let Some(cache) = scan_cache.as_deref_mut() {
+            finder.find_incremental(root.clone(), AlwaysTrueFilter, cache)
+        } else {
+            finder.find(root.clone(), AlwaysTrueFilter)
+        };
+        stats.merge(&finder.stats());
+        check_aborted(&finder)?;
+
+        if let Some(remaining) = remaining.as_mut() {
+            *remaining = remaining.saturating_sub(results.len());
+        }
+        searches.push(results);
+    }
+    Ok((searches, stats))
+}
+
+/// 逐个路径搜索，每找到一个结果就立即对其执行操作，不在内存中收集结果
+///
+/// 用于 --archive：匹配项在遍历过程中写入归档。--max-results 限制所有路径的结果总数。
+/// 返回每个路径的匹配数、所有搜索的累计统计以及是否有操作失败。
+fn stream_to_actions(
+    cli: &Cli,
+    roots: &[PathBuf],
+    actions: &mut [Box<dyn Action>],
+    mut dedup: Option<&mut Dedup>,
+) -> Result<(Vec<usize>, SearchStats, bool)> {
+    let mut remaining = cli.max_results;
+    let mut counts = Vec::new();
+    let mut stats = SearchStats::default();
+    let mut failed = false;
+    for root in roots {
+        if remaining == Some(0) {
+            debug!("已达到结果数上限，跳过剩余路径");
+            break;
+        }
+        debug!("在路径中搜索: {}", root.display());
+
+        let finder = build_finder(cli, cli.build_options().with_max_results(remaining))?;
+        let mut count = 0;
+        std::thread::scope(|scope| {
+            let (sender, receiver) = sync_channel(STREAM_BUFFER);
+            let finder = &finder;
+            scope.spawn(move || finder.find_into(root.clone(), AlwaysTrueFilter, sender));
+            for path in receiver {
+                if dedup.as_mut().is_some_and(|dedup| !dedup.insert(&path)) {
+                    continue;
+                }
+                count += 1;
+                for action in actions.iter_mut() {
+                    if let Err(e) = action.run(&path) {
+                        error!("{}", e);
+                        failed = true;
+                    }
+                }
+            }
+        });
+        stats.merge(&finder.stats());
+        check_aborted(&finder)?;
+
+        if let Some(remaining) = remaining.as_mut() {
+            *remaining = remaining.saturating_sub(count);
+        }
+        counts.push(count);
+    }
+    Ok((counts, stats, failed))
+}
+
+/// --search-archives：在搜索路径下的归档中查找，返回 `归档路径!/内部路径` 形式的结果
+///
+/// 归档本身不需要满足过滤条件；无法读取的归档输出错误后跳过。
+fn search_archives(cli: &Cli, root: &Path) -> Result<Vec<PathBuf>> {
+    let archives = Finder::new(cli.build_options().with_max_results(None)).find(root.to_path_buf(), ArchiveFilter);
+    let finder = build_finder(cli, cli.build_options())?;
+    let mut members = Vec::new();
+    for archive in archives {
+        debug!("在归档中搜索: {}", archive.display());
+        match finder.find_in_archive(&archive, AlwaysTrueFilter) {
+            Ok(found) => members.extend(found),
+            Err(e) => error!("无法读取归档: {}", e),
+        }
+    }
+    Ok(members)
+}
+
+/// 不在本地文件系统中的搜索路径
+enum RemoteRoot {
+    Sftp(SftpUrl),
+    S3(S3Url),
+}
+
+impl RemoteRoot {
+    /// 识别 sftp:// 和 s3:// 地址，本地路径返回 None
+    fn parse(path: &str) -> Option<Self> {
+        SftpUrl::parse(path)
+            .map(RemoteRoot::Sftp)
+            .or_else(|| S3Url::parse(path).map(RemoteRoot::S3))
+    }
+}
+
+/// 搜索远程路径，结果为完整的远程地址
+fn search_remote(cli: &Cli, root: &RemoteRoot) -> Result<Vec<PathBuf>> {
+    match root {
+        RemoteRoot::Sftp(url) => search_sftp(cli, url),
+        RemoteRoot::S3(url) => search_s3(cli, url),
+    }
+}
+
+/// 通过 SFTP 搜索远程目录
+#[cfg(feature = "sftp")]
+fn search_sftp(cli: &Cli, url: &SftpUrl) -> Result<Vec<PathBuf>> {
+    let fs = rust_find::finder::sftp::SftpFileSystem::connect(url)
+        .with_context(|| format!("无法连接到 {}", url))?;
+    let finder = build_finder(cli, cli.build_options())?;
+    let results = finder.find_in(std::sync::Arc::new(fs), url.path(), AlwaysTrueFilter);
+    Ok(results.iter().map(|path| url.join(path)).collect())
+}
+
+#[cfg(not(feature = "sftp"))]
+fn search_sftp(_: &Cli, url: &SftpUrl) -> Result<Vec<PathBuf>> {
+    anyhow::bail!("搜索 {} 需要以 sftp 特性编译（cargo build --features sftp）", url)
+}
+
+/// 搜索 S3 存储桶中前缀下的对象
+#[cfg(feature = "s3")]
+fn search_s3(cli: &Cli, url: &S3Url) -> Result<Vec<PathBuf>> {
+    let fs = rust_find::finder::s3::S3FileSystem::connect(url)
+        .with_context(|| format!("无法列出 {}", url))?;
+    let finder = build_finder(cli, cli.build_options())?;
+    let results = finder.find_in(std::sync::Arc::new(fs), url.root(), AlwaysTrueFilter);
+    Ok(results.iter().map(|path| url.join(path)).collect())
+}
+
+#[cfg(not(feature = "s3"))]
+fn search_s3(_: &Cli, url: &S3Url) -> Result<Vec<PathBuf>> {
+    anyhow::bail!("搜索 {} 需要以 s3 特性编译（cargo build --features s3）", url)
+}
+
+/// 输出 --count 的结果：所有搜索路径的匹配总数，--per-root 时每个搜索路径一行
+fn print_counts(cli: &Cli, roots: &[PathBuf], counts: &[usize]) {
+    if cli.per_root {
+        // 达到结果数上限后未搜索的路径数量为 0
+        for (index, root) in roots.iter().enumerate() {
+            println!("{}\t{}", counts.get(index).unwrap_or(&0), root.display());
+        }
+    } else {
+        println!("{}", counts.iter().sum::<usize>());
+    }
+}
+
+/// 运行基准测试并输出结果表
+fn run_bench(args: &BenchArgs) -> Result<()> {
+    let runs = bench::run(&args.path, &args.config())
+        .with_context(|| format!("基准测试失败: {}", args.path.display()))?;
+    println!("{}", bench::HEADER);
+    for run in &runs {
+        println!("{}", run);
+    }
+    Ok(())
+}
+
+/// 运行权限审计并输出分组报告
+fn run_audit(args: &AuditArgs) -> Result<()> {
+    let report = audit::run(&args.path, args.options())
+        .with_context(|| format!("权限审计失败: {}", args.path.display()))?;
+    print!("{}", report);
+    Ok(())
+}
+
+/// 运行搜索服务，直到进程被终止
+fn run_serve(args: &ServeArgs) -> Result<()> {
+    let server = Server::bind(&args.listen).with_context(|| format!("无法监听 {}", args.listen))?;
+    eprintln!("监听 http://{}", server.local_addr()?);
+    server.run().with_context(|| "搜索服务异常退出")?;
+    Ok(())
+}
+
+/// 运行 gRPC 服务，直到进程被终止
+#[cfg(feature = "grpc")]
+fn run_grpc(args: &GrpcArgs) -> Result<()> {
+    let runtime = tokio::runtime::Runtime::new().with_context(|| "无法创建异步运行时")?;
+    eprintln!("监听 grpc://{}", args.listen);
+    runtime
+        .block_on(rust_find::grpc::run(args.listen))
+        .with_context(|| format!("gRPC 服务异常退出: {}", args.listen))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "grpc"))]
+fn run_grpc(_: &GrpcArgs) -> Result<()> {
+    anyhow::bail!("grpc 子命令需要以 grpc 特性编译（cargo build --features grpc）")
+}
+
+/// 运行守护进程，直到进程被终止
+#[cfg(unix)]
+fn run_daemon(args: &DaemonArgs) -> Result<()> {
+    let socket = args.socket.clone().unwrap_or_else(rust_find::daemon::default_socket_path);
+    let mut daemon = rust_find::daemon::Daemon::bind(&socket)
+        .with_context(|| format!("无法监听 {}", socket.display()))?;
+    for root in &args.watch {
+        daemon.watch(root).with_context(|| format!("无法监视 {}", root.display()))?;
+    }
+    eprintln!("监听 {}", socket.display());
+    daemon.run().with_context(|| "守护进程异常退出")?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn run_daemon(_: &DaemonArgs) -> Result<()> {
+    anyhow::bail!("daemon 子命令仅支持 Unix 系统")
+}
+
+/// 通过守护进程搜索并输出结果
+#[cfg(unix)]
+fn run_query(args: &QueryArgs) -> Result<()> {
+    use std::io::Write;
+
+    let socket = args.socket.clone().unwrap_or_else(rust_find::daemon::default_socket_path);
+    let mut stdout = std::io::stdout().lock();
+    let mut write_error = None;
+    rust_find::daemon::query(&socket, &args.spec(), |path| {
+        if write_error.is_none() {
+            write_error = writeln!(stdout, "{}", path.display()).err();
+        }
+    })?;
+    if let Some(e) = write_error {
+        return Err(e).with_context(|| "输出结果失败");
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn run_query(_: &QueryArgs) -> Result<()> {
+    anyhow::bail!("query 子命令仅支持 Unix 系统")
+}
+
+/// 管理守护进程缓存的目录列表
+#[cfg(unix)]
+fn run_index(args: &IndexArgs) -> Result<()> {
+    match &args.command {
+        rust_find::cli::IndexCommand::Import(args) => {
+            let socket = args.socket.clone().unwrap_or_else(rust_find::daemon::default_socket_path);
+            let count = rust_find::daemon::import(&socket, &args.database)
+                .with_context(|| format!("无法导入 {}", args.database.display()))?;
+            eprintln!("导入了 {} 个目录", count);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn run_index(_: &IndexArgs) -> Result<()> {
+    anyhow::bail!("index 子命令仅支持 Unix 系统")
+}
+
+/// 监视模式：输出初始搜索结果后持续输出新出现或被修改的匹配条目
+fn watch(cli: &Cli) -> Result<()> {
+    let roots: Vec<PathBuf> = cli.paths.iter().map(PathBuf::from).collect();
+    if roots.is_empty() {
+        return Ok(());
+    }
+    let finder = build_finder(cli, cli.build_options())?;
+    let mut printer = Printer::new(cli.output_config()?, std::io::stdout().lock());
+
+    let (sender, receiver) = channel();
+    let watch_roots = roots.clone();
+    let handle = std::thread::spawn(move || finder.watch(&watch_roots, AlwaysTrueFilter, sender));
+
+    for path in receiver {
+        // 嵌套的搜索路径中取最长的前缀
+        let root = roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .unwrap_or(&roots[0]);
+        printer.print(root, &path)?;
+        printer.flush()?;
+    }
+
+    handle
+        .join()
+        .map_err(|_| anyhow::anyhow!("监视线程异常退出"))?
+        .with_context(|| "监视文件系统失败")?;
+    Ok(())
+}
+
+/// 从 USN 变更日志中读取每个搜索路径下发生变化的匹配条目
+fn changed_since_usn(cli: &Cli, usn: i64) -> Result<()> {
+    let finder = build_finder(cli, cli.build_options())?;
+    let mut printer = Printer::new(cli.output_config()?, std::io::stdout().lock());
+    let mut next_usn = usn;
+    for root in cli.paths.iter().map(PathBuf::from) {
+        let changes = finder
+            .changed_since_usn(&root, usn, AlwaysTrueFilter)
+            .with_context(|| format!("无法读取 {} 的变更日志", root.display()))?;
+        for path in &changes.paths {
+            printer.print(&root, path)?;
+        }
+        next_usn = next_usn.max(changes.next_usn);
+    }
+    printer.flush()?;
+    eprintln!("下一个 USN: {}", next_usn);
+    Ok(())
+}
 
 fn main() -> Result<()> {
     // 解析命令行参数
-    let cli = Cli::parse();
+    let mut cli = Cli::parse_with_env_defaults();
+
+    if let Some(shell) = cli.completions {
+        Cli::write_completions(shell, &mut std::io::stdout());
+        return Ok(());
+    }
 
     // 初始化日志
     env_logger::Builder::new()
         .filter_level(if cli.debug {
             log::LevelFilter::Debug
+        } else if cli.quiet {
+            log::LevelFilter::Error
         } else {
             log::LevelFilter::Info
         })
         .init();
 
+    cli.load_start_points().with_context(|| "读取起始路径列表失败")?;
+
     info!("开始运行 rust-find");
     let start_time = Instant::now();
 
-    // 为每个指定的路径执行搜索
-    for path in &cli.paths {
-        debug!("在路径中搜索: {}", path);
+    match &cli.command {
+        Some(Command::Bench(args)) => return run_bench(args),
+        Some(Command::Audit(args)) => return run_audit(args),
+        Some(Command::Serve(args)) => return run_serve(args),
+        Some(Command::Daemon(args)) => return run_daemon(args),
+        Some(Command::Query(args)) => return run_query(args),
+        Some(Command::Grpc(args)) => return run_grpc(args),
+        Some(Command::Index(args)) => return run_index(args),
+        None => {}
+    }
 
-        // 创建查找选项
-        let options = cli.build_options();
+    if cli.watch {
+        return watch(&cli);
+    }
 
-        // 创建过滤器
-        let empty_vec = Vec::new();
-        let name_patterns = if !cli.name.is_empty() {
-            &cli.name
-        } else if !cli.iname.is_empty() {
-            &cli.iname
-        } else {
-            &empty_vec
-        };
+    if let Some(path) = &cli.explain_path {
+        return explain_path(&cli, path);
+    }
 
-        // 创建名称过滤器
-        let name_filter = if !name_patterns.is_empty() {
-            Some(NameFilter::new(&name_patterns[0])
-                .with_context(|| "创建名称过滤器失败")?)
-        } else {
-            None
-        };
+    if let Some(usn) = cli.changed_since_usn {
+        return changed_since_usn(&cli, usn);
+    }
 
-        // 创建查找器并添加过滤器
-        let finder = Finder::new(options);
-        let finder = if let Some(filter) = name_filter {
-            finder.with_filter(filter)
-        } else {
-            finder
-        };
+    // --quiet 只需要知道是否存在匹配
+    if cli.quiet {
+        cli.max_results = Some(1);
+    }
 
-        // 执行搜索
-        struct AlwaysTrueFilter;
-        impl FileFilter for AlwaysTrueFilter {
-            fn matches(&self, _: &DirEntry) -> bool {
-                true
-            }
+    // 快照模式下需要收集所有路径的结果
+    let snapshot_mode = cli.save_snapshot.is_some() || cli.diff_snapshot.is_some();
+    let mut all_results = Vec::new();
+
+    let mut printer = Printer::new(cli.output_config()?, std::io::stdout().lock());
 
-            fn description(&self) -> String {
-                "始终匹配所有文件".to_string()
+    // 指定了操作时不再打印路径
+    let mut actions = cli.build_actions()?;
+
+    // --sort score 按内容匹配次数排序每个路径的结果
+    let scorer = cli.build_scorer()?;
+
+    // 增量扫描缓存在所有路径之间共享
+    let mut scan_cache = cli.cache.as_ref().map(|file| ScanCache::load(Path::new(file)));
+
+    // --summarize 汇总所有搜索路径的结果
+    let mut summary = cli.summarize.then(|| Summary::new(cli.size_measure(), cli.follow_links));
+
+    // --dedup 在所有搜索路径之间去除重复的结果，重复的条目归属于先给出的路径
+    let mut dedup = cli.dedup.then(|| Dedup::new(cli.follow_links));
+
+    // 非递归列出和增量扫描逐个路径进行，其余情况下所有路径在一次并行遍历中搜索
+    let roots: Vec<PathBuf> = cli.paths.iter().map(PathBuf::from).collect();
+
+    // sftp:// 和 s3:// 路径单独搜索，结果只用于输出和计数
+    let remote: Vec<Option<RemoteRoot>> = cli.paths.iter().map(|path| RemoteRoot::parse(path)).collect();
+    let has_remote = remote.iter().any(Option::is_some);
+    if has_remote && !actions.is_empty() {
+        anyhow::bail!("不能对远程路径中的结果执行操作");
+    }
+    let local_roots: Vec<PathBuf> = roots
+        .iter()
+        .zip(&remote)
+        .filter(|(_, url)| url.is_none())
+        .map(|(root, _)| root.clone())
+        .collect();
+
+    // 只需要数量时不收集结果路径
+    if cli.count
+        && !cli.no_recurse
+        && scan_cache.is_none()
+        && dedup.is_none()
+        && !snapshot_mode
+        && !cli.search_archives
+        && !has_remote
+    {
+        let finder = build_finder(&cli, cli.build_options())?;
+        let counts = finder.count_roots(&roots, AlwaysTrueFilter);
+        check_aborted(&finder)?;
+        print_counts(&cli, &roots, &counts);
+        let stats = finder.stats();
+        if cli.stats {
+            eprintln!("{}", stats);
+        }
+        info!("搜索完成，耗时 {:.2?}", start_time.elapsed());
+        exit_on_errors(&cli, &stats);
+        return Ok(());
+    }
+    let mut counts = Vec::with_capacity(roots.len());
+    let mut matched = false;
+    // 与 GNU find 的 -delete 和 -exec ... + 相同，操作失败时以状态 1 退出
+    let mut action_failed = false;
+
+    // --archive 在遍历过程中写入匹配项；需要先得到全部结果（排序、汇总、快照等）时在搜索结束后写入
+    let streaming = cli.archive.is_some()
+        && !cli.no_recurse
+        && scan_cache.is_none()
+        && !cli.deterministic
+        && !cli.search_archives
+        && scorer.is_none()
+        && summary.is_none()
+        && !snapshot_mode;
+    // 边搜索边执行操作时每个路径的匹配数
+    let mut streamed = vec![0; roots.len()];
+
+    let (local_searches, stats) = if local_roots.is_empty() {
+        (Vec::new(), SearchStats::default())
+    } else if streaming {
+        // 有操作时不存在远程路径，本地路径就是全部搜索路径
+        let (counts, stats, failed) = stream_to_actions(&cli, &local_roots, &mut actions, dedup.as_mut())?;
+        action_failed |= failed;
+        streamed[..counts.len()].copy_from_slice(&counts);
+        (vec![Vec::new(); local_roots.len()], stats)
+    } else if cli.no_recurse || scan_cache.is_some() {
+        search_each(&cli, &local_roots, scan_cache.as_mut())?
+    } else {
+        let finder = build_finder(&cli, cli.build_options())?;
+        let searches = finder.find_parallel_roots(&local_roots, AlwaysTrueFilter);
+        check_aborted(&finder)?;
+        (searches, finder.stats())
+    };
+
+    // 按给出的顺序合并本地和远程路径的结果
+    let mut local_searches = local_searches.into_iter();
+    let mut searches = Vec::with_capacity(roots.len());
+    for url in &remote {
+        match url {
+            Some(url) => searches.push(search_remote(&cli, url)?),
+            None => searches.push(local_searches.next().unwrap_or_default()),
+        }
+    }
+
+    for (((root, mut results), url), streamed) in roots.iter().zip(searches).zip(&remote).zip(streamed) {
+        if let Some(dedup) = dedup.as_mut() {
+            results.retain(|entry| dedup.insert(entry));
+        }
+        if let Some(scorer) = &scorer {
+            scorer.sort(&mut results);
+        }
+
+        // 对结果执行操作，单个结果失败不会中止整个运行
+        for action in actions.iter_mut() {
+            for entry in &results {
+                if let Err(e) = action.run(entry) {
+                    error!("{}", e);
+                    action_failed = true;
+                }
             }
         }
 
-        let filter = AlwaysTrueFilter;
-        let results = if cli.parallel {
-            finder.find_parallel(std::path::PathBuf::from(path), filter)
+        // 归档中的条目只参与计数和打印
+        let members = if cli.search_archives && !cli.no_recurse && url.is_none() {
+            search_archives(&cli, root)?
         } else {
-            finder.find(std::path::PathBuf::from(path), filter)
+            Vec::new()
         };
 
-        // 打印结果
-        for entry in results {
-            println!("{}", entry.as_path().display());
+        matched |= !results.is_empty() || !members.is_empty() || streamed > 0;
+
+        // 打印结果（差异模式下只输出差异）
+        if cli.count {
+            counts.push(results.len() + members.len() + streamed);
+        } else if let Some(summary) = summary.as_mut() {
+            for entry in &results {
+                summary.add(entry);
+            }
+        } else if cli.diff_snapshot.is_none() && actions.is_empty() && !cli.quiet {
+            for entry in results.iter().chain(&members) {
+                printer.print(root, entry)?;
+            }
+        }
+
+        if snapshot_mode {
+            all_results.extend(results);
+        }
+    }
+    printer.flush()?;
+
+    if let Some(summary) = &summary {
+        println!("{}", summary);
+    }
+    if cli.count {
+        print_counts(&cli, &roots, &counts);
+    }
+
+    if let (Some(cache), Some(file)) = (&scan_cache, &cli.cache) {
+        cache.save(Path::new(file))
+            .with_context(|| format!("保存扫描缓存失败: {}", file))?;
+    }
+
+    for action in actions.iter_mut() {
+        if let Err(e) = action.finish() {
+            error!("{}", e);
+            action_failed = true;
+        }
+    }
+
+    if snapshot_mode {
+        let snapshot = Snapshot::from_paths(&all_results);
+
+        if let Some(previous_file) = &cli.diff_snapshot {
+            let previous = Snapshot::load(previous_file)
+                .with_context(|| format!("加载快照失败: {}", previous_file))?;
+            for line in snapshot.diff(&previous, &cli.diff_fields).lines() {
+                println!("{}", line);
+            }
+        }
+
+        if let Some(snapshot_file) = &cli.save_snapshot {
+            snapshot.save(snapshot_file)
+                .with_context(|| format!("保存快照失败: {}", snapshot_file))?;
         }
     }
 
     let elapsed = start_time.elapsed();
     info!("搜索完成，耗时 {:.2?}", elapsed);
 
+    if cli.stats {
+        eprintln!("{}", stats);
+    }
+
+    // --quiet 的退出状态只取决于是否存在匹配
+    if cli.quiet {
+        std::process::exit(if matched { 0 } else { 1 });
+    }
+    exit_on_errors(&cli, &stats);
+    if action_failed {
+        std::process::exit(1);
+    }
+
     Ok(())
 }
\ No newline at end of file
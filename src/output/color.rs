@@ -0,0 +1,225 @@
+//! 按条目类型着色的终端输出
+//!
+//! 颜色规则取自 `LS_COLORS` 环境变量（与 `ls --color` 相同的格式），
+//! 未设置时使用与 GNU ls 相同的默认配色。遵循 `NO_COLOR` 约定：
+//! 该变量非空时 `--color=auto` 不输出颜色。
+
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::io::IsTerminal;
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use super::meta;
+
+/// 未设置 `LS_COLORS` 时使用的配色
+const DEFAULT_LS_COLORS: &str = "di=01;34:ln=01;36:pi=40;33:so=01;35:bd=40;33;01:cd=40;33;01:\
+or=40;31;01:su=37;41:sg=30;43:tw=30;42:ow=34;42:st=37;44:ex=01;32";
+
+/// 何时输出颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ColorChoice {
+    /// 标准输出为终端且未设置 NO_COLOR 时着色
+    #[default]
+    Auto,
+    /// 始终着色
+    Always,
+    /// 从不着色
+    Never,
+}
+
+impl ColorChoice {
+    /// 根据当前环境判断是否应该着色
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+                let dumb = std::env::var_os("TERM").is_some_and(|term| term == "dumb");
+                !no_color && !dumb && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// 解析后的 `LS_COLORS` 配色
+///
+/// # 示例
+/// ```
+/// use rust_find::output::LsColors;
+///
+/// let colors = LsColors::parse("di=01;34:*.rs=33");
+/// assert_eq!(colors.extension_style("main.rs"), Some("33"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LsColors {
+    /// 按类型的配色，键为 `di`、`ln`、`ex` 等
+    types: HashMap<String, String>,
+    /// 按文件名后缀的配色（`*.ext=...`），按出现顺序保存
+    suffixes: Vec<(String, String)>,
+}
+
+impl LsColors {
+    /// 从 `LS_COLORS` 环境变量读取配色，未设置或为空时使用默认配色
+    pub fn from_env() -> Self {
+        match std::env::var("LS_COLORS") {
+            Ok(value) if !value.is_empty() => Self::parse(&value),
+            _ => Self::parse(DEFAULT_LS_COLORS),
+        }
+    }
+
+    /// 解析 `LS_COLORS` 格式的字符串，忽略无法识别的条目
+    pub fn parse(spec: &str) -> Self {
+        let mut colors = Self::default();
+        for entry in spec.split(':') {
+            let Some((key, style)) = entry.split_once('=') else {
+                continue;
+            };
+            if style.is_empty() || style.chars().any(|c| !c.is_ascii_digit() && c != ';') {
+                continue;
+            }
+            match key.strip_prefix('*') {
+                Some(suffix) if !suffix.is_empty() => colors.suffixes.push((suffix.to_string(), style.to_string())),
+                Some(_) => {}
+                None => {
+                    colors.types.insert(key.to_string(), style.to_string());
+                }
+            }
+        }
+        colors
+    }
+
+    /// 按文件名后缀查找配色，后出现的规则优先
+    pub fn extension_style(&self, name: &str) -> Option<&str> {
+        self.suffixes
+            .iter()
+            .rev()
+            .find(|(suffix, _)| name.ends_with(suffix.as_str()))
+            .map(|(_, style)| style.as_str())
+            .filter(|style| !is_reset(style))
+    }
+
+    /// 获取条目的配色，无需着色时返回 None
+    ///
+    /// 优先级与 GNU ls 相同：特殊类型和权限优先，普通文件再按后缀匹配。
+    pub fn style_for(&self, path: &Path, metadata: &Metadata) -> Option<&str> {
+        let file_type = metadata.file_type();
+        let key = match meta::type_char(&file_type) {
+            'l' => {
+                if path.metadata().is_err() && self.types.contains_key("or") {
+                    "or"
+                } else {
+                    "ln"
+                }
+            }
+            'd' => {
+                let mode = meta::mode_bits(metadata);
+                match (mode & 0o1000 != 0, mode & 0o002 != 0) {
+                    (true, true) => "tw",
+                    (false, true) => "ow",
+                    (true, false) => "st",
+                    (false, false) => "di",
+                }
+            }
+            'p' => "pi",
+            's' => "so",
+            'b' => "bd",
+            'c' => "cd",
+            _ => {
+                let mode = meta::mode_bits(metadata);
+                let special = if mode & 0o4000 != 0 {
+                    Some("su")
+                } else if mode & 0o2000 != 0 {
+                    Some("sg")
+                } else if cfg!(unix) && mode & 0o111 != 0 {
+                    Some("ex")
+                } else {
+                    None
+                };
+
+                if let Some(style) = special.and_then(|key| self.type_style(key)) {
+                    return Some(style);
+                }
+                let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+                return self.extension_style(&name).or_else(|| self.type_style("fi"));
+            }
+        };
+
+        self.type_style(key).or_else(|| match key {
+            // 未配置特殊目录颜色时退回普通目录颜色
+            "tw" | "ow" | "st" => self.type_style("di"),
+            _ => None,
+        })
+    }
+
+    /// 按类型键查找配色，`0`/`00` 表示不着色
+    fn type_style(&self, key: &str) -> Option<&str> {
+        self.types
+            .get(key)
+            .map(String::as_str)
+            .filter(|style| !is_reset(style))
+    }
+}
+
+/// `0`/`00` 等只表示重置的配色
+fn is_reset(style: &str) -> bool {
+    style.trim_start_matches('0').is_empty()
+}
+
+/// 用 ANSI 转义序列包裹文本
+pub fn paint(style: &str, text: &str) -> String {
+    format!("\x1b[{}m{}\x1b[0m", style, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_ls_colors() {
+        let colors = LsColors::parse("di=01;34:ln=01;36:*.tar=01;31:*.gz=0:bogus:xx=abc");
+        assert_eq!(colors.type_style("di"), Some("01;34"));
+        assert_eq!(colors.extension_style("a.tar"), Some("01;31"));
+        assert_eq!(colors.extension_style("a.txt"), None);
+        assert_eq!(colors.extension_style("a.gz"), None);
+        assert!(!colors.types.contains_key("xx"));
+    }
+
+    #[test]
+    fn test_style_for_entries() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let file = dir.path().join("archive.tar");
+        std::fs::write(&file, "x")?;
+
+        let colors = LsColors::parse("di=01;34:*.tar=01;31");
+        assert_eq!(colors.style_for(dir.path(), &dir.path().symlink_metadata()?), Some("01;34"));
+        assert_eq!(colors.style_for(&file, &file.symlink_metadata()?), Some("01;31"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let script = dir.path().join("run.sh");
+            std::fs::write(&script, "#!/bin/sh")?;
+            std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))?;
+            let colors = LsColors::parse("ex=01;32:*.sh=33");
+            assert_eq!(colors.style_for(&script, &script.symlink_metadata()?), Some("01;32"));
+
+            let link = dir.path().join("dangling");
+            std::os::unix::fs::symlink("missing", &link)?;
+            let colors = LsColors::parse("ln=01;36:or=40;31");
+            assert_eq!(colors.style_for(&link, &link.symlink_metadata()?), Some("40;31"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_color_choice() {
+        assert!(ColorChoice::Always.enabled());
+        assert!(!ColorChoice::Never.enabled());
+        assert_eq!(paint("01;34", "dir"), "\x1b[01;34mdir\x1b[0m");
+    }
+}
@@ -0,0 +1,100 @@
+//! 跨搜索路径去除重复的结果（`--dedup`）
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::meta;
+
+/// 条目的身份
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum Identity {
+    /// (设备号, inode)
+    Inode(u64, u64),
+    /// 无法取得 inode 时使用规范化的路径
+    Path(PathBuf),
+}
+
+/// 记录已输出的条目，过滤掉重复出现的条目
+///
+/// 条目按 (设备号, inode) 识别，因此重叠的搜索路径（如 `/` 和 `/home`）、
+/// 跟随符号链接后再次到达的条目以及同一文件的多个硬链接都只保留第一次出现的路径。
+#[derive(Debug, Default)]
+pub struct Dedup {
+    follow_links: bool,
+    seen: HashSet<Identity>,
+}
+
+impl Dedup {
+    /// 创建空的记录
+    ///
+    /// `follow_links` 为 true 时按符号链接指向的目标识别条目，否则按链接本身识别。
+    pub fn new(follow_links: bool) -> Self {
+        Self { follow_links, ..Self::default() }
+    }
+
+    /// 记录一个条目，条目第一次出现时返回 true
+    pub fn insert(&mut self, path: &Path) -> bool {
+        self.seen.insert(self.identity(path))
+    }
+
+    fn identity(&self, path: &Path) -> Identity {
+        let metadata = if self.follow_links { path.metadata() } else { path.symlink_metadata() };
+        match metadata {
+            Ok(metadata) if cfg!(unix) => Identity::Inode(meta::device(&metadata), meta::inode(&metadata)),
+            _ => Identity::Path(canonical(path, self.follow_links)),
+        }
+    }
+}
+
+/// 规范化路径，不跟随符号链接时保留最后一个组件本身
+fn canonical(path: &Path, follow_links: bool) -> PathBuf {
+    let resolved = match (follow_links, path.parent(), path.file_name()) {
+        (false, Some(parent), Some(name)) => {
+            let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+            parent.canonicalize().map(|parent| parent.join(name))
+        }
+        _ => path.canonicalize(),
+    };
+    resolved.unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_dedup_overlapping_paths() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        fs::create_dir(dir.path().join("sub"))?;
+        fs::write(dir.path().join("sub/file"), "x")?;
+        fs::write(dir.path().join("other"), "x")?;
+
+        let mut dedup = Dedup::new(false);
+        assert!(dedup.insert(&dir.path().join("sub/file")));
+        assert!(dedup.insert(&dir.path().join("other")));
+        assert!(!dedup.insert(&dir.path().join("sub/../sub/file")));
+        assert!(!dedup.insert(&dir.path().join("sub/file")));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dedup_through_symlinks() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("file"), "x")?;
+        std::os::unix::fs::symlink(dir.path().join("file"), dir.path().join("link"))?;
+
+        let mut dedup = Dedup::new(false);
+        assert!(dedup.insert(&dir.path().join("file")));
+        assert!(dedup.insert(&dir.path().join("link")));
+
+        let mut dedup = Dedup::new(true);
+        assert!(dedup.insert(&dir.path().join("file")));
+        assert!(!dedup.insert(&dir.path().join("link")));
+
+        Ok(())
+    }
+}
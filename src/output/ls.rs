@@ -0,0 +1,113 @@
+//! `--ls` 长格式输出
+//!
+//! 与 `find -ls` 相同，每行包含 inode、占用块数（KiB）、权限字符串、硬链接数、
+//! 属主、属组、大小、修改时间和路径，符号链接额外显示链接目标。
+
+use std::fs::Metadata;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local};
+
+use super::color::{paint, LsColors};
+use super::meta::{self, NameCache};
+
+/// 超过该时间的修改时间显示年份而不是时分（与 `ls -l` 相同，约六个月）
+const RECENT: Duration = Duration::from_secs(182 * 24 * 60 * 60);
+
+/// 生成一行长格式输出（不含结束符）
+///
+/// 无法读取元数据时只输出路径。指定配色时，路径按条目类型着色。
+pub fn format_long(
+    path: &Path,
+    display_path: &Path,
+    names: &mut NameCache,
+    colors: Option<&LsColors>,
+) -> String {
+    let Ok(metadata) = path.symlink_metadata() else {
+        return display_path.to_string_lossy().into_owned();
+    };
+
+    let (user, group) = match meta::owner_ids(&metadata) {
+        Some((uid, gid)) => (names.user(uid).to_string(), names.group(gid).to_string()),
+        None => ("-".to_string(), "-".to_string()),
+    };
+
+    let mut line = format!(
+        "{:>9} {:>6} {} {:>3} {:<8} {:<8} {:>8} {} {}",
+        meta::inode(&metadata),
+        meta::kib_blocks(&metadata),
+        meta::mode_string(&metadata),
+        meta::link_count(&metadata),
+        user,
+        group,
+        metadata.len(),
+        format_mtime(&metadata, SystemTime::now()),
+        match colors.and_then(|colors| colors.style_for(path, &metadata)) {
+            Some(style) => paint(style, &display_path.to_string_lossy()),
+            None => display_path.to_string_lossy().into_owned(),
+        },
+    );
+
+    if metadata.file_type().is_symlink() {
+        if let Ok(target) = std::fs::read_link(path) {
+            line.push_str(" -> ");
+            line.push_str(&target.to_string_lossy());
+        }
+    }
+
+    line
+}
+
+/// 格式化修改时间：近期的显示时分，较早或未来的显示年份
+fn format_mtime(metadata: &Metadata, now: SystemTime) -> String {
+    let Ok(modified) = metadata.modified() else {
+        return format!("{:>12}", "?");
+    };
+
+    let recent = match now.duration_since(modified) {
+        Ok(age) => age < RECENT,
+        Err(_) => false,
+    };
+    let format = if recent { "%b %e %H:%M" } else { "%b %e  %Y" };
+    DateTime::<Local>::from(modified).format(format).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_format_long() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let file = dir.path().join("data.txt");
+        std::fs::write(&file, "hello")?;
+
+        let line = format_long(&file, &file, &mut NameCache::new(), None);
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        assert!(columns[2].starts_with('-'));
+        assert_eq!(columns[6], "5");
+        assert!(line.ends_with(&*file.to_string_lossy()));
+
+        // 无法读取元数据时只输出路径
+        let missing = dir.path().join("missing");
+        assert_eq!(format_long(&missing, &missing, &mut NameCache::new(), None), missing.to_string_lossy());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_format_long_symlink_target() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink("target.txt", &link)?;
+
+        let line = format_long(&link, &link, &mut NameCache::new(), None);
+        assert!(line.ends_with(" -> target.txt"));
+        assert!(line.split_whitespace().nth(2).unwrap().starts_with('l'));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,498 @@
+//! 输出中使用的文件元数据格式化
+//!
+//! 将不同平台的元数据（权限位、属主、条目类型等）统一为输出需要的形式，
+//! 非 Unix 平台上无法获得的信息使用合理的替代值。
+
+use std::collections::HashMap;
+use std::fs::{FileType, Metadata};
+use std::path::Path;
+
+/// 条目类型字符，与 GNU find 的 `%y` 相同
+///
+/// `f` 普通文件、`d` 目录、`l` 符号链接、`p` 命名管道、`s` 套接字、
+/// `c` 字符设备、`b` 块设备，无法识别时为 `U`。
+pub fn type_char(file_type: &FileType) -> char {
+    if file_type.is_symlink() {
+        return 'l';
+    }
+    if file_type.is_dir() {
+        return 'd';
+    }
+    if file_type.is_file() {
+        return 'f';
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if file_type.is_fifo() {
+            return 'p';
+        }
+        if file_type.is_socket() {
+            return 's';
+        }
+        if file_type.is_char_device() {
+            return 'c';
+        }
+        if file_type.is_block_device() {
+            return 'b';
+        }
+    }
+
+    'U'
+}
+
+/// 权限位（包括 setuid/setgid/sticky 位）
+///
+/// 非 Unix 平台上根据只读属性返回 `0o444` 或 `0o644`，目录额外带执行位。
+pub fn mode_bits(metadata: &Metadata) -> u32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o7777
+    }
+
+    #[cfg(not(unix))]
+    {
+        let mut mode = if metadata.permissions().readonly() { 0o444 } else { 0o644 };
+        if metadata.is_dir() {
+            mode |= 0o111;
+        }
+        mode
+    }
+}
+
+/// `ls -l` 风格的权限字符串，如 `-rw-r--r--`、`drwxr-sr-x`
+pub fn mode_string(metadata: &Metadata) -> String {
+    let mode = mode_bits(metadata);
+    let type_char = match type_char(&metadata.file_type()) {
+        'f' | 'U' => '-',
+        other => other,
+    };
+
+    let mut result = String::with_capacity(10);
+    result.push(type_char);
+    for (shift, special, special_char) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits = (mode >> shift) & 0o7;
+        result.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        result.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        result.push(match (bits & 0o1 != 0, mode & special != 0) {
+            (true, true) => special_char,
+            (false, true) => special_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    result
+}
+
+/// inode 编号，非 Unix 平台上返回 0
+pub fn inode(metadata: &Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        metadata.ino()
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        0
+    }
+}
+
+/// 所在设备号，非 Unix 平台上返回 0
+pub fn device(metadata: &Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        metadata.dev()
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        0
+    }
+}
+
+/// 硬链接数，非 Unix 平台上返回 1
+pub fn link_count(metadata: &Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        metadata.nlink()
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        1
+    }
+}
+
+/// 占用的磁盘空间（以 1 KiB 为单位）
+///
+/// Unix 上根据分配的 512 字节块计算，其他平台按文件大小向上取整。
+pub fn kib_blocks(metadata: &Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        metadata.blocks().div_ceil(2)
+    }
+
+    #[cfg(not(unix))]
+    {
+        metadata.len().div_ceil(1024)
+    }
+}
+
+/// 实际分配的磁盘空间（字节）
+///
+/// 压缩、稀疏或去重的文件系统上可能远小于文件大小，也可能因块对齐而更大。
+/// 非 Unix 平台上没有分配信息，返回文件大小。
+pub fn disk_usage(metadata: &Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        metadata.blocks() * 512
+    }
+
+    #[cfg(not(unix))]
+    {
+        metadata.len()
+    }
+}
+
+/// 属主和属组的数字 ID，非 Unix 平台上返回 None
+pub fn owner_ids(metadata: &Metadata) -> Option<(u32, u32)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some((metadata.uid(), metadata.gid()))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
+}
+
+/// 用户名和组名缓存
+///
+/// 查询用户数据库可能较慢（例如经过 NSS/LDAP），同一次运行中的结果会被缓存。
+/// 无法解析的 ID 以数字形式输出。
+#[derive(Debug, Default)]
+pub struct NameCache {
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+}
+
+impl NameCache {
+    /// 创建空缓存
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 获取用户名
+    pub fn user(&mut self, uid: u32) -> &str {
+        self.users
+            .entry(uid)
+            .or_insert_with(|| lookup_user(uid).unwrap_or_else(|| uid.to_string()))
+    }
+
+    /// 获取组名
+    pub fn group(&mut self, gid: u32) -> &str {
+        self.groups
+            .entry(gid)
+            .or_insert_with(|| lookup_group(gid).unwrap_or_else(|| gid.to_string()))
+    }
+}
+
+/// 初始查询缓冲区大小
+#[cfg(unix)]
+const LOOKUP_BUFFER_SIZE: usize = 1024;
+
+/// 查询缓冲区的上限，防止异常的 ERANGE 循环
+#[cfg(unix)]
+const LOOKUP_BUFFER_LIMIT: usize = 1 << 20;
+
+/// 从用户数据库查询用户名
+#[cfg(unix)]
+fn lookup_user(uid: u32) -> Option<String> {
+    let mut buffer = vec![0 as libc::c_char; LOOKUP_BUFFER_SIZE];
+    loop {
+        // SAFETY: passwd 为输出参数；buffer 在调用期间有效且长度正确，
+        // 返回的字符串指针指向 buffer 内部，在 buffer 释放前读取
+        let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result: *mut libc::passwd = std::ptr::null_mut();
+        let code = unsafe {
+            libc::getpwuid_r(uid, &mut passwd, buffer.as_mut_ptr(), buffer.len(), &mut result)
+        };
+        if code == libc::ERANGE && buffer.len() < LOOKUP_BUFFER_LIMIT {
+            buffer.resize(buffer.len() * 2, 0);
+            continue;
+        }
+        if code != 0 || result.is_null() {
+            return None;
+        }
+        let name = unsafe { std::ffi::CStr::from_ptr(passwd.pw_name) };
+        return Some(name.to_string_lossy().into_owned());
+    }
+}
+
+/// 从组数据库查询组名
+#[cfg(unix)]
+fn lookup_group(gid: u32) -> Option<String> {
+    let mut buffer = vec![0 as libc::c_char; LOOKUP_BUFFER_SIZE];
+    loop {
+        // SAFETY: 同 lookup_user
+        let mut group: libc::group = unsafe { std::mem::zeroed() };
+        let mut result: *mut libc::group = std::ptr::null_mut();
+        let code = unsafe {
+            libc::getgrgid_r(gid, &mut group, buffer.as_mut_ptr(), buffer.len(), &mut result)
+        };
+        if code == libc::ERANGE && buffer.len() < LOOKUP_BUFFER_LIMIT {
+            buffer.resize(buffer.len() * 2, 0);
+            continue;
+        }
+        if code != 0 || result.is_null() {
+            return None;
+        }
+        let name = unsafe { std::ffi::CStr::from_ptr(group.gr_name) };
+        return Some(name.to_string_lossy().into_owned());
+    }
+}
+
+/// 用户 ID 是否对应用户数据库中存在的用户
+pub fn user_exists(uid: u32) -> bool {
+    lookup_user(uid).is_some()
+}
+
+/// 按用户名查询用户 ID，数字形式直接解析
+#[cfg(unix)]
+pub fn user_id(name: &str) -> Option<u32> {
+    if let Ok(uid) = name.parse() {
+        return Some(uid);
+    }
+    let c_name = std::ffi::CString::new(name).ok()?;
+    let mut buffer = vec![0 as libc::c_char; LOOKUP_BUFFER_SIZE];
+    loop {
+        // SAFETY: 同 lookup_user
+        let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result: *mut libc::passwd = std::ptr::null_mut();
+        let code = unsafe {
+            libc::getpwnam_r(c_name.as_ptr(), &mut passwd, buffer.as_mut_ptr(), buffer.len(), &mut result)
+        };
+        if code == libc::ERANGE && buffer.len() < LOOKUP_BUFFER_LIMIT {
+            buffer.resize(buffer.len() * 2, 0);
+            continue;
+        }
+        return (code == 0 && !result.is_null()).then_some(passwd.pw_uid);
+    }
+}
+
+/// 按组名查询组 ID，数字形式直接解析
+#[cfg(unix)]
+pub fn group_id(name: &str) -> Option<u32> {
+    if let Ok(gid) = name.parse() {
+        return Some(gid);
+    }
+    let c_name = std::ffi::CString::new(name).ok()?;
+    let mut buffer = vec![0 as libc::c_char; LOOKUP_BUFFER_SIZE];
+    loop {
+        // SAFETY: 同 lookup_user
+        let mut group: libc::group = unsafe { std::mem::zeroed() };
+        let mut result: *mut libc::group = std::ptr::null_mut();
+        let code = unsafe {
+            libc::getgrnam_r(c_name.as_ptr(), &mut group, buffer.as_mut_ptr(), buffer.len(), &mut result)
+        };
+        if code == libc::ERANGE && buffer.len() < LOOKUP_BUFFER_LIMIT {
+            buffer.resize(buffer.len() * 2, 0);
+            continue;
+        }
+        return (code == 0 && !result.is_null()).then_some(group.gr_gid);
+    }
+}
+
+/// 非 Unix 平台没有数字用户 ID
+#[cfg(not(unix))]
+fn lookup_user(_uid: u32) -> Option<String> {
+    None
+}
+
+/// 非 Unix 平台没有用户数据库，只接受数字形式
+#[cfg(not(unix))]
+pub fn user_id(name: &str) -> Option<u32> {
+    name.parse().ok()
+}
+
+/// 非 Unix 平台没有组数据库，只接受数字形式
+#[cfg(not(unix))]
+pub fn group_id(name: &str) -> Option<u32> {
+    name.parse().ok()
+}
+
+/// 非 Unix 平台没有数字组 ID
+#[cfg(not(unix))]
+fn lookup_group(_gid: u32) -> Option<String> {
+    None
+}
+
+/// 属性不存在时返回的错误码
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const ENOATTR: i32 = libc::ENODATA;
+#[cfg(target_os = "macos")]
+const ENOATTR: i32 = libc::ENOATTR;
+
+/// 读取扩展属性的值
+///
+/// 属性不存在或文件系统不支持扩展属性时返回 `Ok(None)`。`follow` 为 false 时读取
+/// 符号链接本身的属性。只支持 Linux 和 macOS，其他平台上总是返回 `Ok(None)`。
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+pub fn xattr(path: &Path, name: &str, follow: bool) -> std::io::Result<Option<Vec<u8>>> {
+    use std::ffi::CString;
+    use std::io::{Error, ErrorKind};
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|_| Error::from(ErrorKind::InvalidInput))?;
+    let c_name = CString::new(name).map_err(|_| Error::from(ErrorKind::InvalidInput))?;
+    let mut value = Vec::new();
+    loop {
+        // 先查询长度，再读取值
+        let size = get_xattr(&c_path, &c_name, &mut [], follow);
+        if size >= 0 {
+            value.resize(size as usize, 0);
+            let read = get_xattr(&c_path, &c_name, &mut value, follow);
+            if read >= 0 {
+                value.truncate(read as usize);
+                return Ok(Some(value));
+            }
+        }
+
+        let error = Error::last_os_error();
+        match error.raw_os_error() {
+            Some(code) if code == ENOATTR || code == libc::ENOTSUP => return Ok(None),
+            // 两次调用之间属性值变长，重新查询长度
+            Some(libc::ERANGE) if value.len() < LOOKUP_BUFFER_LIMIT => continue,
+            _ => return Err(error),
+        }
+    }
+}
+
+/// 调用平台的 getxattr，返回值的长度或 -1
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+fn get_xattr(path: &std::ffi::CStr, name: &std::ffi::CStr, buffer: &mut [u8], follow: bool) -> isize {
+    let value = buffer.as_mut_ptr().cast::<libc::c_void>();
+    // SAFETY: path 和 name 是以 NUL 结尾的字符串，buffer 在调用期间有效，
+    // 长度为 0 时只查询属性值的长度，不写入 buffer
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    unsafe {
+        if follow {
+            libc::getxattr(path.as_ptr(), name.as_ptr(), value, buffer.len())
+        } else {
+            libc::lgetxattr(path.as_ptr(), name.as_ptr(), value, buffer.len())
+        }
+    }
+    // SAFETY: 同上
+    #[cfg(target_os = "macos")]
+    unsafe {
+        let options = if follow { 0 } else { libc::XATTR_NOFOLLOW };
+        libc::getxattr(path.as_ptr(), name.as_ptr(), value, buffer.len(), 0, options)
+    }
+}
+
+/// 读取 chattr 风格的 inode 标志（`FS_IOC_GETFLAGS`）
+///
+/// 需要打开文件，调用方应只对普通文件和目录调用。文件系统不支持 inode 标志时返回 `Ok(None)`。
+/// 只支持 Linux，其他平台上总是返回 `Ok(None)`。
+#[cfg(target_os = "linux")]
+pub fn inode_flags(path: &Path) -> std::io::Result<Option<u32>> {
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::io::AsRawFd;
+
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)?;
+    let mut flags: libc::c_long = 0;
+    // SAFETY: 文件描述符在调用期间有效，flags 足以容纳内核写入的标志
+    let code = unsafe { libc::ioctl(file.as_raw_fd(), libc::FS_IOC_GETFLAGS, &mut flags) };
+    if code == 0 {
+        return Ok(Some(flags as u32));
+    }
+
+    let error = std::io::Error::last_os_error();
+    match error.raw_os_error() {
+        Some(libc::ENOTTY | libc::ENOTSUP | libc::EINVAL) => Ok(None),
+        _ => Err(error),
+    }
+}
+
+/// 其他平台上没有 inode 标志
+#[cfg(not(target_os = "linux"))]
+pub fn inode_flags(_path: &Path) -> std::io::Result<Option<u32>> {
+    Ok(None)
+}
+
+/// 其他平台上不支持读取扩展属性
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+pub fn xattr(_path: &Path, _name: &str, _follow: bool) -> std::io::Result<Option<Vec<u8>>> {
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_type_char() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let file = dir.path().join("file");
+        std::fs::write(&file, "x")?;
+
+        assert_eq!(type_char(&std::fs::symlink_metadata(dir.path())?.file_type()), 'd');
+        assert_eq!(type_char(&std::fs::symlink_metadata(&file)?.file_type()), 'f');
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_mode_string() -> Result<(), Box<dyn std::error::Error>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir()?;
+        let file = dir.path().join("file");
+        std::fs::write(&file, "x")?;
+
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o644))?;
+        assert_eq!(mode_string(&std::fs::symlink_metadata(&file)?), "-rw-r--r--");
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o4754))?;
+        assert_eq!(mode_string(&std::fs::symlink_metadata(&file)?), "-rwsr-xr--");
+        std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o1776))?;
+        assert_eq!(mode_string(&std::fs::symlink_metadata(dir.path())?), "drwxrwxrwT");
+        std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o755))?;
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_name_cache_root() {
+        let mut cache = NameCache::new();
+        assert_eq!(cache.user(0), "root");
+        // 不存在的 ID 以数字形式输出
+        assert_eq!(cache.group(4_000_000_000), "4000000000");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_name_to_id() {
+        assert_eq!(user_id("root"), Some(0));
+        assert_eq!(user_id("1234"), Some(1234));
+        assert_eq!(group_id("4321"), Some(4321));
+        assert_eq!(user_id("no-such-user-rust-find"), None);
+    }
+}
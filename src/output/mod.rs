@@ -0,0 +1,456 @@
+//! 搜索结果输出
+//!
+//! 本模块负责将搜索结果写入输出流，包括：
+//! - 路径格式控制（原样、绝对路径、相对当前目录、相对搜索根目录）
+//! - 输出格式（纯文本、JSON、CSV）
+//! - 记录分隔符（换行或 NUL，便于与 `xargs -0` 配合）
+//! - GNU find 兼容的 `--printf` 格式字符串
+//! - `--ls` 长格式输出
+//! - 按 `LS_COLORS` 着色（`--color`）
+//! - 匹配结果的数量和总大小汇总（`--summarize`）
+//! - 跨搜索路径去除重复的结果（`--dedup`）
+
+mod color;
+mod dedup;
+mod ls;
+pub(crate) mod meta;
+mod printf;
+mod summary;
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, SecondsFormat};
+use clap::ValueEnum;
+
+pub use color::{ColorChoice, LsColors};
+pub use dedup::Dedup;
+pub use meta::NameCache;
+pub use printf::PrintfFormat;
+pub use summary::Summary;
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// 每行一个路径
+    #[default]
+    Plain,
+    /// 每行一个 JSON 对象（NDJSON），包含路径和所属的搜索根目录
+    Json,
+    /// 符合 RFC 4180 的 CSV，首行为表头，列由 `--fields` 指定
+    Csv,
+}
+
+/// 结果的排序方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    /// 按内容匹配的相关性（匹配次数）从高到低排序
+    Score,
+}
+
+/// CSV 输出中可选择的列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Field {
+    /// 路径（按路径显示方式转换）
+    Path,
+    /// 文件名
+    Name,
+    /// 所属的搜索根目录
+    Root,
+    /// 文件大小（字节）
+    Size,
+    /// 修改时间（RFC 3339，本地时区）
+    Mtime,
+    /// 条目类型：file、dir、symlink 或 other
+    Type,
+}
+
+impl Field {
+    /// 默认输出的列
+    pub const DEFAULT: [Field; 4] = [Field::Path, Field::Size, Field::Mtime, Field::Type];
+
+    /// 列名（用于表头）
+    pub fn name(&self) -> &'static str {
+        match self {
+            Field::Path => "path",
+            Field::Name => "name",
+            Field::Root => "root",
+            Field::Size => "size",
+            Field::Mtime => "mtime",
+            Field::Type => "type",
+        }
+    }
+
+    /// 是否需要读取文件元数据
+    fn needs_metadata(&self) -> bool {
+        matches!(self, Field::Size | Field::Mtime | Field::Type)
+    }
+}
+
+/// 路径显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathStyle {
+    /// 按遍历得到的形式输出
+    #[default]
+    AsFound,
+    /// 输出绝对路径
+    Absolute,
+    /// 输出相对于当前工作目录的路径
+    Relative,
+    /// 输出相对于所属搜索根目录的路径
+    RelativeToRoot,
+}
+
+/// 输出配置
+#[derive(Debug, Clone, Default)]
+pub struct OutputConfig {
+    /// 输出格式
+    pub format: OutputFormat,
+    /// 路径显示方式
+    pub path_style: PathStyle,
+    /// 使用 NUL 而不是换行结束每条记录（`--print0`）
+    pub null_terminated: bool,
+    /// CSV 输出的列，为空时使用 [`Field::DEFAULT`]
+    pub fields: Vec<Field>,
+    /// `--printf` 格式，设置后优先于输出格式
+    pub printf: Option<PrintfFormat>,
+    /// 使用 `find -ls` 风格的长格式输出
+    pub long_listing: bool,
+    /// 纯文本和长格式输出的配色，None表示不着色
+    pub colors: Option<LsColors>,
+}
+
+impl OutputConfig {
+    /// 每条记录的结束符
+    fn terminator(&self) -> &'static [u8] {
+        match (self.null_terminated, self.format) {
+            (true, _) => b"\0",
+            // RFC 4180 规定记录以 CRLF 结束
+            (false, OutputFormat::Csv) => b"\r\n",
+            (false, _) => b"\n",
+        }
+    }
+
+    /// CSV 输出的列
+    fn csv_fields(&self) -> &[Field] {
+        if self.fields.is_empty() {
+            &Field::DEFAULT
+        } else {
+            &self.fields
+        }
+    }
+}
+
+/// 搜索结果输出器
+///
+/// # 示例
+/// ```
+/// use rust_find::output::{OutputConfig, PathStyle, Printer};
+/// use std::path::Path;
+///
+/// let config = OutputConfig { path_style: PathStyle::RelativeToRoot, ..Default::default() };
+/// let mut printer = Printer::new(config, Vec::new());
+/// printer.print(Path::new("src"), Path::new("src/lib.rs")).unwrap();
+/// assert_eq!(printer.into_inner(), b"lib.rs\n");
+/// ```
+pub struct Printer<W: Write> {
+    config: OutputConfig,
+    writer: W,
+    current_dir: Option<PathBuf>,
+    header_written: bool,
+    names: NameCache,
+}
+
+impl<W: Write> Printer<W> {
+    /// 创建新的输出器
+    pub fn new(config: OutputConfig, writer: W) -> Self {
+        Self {
+            config,
+            writer,
+            current_dir: std::env::current_dir().ok(),
+            header_written: false,
+            names: NameCache::new(),
+        }
+    }
+
+    /// 输出一个搜索结果
+    ///
+    /// # 参数
+    /// - `root`: 找到该结果的搜索根目录
+    /// - `path`: 结果路径
+    pub fn print(&mut self, root: &Path, path: &Path) -> io::Result<()> {
+        let display_path = self.format_path(root, path);
+
+        // --printf 完全控制输出内容，包括结束符
+        if let Some(format) = &self.config.printf {
+            let output = format.render(root, path, &display_path, &mut self.names);
+            return self.writer.write_all(output.as_bytes());
+        }
+
+        if self.config.long_listing {
+            let line = ls::format_long(path, &display_path, &mut self.names, self.config.colors.as_ref());
+            self.writer.write_all(line.as_bytes())?;
+            return self.writer.write_all(self.config.terminator());
+        }
+
+        match self.config.format {
+            OutputFormat::Plain => {
+                let style = self.config.colors.as_ref().and_then(|colors| {
+                    let metadata = path.symlink_metadata().ok()?;
+                    colors.style_for(path, &metadata)
+                });
+                match style {
+                    Some(style) => {
+                        let painted = color::paint(style, &display_path.to_string_lossy());
+                        self.writer.write_all(painted.as_bytes())?;
+                    }
+                    None => write_path(&mut self.writer, &display_path)?,
+                }
+            }
+            OutputFormat::Json => {
+                let record = serde_json::json!({
+                    "path": display_path.to_string_lossy(),
+                    "root": root.to_string_lossy(),
+                });
+                write!(self.writer, "{}", record)?;
+            }
+            OutputFormat::Csv => {
+                if !self.header_written {
+                    self.header_written = true;
+                    let header: Vec<String> =
+                        self.config.csv_fields().iter().map(|f| f.name().to_string()).collect();
+                    self.write_csv_record(&header)?;
+                    self.writer.write_all(self.config.terminator())?;
+                }
+                let record = self.csv_record(root, path, &display_path);
+                self.write_csv_record(&record)?;
+            }
+        }
+        self.writer.write_all(self.config.terminator())
+    }
+
+    /// 刷新输出缓冲区
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// 取回底层的写入器
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// 生成一条 CSV 记录的各列
+    fn csv_record(&self, root: &Path, path: &Path, display_path: &Path) -> Vec<String> {
+        let fields = self.config.csv_fields();
+        let metadata = if fields.iter().any(Field::needs_metadata) {
+            path.symlink_metadata().ok()
+        } else {
+            None
+        };
+
+        fields
+            .iter()
+            .map(|field| match field {
+                Field::Path => display_path.to_string_lossy().into_owned(),
+                Field::Name => path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.to_string_lossy().into_owned()),
+                Field::Root => root.to_string_lossy().into_owned(),
+                Field::Size => metadata.as_ref().map(|m| m.len().to_string()).unwrap_or_default(),
+                Field::Mtime => metadata
+                    .as_ref()
+                    .and_then(|m| m.modified().ok())
+                    .map(|time| {
+                        DateTime::<Local>::from(time).to_rfc3339_opts(SecondsFormat::Secs, false)
+                    })
+                    .unwrap_or_default(),
+                Field::Type => metadata
+                    .as_ref()
+                    .map(|m| type_name(&m.file_type()).to_string())
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// 写出一条 CSV 记录（不含结束符）
+    fn write_csv_record(&mut self, values: &[String]) -> io::Result<()> {
+        let line: Vec<String> = values.iter().map(|value| csv_escape(value)).collect();
+        self.writer.write_all(line.join(",").as_bytes())
+    }
+
+    /// 按配置的路径显示方式转换路径
+    fn format_path(&self, root: &Path, path: &Path) -> PathBuf {
+        match self.config.path_style {
+            PathStyle::AsFound => path.to_path_buf(),
+            PathStyle::Absolute => self.absolute(path),
+            PathStyle::Relative => {
+                let absolute = self.absolute(path);
+                match &self.current_dir {
+                    Some(cwd) => strip_or_dot(&absolute, cwd),
+                    None => absolute,
+                }
+            }
+            PathStyle::RelativeToRoot => strip_or_dot(path, root),
+        }
+    }
+
+    /// 将路径转换为绝对路径，无法规范化时基于当前目录拼接
+    fn absolute(&self, path: &Path) -> PathBuf {
+        if let Ok(canonical) = path.canonicalize() {
+            return canonical;
+        }
+        match &self.current_dir {
+            Some(cwd) if path.is_relative() => cwd.join(path),
+            _ => path.to_path_buf(),
+        }
+    }
+}
+
+/// 原样写出路径的字节，不对非 UTF-8 文件名做有损转换
+#[cfg(unix)]
+fn write_path<W: Write>(writer: &mut W, path: &Path) -> io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    writer.write_all(path.as_os_str().as_bytes())
+}
+
+/// 写出路径（非 Unix 平台上按 UTF-8 输出）
+#[cfg(not(unix))]
+fn write_path<W: Write>(writer: &mut W, path: &Path) -> io::Result<()> {
+    write!(writer, "{}", path.display())
+}
+
+/// 按 RFC 4180 转义 CSV 字段：包含逗号、引号或换行时加引号，引号加倍
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 条目类型名称
+fn type_name(file_type: &std::fs::FileType) -> &'static str {
+    if file_type.is_symlink() {
+        "symlink"
+    } else if file_type.is_dir() {
+        "dir"
+    } else if file_type.is_file() {
+        "file"
+    } else {
+        "other"
+    }
+}
+
+/// 去掉路径前缀，结果为空时返回 `.`
+fn strip_or_dot(path: &Path, prefix: &Path) -> PathBuf {
+    match path.strip_prefix(prefix) {
+        Ok(stripped) if stripped.as_os_str().is_empty() => PathBuf::from("."),
+        Ok(stripped) => stripped.to_path_buf(),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(config: OutputConfig, root: &str, path: &str) -> String {
+        let mut printer = Printer::new(config, Vec::new());
+        printer.print(Path::new(root), Path::new(path)).unwrap();
+        String::from_utf8(printer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_plain_output() {
+        let output = render(OutputConfig::default(), "a", "a/b/c.txt");
+        assert_eq!(output, "a/b/c.txt\n");
+    }
+
+    #[test]
+    fn test_relative_to_root() {
+        let config = OutputConfig {
+            path_style: PathStyle::RelativeToRoot,
+            ..Default::default()
+        };
+        assert_eq!(render(config.clone(), "a/b", "a/b/c.txt"), "c.txt\n");
+        assert_eq!(render(config, "a/b", "a/b"), ".\n");
+    }
+
+    #[test]
+    fn test_json_output_includes_root() {
+        let config = OutputConfig {
+            format: OutputFormat::Json,
+            path_style: PathStyle::RelativeToRoot,
+            ..Default::default()
+        };
+        let output = render(config, "a", "a/b.txt");
+        let value: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(value["path"], "b.txt");
+        assert_eq!(value["root"], "a");
+    }
+
+    #[test]
+    fn test_null_terminated_output() {
+        let config = OutputConfig {
+            null_terminated: true,
+            ..Default::default()
+        };
+        let mut printer = Printer::new(config, Vec::new());
+        printer.print(Path::new("a"), Path::new("a/with space.txt")).unwrap();
+        printer.print(Path::new("a"), Path::new("a/new\nline.txt")).unwrap();
+        assert_eq!(printer.into_inner(), b"a/with space.txt\0a/new\nline.txt\0");
+    }
+
+    #[test]
+    fn test_csv_escape() {
+        assert_eq!(csv_escape("plain.txt"), "plain.txt");
+        assert_eq!(csv_escape("a,b.txt"), "\"a,b.txt\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_escape("new\nline"), "\"new\nline\"");
+    }
+
+    #[test]
+    fn test_csv_output_with_fields() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("a,b.txt");
+        std::fs::write(&file, "12345")?;
+
+        let config = OutputConfig {
+            format: OutputFormat::Csv,
+            path_style: PathStyle::RelativeToRoot,
+            fields: vec![Field::Path, Field::Size, Field::Type],
+            ..Default::default()
+        };
+        let mut printer = Printer::new(config, Vec::new());
+        printer.print(dir.path(), &file)?;
+        printer.print(dir.path(), dir.path())?;
+
+        let output = String::from_utf8(printer.into_inner())?;
+        let lines: Vec<&str> = output.split_terminator("\r\n").collect();
+        assert_eq!(lines[0], "path,size,type");
+        assert_eq!(lines[1], "\"a,b.txt\",5,file");
+        assert!(lines[2].starts_with(".,") && lines[2].ends_with(",dir"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_colored_plain_output() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("plain.txt");
+        std::fs::write(&file, "x")?;
+
+        let config = OutputConfig {
+            path_style: PathStyle::RelativeToRoot,
+            colors: Some(LsColors::parse("di=01;34")),
+            ..Default::default()
+        };
+        let mut printer = Printer::new(config, Vec::new());
+        printer.print(dir.path(), dir.path())?;
+        printer.print(dir.path(), &file)?;
+
+        let output = String::from_utf8(printer.into_inner())?;
+        assert_eq!(output, "\x1b[01;34m.\x1b[0m\nplain.txt\n");
+        Ok(())
+    }
+}
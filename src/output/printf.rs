@@ -0,0 +1,345 @@
+//! GNU find `-printf` 兼容的格式字符串
+//!
+//! 支持的指令：
+//! - `%p` 路径、`%P` 相对搜索根目录的路径、`%f` 文件名、`%h` 所在目录、`%H` 搜索根目录
+//! - `%s` 大小（字节）、`%d` 深度、`%m` 八进制权限位、`%y` 条目类型
+//! - `%u`/`%g` 属主/属组名称、`%U`/`%G` 属主/属组 ID
+//! - `%Tk`/`%Ak` 修改/访问时间，`k` 为 strftime 格式字符，另支持 `@`（纪元秒）和 `+`
+//! - `%%` 百分号
+//!
+//! 指令可以带宽度，如 `%10s`（右对齐）或 `%-20f`（左对齐）。
+//! 支持的转义序列：`\n`、`\t`、`\r`、`\0`、`\a`、`\v`、`\f`、`\\`。
+//! 与 GNU find 相同，输出不会自动追加换行。
+
+use std::fs::Metadata;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Local};
+
+use crate::errors::{FindError, FindResult};
+use super::meta::{self, NameCache};
+
+/// `%T`/`%A` 可以直接交给 strftime 的格式字符
+const TIME_SPECS: &str = "aAbBcCdDeFgGhHIjklmMprRsSTuUVwWxXyYzZ";
+
+/// 解析后的 `--printf` 格式
+///
+/// # 示例
+/// ```
+/// use rust_find::output::PrintfFormat;
+///
+/// let format = PrintfFormat::parse("%f %s\\n").unwrap();
+/// assert!(format.needs_metadata());
+/// assert!(PrintfFormat::parse("%Q").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrintfFormat {
+    segments: Vec<Segment>,
+}
+
+/// 格式字符串的组成部分
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    /// 原样输出的文本
+    Literal(String),
+    /// 格式指令及其宽度
+    Directive {
+        directive: Directive,
+        width: Option<usize>,
+        left_align: bool,
+    },
+}
+
+/// 格式指令
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Directive {
+    Path,
+    RelativePath,
+    Name,
+    Dir,
+    Root,
+    Size,
+    Depth,
+    Mode,
+    Type,
+    User,
+    Group,
+    Uid,
+    Gid,
+    ModifiedTime(char),
+    AccessTime(char),
+}
+
+impl Directive {
+    /// 是否需要读取文件元数据
+    fn needs_metadata(&self) -> bool {
+        !matches!(
+            self,
+            Directive::Path
+                | Directive::RelativePath
+                | Directive::Name
+                | Directive::Dir
+                | Directive::Root
+                | Directive::Depth
+        )
+    }
+}
+
+impl PrintfFormat {
+    /// 解析格式字符串
+    ///
+    /// # 错误
+    /// 遇到未知指令、未知的时间格式字符或不完整的指令时返回 Other 错误
+    pub fn parse(format: &str) -> FindResult<Self> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = format.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    let escaped = match chars.next() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('0') => '\0',
+                        Some('a') => '\x07',
+                        Some('v') => '\x0b',
+                        Some('f') => '\x0c',
+                        Some('\\') => '\\',
+                        Some(other) => {
+                            literal.push('\\');
+                            other
+                        }
+                        None => '\\',
+                    };
+                    literal.push(escaped);
+                }
+                '%' => {
+                    if chars.peek() == Some(&'%') {
+                        chars.next();
+                        literal.push('%');
+                        continue;
+                    }
+
+                    let left_align = chars.next_if_eq(&'-').is_some();
+                    let mut digits = String::new();
+                    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(digit);
+                    }
+                    let width = digits.parse().ok();
+
+                    let directive = match chars.next() {
+                        Some('p') => Directive::Path,
+                        Some('P') => Directive::RelativePath,
+                        Some('f') => Directive::Name,
+                        Some('h') => Directive::Dir,
+                        Some('H') => Directive::Root,
+                        Some('s') => Directive::Size,
+                        Some('d') => Directive::Depth,
+                        Some('m') => Directive::Mode,
+                        Some('y') => Directive::Type,
+                        Some('u') => Directive::User,
+                        Some('g') => Directive::Group,
+                        Some('U') => Directive::Uid,
+                        Some('G') => Directive::Gid,
+                        Some(kind @ ('T' | 'A')) => {
+                            let spec = match chars.next() {
+                                Some(spec) if spec == '@' || spec == '+' || TIME_SPECS.contains(spec) => spec,
+                                Some(spec) => {
+                                    return Err(invalid_format(format, &format!("未知的时间格式 %{}{}", kind, spec)))
+                                }
+                                None => return Err(invalid_format(format, &format!("%{} 缺少时间格式字符", kind))),
+                            };
+                            if kind == 'T' {
+                                Directive::ModifiedTime(spec)
+                            } else {
+                                Directive::AccessTime(spec)
+                            }
+                        }
+                        Some(other) => return Err(invalid_format(format, &format!("未知的指令 %{}", other))),
+                        None => return Err(invalid_format(format, "格式字符串以 % 结尾")),
+                    };
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Directive { directive, width, left_align });
+                }
+                _ => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// 是否有指令需要读取文件元数据
+    pub fn needs_metadata(&self) -> bool {
+        self.segments.iter().any(|segment| match segment {
+            Segment::Directive { directive, .. } => directive.needs_metadata(),
+            Segment::Literal(_) => false,
+        })
+    }
+
+    /// 按格式生成一个结果的输出
+    ///
+    /// # 参数
+    /// - `root`: 找到该结果的搜索根目录
+    /// - `path`: 结果路径
+    /// - `display_path`: 按路径显示方式转换后的路径（用于 `%p`）
+    /// - `names`: 用户名和组名缓存
+    pub fn render(&self, root: &Path, path: &Path, display_path: &Path, names: &mut NameCache) -> String {
+        let metadata = if self.needs_metadata() {
+            path.symlink_metadata().ok()
+        } else {
+            None
+        };
+
+        let mut output = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => output.push_str(text),
+                Segment::Directive { directive, width, left_align } => {
+                    let value = render_directive(*directive, root, path, display_path, metadata.as_ref(), names);
+                    match (width, left_align) {
+                        (Some(width), true) => output.push_str(&format!("{:<width$}", value, width = width)),
+                        (Some(width), false) => output.push_str(&format!("{:>width$}", value, width = width)),
+                        (None, _) => output.push_str(&value),
+                    }
+                }
+            }
+        }
+        output
+    }
+}
+
+/// 生成单个指令的值，元数据不可用时返回空字符串
+fn render_directive(
+    directive: Directive,
+    root: &Path,
+    path: &Path,
+    display_path: &Path,
+    metadata: Option<&Metadata>,
+    names: &mut NameCache,
+) -> String {
+    match directive {
+        Directive::Path => display_path.to_string_lossy().into_owned(),
+        Directive::RelativePath => path
+            .strip_prefix(root)
+            .map(|relative| relative.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| path.to_string_lossy().into_owned()),
+        Directive::Name => path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string_lossy().into_owned()),
+        Directive::Dir => match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_string_lossy().into_owned(),
+            _ => ".".to_string(),
+        },
+        Directive::Root => root.to_string_lossy().into_owned(),
+        Directive::Depth => path
+            .strip_prefix(root)
+            .map(|relative| relative.components().count())
+            .unwrap_or(0)
+            .to_string(),
+        _ => match metadata {
+            Some(metadata) => render_metadata(directive, metadata, names),
+            None => String::new(),
+        },
+    }
+}
+
+/// 生成依赖元数据的指令值
+fn render_metadata(directive: Directive, metadata: &Metadata, names: &mut NameCache) -> String {
+    let ids = meta::owner_ids(metadata);
+    match directive {
+        Directive::Size => metadata.len().to_string(),
+        Directive::Mode => format!("{:o}", meta::mode_bits(metadata)),
+        Directive::Type => meta::type_char(&metadata.file_type()).to_string(),
+        Directive::User => ids.map(|(uid, _)| names.user(uid).to_string()).unwrap_or_default(),
+        Directive::Group => ids.map(|(_, gid)| names.group(gid).to_string()).unwrap_or_default(),
+        Directive::Uid => ids.map(|(uid, _)| uid.to_string()).unwrap_or_default(),
+        Directive::Gid => ids.map(|(_, gid)| gid.to_string()).unwrap_or_default(),
+        Directive::ModifiedTime(spec) => metadata.modified().map(|t| format_time(t, spec)).unwrap_or_default(),
+        Directive::AccessTime(spec) => metadata.accessed().map(|t| format_time(t, spec)).unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// 按 `%T`/`%A` 的格式字符格式化时间
+fn format_time(time: SystemTime, spec: char) -> String {
+    match spec {
+        '@' => match time.duration_since(UNIX_EPOCH) {
+            Ok(duration) => format!("{}.{:09}", duration.as_secs(), duration.subsec_nanos()),
+            Err(_) => "0".to_string(),
+        },
+        '+' => DateTime::<Local>::from(time).format("%Y-%m-%d+%H:%M:%S%.9f").to_string(),
+        _ => DateTime::<Local>::from(time).format(&format!("%{}", spec)).to_string(),
+    }
+}
+
+/// 构造格式错误
+fn invalid_format(format: &str, message: &str) -> FindError {
+    FindError::Other {
+        message: format!("无效的 --printf 格式: {}", message),
+        context: Some(format.to_string()),
+        timestamp: SystemTime::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn render(format: &str, root: &Path, path: &Path) -> String {
+        let format = PrintfFormat::parse(format).unwrap();
+        format.render(root, path, path, &mut NameCache::new())
+    }
+
+    #[test]
+    fn test_printf_path_directives() {
+        let root = Path::new("base");
+        let path = Path::new("base/sub/file.txt");
+        assert_eq!(render("%p|%P|%f|%h|%H|%d\\n", root, path), "base/sub/file.txt|sub/file.txt|file.txt|base/sub|base|2\n");
+        assert_eq!(render("100%%", root, path), "100%");
+        assert_eq!(render("[%-6f][%10f]", root, Path::new("base/ab")), "[ab    ][        ab]");
+    }
+
+    #[test]
+    fn test_printf_metadata_directives() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let file = dir.path().join("data.bin");
+        std::fs::write(&file, "hello")?;
+
+        assert_eq!(render("%s %y", dir.path(), &file), "5 f");
+        assert_eq!(render("%y", dir.path(), dir.path()), "d");
+
+        let modified = file.symlink_metadata()?.modified()?;
+        let expected = DateTime::<Local>::from(modified).format("%Y-%m-%d").to_string();
+        assert_eq!(render("%TY-%Tm-%Td", dir.path(), &file), expected);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o640))?;
+            assert_eq!(render("%m", dir.path(), &file), "640");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_printf_invalid_format() {
+        assert!(PrintfFormat::parse("%Q").is_err());
+        assert!(PrintfFormat::parse("%Tq").is_err());
+        assert!(PrintfFormat::parse("%T").is_err());
+        assert!(PrintfFormat::parse("trailing %").is_err());
+        assert!(!PrintfFormat::parse("%p %f\\n").unwrap().needs_metadata());
+    }
+}
@@ -0,0 +1,96 @@
+//! 匹配结果的数量和总大小统计（`--summarize`）
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use crate::finder::filter::SizeMeasure;
+use super::meta;
+
+/// 匹配结果的汇总
+///
+/// 统计实际占用的磁盘空间时，与 `du` 相同，同一文件的多个硬链接只计算一次。
+#[derive(Debug, Default)]
+pub struct Summary {
+    measure: SizeMeasure,
+    follow_links: bool,
+    entries: u64,
+    bytes: u64,
+    /// 已统计的多链接文件的 (设备号, inode)
+    seen: HashSet<(u64, u64)>,
+}
+
+impl Summary {
+    /// 创建空的汇总
+    ///
+    /// `follow_links` 为 true 时统计符号链接指向的目标，否则统计链接本身。
+    pub fn new(measure: SizeMeasure, follow_links: bool) -> Self {
+        Self { measure, follow_links, ..Self::default() }
+    }
+
+    /// 统计一个匹配的条目，无法读取元数据的条目只计入数量
+    pub fn add(&mut self, path: &Path) {
+        self.entries += 1;
+        let metadata = if self.follow_links { path.metadata() } else { path.symlink_metadata() };
+        let Ok(metadata) = metadata else {
+            return;
+        };
+        if self.measure == SizeMeasure::OnDisk
+            && !metadata.is_dir()
+            && meta::link_count(&metadata) > 1
+            && !self.seen.insert((meta::device(&metadata), meta::inode(&metadata)))
+        {
+            return;
+        }
+        self.bytes += self.measure.of(&metadata);
+    }
+
+    /// 统计的条目数
+    pub fn entries(&self) -> u64 {
+        self.entries
+    }
+
+    /// 总大小（字节）
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.measure {
+            SizeMeasure::Apparent => write!(f, "{} 个条目，共 {} 字节", self.entries, self.bytes),
+            SizeMeasure::OnDisk => write!(f, "{} 个条目，占用磁盘 {} 字节", self.entries, self.bytes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_summary_counts_hard_links_once_on_disk() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let file = dir.path().join("data");
+        fs::write(&file, vec![1u8; 10_000])?;
+        fs::hard_link(&file, dir.path().join("link"))?;
+
+        let mut apparent = Summary::new(SizeMeasure::Apparent, false);
+        let mut on_disk = Summary::new(SizeMeasure::OnDisk, false);
+        for name in ["data", "link"] {
+            apparent.add(&dir.path().join(name));
+            on_disk.add(&dir.path().join(name));
+        }
+        assert_eq!(apparent.entries(), 2);
+        assert_eq!(apparent.bytes(), 20_000);
+        assert_eq!(on_disk.entries(), 2);
+        assert_eq!(on_disk.bytes(), meta::disk_usage(&file.metadata()?));
+        assert_eq!(apparent.to_string(), "2 个条目，共 20000 字节");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,428 @@
+//! 搜索服务
+//!
+//! `rust-find serve` 在本地端口上提供一个简单的 HTTP 接口，编辑器等工具可以通过常驻进程搜索，
+//! 无需每次启动新进程：
+//!
+//! ```text
+//! POST /search
+//! {"paths": ["src"], "name": ["*.rs"], "query": "size > 1k"}
+//! ```
+//!
+//! 请求体为 [`FilterSpec`] 的 JSON，结果以 NDJSON 流式返回，每行一个 `{"path": "..."}`，
+//! 客户端断开连接时搜索随之停止。请求无效时返回 400 和 `{"error": "..."}`。
+//!
+//! 每个连接只处理一个请求，响应结束后关闭连接。
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::mpsc::sync_channel;
+use std::time::Duration;
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::errors::{FindError, FindResult};
+use crate::finder::content::ContentFilter;
+use crate::finder::filter::{FileFilter, FilterCost, MultiNameFilter, PathFilter, SizeFilter, SizeMeasure, TypeFilter};
+use crate::finder::query::QueryFilter;
+use crate::finder::size::ByteSize;
+use crate::finder::{FindEntry, FindOptions, Finder};
+
+/// 请求头的最大总长度
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+
+/// 请求体的最大长度
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+/// 读取请求的超时时间
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 结果通道的容量，客户端读取较慢时对搜索施加背压
+const RESULT_BUFFER: usize = 256;
+
+/// 搜索请求
+///
+/// 字段含义与同名的命令行选项相同，所有字段都可以省略；多个过滤条件须全部满足。
+/// 也用作守护进程（`rust-find daemon`）的请求。
+///
+/// # 示例
+/// ```
+/// use rust_find::serve::FilterSpec;
+///
+/// let spec: FilterSpec = serde_json::from_str(r#"{"paths": ["src"], "name": ["*.rs"], "type": "f"}"#).unwrap();
+/// assert!(spec.finder().is_ok());
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterSpec {
+    /// 搜索路径，为空时搜索服务的当前目录
+    pub paths: Vec<PathBuf>,
+    /// 文件名模式，任一匹配即可
+    pub name: Vec<String>,
+    /// 不区分大小写的文件名模式，任一匹配即可
+    pub iname: Vec<String>,
+    /// 文件类型（同 `--type`，如 `f`、`d,l`）
+    #[serde(rename = "type")]
+    pub file_type: Option<String>,
+    /// 路径的 glob 模式，须全部满足
+    pub path: Vec<String>,
+    /// 查询表达式（同 `--query`）
+    pub query: Option<String>,
+    /// 最小文件大小（如 `10M`）
+    pub min_size: Option<String>,
+    /// 最大文件大小
+    pub max_size: Option<String>,
+    /// 文件内容包含的文本，须全部满足
+    pub contains: Vec<String>,
+    /// 最大搜索深度
+    pub max_depth: Option<usize>,
+    /// 是否包含隐藏文件
+    pub hidden: bool,
+    /// 最多返回的结果数
+    pub max_results: Option<usize>,
+}
+
+impl FilterSpec {
+    /// 按请求构建查找器
+    ///
+    /// # 错误
+    /// 如果任一模式、类型、大小或查询表达式无效，返回对应的错误
+    pub fn finder(&self) -> FindResult<Finder> {
+        let options = FindOptions::new()
+            .with_max_depth(self.max_depth)
+            .with_ignore_hidden(!self.hidden)
+            .with_max_results(self.max_results);
+
+        let mut filters: Vec<Box<dyn FileFilter + Send + Sync>> = Vec::new();
+        if !self.name.is_empty() {
+            filters.push(Box::new(MultiNameFilter::new(&self.name, false)?));
+        }
+        if !self.iname.is_empty() {
+            filters.push(Box::new(MultiNameFilter::new(&self.iname, true)?));
+        }
+        if let Some(file_type) = &self.file_type {
+            filters.push(Box::new(TypeFilter::new(file_type)?));
+        }
+        for pattern in &self.path {
+            filters.push(Box::new(PathFilter::new(pattern)?.with_roots(self.roots())));
+        }
+        if let Some(query) = &self.query {
+            filters.push(Box::new(QueryFilter::parse(query)?));
+        }
+        if let Some(min) = &self.min_size {
+            filters.push(Box::new(SizeFilter::at_least(min.parse::<ByteSize>()?, SizeMeasure::Apparent)));
+        }
+        if let Some(max) = &self.max_size {
+            filters.push(Box::new(SizeFilter::at_most(max.parse::<ByteSize>()?, SizeMeasure::Apparent)));
+        }
+
+        let mut content_filters: Vec<Box<dyn FileFilter + Send + Sync>> = Vec::new();
+        for text in &self.contains {
+            content_filters.push(Box::new(ContentFilter::literal(text)?));
+        }
+
+        Ok(Finder::new(options)
+            .with_filters(filters)
+            .with_content_filters(content_filters))
+    }
+
+    /// 搜索路径，未指定时为当前目录
+    pub fn roots(&self) -> Vec<PathBuf> {
+        if self.paths.is_empty() {
+            vec![PathBuf::from(".")]
+        } else {
+            self.paths.clone()
+        }
+    }
+}
+
+/// 匹配所有条目，请求中的过滤条件都已加入查找器
+pub(crate) struct MatchAll;
+
+impl FileFilter for MatchAll {
+    fn matches(&self, _: &FindEntry) -> bool {
+        true
+    }
+
+    fn cost(&self) -> FilterCost {
+        FilterCost::Path
+    }
+
+    fn description(&self) -> String {
+        "matches everything".to_string()
+    }
+}
+
+/// 搜索服务
+///
+/// # 示例
+/// ```no_run
+/// use rust_find::serve::Server;
+///
+/// let server = Server::bind("127.0.0.1:7878").unwrap();
+/// eprintln!("监听 http://{}", server.local_addr().unwrap());
+/// server.run().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Server {
+    listener: TcpListener,
+}
+
+impl Server {
+    /// 在指定地址上监听
+    ///
+    /// # 错误
+    /// 如果无法绑定地址，返回IO错误
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+        })
+    }
+
+    /// 实际监听的地址（绑定端口 0 时可以由此得到分配的端口）
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// 持续接受连接，每个连接在单独的线程中处理
+    ///
+    /// # 错误
+    /// 只有监听套接字本身出错时返回；单个连接的错误记录到日志后忽略
+    pub fn run(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    warn!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            };
+            std::thread::spawn(move || {
+                let peer = stream.peer_addr().ok();
+                if let Err(e) = handle_connection(stream) {
+                    debug!("Connection from {:?} failed: {}", peer, e);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+/// 解析后的请求
+#[derive(Debug)]
+struct Request {
+    method: String,
+    target: String,
+    body: Vec<u8>,
+}
+
+/// 处理一个连接上的请求
+fn handle_connection(stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    let request = match read_request(&mut reader) {
+        Ok(request) => request,
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            return respond_error(&mut writer, "400 Bad Request", &e.to_string());
+        }
+        Err(e) => return Err(e),
+    };
+    debug!("{} {}", request.method, request.target);
+
+    // 忽略查询字符串
+    let path = request.target.split('?').next().unwrap_or_default();
+    match (request.method.as_str(), path) {
+        ("POST", "/search") => search(&mut writer, &request.body),
+        (_, "/search") => respond_error(&mut writer, "405 Method Not Allowed", "/search 只接受 POST 请求"),
+        _ => respond_error(&mut writer, "404 Not Found", &format!("未知的路径: {}", path)),
+    }
+}
+
+/// 读取请求行、请求头和请求体
+///
+/// 格式错误或超出长度限制时返回 `InvalidData` 错误。
+fn read_request(reader: &mut impl BufRead) -> io::Result<Request> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+    let mut header_bytes = 0;
+    let mut read_line = |reader: &mut dyn BufRead| -> io::Result<String> {
+        let mut line = String::new();
+        let read = reader.take((MAX_HEADER_BYTES - header_bytes) as u64).read_line(&mut line)?;
+        header_bytes += read;
+        if !line.ends_with('\n') {
+            return Err(invalid("请求头不完整或过长"));
+        }
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    };
+
+    let request_line = read_line(reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid("无效的请求行"));
+    };
+    let (method, target) = (method.to_string(), target.to_string());
+
+    let mut content_length = 0;
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(invalid("无效的请求头"));
+        };
+        if name.trim().eq_ignore_ascii_case("content-length") {
+            content_length = value.trim().parse().map_err(|_| invalid("无效的 Content-Length"))?;
+        } else if name.trim().eq_ignore_ascii_case("transfer-encoding") {
+            return Err(invalid("不支持分块传输的请求体，请指定 Content-Length"));
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err(invalid("请求体过大"));
+    }
+
+    let mut body = Vec::with_capacity(content_length as usize);
+    reader.take(content_length).read_to_end(&mut body)?;
+    if body.len() as u64 != content_length {
+        return Err(invalid("请求体不完整"));
+    }
+    Ok(Request { method, target, body })
+}
+
+/// 执行搜索，以 NDJSON 流式返回结果
+fn search(writer: &mut TcpStream, body: &[u8]) -> io::Result<()> {
+    let spec: FilterSpec = match serde_json::from_slice(body) {
+        Ok(spec) => spec,
+        Err(e) => return respond_error(writer, "400 Bad Request", &format!("无效的请求: {}", e)),
+    };
+    let finder = match spec.finder() {
+        Ok(finder) => finder,
+        Err(e) => return respond_error(writer, "400 Bad Request", &e.to_string()),
+    };
+    if let Some(missing) = spec.roots().into_iter().find(|root| !root.exists()) {
+        let error = FindError::FileNotFound(missing);
+        return respond_error(writer, "400 Bad Request", &error.to_string());
+    }
+
+    // 没有 Content-Length，响应体在连接关闭时结束
+    write!(
+        writer,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nConnection: close\r\n\r\n"
+    )?;
+
+    let (sender, receiver) = sync_channel(RESULT_BUFFER);
+    std::thread::scope(|scope| {
+        let finder = &finder;
+        let roots = spec.roots();
+        scope.spawn(move || {
+            for root in roots {
+                // 接收方关闭（客户端断开）后 find_into 提前返回，之后的路径也不再搜索
+                finder.find_into(root, MatchAll, sender.clone());
+            }
+        });
+
+        let mut output = io::BufWriter::new(&mut *writer);
+        for path in receiver {
+            let line = json!({ "path": path.to_string_lossy() });
+            // 写入失败（客户端断开）时丢弃接收端，搜索线程随之停止
+            writeln!(output, "{}", line)?;
+            // 每个结果都立即发送，客户端可以边搜索边处理
+            output.flush()?;
+        }
+        output.flush()
+    })
+}
+
+/// 返回 JSON 格式的错误
+fn respond_error(writer: &mut impl Write, status: &str, message: &str) -> io::Result<()> {
+    let body = json!({ "error": message }).to_string();
+    write!(
+        writer,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    /// 发送请求并返回完整的响应
+    fn send(addr: SocketAddr, request: &str) -> io::Result<String> {
+        let mut stream = TcpStream::connect(addr)?;
+        stream.write_all(request.as_bytes())?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        Ok(response)
+    }
+
+    fn post(addr: SocketAddr, body: &str) -> io::Result<String> {
+        send(
+            addr,
+            &format!("POST /search HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", body.len(), body),
+        )
+    }
+
+    #[test]
+    fn test_search_streams_ndjson() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        fs::create_dir(dir.path().join("src"))?;
+        fs::write(dir.path().join("src/lib.rs"), "fn main() {}")?;
+        fs::write(dir.path().join("src/notes.txt"), "fn")?;
+        fs::write(dir.path().join("README.md"), "")?;
+
+        let server = Server::bind("127.0.0.1:0")?;
+        let addr = server.local_addr()?;
+        std::thread::spawn(move || server.run());
+
+        let body = json!({ "paths": [dir.path()], "name": ["*.rs", "*.txt"], "contains": ["main"] }).to_string();
+        let response = post(addr, &body)?;
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK"), "{}", head);
+        assert!(head.contains("application/x-ndjson"));
+        let paths: Vec<String> = body
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["path"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(paths, [dir.path().join("src/lib.rs").to_string_lossy()]);
+
+        let response = post(addr, r#"{"query": "size >"}"#)?;
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"), "{}", response);
+        assert!(response.contains("expected a value"), "{}", response);
+
+        let response = post(addr, r#"{"colour": "red"}"#)?;
+        assert!(response.contains("unknown field"), "{}", response);
+
+        let response = send(addr, "GET /search HTTP/1.1\r\n\r\n")?;
+        assert!(response.starts_with("HTTP/1.1 405"), "{}", response);
+        let response = send(addr, "GET /other HTTP/1.1\r\n\r\n")?;
+        assert!(response.starts_with("HTTP/1.1 404"), "{}", response);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_request_limits() {
+        let mut request = "POST /search HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello".as_bytes();
+        let parsed = read_request(&mut request).unwrap();
+        assert_eq!((parsed.method.as_str(), parsed.target.as_str()), ("POST", "/search"));
+        assert_eq!(parsed.body, b"hello");
+
+        let too_large = format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_BYTES + 1);
+        let error = read_request(&mut too_large.as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let truncated = "POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nabc";
+        assert_eq!(read_request(&mut truncated.as_bytes()).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(read_request(&mut "GARBAGE\r\n\r\n".as_bytes()).is_err());
+    }
+}
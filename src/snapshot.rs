@@ -0,0 +1,307 @@
+//! 文件快照与差异比较
+//!
+//! 本模块可以把一次搜索的结果连同文件属性保存为快照文件，
+//! 并在之后的搜索中与快照进行比较，报告新增、删除和属性变化的条目。
+//! 通过选择需要比较的字段（权限、属主、大小、修改时间），
+//! 可以将其用作轻量级的文件完整性检查工具。
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{FindError, FindResult};
+
+/// 差异比较时可选的文件属性字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DiffField {
+    /// 权限位
+    Perm,
+    /// 属主和属组
+    Owner,
+    /// 文件大小
+    Size,
+    /// 修改时间
+    Mtime,
+}
+
+impl DiffField {
+    /// 字段的简短名称，用于差异输出
+    pub fn name(&self) -> &'static str {
+        match self {
+            DiffField::Perm => "perm",
+            DiffField::Owner => "owner",
+            DiffField::Size => "size",
+            DiffField::Mtime => "mtime",
+        }
+    }
+}
+
+/// 快照中单个条目记录的属性
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// 条目路径
+    pub path: PathBuf,
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 修改时间（自 UNIX 纪元起的秒数）
+    pub mtime: i64,
+    /// 权限位（非 Unix 平台上仅记录只读标志）
+    pub mode: u32,
+    /// 属主 ID（非 Unix 平台上为 None）
+    pub uid: Option<u32>,
+    /// 属组 ID（非 Unix 平台上为 None）
+    pub gid: Option<u32>,
+}
+
+impl SnapshotEntry {
+    /// 读取路径的元数据并创建快照条目（不跟随符号链接）
+    pub fn from_path(path: &Path) -> FindResult<Self> {
+        let metadata = fs::symlink_metadata(path).map_err(|e| FindError::FilesystemError {
+            source: e,
+            path: path.to_path_buf(),
+        })?;
+
+        let mtime = metadata
+            .modified()
+            .ok()
+            .map(|t| match t.duration_since(UNIX_EPOCH) {
+                Ok(d) => d.as_secs() as i64,
+                Err(e) => -(e.duration().as_secs() as i64),
+            })
+            .unwrap_or(0);
+
+        #[cfg(unix)]
+        let (mode, uid, gid) = {
+            use std::os::unix::fs::MetadataExt;
+            (metadata.mode() & 0o7777, Some(metadata.uid()), Some(metadata.gid()))
+        };
+
+        #[cfg(not(unix))]
+        let (mode, uid, gid) = (u32::from(metadata.permissions().readonly()), None, None);
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            size: metadata.len(),
+            mtime,
+            mode,
+            uid,
+            gid,
+        })
+    }
+
+    /// 返回与另一个条目相比发生变化的字段
+    pub fn changed_fields(&self, other: &SnapshotEntry, fields: &[DiffField]) -> Vec<DiffField> {
+        fields
+            .iter()
+            .copied()
+            .filter(|field| match field {
+                DiffField::Perm => self.mode != other.mode,
+                DiffField::Owner => self.uid != other.uid || self.gid != other.gid,
+                DiffField::Size => self.size != other.size,
+                DiffField::Mtime => self.mtime != other.mtime,
+            })
+            .collect()
+    }
+}
+
+/// 文件快照
+///
+/// 以路径为键保存一组条目的属性，可写入文件或从文件加载。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// 创建快照的时间（自 UNIX 纪元起的秒数）
+    pub created: u64,
+    /// 快照条目，按路径排序
+    pub entries: BTreeMap<PathBuf, SnapshotEntry>,
+}
+
+impl Snapshot {
+    /// 根据一组路径创建快照
+    ///
+    /// 无法读取元数据的路径会被跳过（例如在搜索结束后被删除的文件）。
+    pub fn from_paths<I, P>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let entries = paths
+            .into_iter()
+            .filter_map(|path| SnapshotEntry::from_path(path.as_ref()).ok())
+            .map(|entry| (entry.path.clone(), entry))
+            .collect();
+
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Self { created, entries }
+    }
+
+    /// 将快照保存到文件
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> FindResult<()> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|e| FindError::FilesystemError {
+            source: e,
+            path: path.to_path_buf(),
+        })?;
+
+        serde_json::to_writer(BufWriter::new(file), self).map_err(|e| FindError::Other {
+            message: format!("写入快照失败: {}", e),
+            context: Some(path.display().to_string()),
+            timestamp: SystemTime::now(),
+        })
+    }
+
+    /// 从文件加载快照
+    pub fn load<P: AsRef<Path>>(path: P) -> FindResult<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| FindError::FilesystemError {
+            source: e,
+            path: path.to_path_buf(),
+        })?;
+
+        serde_json::from_reader(BufReader::new(file)).map_err(|e| FindError::Other {
+            message: format!("无效的快照文件: {}", e),
+            context: Some(path.display().to_string()),
+            timestamp: SystemTime::now(),
+        })
+    }
+
+    /// 将当前快照与旧快照进行比较
+    ///
+    /// # 参数
+    /// - `previous`: 之前保存的快照
+    /// - `fields`: 需要比较的属性字段，为空时只比较条目是否存在
+    pub fn diff(&self, previous: &Snapshot, fields: &[DiffField]) -> SnapshotDiff {
+        let mut diff = SnapshotDiff::default();
+
+        for (path, entry) in &self.entries {
+            match previous.entries.get(path) {
+                None => diff.added.push(path.clone()),
+                Some(old) => {
+                    let changed = old.changed_fields(entry, fields);
+                    if !changed.is_empty() {
+                        diff.changed.push((path.clone(), changed));
+                    }
+                }
+            }
+        }
+
+        diff.removed = previous
+            .entries
+            .keys()
+            .filter(|path| !self.entries.contains_key(*path))
+            .cloned()
+            .collect();
+
+        diff
+    }
+}
+
+/// 两个快照之间的差异
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    /// 新增的条目
+    pub added: Vec<PathBuf>,
+    /// 删除的条目
+    pub removed: Vec<PathBuf>,
+    /// 属性发生变化的条目及其变化的字段
+    pub changed: Vec<(PathBuf, Vec<DiffField>)>,
+}
+
+impl SnapshotDiff {
+    /// 检查是否没有任何差异
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// 将差异格式化为文本行
+    ///
+    /// 新增条目以 `+` 开头，删除条目以 `-` 开头，
+    /// 属性变化的条目以 `~` 开头并附带变化的字段列表。
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for path in &self.added {
+            lines.push(format!("+ {}", path.display()));
+        }
+        for path in &self.removed {
+            lines.push(format!("- {}", path.display()));
+        }
+        for (path, fields) in &self.changed {
+            let names: Vec<&str> = fields.iter().map(|f| f.name()).collect();
+            lines.push(format!("~ {} [{}]", path.display(), names.join(",")));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_snapshot_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let file = dir.path().join("a.txt");
+        File::create(&file)?.write_all(b"abc")?;
+
+        let snapshot = Snapshot::from_paths([&file]);
+        let snapshot_file = dir.path().join("snap.json");
+        snapshot.save(&snapshot_file)?;
+
+        let loaded = Snapshot::load(&snapshot_file)?;
+        assert_eq!(loaded, snapshot);
+        assert_eq!(loaded.entries[&file].size, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_diff_presence() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let kept = dir.path().join("kept.txt");
+        let removed = dir.path().join("removed.txt");
+        let added = dir.path().join("added.txt");
+        File::create(&kept)?;
+        File::create(&removed)?;
+        File::create(&added)?;
+
+        let old = Snapshot::from_paths([&kept, &removed]);
+        let new = Snapshot::from_paths([&kept, &added]);
+
+        let diff = new.diff(&old, &[]);
+        assert_eq!(diff.added, vec![added]);
+        assert_eq!(diff.removed, vec![removed]);
+        assert!(diff.changed.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_diff_fields() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let file = dir.path().join("data.txt");
+        File::create(&file)?.write_all(b"old")?;
+
+        let old = Snapshot::from_paths([&file]);
+        File::create(&file)?.write_all(b"new content")?;
+        let new = Snapshot::from_paths([&file]);
+
+        // 仅比较存在性时不报告变化
+        assert!(new.diff(&old, &[]).is_empty());
+
+        let diff = new.diff(&old, &[DiffField::Size, DiffField::Owner]);
+        assert_eq!(diff.changed, vec![(file.clone(), vec![DiffField::Size])]);
+        assert_eq!(diff.lines(), vec![format!("~ {} [size]", file.display())]);
+
+        Ok(())
+    }
+}
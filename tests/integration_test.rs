@@ -136,7 +136,7 @@ fn test_symlink_loop() -> Result<(), Box<dyn std::error::Error>> {
            .arg("--follow-links")
            .arg("--ignore-io-errors")
            .assert()
-           .success();
+           .code(1); // 被忽略的错误不输出，但仍使退出状态非零
         
         let stderr = String::from_utf8(output.get_output().stderr.clone())?;
         assert!(!stderr.is_empty()); // Just check for any error output
@@ -156,7 +156,7 @@ fn test_broken_symlink() -> Result<(), Box<dyn std::error::Error>> {
            .arg("--follow-links")
            .arg("--ignore-io-errors")
            .assert()
-           .success();
+           .code(1); // 被忽略的错误不输出，但仍使退出状态非零
         
         let stderr = String::from_utf8(output.get_output().stderr.clone())?;
         assert!(!stderr.is_empty()); // Just check for any error output
@@ -211,4 +211,2057 @@ fn test_permission_error() -> Result<(), Box<dyn std::error::Error>> {
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_snapshot_diff() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let snapshot_dir = tempdir()?;
+    let snapshot = snapshot_dir.path().join("snap.json");
+    std::fs::write(dir.path().join("kept.txt"), "old")?;
+    std::fs::write(dir.path().join("removed.txt"), "x")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path())
+       .arg("--save-snapshot")
+       .arg(&snapshot)
+       .assert()
+       .success();
+
+    std::fs::remove_file(dir.path().join("removed.txt"))?;
+    std::fs::write(dir.path().join("added.txt"), "y")?;
+    std::fs::write(dir.path().join("kept.txt"), "new content")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--diff-snapshot")
+       .arg(&snapshot)
+       .arg("--diff-fields")
+       .arg("perm,size")
+       .assert()
+       .success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("+ ") && stdout.contains("added.txt"));
+    assert!(stdout.contains("- ") && stdout.contains("removed.txt"));
+    assert!(stdout.contains("kept.txt [size]"));
+
+    Ok(())
+}
+
+#[test]
+fn test_print0_output() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join("with space.txt"), "x")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--name").arg("*.txt")
+       .arg("-0")
+       .assert()
+       .success();
+
+    let stdout = output.get_output().stdout.clone();
+    assert!(stdout.ends_with(b"with space.txt\0"));
+    assert!(!stdout.contains(&b'\n'));
+
+    Ok(())
+}
+
+#[test]
+fn test_csv_output() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join("quote\"d.txt"), "abc")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--name").arg("*.txt")
+       .arg("--relative-to-root")
+       .arg("--format").arg("csv")
+       .arg("--fields").arg("name,size,type")
+       .assert()
+       .success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert_eq!(stdout, "name,size,type\r\n\"quote\"\"d.txt\",3,file\r\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_printf_output() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::create_dir(dir.path().join("sub"))?;
+    std::fs::write(dir.path().join("sub").join("a.txt"), "abcd")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--name").arg("*.txt")
+       .arg("--printf").arg("%P %f %s %d %y\\n")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert_eq!(stdout, "sub/a.txt a.txt 4 2 f\n");
+
+    // 未知指令在搜索开始前报错
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path()).arg("--printf").arg("%Q").assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn test_color_output() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::create_dir(dir.path().join("sub"))?;
+    std::fs::write(dir.path().join("a.rs"), "x")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--relative-to-root")
+       .arg("--color").arg("always")
+       .env("LS_COLORS", "di=01;34:*.rs=33")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("\x1b[01;34msub\x1b[0m"));
+    assert!(stdout.contains("\x1b[33ma.rs\x1b[0m"));
+
+    // 输出不是终端时 auto 不着色
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .env("LS_COLORS", "di=01;34")
+       .assert()
+       .success();
+    assert!(!output.get_output().stdout.contains(&0x1b));
+
+    Ok(())
+}
+
+#[test]
+fn test_ls_output() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join("a.txt"), "abcdef")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--name").arg("a.txt")
+       .arg("--ls")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    let columns: Vec<&str> = stdout.split_whitespace().collect();
+    assert!(columns[2].starts_with("-r"));
+    assert_eq!(columns[6], "6");
+    assert!(stdout.trim_end().ends_with("a.txt"));
+
+    Ok(())
+}
+
+#[test]
+fn test_relative_to_root_json() -> Result<(), Box<dyn std::error::Error>> {
+    let dir1 = tempdir()?;
+    let dir2 = tempdir()?;
+    std::fs::write(dir1.path().join("one.txt"), "1")?;
+    std::fs::create_dir(dir2.path().join("sub"))?;
+    std::fs::write(dir2.path().join("sub").join("two.txt"), "2")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir1.path())
+       .arg(dir2.path())
+       .arg("--relative-to-root")
+       .arg("--format")
+       .arg("json")
+       .assert()
+       .success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    let records: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+
+    let one = records.iter().find(|r| r["path"] == "one.txt").expect("one.txt");
+    assert_eq!(one["root"], dir1.path().to_string_lossy().as_ref());
+    let two = records.iter().find(|r| r["path"] == "sub/two.txt").expect("sub/two.txt");
+    assert_eq!(two["root"], dir2.path().to_string_lossy().as_ref());
+
+    Ok(())
+}
+
+#[test]
+fn test_symlink_policy_root_links() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)] {
+        let dir = tempdir()?;
+        let target = dir.path().join("target");
+        let other = dir.path().join("other");
+        std::fs::create_dir(&target)?;
+        std::fs::create_dir(&other)?;
+        std::fs::write(target.join("inside.txt"), "x")?;
+        std::fs::write(other.join("elsewhere.txt"), "x")?;
+        std::os::unix::fs::symlink(&other, target.join("nested_link"))?;
+        let root_link = dir.path().join("root_link");
+        std::os::unix::fs::symlink(&target, &root_link)?;
+
+        // -P: 不跟随作为搜索路径的符号链接
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(&root_link).arg("-P").assert().success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        assert!(!stdout.contains("inside.txt"));
+
+        // -H: 只跟随搜索路径本身
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(&root_link).arg("-H").assert().success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        assert!(stdout.contains("inside.txt"));
+        assert!(!stdout.contains("elsewhere.txt"));
+
+        // -L: 始终跟随
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(&root_link).arg("-L").assert().success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        assert!(stdout.contains("inside.txt"));
+        assert!(stdout.contains("elsewhere.txt"));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_symlink_cycles() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)] {
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        std::fs::create_dir_all(root.join("sub"))?;
+        std::fs::write(root.join("sub").join("data.txt"), "x")?;
+        std::os::unix::fs::symlink(&root, root.join("sub").join("loop"))?;
+        std::os::unix::fs::symlink(root.join("sub"), root.join("alias"))?;
+
+        // 循环链接被跳过并报告，同一目录只被搜索一次
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(&root).arg("-L").arg("--name").arg("data.txt").assert().success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        let stderr = String::from_utf8(output.get_output().stderr.clone())?;
+        assert_eq!(stdout.lines().count(), 1);
+        assert!(stderr.contains("符号链接问题"));
+
+        // 不跟随任何符号链接
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(&root)
+            .arg("-L")
+            .arg("--max-symlink-depth").arg("0")
+            .arg("--name").arg("*")
+            .assert()
+            .success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        assert!(stdout.contains("data.txt"));
+        assert!(!stdout.contains("alias"));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_broken_symlinks() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)] {
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        std::fs::create_dir(&root)?;
+        std::fs::write(root.join("real.txt"), "x")?;
+        std::os::unix::fs::symlink(root.join("real.txt"), root.join("alive"))?;
+        std::os::unix::fs::symlink(root.join("gone.txt"), root.join("dead"))?;
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(&root).arg("--broken-symlinks").assert().success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines, vec![root.join("dead").to_string_lossy()]);
+
+        // 可以作为 --delete 的过滤条件
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        cmd.arg(&root).arg("--broken-symlinks").arg("--delete").assert().success();
+        assert!(root.join("dead").symlink_metadata().is_err());
+        assert!(root.join("alive").exists());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_links_filter() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)] {
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        std::fs::create_dir(&root)?;
+        std::fs::write(root.join("single.txt"), "x")?;
+        std::fs::write(root.join("shared.txt"), "x")?;
+        std::fs::hard_link(root.join("shared.txt"), root.join("shared-copy.txt"))?;
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(&root).arg("--links").arg("+1").assert().success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        assert!(stdout.contains("shared.txt"));
+        assert!(stdout.contains("shared-copy.txt"));
+        assert!(!stdout.contains("single.txt"));
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(&root).arg("--links").arg("-2").assert().success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        assert!(stdout.contains("single.txt"));
+        assert!(!stdout.contains("shared"));
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        cmd.arg(&root).arg("--links").arg("many").assert().failure();
+    }
+    Ok(())
+}
+
+#[test]
+fn test_inum_and_samefile() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)] {
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        std::fs::create_dir_all(root.join("sub"))?;
+        std::fs::write(root.join("original.txt"), "x")?;
+        std::fs::write(root.join("unrelated.txt"), "x")?;
+        std::fs::hard_link(root.join("original.txt"), root.join("sub").join("alias.txt"))?;
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(&root).arg("--samefile").arg(root.join("original.txt")).assert().success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        assert_eq!(stdout.lines().count(), 2);
+        assert!(stdout.contains("alias.txt"));
+        assert!(!stdout.contains("unrelated.txt"));
+
+        let inode = std::fs::metadata(root.join("unrelated.txt"))?.ino();
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(&root).arg("--inum").arg(inode.to_string()).assert().success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        assert_eq!(stdout.trim(), root.join("unrelated.txt").to_string_lossy());
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        cmd.arg(&root).arg("--samefile").arg(root.join("missing")).assert().failure();
+    }
+    Ok(())
+}
+
+#[test]
+fn test_content_search() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir_all(root.join("src"))?;
+    std::fs::write(root.join("src").join("lib.rs"), "// TODO: remove\npub fn a() {}\n")?;
+    std::fs::write(root.join("src").join("main.rs"), "fn main() {}\n")?;
+    std::fs::write(root.join("notes.txt"), "TODO later\n")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&root)
+        .arg("--name").arg("*.rs")
+        .arg("--contains").arg("TODO")
+        .assert()
+        .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert_eq!(stdout.lines().collect::<Vec<_>>(), vec![root.join("src").join("lib.rs").to_string_lossy()]);
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&root).arg("--contains-regex").arg(r"^fn\s+main").assert().success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("main.rs"));
+    assert_eq!(stdout.lines().count(), 1);
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root).arg("--contains-regex").arg("(").assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn test_text_and_binary_filters() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir(&root)?;
+    std::fs::write(root.join("readme.txt"), "version 1\n")?;
+    std::fs::write(root.join("app.bin"), b"\x00\x01version 1\n")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&root).arg("--binary-only").assert().success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert_eq!(stdout.lines().collect::<Vec<_>>(), vec![root.join("app.bin").to_string_lossy()]);
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&root).arg("--text-only").assert().success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert_eq!(stdout.lines().collect::<Vec<_>>(), vec![root.join("readme.txt").to_string_lossy()]);
+
+    // 内容搜索默认也会搜索二进制文件
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&root).arg("--contains").arg("version").assert().success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert_eq!(stdout.lines().count(), 2);
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&root).arg("--contains").arg("version").arg("--skip-binary").assert().success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert_eq!(stdout.lines().collect::<Vec<_>>(), vec![root.join("readme.txt").to_string_lossy()]);
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root).arg("--text-only").arg("--binary-only").assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn test_mime_filter() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir(&root)?;
+    std::fs::write(root.join("image.dat"), b"GIF89a\x01\x00\x01\x00")?;
+    std::fs::write(root.join("fake.gif"), "not really a gif\n")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&root).arg("--mime").arg("image/*").assert().success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert_eq!(stdout.lines().collect::<Vec<_>>(), vec![root.join("image.dat").to_string_lossy()]);
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root).arg("--mime").arg("gif").assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn test_watch() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir(&root)?;
+    std::fs::write(root.join("existing.log"), "x")?;
+
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("rust-find"))
+        .arg(&root)
+        .args(["--name", "*.log", "--watch"])
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let stdout = child.stdout.take().unwrap();
+    let (sender, receiver) = channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let timeout = Duration::from_secs(10);
+    let first = receiver.recv_timeout(timeout);
+    std::fs::write(root.join("new.txt"), "x")?;
+    std::fs::write(root.join("new.log"), "x")?;
+    let second = receiver.recv_timeout(timeout);
+    child.kill()?;
+    child.wait()?;
+
+    assert_eq!(first?, root.join("existing.log").display().to_string());
+    assert_eq!(second?, root.join("new.log").display().to_string());
+
+    Ok(())
+}
+
+#[test]
+fn test_incremental_cache() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir_all(root.join("sub"))?;
+    std::fs::write(root.join("sub/a.log"), "x")?;
+    let cache = dir.path().join("scan-cache.json");
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .args(["--name", "*.log", "--cache"])
+       .arg(&cache)
+       .assert()
+       .success()
+       .stdout(format!("{}\n", root.join("sub/a.log").display()));
+    assert!(cache.exists());
+
+    std::fs::write(root.join("sub/b.log"), "x")?;
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .args(["--name", "*.log", "--cache"])
+       .arg(&cache)
+       .assert()
+       .success()
+       .stdout(format!(
+           "{}\n{}\n",
+           root.join("sub/a.log").display(),
+           root.join("sub/b.log").display()
+       ));
+
+    Ok(())
+}
+
+#[test]
+fn test_bench_subcommand() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join("a.txt"), "x")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg("bench")
+       .arg(dir.path())
+       .args(["--threads", "1,2", "--filters", "name", "--iterations", "1"])
+       .assert()
+       .success()
+       .stdout(predicate::str::contains("entries/sec"))
+       .stdout(predicate::str::contains("serial"))
+       .stdout(predicate::str::contains("parallel x2"));
+
+    Ok(())
+}
+
+#[test]
+fn test_directory_named_like_subcommand() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::create_dir(dir.path().join("bench"))?;
+    std::fs::write(dir.path().join("bench/a.txt"), "x")?;
+
+    // 子命令名优先，同名目录需要写作 ./bench 或放在 -- 之后
+    for args in [&["./bench"][..], &["--", "bench"]] {
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        cmd.current_dir(dir.path())
+           .args(["--name", "*.txt"])
+           .args(args)
+           .assert()
+           .success()
+           .stdout(predicate::str::contains("a.txt"));
+    }
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.current_dir(dir.path())
+       .arg("bench")
+       .assert()
+       .failure();
+
+    // 搜索选项不能与子命令一起使用
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.current_dir(dir.path())
+       .args(["bench", "bench", "--name", "*.txt"])
+       .assert()
+       .failure();
+
+    Ok(())
+}
+
+#[test]
+fn test_completions() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.args(["--completions", "zsh"])
+       .assert()
+       .success()
+       .stdout(predicate::str::contains("#compdef rust-find"))
+       .stdout(predicate::str::contains("--max-depth"));
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.args(["--completions", "tcsh"])
+       .assert()
+       .failure();
+
+    Ok(())
+}
+
+#[test]
+fn test_env_default_options() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir_all(root.join("sub"))?;
+    std::fs::write(root.join("a.log"), "x")?;
+    std::fs::write(root.join("sub/b.log"), "x")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.env("RUST_FIND_OPTS", "--name '*.log' --max-depth 1")
+       .arg(&root)
+       .assert()
+       .success()
+       .stdout(format!("{}\n", root.join("a.log").display()));
+
+    // 命令行中的选项覆盖环境变量中的默认值
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.env("RUST_FIND_OPTS", "--name '*.log' --max-depth 1")
+       .arg(&root)
+       .args(["--max-depth", "2"])
+       .assert()
+       .success()
+       .stdout(predicate::str::contains("b.log"));
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.env("RUST_FIND_OPTS", "--name '*.log")
+       .arg(&root)
+       .assert()
+       .failure()
+       .stderr(predicate::str::contains("RUST_FIND_OPTS"));
+
+    Ok(())
+}
+
+#[test]
+fn test_sort_by_score() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir(&root)?;
+    std::fs::write(root.join("few.txt"), "TODO\n")?;
+    std::fs::write(root.join("many.txt"), "TODO\nTODO TODO\n")?;
+    std::fs::write(root.join("some.txt"), "TODO TODO\n")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .args(["--contains", "TODO", "--sort", "score"])
+       .assert()
+       .success()
+       .stdout(format!(
+           "{}\n{}\n{}\n",
+           root.join("many.txt").display(),
+           root.join("some.txt").display(),
+           root.join("few.txt").display()
+       ));
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .args(["--sort", "score"])
+       .assert()
+       .failure()
+       .stderr(predicate::str::contains("--sort score"));
+
+    Ok(())
+}
+
+#[test]
+fn test_depth_first_post() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir_all(root.join("sub"))?;
+    std::fs::write(root.join("sub/a.txt"), "x")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .arg("--depth")
+       .assert()
+       .success()
+       .stdout(format!(
+           "{}\n{}\n{}\n",
+           root.join("sub/a.txt").display(),
+           root.join("sub").display(),
+           root.display()
+       ));
+
+    Ok(())
+}
+
+#[test]
+fn test_deterministic_output() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    for sub in ["b", "a", "c/d"] {
+        std::fs::create_dir_all(root.join(sub))?;
+        for i in 0..10 {
+            std::fs::write(root.join(sub).join(format!("{}.txt", i)), "x")?;
+        }
+    }
+
+    let run = || -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::cargo_bin("rust-find")?
+            .arg(&root)
+            .args(["--parallel", "--deterministic"])
+            .output()?;
+        assert!(output.status.success());
+        Ok(String::from_utf8(output.stdout)?)
+    };
+    let first = run()?;
+    assert_eq!(first, run()?);
+
+    let lines: Vec<&str> = first.lines().collect();
+    let mut sorted = lines.clone();
+    sorted.sort_by_key(|line| std::path::PathBuf::from(line));
+    assert_eq!(lines, sorted);
+    assert_eq!(lines.len(), 35);
+
+    Ok(())
+}
+
+#[test]
+fn test_long_paths() -> Result<(), Box<dyn std::error::Error>> {
+    // 超过 Windows MAX_PATH（260 个字符）的路径
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    let mut deep = root.clone();
+    for i in 0..8 {
+        deep.push(format!("{}{}", "d".repeat(40), i));
+    }
+    std::fs::create_dir_all(&deep)?;
+    std::fs::write(deep.join("needle.txt"), "needle\n")?;
+    let expected = deep.join("needle.txt");
+    assert!(expected.as_os_str().len() > 260);
+
+    for parallel in [false, true] {
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        cmd.arg(&root).args(["--contains", "needle"]);
+        if parallel {
+            cmd.arg("--parallel");
+        }
+        cmd.assert()
+           .success()
+           .stdout(format!("{}\n", expected.display()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_security_audit() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)] {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        std::fs::create_dir(&root)?;
+        for (name, mode) in [("setuid", 0o4755), ("setgid", 0o2755), ("plain", 0o755)] {
+            let path = root.join(name);
+            std::fs::write(&path, "x")?;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode))?;
+        }
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(&root).arg("--security-audit").assert().success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        assert_eq!(stdout.lines().count(), 2);
+        assert!(stdout.lines().any(|line| line.contains("-rwsr-xr-x") && line.ends_with("setuid")));
+        assert!(stdout.lines().any(|line| line.contains("-rwxr-sr-x") && line.ends_with("setgid")));
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        cmd.arg(&root)
+           .arg("--suid")
+           .assert()
+           .success()
+           .stdout(format!("{}\n", root.join("setuid").display()));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_audit_subcommand() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)] {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        std::fs::create_dir_all(root.join("shared"))?;
+        std::fs::create_dir_all(root.join("sticky"))?;
+        std::fs::write(root.join("notes.txt"), "x")?;
+        std::fs::write(root.join("orphan"), "x")?;
+        std::fs::set_permissions(root.join("shared"), std::fs::Permissions::from_mode(0o777))?;
+        std::fs::set_permissions(root.join("sticky"), std::fs::Permissions::from_mode(0o1777))?;
+        std::fs::set_permissions(root.join("notes.txt"), std::fs::Permissions::from_mode(0o666))?;
+        // 修改属主需要特权，没有权限时跳过这一项检查
+        let orphaned = std::os::unix::fs::chown(root.join("orphan"), Some(4_000_000), None).is_ok();
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg("audit").arg(&root).assert().success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        let sections: Vec<&str> = stdout.split("\n\n").collect();
+        assert_eq!(sections.len(), 3);
+        assert!(sections[0].starts_with("== 全局可写的文件 (1) =="));
+        assert!(sections[0].ends_with(&root.join("notes.txt").display().to_string()));
+        assert!(sections[1].contains(&root.join("shared").display().to_string()));
+        assert!(!sections[1].contains("sticky"));
+        if orphaned {
+            assert!(sections[2].contains(&root.join("orphan").display().to_string()));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_size_and_summarize() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir(&root)?;
+    std::fs::write(root.join("small.txt"), vec![b'x'; 10])?;
+    std::fs::write(root.join("large.txt"), vec![b'x'; 5000])?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .arg("--name").arg("*.txt")
+       .arg("--size").arg("+100")
+       .assert()
+       .success()
+       .stdout(format!("{}\n", root.join("large.txt").display()));
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .arg("--name").arg("*.txt")
+       .arg("--min-size").arg("1k")
+       .arg("--max-size").arg("0.01MB")
+       .assert()
+       .success()
+       .stdout(format!("{}\n", root.join("large.txt").display()));
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .arg("--size").arg("+1.5X")
+       .assert()
+       .failure()
+       .stderr(predicate::str::contains("Invalid size '1.5X'"));
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .arg("--name").arg("*.txt")
+       .arg("--summarize")
+       .assert()
+       .success()
+       .stdout("2 个条目，共 5010 字节\n");
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&root)
+       .arg("--name").arg("*.txt")
+       .arg("--summarize")
+       .arg("--size-on-disk")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.starts_with("2 个条目，占用磁盘 "));
+
+    Ok(())
+}
+
+#[test]
+fn test_type_filter_multiple_codes() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)] {
+        let dir = tempdir()?;
+        let root = dir.path().join("tree");
+        std::fs::create_dir_all(root.join("sub"))?;
+        std::fs::write(root.join("file"), "x")?;
+        std::os::unix::fs::symlink("file", root.join("link"))?;
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(&root).arg("--type").arg("f,l").assert().success();
+        let mut lines: Vec<String> = String::from_utf8(output.get_output().stdout.clone())?
+            .lines()
+            .map(String::from)
+            .collect();
+        lines.sort();
+        assert_eq!(lines, [root.join("file").display().to_string(), root.join("link").display().to_string()]);
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        cmd.arg(&root)
+           .arg("-t").arg("f,q")
+           .assert()
+           .failure()
+           .stderr(predicate::str::contains("无效的文件类型: q"));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_type_directories_only() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir_all(root.join("sub"))?;
+    std::fs::write(root.join("sub").join("file.txt"), "x")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .arg("-t").arg("d")
+       .arg("--name").arg("sub")
+       .assert()
+       .success()
+       .stdout(format!("{}\n", root.join("sub").display()));
+    Ok(())
+}
+
+#[test]
+fn test_multiple_name_patterns() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir_all(root.join("sub"))?;
+    for name in ["main.rs", "sub/notes.TXT", "sub/data.json"] {
+        std::fs::write(root.join(name), "x")?;
+    }
+
+    for parallel in [false, true] {
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        cmd.arg(&root).arg("-n").arg("*.rs").arg("-n").arg("*.json");
+        if parallel {
+            cmd.arg("--parallel");
+        }
+        let output = cmd.assert().success();
+        let mut lines: Vec<String> = String::from_utf8(output.get_output().stdout.clone())?
+            .lines()
+            .map(String::from)
+            .collect();
+        lines.sort();
+        assert_eq!(
+            lines,
+            [root.join("main.rs").display().to_string(), root.join("sub/data.json").display().to_string()]
+        );
+    }
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&root).arg("-i").arg("*.txt").arg("-i").arg("MAIN.*").assert().success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert_eq!(stdout.lines().count(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_path_globstar() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    for name in ["src/tests/a.rs", "src/net/tests/b.rs", "src/net/tests/data/c.rs", "src/lib.rs"] {
+        let path = root.join(name);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, "x")?;
+    }
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.current_dir(&root)
+       .arg(".")
+       .arg("--path").arg("src/**/tests/*.rs")
+       .assert()
+       .success();
+    let mut lines: Vec<String> = String::from_utf8(output.get_output().stdout.clone())?
+        .lines()
+        .map(String::from)
+        .collect();
+    lines.sort();
+    assert_eq!(lines, ["./src/net/tests/b.rs", "./src/tests/a.rs"]);
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.current_dir(&root)
+       .arg(".")
+       .arg("--path").arg("./src/*.rs")
+       .arg("--path-wildcard-slash")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert_eq!(stdout.lines().count(), 4);
+    Ok(())
+}
+
+#[test]
+fn test_negated_name_patterns() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir(&root)?;
+    for name in ["app.js", "app.min.js", "style.css"] {
+        std::fs::write(root.join(name), "x")?;
+    }
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .arg("--name").arg("*.js")
+       .arg("--not-name").arg("*.min.js")
+       .assert()
+       .success()
+       .stdout(format!("{}\n", root.join("app.js").display()));
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .arg("--name").arg("!*.js")
+       .arg("-t").arg("f")
+       .assert()
+       .success()
+       .stdout(format!("{}\n", root.join("style.css").display()));
+    Ok(())
+}
+
+#[test]
+fn test_files0_from_stdin() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir_all(root.join("sub"))?;
+    std::fs::write(root.join("a.txt"), "x")?;
+    std::fs::write(root.join("b.log"), "x")?;
+    std::fs::write(root.join("sub").join("c.txt"), "x")?;
+
+    let list = format!("{}\0{}\0", root.join("a.txt").display(), root.join("sub").display());
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg("--files0-from").arg("-")
+       .arg("--name").arg("*.txt")
+       .write_stdin(list)
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        [root.join("a.txt").display().to_string(), root.join("sub/c.txt").display().to_string()]
+    );
+
+    let list_file = dir.path().join("list");
+    std::fs::write(&list_file, format!("{}\n", root.join("b.log").display()))?;
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg("--files-from").arg(&list_file)
+       .assert()
+       .success()
+       .stdout(format!("{}\n", root.join("b.log").display()));
+    Ok(())
+}
+
+#[test]
+fn test_dedup_overlapping_roots() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir_all(root.join("sub"))?;
+    std::fs::write(root.join("a.txt"), "x")?;
+    std::fs::write(root.join("sub").join("b.txt"), "x")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&root).arg(root.join("sub"))
+       .arg("--name").arg("*.txt")
+       .arg("--deterministic")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert_eq!(stdout.lines().count(), 3);
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&root).arg(root.join("sub"))
+       .arg("--name").arg("*.txt")
+       .arg("--deterministic")
+       .arg("--dedup")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        [root.join("a.txt").display().to_string(), root.join("sub/b.txt").display().to_string()]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_count_matches() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir_all(root.join("sub"))?;
+    std::fs::write(root.join("a.txt"), "x")?;
+    std::fs::write(root.join("b.log"), "x")?;
+    std::fs::write(root.join("sub").join("c.txt"), "x")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .arg("--name").arg("*.txt")
+       .arg("--count")
+       .assert()
+       .success()
+       .stdout("2\n");
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root).arg(root.join("sub"))
+       .arg("--name").arg("*.txt")
+       .arg("--count").arg("--per-root")
+       .assert()
+       .success()
+       .stdout(format!("2\t{}\n1\t{}\n", root.display(), root.join("sub").display()));
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root).arg(root.join("sub"))
+       .arg("--name").arg("*.txt")
+       .arg("--count").arg("--dedup")
+       .assert()
+       .success()
+       .stdout("2\n");
+    Ok(())
+}
+
+#[test]
+fn test_quiet_exit_code() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir_all(&root)?;
+    std::fs::write(root.join("Cargo.lock"), "x")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .arg("-q")
+       .arg("--name").arg("*.lock")
+       .assert()
+       .success()
+       .stdout("")
+       .stderr("");
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .arg("--quiet")
+       .arg("--name").arg("*.toml")
+       .assert()
+       .code(1)
+       .stdout("")
+       .stderr("");
+    Ok(())
+}
+
+#[test]
+fn test_stats_summary_on_stderr() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir_all(root.join("sub"))?;
+    std::fs::write(root.join("a.txt"), "hello")?;
+    std::fs::write(root.join("sub").join("b.txt"), "hi")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&root)
+       .arg("--name").arg("*.txt")
+       .arg("--stats")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    let stderr = String::from_utf8(output.get_output().stderr.clone())?;
+    assert_eq!(stdout.lines().count(), 2);
+    assert!(stderr.contains("遍历的目录: 2"), "{}", stderr);
+    assert!(stderr.contains("匹配的条目: 2"), "{}", stderr);
+    assert!(stderr.contains("匹配文件的大小: 7 字节"), "{}", stderr);
+    Ok(())
+}
+
+#[test]
+fn test_error_format_json() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let missing = dir.path().join("missing");
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&missing)
+       .arg("--error-format").arg("json")
+       .assert()
+       .code(1);
+    let stderr = String::from_utf8(output.get_output().stderr.clone())?;
+    let line = stderr
+        .lines()
+        .find(|line| line.starts_with('{'))
+        .expect("JSON error line");
+    let value: serde_json::Value = serde_json::from_str(line)?;
+    assert_eq!(value["code"], "not_found");
+    assert_eq!(value["path"], missing.display().to_string());
+    assert!(value["errno"].is_i64());
+    Ok(())
+}
+
+#[test]
+fn test_error_policy_fail_aborts() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let missing = dir.path().join("missing");
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&missing)
+       .arg("--on-not-found").arg("silent")
+       .arg("--no-fail-on-error")
+       .assert()
+       .success()
+       .stderr(predicate::str::contains("文件未找到").not());
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&missing)
+       .arg("--on-not-found").arg("fail")
+       .assert()
+       .failure()
+       .stderr(predicate::str::contains("搜索已中止"))
+       .stderr(predicate::str::contains("文件未找到"));
+    Ok(())
+}
+
+#[test]
+fn test_exit_status_reflects_traversal_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir_all(&root)?;
+    std::fs::write(root.join("a.txt"), "x")?;
+    let missing = dir.path().join("missing");
+
+    // 被忽略的错误同样使退出状态非零，但不影响其他路径的输出
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root).arg(&missing)
+       .arg("--name").arg("*.txt")
+       .arg("--ignore-io-errors")
+       .assert()
+       .code(1)
+       .stdout(format!("{}\n", root.join("a.txt").display()));
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root).arg(&missing)
+       .arg("--name").arg("*.txt")
+       .arg("--no-fail-on-error")
+       .assert()
+       .success();
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&missing).arg(&root)
+       .arg("--strict")
+       .assert()
+       .failure()
+       .stderr(predicate::str::contains("搜索已中止"));
+    Ok(())
+}
+
+#[test]
+fn test_explain_filters() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir_all(&root)?;
+    std::fs::write(root.join("notes.txt"), "TODO")?;
+    std::fs::write(root.join("main.rs"), "TODO")?;
+
+    // 指定路径时逐个列出过滤器的判断，被拒绝后仍检查剩余的过滤器
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg("--name").arg("*.rs")
+       .arg("--contains").arg("TODO")
+       .arg("--explain-path").arg(root.join("notes.txt"))
+       .assert()
+       .code(1);
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("notes.txt: 不匹配"), "{}", stdout);
+    assert!(stdout.contains("拒绝  文件名匹配任一模式 [*.rs]"), "{}", stdout);
+    assert!(stdout.contains("通过  content matches 'TODO'"), "{}", stdout);
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg("--name").arg("*.rs")
+       .arg("--explain-path").arg(root.join("main.rs"))
+       .assert()
+       .success()
+       .stdout(predicate::str::contains("main.rs: 匹配"));
+
+    // 搜索时解释输出到标准错误，结果不变
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&root)
+       .arg("--name").arg("*.rs")
+       .arg("--explain")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    let stderr = String::from_utf8(output.get_output().stderr.clone())?;
+    assert_eq!(stdout.trim(), root.join("main.rs").display().to_string());
+    assert!(stderr.contains("notes.txt: 不匹配"), "{}", stderr);
+    Ok(())
+}
+
+#[test]
+fn test_query_filter() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let root = dir.path().join("tree");
+    std::fs::create_dir_all(root.join("logs"))?;
+    std::fs::write(root.join("logs/big.log"), vec![b'x'; 4096])?;
+    std::fs::write(root.join("logs/small.log"), "x")?;
+    std::fs::write(root.join("big.txt"), vec![b'x'; 4096])?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(&root)
+       .arg("--query").arg("type = file AND size > 1k AND (name LIKE '%.log' OR mtime > 30d)")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("big.log"), "{}", stdout);
+    assert!(!stdout.contains("small.log"), "{}", stdout);
+    assert!(!stdout.contains("big.txt"), "{}", stdout);
+
+    // 解析错误标出出错的位置
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&root)
+       .arg("--query").arg("type = file AND size >")
+       .assert()
+       .failure()
+       .stderr(predicate::str::contains("expected a value, found end of query at column 23"));
+    Ok(())
+}
+
+#[test]
+fn test_search_archives() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join("plain.txt"), "x")?;
+    let mut tar = tar::Builder::new(std::fs::File::create(dir.path().join("backup.tar"))?);
+    for (name, contents) in [("logs/app.txt", "log"), ("data.bin", "\0")] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        tar.append_data(&mut header, name, contents.as_bytes())?;
+    }
+    tar.finish()?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--name").arg("*.txt")
+       .arg("--search-archives")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("plain.txt"));
+    assert!(stdout.contains("backup.tar!/logs/app.txt"));
+    assert!(!stdout.contains("data.bin"));
+    assert_eq!(stdout.lines().count(), 2);
+
+    // 未指定时不进入归档
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path())
+       .arg("--name").arg("*.txt")
+       .assert()
+       .success()
+       .stdout(predicate::str::contains("!/").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_sftp_root_without_feature() -> Result<(), Box<dyn std::error::Error>> {
+    if cfg!(feature = "sftp") {
+        return Ok(());
+    }
+    // sftp:// 路径不按本地路径检查，未启用 sftp 特性时给出明确的错误
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg("sftp://admin@example.invalid/srv")
+       .assert()
+       .failure()
+       .stderr(predicate::str::contains("sftp 特性"));
+
+    Ok(())
+}
+
+#[test]
+fn test_s3_root_without_feature() -> Result<(), Box<dyn std::error::Error>> {
+    if cfg!(feature = "s3") {
+        return Ok(());
+    }
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg("s3://backups/db")
+       .assert()
+       .failure()
+       .stderr(predicate::str::contains("s3 特性"));
+
+    Ok(())
+}
+
+#[test]
+fn test_git_tracked() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .arg("-C").arg(dir.path())
+            .args(args)
+            .status()
+            .map(|status| assert!(status.success()))
+    };
+    git(&["init", "-q"])?;
+    std::fs::write(dir.path().join("tracked.rs"), "")?;
+    std::fs::write(dir.path().join("untracked.rs"), "")?;
+    std::fs::create_dir(dir.path().join("target"))?;
+    std::fs::write(dir.path().join("target/build.rs"), "")?;
+    git(&["add", "tracked.rs"])?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--name").arg("*.rs")
+       .arg("--git-tracked")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("tracked.rs"));
+    assert_eq!(stdout.lines().count(), 1);
+
+    // 不在 git 仓库中时报错
+    let outside = tempdir()?;
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(outside.path())
+       .arg("--git-tracked")
+       .assert()
+       .failure();
+    Ok(())
+}
+
+#[test]
+fn test_git_untracked_with_size() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .arg("-C").arg(dir.path())
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .map(|status| assert!(status.success()))
+    };
+    git(&["init", "-q"])?;
+    std::fs::write(dir.path().join("committed.bin"), vec![0u8; 2048])?;
+    git(&["add", "committed.bin"])?;
+    git(&["commit", "-qm", "init"])?;
+    std::fs::write(dir.path().join("big.bin"), vec![0u8; 2048])?;
+    std::fs::write(dir.path().join("small.bin"), "x")?;
+
+    // 提交前检查未跟踪的大文件
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--git-untracked")
+       .arg("--size").arg("+1k")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("big.bin"));
+    assert_eq!(stdout.lines().count(), 1);
+
+    std::fs::write(dir.path().join("committed.bin"), "changed")?;
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--git-modified")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("committed.bin"));
+    assert_eq!(stdout.lines().count(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_changed_in_git() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .arg("-C").arg(dir.path())
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .map(|status| assert!(status.success()))
+    };
+    git(&["init", "-q", "-b", "main"])?;
+    std::fs::write(dir.path().join("lib.rs"), "")?;
+    git(&["add", "."])?;
+    git(&["commit", "-qm", "init"])?;
+    git(&["checkout", "-qb", "feature"])?;
+    std::fs::write(dir.path().join("feature.rs"), "")?;
+    git(&["add", "."])?;
+    git(&["commit", "-qm", "feature"])?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--changed-in-git").arg("main")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("feature.rs"));
+    assert_eq!(stdout.lines().count(), 1);
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path())
+       .arg("--changed-in-git").arg("missing")
+       .assert()
+       .failure();
+    Ok(())
+}
+
+#[test]
+fn test_ok_and_interactive_delete() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join("a.tmp"), "")?;
+    std::fs::write(dir.path().join("b.tmp"), "")?;
+
+    // 拒绝第一个，确认第二个
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path())
+       .arg("--name").arg("*.tmp")
+       .arg("--delete").arg("--interactive")
+       .write_stdin("n\ny\n")
+       .assert()
+       .success()
+       .stderr(predicate::str::contains("< 删除 "));
+    let remaining: Vec<_> = ["a.tmp", "b.tmp"]
+        .into_iter()
+        .filter(|name| dir.path().join(name).exists())
+        .collect();
+    assert_eq!(remaining.len(), 1);
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path())
+       .arg("--name").arg("*.tmp")
+       .arg("--ok").arg("rm").arg("{}").arg(";")
+       .write_stdin("yes\n")
+       .assert()
+       .success()
+       .stderr(predicate::str::contains("< rm {} "));
+    assert!(!dir.path().join(remaining[0]).exists());
+    Ok(())
+}
+
+#[test]
+fn test_rename_template() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join("report.txt"), "")?;
+    std::fs::write(dir.path().join("notes"), "")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path())
+       .arg("--type").arg("f")
+       .arg("--rename").arg("{parent}_{stem}.{ext}")
+       .arg("--dry-run")
+       .assert()
+       .success()
+       .stdout(predicate::str::contains("将重命名: "));
+    assert!(dir.path().join("report.txt").exists());
+
+    let parent = dir.path().canonicalize()?.file_name().unwrap().to_string_lossy().into_owned();
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path())
+       .arg("--type").arg("f")
+       .arg("--rename").arg("{parent}_{stem}.{ext}")
+       .assert()
+       .success();
+    assert!(dir.path().join(format!("{}_report.txt", parent)).exists());
+    assert!(dir.path().join(format!("{}_notes", parent)).exists());
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path())
+       .arg("--rename").arg("{size}")
+       .assert()
+       .failure()
+       .stderr(predicate::str::contains("无效的占位符"));
+    Ok(())
+}
+
+#[test]
+fn test_copy_to_and_move_to() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let src = dir.path().join("src");
+    std::fs::create_dir_all(src.join("docs"))?;
+    std::fs::write(src.join("docs/a.md"), "a")?;
+    std::fs::write(src.join("b.md"), "b")?;
+    std::fs::write(src.join("c.txt"), "c")?;
+
+    let copies = dir.path().join("copies");
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&src)
+       .arg("--name").arg("*.md")
+       .arg("--copy-to").arg(&copies)
+       .arg("--preserve-paths")
+       .assert()
+       .success();
+    assert_eq!(std::fs::read_to_string(copies.join("docs/a.md"))?, "a");
+    assert!(copies.join("b.md").exists());
+    assert!(!copies.join("c.txt").exists());
+    assert!(src.join("docs/a.md").exists());
+
+    let moved = dir.path().join("moved");
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&src)
+       .arg("--name").arg("*.md")
+       .arg("--move-to").arg(&moved)
+       .assert()
+       .success();
+    assert!(moved.join("a.md").exists());
+    assert!(moved.join("b.md").exists());
+    assert!(!src.join("docs/a.md").exists());
+    Ok(())
+}
+
+#[test]
+fn test_archive_matches() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let logs = dir.path().join("logs");
+    std::fs::create_dir_all(logs.join("app"))?;
+    std::fs::write(logs.join("app/old.log"), "old")?;
+    std::fs::write(logs.join("keep.txt"), "keep")?;
+    let output = dir.path().join("logs.tar.zst");
+
+    // 归档在结束时才写完，不能同时删除原文件
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&logs)
+       .arg("--name").arg("*.log")
+       .arg("--archive").arg(&output)
+       .arg("--delete")
+       .assert()
+       .failure();
+    assert!(logs.join("app/old.log").exists());
+    assert!(!output.exists());
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&logs)
+       .arg("--name").arg("*.log")
+       .arg("--archive").arg(&output)
+       .assert()
+       .success();
+    assert!(logs.join("app/old.log").exists());
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path())
+       .arg("--name").arg("*.log")
+       .arg("--contains").arg("old")
+       .arg("--search-archives")
+       .assert()
+       .success()
+       .stdout(predicate::str::contains("logs.tar.zst!/app/old.log"));
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&logs)
+       .arg("--archive").arg(dir.path().join("logs.zip"))
+       .assert()
+       .failure();
+
+    // 无法创建归档时以非零状态退出
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&logs)
+       .arg("--name").arg("*.log")
+       .arg("--archive").arg(dir.path().join("missing/logs.tar"))
+       .assert()
+       .code(1);
+
+    // 边搜索边写入时，结果数上限和去重作用于所有搜索路径
+    let plain = dir.path().join("logs.tar");
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&logs).arg(&logs)
+       .arg("--name").arg("*.txt")
+       .arg("--dedup")
+       .arg("--archive").arg(&plain)
+       .assert()
+       .success();
+    let names = |archive: &std::path::Path| -> std::io::Result<Vec<String>> {
+        tar::Archive::new(std::fs::File::open(archive)?)
+            .entries()?
+            .map(|entry| Ok(entry?.path()?.display().to_string()))
+            .collect()
+    };
+    assert_eq!(names(&plain)?, ["keep.txt"]);
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(&logs).arg(&logs)
+       .args(["--type", "f", "--max-results", "1"])
+       .arg("--archive").arg(&plain)
+       .assert()
+       .success();
+    assert_eq!(names(&plain)?.len(), 1);
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_chmod_action() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir()?;
+    for name in ["a.sh", "b.sh"] {
+        std::fs::write(dir.path().join(name), "")?;
+        std::fs::set_permissions(dir.path().join(name), std::fs::Permissions::from_mode(0o644))?;
+    }
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path())
+       .arg("--name").arg("*.sh")
+       .arg("--chmod").arg("u+x,go-r")
+       .assert()
+       .success()
+       .stderr(predicate::str::contains("chmod: 已修改 2 个条目"));
+    let mode = std::fs::metadata(dir.path().join("a.sh"))?.permissions().mode() & 0o7777;
+    assert_eq!(mode, 0o700);
+    Ok(())
+}
+
+#[test]
+fn test_action_failure_exit_status() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)] {
+        let dir = tempdir()?;
+        std::fs::write(dir.path().join("a.txt"), "x")?;
+
+        // 单个命令失败不会中止运行，但退出状态为 1
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        cmd.arg(dir.path())
+           .arg("--name").arg("*.txt")
+           .arg("--exec").arg("false").arg(";")
+           .assert()
+           .code(1);
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        cmd.arg(dir.path())
+           .arg("--name").arg("*.txt")
+           .arg("--exec").arg("false").arg("{}").arg("+")
+           .assert()
+           .code(1);
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        cmd.arg(dir.path())
+           .arg("--name").arg("*.txt")
+           .arg("--exec").arg("true").arg(";")
+           .assert()
+           .success();
+    }
+    Ok(())
+}
+
+#[test]
+fn test_exec_and_execdir() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)] {
+        let dir = tempdir()?;
+        std::fs::create_dir(dir.path().join("sub"))?;
+        std::fs::write(dir.path().join("sub").join("file.txt"), "x")?;
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(dir.path())
+           .arg("--exec").arg("echo").arg("found:{}").arg(";")
+           .assert()
+           .success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        assert!(stdout.contains(&format!("found:{}", dir.path().join("sub").join("file.txt").display())));
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(dir.path())
+           .arg("--execdir").arg("sh").arg("-c").arg("echo $(pwd)/{}").arg(";")
+           .assert()
+           .success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        let sub = dir.path().join("sub").canonicalize()?;
+        assert!(stdout.contains(&format!("{}/./file.txt", sub.display())));
+
+        // 没有 {} 时按原样执行，不追加路径
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        cmd.arg(dir.path())
+           .args(["--type", "f"])
+           .arg("--exec").arg("echo").arg("ARGS").arg(";")
+           .assert()
+           .success()
+           .stdout("ARGS\n");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_exec_batched() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)] {
+        let dir = tempdir()?;
+        std::fs::write(dir.path().join("a.txt"), "x")?;
+        std::fs::write(dir.path().join("b.txt"), "x")?;
+
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        let output = cmd.arg(dir.path())
+           .arg("--exec").arg("echo").arg("batch:").arg("{}").arg("+")
+           .assert()
+           .success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("batch:"));
+        assert!(lines[0].contains("a.txt") && lines[0].contains("b.txt"));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_dry_run_applies_to_all_actions() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join("junk.tmp"), "x")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--name").arg("*.tmp")
+       .arg("--exec").arg("rm").arg("{}").arg(";")
+       .arg("--delete")
+       .arg("--dry-run")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("将执行: rm"));
+    assert!(stdout.contains("将删除:"));
+    assert!(dir.path().join("junk.tmp").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_max_results_across_paths() -> Result<(), Box<dyn std::error::Error>> {
+    let dir1 = tempdir()?;
+    let dir2 = tempdir()?;
+    for i in 0..3 {
+        std::fs::write(dir1.path().join(format!("{}.txt", i)), "x")?;
+        std::fs::write(dir2.path().join(format!("{}.txt", i)), "x")?;
+    }
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir1.path())
+       .arg(dir2.path())
+       .arg("--name").arg("*.txt")
+       .arg("--max-results").arg("4")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert_eq!(stdout.lines().count(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_respect_gitignore() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::create_dir_all(dir.path().join("node_modules/pkg"))?;
+    std::fs::write(dir.path().join("node_modules/pkg/index.js"), "x")?;
+    std::fs::write(dir.path().join("app.js"), "x")?;
+    std::fs::write(dir.path().join("debug.log"), "x")?;
+    std::fs::write(dir.path().join(".gitignore"), "node_modules/\n*.log\n")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--respect-gitignore")
+       .arg("--relative-to-root")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.lines().any(|line| line == "app.js"));
+    assert!(!stdout.contains("node_modules"));
+    assert!(!stdout.contains("debug.log"));
+
+    Ok(())
+}
+
+#[test]
+fn test_findignore() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let config = tempdir()?;
+    std::fs::create_dir_all(config.path().join("rust-find"))?;
+    std::fs::write(config.path().join("rust-find").join("ignore"), "*.cache\n")?;
+    std::fs::create_dir(dir.path().join("build"))?;
+    std::fs::write(dir.path().join("build").join("out.o"), "x")?;
+    std::fs::write(dir.path().join("data.cache"), "x")?;
+    std::fs::write(dir.path().join("main.c"), "x")?;
+    std::fs::write(dir.path().join(".findignore"), "build/\n")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--relative-to-root")
+       .env("XDG_CONFIG_HOME", config.path())
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.lines().any(|line| line == "main.c"));
+    assert!(!stdout.contains("build"));
+    assert!(!stdout.contains("data.cache"));
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--relative-to-root")
+       .arg("--no-findignore")
+       .env("XDG_CONFIG_HOME", config.path())
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("build/out.o"));
+    assert!(stdout.contains("data.cache"));
+
+    Ok(())
+}
+
+#[test]
+fn test_exclude_options() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::create_dir_all(dir.path().join("target/debug"))?;
+    std::fs::write(dir.path().join("target/debug/app"), "x")?;
+    std::fs::write(dir.path().join("main.rs"), "x")?;
+    std::fs::write(dir.path().join("main.rs.orig"), "x")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--relative-to-root")
+       .arg("--exclude").arg("*.orig")
+       .arg("--exclude-dir").arg("target")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    let mut lines: Vec<&str> = stdout.lines().collect();
+    lines.sort();
+    assert_eq!(lines, vec!["main.rs"]);
+
+    // 无效的模式在解析参数时报错
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path()).arg("--exclude").arg("[").assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn test_one_file_system() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::create_dir(dir.path().join("sub"))?;
+    std::fs::write(dir.path().join("sub").join("file.txt"), "x")?;
+
+    // 同一文件系统内的条目不受影响
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path())
+        .arg("-x")
+        .arg("--name").arg("*.txt")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file.txt"));
+
+    // /dev/shm 通常是挂载在 /dev 下的独立文件系统
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let dev = std::path::Path::new("/dev");
+        let shm = dev.join("shm");
+        let is_mount = match (dev.metadata(), shm.metadata()) {
+            (Ok(dev), Ok(shm)) => dev.dev() != shm.dev(),
+            _ => false,
+        };
+        if let (true, Ok(marker)) = (is_mount, tempfile::Builder::new().prefix("rust-find-").tempfile_in(&shm)) {
+            // 与 find -xdev 相同，挂载点本身会输出，但不会进入
+            let mut cmd = Command::cargo_bin("rust-find")?;
+            let output = cmd.arg("/dev").arg("--one-file-system").arg("--max-depth").arg("2").assert().success();
+            let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+            assert!(!stdout.lines().any(|line| line.starts_with("/dev/shm/")));
+            drop(marker);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_delete_action() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join("junk.tmp"), "x")?;
+    std::fs::write(dir.path().join("keep.txt"), "x")?;
+
+    // 没有过滤条件时拒绝执行
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path()).arg("--delete").assert().failure();
+    assert!(dir.path().join("keep.txt").exists());
+
+    // 演练模式不删除文件
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--name").arg("*.tmp")
+       .arg("--delete").arg("--dry-run")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("junk.tmp"));
+    assert!(dir.path().join("junk.tmp").exists());
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path())
+       .arg("--name").arg("*.tmp")
+       .arg("--delete")
+       .assert()
+       .success();
+    assert!(!dir.path().join("junk.tmp").exists());
+    assert!(dir.path().join("keep.txt").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_trash_action() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join("junk.tmp"), "x")?;
+
+    // 没有过滤条件时拒绝执行
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path()).arg("--trash").assert().failure();
+
+    // 不能与 --delete 同时使用
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path())
+       .arg("--name").arg("*.tmp")
+       .arg("--trash").arg("--delete")
+       .assert()
+       .failure();
+
+    // 演练模式不移动文件
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--name").arg("*.tmp")
+       .arg("--trash").arg("--dry-run")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("将移至回收站"));
+    assert!(dir.path().join("junk.tmp").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_no_recurse() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::create_dir(dir.path().join("sub"))?;
+    std::fs::write(dir.path().join("top.txt"), "x")?;
+    std::fs::write(dir.path().join("sub").join("nested.txt"), "x")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path()).arg("--no-recurse").assert().success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("top.txt"));
+    assert!(stdout.contains("sub"));
+    assert!(!stdout.contains("nested.txt"));
+
+    Ok(())
+}
+
+#[test]
+fn test_native_readdir() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::create_dir_all(dir.path().join("src/nested"))?;
+    std::fs::write(dir.path().join("src/a.rs"), "x")?;
+    std::fs::write(dir.path().join("src/nested/b.rs"), "xx")?;
+    std::fs::write(dir.path().join("notes.txt"), "x")?;
+
+    // 与普通遍历的结果相同
+    let mut results = Vec::new();
+    for native in [false, true] {
+        let mut cmd = Command::cargo_bin("rust-find")?;
+        cmd.arg(dir.path()).arg("--name").arg("*.rs").arg("--type").arg("f");
+        if native {
+            cmd.arg("--native-readdir");
+        }
+        let output = cmd.assert().success();
+        let mut lines: Vec<String> = String::from_utf8(output.get_output().stdout.clone())?
+            .lines()
+            .map(String::from)
+            .collect();
+        lines.sort();
+        results.push(lines);
+    }
+    assert_eq!(results[0].len(), 2);
+    assert_eq!(results[0], results[1]);
+
+    // 忽略文件和 -x 与普通遍历相同
+    std::fs::write(dir.path().join(".gitignore"), "nested/\n")?;
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--name").arg("*.rs")
+       .arg("--native-readdir")
+       .arg("--respect-gitignore")
+       .arg("-x")
+       .arg("--relative-to-root")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert_eq!(stdout.lines().collect::<Vec<_>>(), ["src/a.rs"]);
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    cmd.arg(dir.path())
+       .arg("--native-readdir")
+       .arg("--follow-links")
+       .assert()
+       .failure();
+    Ok(())
+}
+
+#[test]
+fn test_attr_filter_sidecar() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join("alpha.csv"), "x")?;
+    std::fs::write(dir.path().join("alpha.csv.meta"), "project=alpha\n")?;
+    std::fs::write(dir.path().join("beta.csv"), "x")?;
+    std::fs::write(dir.path().join("beta.csv.meta"), "project=beta\n")?;
+
+    let mut cmd = Command::cargo_bin("rust-find")?;
+    let output = cmd.arg(dir.path())
+       .arg("--attr").arg("project=alpha")
+       .assert()
+       .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("alpha.csv"));
+    assert!(!stdout.contains("beta.csv"));
+    assert_eq!(stdout.lines().count(), 1);
+
+    Ok(())
+}